@@ -0,0 +1,211 @@
+use crate::git::{self, Blame};
+use crate::repo_mapper::{CodeComponent, RepositoryMapper};
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A marker left in the code for later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationKind {
+    Todo,
+    Fixme,
+    Hack,
+    Xxx,
+    /// A `SAFETY:` comment justifying an `unsafe` block
+    Safety,
+    /// A Rust `#[allow(...)]` or `#![allow(...)]` attribute
+    Allow,
+}
+
+impl AnnotationKind {
+    /// Every kind, in order
+    pub const ALL: [Self; 6] = [
+        Self::Todo,
+        Self::Fixme,
+        Self::Hack,
+        Self::Xxx,
+        Self::Safety,
+        Self::Allow,
+    ];
+}
+
+impl fmt::Display for AnnotationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Todo => "TODO",
+            Self::Fixme => "FIXME",
+            Self::Hack => "HACK",
+            Self::Xxx => "XXX",
+            Self::Safety => "SAFETY",
+            Self::Allow => "allow",
+        })
+    }
+}
+
+impl std::str::FromStr for AnnotationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown annotation kind '{s}', expected todo, fixme, hack, xxx, safety or allow"
+                )
+            })
+    }
+}
+
+/// A `TODO`-style comment or an `#[allow]` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    pub file: String,
+    pub line: usize,
+    /// The comment after the marker, or the lints an `#[allow]` silences
+    pub text: String,
+    /// ID of the component the annotation is on or in
+    pub component: Option<String>,
+    /// Who last changed the line and when, if asked for and committed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<Blame>,
+}
+
+/// `TODO`-style markers at the start of a line, block or doc comment
+fn marker_regex() -> Regex {
+    Regex::new(
+        r"(?m)(?:(?://+!?|/\*+!?|#+)|^[ \t]*\*+)[ \t]*(TODO|FIXME|HACK|XXX|SAFETY)\b:?[ \t]*(.*)$",
+    )
+    .expect("Invalid marker regex")
+}
+
+fn allow_regex() -> Regex {
+    Regex::new(r"(?m)^[ \t]*#!?\[allow\(([^)]*)\)\]").expect("Invalid allow regex")
+}
+
+/// Annotations in every analyzed file, by file and line, with blame from git if asked for
+pub(crate) fn find_annotations(mapper: &RepositoryMapper, with_blame: bool) -> Vec<Annotation> {
+    let marker = marker_regex();
+    let allow = allow_regex();
+    let mut components: HashMap<&Path, Vec<&CodeComponent>> = HashMap::new();
+    for component in mapper.graph.components.values() {
+        components
+            .entry(component.file_path.as_path())
+            .or_default()
+            .push(component);
+    }
+
+    let files: BTreeMap<String, &Path> = mapper
+        .files
+        .values()
+        .map(|file| (file.display().to_string(), file.as_path()))
+        .collect();
+    let mut annotations = Vec::new();
+    for (label, file) in files {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("Skipping unreadable file {}: {e}", file.display());
+                continue;
+            }
+        };
+        let is_rust = file.extension().is_some_and(|ext| ext == "rs");
+        let mut found: Vec<(usize, AnnotationKind, String)> = marker
+            .captures_iter(&content)
+            .filter_map(|captures| {
+                let offset = captures.get(1)?.start();
+                let kind = captures[1].parse().ok()?;
+                let text = captures[2].trim().trim_end_matches("*/").trim_end();
+                Some((offset, kind, text.to_string()))
+            })
+            .collect();
+        if is_rust {
+            found.extend(allow.captures_iter(&content).filter_map(|captures| {
+                let lints: Vec<&str> = captures[1]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|lint| !lint.is_empty())
+                    .collect();
+                Some((
+                    captures.get(0)?.start(),
+                    AnnotationKind::Allow,
+                    lints.join(", "),
+                ))
+            }));
+        }
+        if found.is_empty() {
+            continue;
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        let in_file = components.get(file).map_or(&[][..], Vec::as_slice);
+        let mut in_file_annotations: Vec<Annotation> = found
+            .into_iter()
+            .map(|(offset, kind, text)| {
+                let line = content[..offset].matches('\n').count() + 1;
+                Annotation {
+                    kind,
+                    file: label.clone(),
+                    line,
+                    text,
+                    component: owner(in_file, &lines, line).map(|c| c.id.clone()),
+                    blame: None,
+                }
+            })
+            .collect();
+        in_file_annotations.sort_by_key(|annotation| annotation.line);
+
+        if with_blame {
+            let numbers: Vec<usize> = in_file_annotations.iter().map(|a| a.line).collect();
+            match git::blame(file, &numbers) {
+                Ok(mut blamed) => {
+                    for annotation in &mut in_file_annotations {
+                        annotation.blame = blamed.remove(&annotation.line);
+                    }
+                }
+                Err(e) => debug!("No blame for {}: {e}", file.display()),
+            }
+        }
+        annotations.extend(in_file_annotations);
+    }
+    annotations
+}
+
+/// The component an annotation at `line` belongs to: the one it directly precedes in a run
+/// of comments and attributes, otherwise the innermost one containing it
+fn owner<'a>(
+    components: &[&'a CodeComponent],
+    lines: &[&str],
+    line: usize,
+) -> Option<&'a CodeComponent> {
+    let is_leading = |text: &str| {
+        let text = text.trim_start();
+        ["//", "/*", "*", "#", "@"]
+            .iter()
+            .any(|prefix| text.starts_with(prefix))
+            && !text.starts_with("#![")
+            && !text.starts_with("//!")
+    };
+    let following = components
+        .iter()
+        .filter(|component| component.start_line > line)
+        .min_by_key(|component| component.start_line)
+        .filter(|component| {
+            (line..component.start_line)
+                .all(|n| lines.get(n - 1).is_some_and(|text| is_leading(text)))
+        });
+    following
+        .or_else(|| {
+            components
+                .iter()
+                .filter(|c| c.start_line <= line && line <= c.end_line)
+                .min_by_key(|c| c.end_line - c.start_line)
+        })
+        .copied()
+}
@@ -0,0 +1,257 @@
+use crate::git;
+use crate::imports::normalize_path;
+use crate::repo_mapper::{ArchitecturalLayer, RepositoryMapper};
+use crate::repo_settings::RepoSettings;
+use crate::suggest::{HISTORY_DEPTH, MAX_COMMIT_FILES};
+use crate::test_correlation::{test_subject, words};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Built-in layers from top to bottom, recognized by words in file and directory names.
+/// Tests are recognized by their naming conventions instead.
+const HEURISTIC_LAYERS: &[(&str, &str, &[&str])] = &[
+    ("tests", "Tests and test helpers", &[]),
+    (
+        "cli",
+        "Command-line entry points",
+        &[
+            "cli", "cmd", "command", "commands", "bin", "console", "main",
+        ],
+    ),
+    (
+        "handlers",
+        "HTTP handlers, routes and controllers",
+        &[
+            "handler",
+            "handlers",
+            "controller",
+            "controllers",
+            "route",
+            "routes",
+            "router",
+            "api",
+            "view",
+            "views",
+            "endpoint",
+            "endpoints",
+            "server",
+            "web",
+            "http",
+        ],
+    ),
+    (
+        "domain",
+        "Business logic and domain models",
+        &[
+            "domain", "service", "services", "core", "model", "models", "entity", "entities",
+            "usecase", "usecases", "logic",
+        ],
+    ),
+    (
+        "persistence",
+        "Database access and storage",
+        &[
+            "db",
+            "database",
+            "repository",
+            "repositories",
+            "store",
+            "storage",
+            "dao",
+            "persistence",
+            "migration",
+            "migrations",
+            "schema",
+            "sql",
+        ],
+    ),
+    (
+        "shared",
+        "Utilities shared by every layer",
+        &["util", "utils", "common", "shared", "helper", "helpers"],
+    ),
+];
+
+/// An import from a file into a layer above its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub from: String,
+    pub to: String,
+    pub from_layer: String,
+    pub to_layer: String,
+}
+
+/// A file many others import that also changes often
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub path: String,
+    /// Files importing this one
+    pub fan_in: usize,
+    /// Recent commits changing this file
+    pub churn: usize,
+    /// `fan_in * churn`
+    pub score: usize,
+}
+
+/// The built-in layer of a file, from its name and the directories containing it
+fn heuristic_layer(relative: &Path) -> Option<usize> {
+    if test_subject(relative).is_some() {
+        return Some(0);
+    }
+    let stem = relative.file_stem()?.to_str()?;
+    // The file name decides first, then the directories from the innermost out
+    let names = std::iter::once(stem).chain(
+        relative
+            .parent()
+            .into_iter()
+            .flat_map(|dir| dir.iter().rev())
+            .filter_map(|name| name.to_str()),
+    );
+    for name in names {
+        for word in words(name) {
+            if let Some(idx) = HEURISTIC_LAYERS
+                .iter()
+                .position(|(_, _, keywords)| keywords.contains(&word.as_str()))
+            {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+/// Group analyzed files into layers, from `.contexter.toml` rules where a root has them and
+/// by name otherwise, and find imports from a lower layer into a higher one
+pub(crate) fn detect_layers(
+    mapper: &RepositoryMapper,
+    settings: &[RepoSettings],
+) -> (Vec<ArchitecturalLayer>, Vec<LayerViolation>) {
+    // Layers in order from top to bottom, as (name, description)
+    let mut order: Vec<(String, String)> = Vec::new();
+    let push_layer = |order: &mut Vec<(String, String)>, name: &str, description: &str| {
+        if !order.iter().any(|(existing, _)| existing == name) {
+            order.push((name.to_string(), description.to_string()));
+        }
+    };
+    for root_settings in settings {
+        for rule in &root_settings.layers {
+            push_layer(&mut order, &rule.name, &rule.description);
+        }
+    }
+    if settings
+        .iter()
+        .any(|root_settings| root_settings.layers.is_empty())
+    {
+        for (name, description, _) in HEURISTIC_LAYERS {
+            push_layer(&mut order, name, description);
+        }
+    }
+    let rank = |name: &str| order.iter().position(|(existing, _)| existing == name);
+
+    let mut assigned: BTreeMap<String, usize> = BTreeMap::new();
+    for file in mapper.files.values() {
+        let root = mapper
+            .roots
+            .iter()
+            .enumerate()
+            .filter(|(_, root)| file.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count());
+        let relative = root.map_or(file.as_path(), |(_, root)| {
+            file.strip_prefix(root).unwrap_or(file)
+        });
+        let rules = root
+            .and_then(|(idx, _)| settings.get(idx))
+            .map(|root_settings| root_settings.layers.as_slice())
+            .unwrap_or_default();
+        let layer = if rules.is_empty() {
+            heuristic_layer(relative).and_then(|idx| rank(HEURISTIC_LAYERS[idx].0))
+        } else {
+            rules
+                .iter()
+                .find(|rule| rule.matches(relative))
+                .and_then(|rule| rank(&rule.name))
+        };
+        if let Some(layer) = layer {
+            assigned.insert(file.display().to_string(), layer);
+        }
+    }
+
+    let layers = order
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (name, description))| {
+            let files: Vec<String> = assigned
+                .iter()
+                .filter(|(_, &layer)| layer == idx)
+                .map(|(file, _)| file.clone())
+                .collect();
+            (!files.is_empty()).then(|| ArchitecturalLayer {
+                name: name.clone(),
+                files,
+                description: description.clone(),
+            })
+        })
+        .collect();
+
+    let violations = mapper
+        .file_graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            let from = *assigned.get(&edge.from)?;
+            let to = *assigned.get(&edge.to)?;
+            (from > to).then(|| LayerViolation {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                from_layer: order[from].0.clone(),
+                to_layer: order[to].0.clone(),
+            })
+        })
+        .collect();
+    (layers, violations)
+}
+
+/// Files ranked by how many files import them times how often they changed recently
+pub(crate) fn find_hotspots(mapper: &RepositoryMapper, limit: usize) -> Vec<Hotspot> {
+    let mut churn: HashMap<String, usize> = HashMap::new();
+    for root in &mapper.roots {
+        let commits = match git::recent_commits(root, HISTORY_DEPTH) {
+            Ok(commits) => commits,
+            Err(e) => {
+                warn!("Skipping git history of {}: {e}", root.display());
+                continue;
+            }
+        };
+        for commit in commits {
+            if commit.files.len() > MAX_COMMIT_FILES {
+                continue;
+            }
+            for file in &commit.files {
+                if let Some(analyzed) = mapper.files.get(&normalize_path(&root.join(file))) {
+                    *churn.entry(analyzed.display().to_string()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let mut hotspots: Vec<Hotspot> = mapper
+        .file_graph
+        .files
+        .iter()
+        .filter_map(|(path, node)| {
+            let churn = churn.get(path).copied().unwrap_or_default();
+            let score = node.fan_in * churn;
+            (score > 0).then(|| Hotspot {
+                path: path.clone(),
+                fan_in: node.fan_in,
+                churn,
+                score,
+            })
+        })
+        .collect();
+    hotspots.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    hotspots.truncate(limit);
+    hotspots
+}
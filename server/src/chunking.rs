@@ -0,0 +1,341 @@
+use crate::contexter::{
+    render_project_files, ConcatOptions, ProjectFile, RenderedFile, Section, SEPARATOR,
+};
+use crate::repo_mapper::ComponentType;
+use crate::syntax::{self, SourceLanguage};
+use crate::truncate::estimate_tokens;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One part of context split to fit a token budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextChunk {
+    /// Counting from 1
+    pub part: usize,
+    pub parts: usize,
+    /// Files in this part, whole or in part, in order
+    pub files: Vec<String>,
+    /// Estimated tokens of `content`
+    pub tokens: usize,
+    /// The part's header and table of contents, then its files
+    pub content: String,
+}
+
+/// A file, or a run of its lines when it is too large for one part
+#[derive(Debug)]
+struct Piece<'a> {
+    file: &'a RenderedFile,
+    text: String,
+}
+
+impl Piece<'_> {
+    fn tokens(&self) -> usize {
+        estimate_tokens(&self.text)
+    }
+}
+
+/// Splits project files, rendered as by [`crate::contexter::concatenate_project_files_with`],
+/// into parts of at most `max_tokens` estimated tokens each. Parts break between files
+/// where they can, and files too large for a part between functions, falling back to
+/// between lines.
+pub fn chunk_project_files_with<F>(
+    files: Vec<ProjectFile>,
+    options: &ConcatOptions,
+    max_tokens: usize,
+    transform: F,
+) -> io::Result<Vec<ContextChunk>>
+where
+    F: FnMut(&Path, String) -> Option<String>,
+{
+    let rendered = render_project_files(files, options, transform)?;
+    if rendered.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Room for a part banner with the widest part numbers and file names, and a section
+    // banner. No part holds less than a character, so there are at most as many parts as
+    // characters.
+    let bound: usize = rendered.iter().map(|file| file.render().len()).sum();
+    let longest = rendered
+        .iter()
+        .map(|file| file.display_path.as_str())
+        .max_by_key(|name| name.len())
+        .unwrap_or("");
+    let part_overhead = estimate_tokens(&part_banner(bound, bound, Some((longest, Some(longest)))))
+        .max(estimate_tokens(&part_banner(bound, bound, None)));
+    let banner = estimate_tokens(&Section::Configuration.banner());
+    let budget = max_tokens
+        .checked_sub(part_overhead + banner)
+        .filter(|budget| *budget > 0);
+    let Some(budget) = budget else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Parts of {max_tokens} tokens leave no room for files after the part banner of \
+                {part_overhead} tokens"
+            ),
+        ));
+    };
+
+    let mut pieces = Vec::new();
+    for file in &rendered {
+        pieces.extend(split(file, budget));
+    }
+
+    // The table of contents opens part 1. Every part holds a piece or a table line, so
+    // there are at most this many parts, and no entry is longer than with this number.
+    let most_parts = pieces.len() + rendered.len();
+    let widest: Vec<String> = rendered
+        .iter()
+        .map(|file| toc_line(&file.display_path, most_parts, most_parts))
+        .collect();
+    let widest_tokens =
+        estimate_tokens(TOC_TITLE) + widest.iter().map(|l| estimate_tokens(l)).sum::<usize>();
+    // Too large to share part 1 with files, the table fills parts of its own
+    let table_parts = if widest_tokens <= budget + banner {
+        Vec::new()
+    } else {
+        group_lines(&widest, budget + banner)
+    };
+    let first_used = if table_parts.is_empty() {
+        widest_tokens
+    } else {
+        0
+    };
+    let mut packed: Vec<Vec<Piece>> = table_parts.iter().map(|_| Vec::new()).collect();
+    packed.extend(pack(pieces, budget, banner, first_used));
+
+    let parts = packed.len();
+    let tables = tables_of_contents(&packed, &table_parts);
+
+    let chunks = packed
+        .into_iter()
+        .enumerate()
+        .map(|(idx, part)| {
+            let files = part.first().map(|first| {
+                let last = part.last().map_or("", |p| p.file.display_path.as_str());
+                let first = first.file.display_path.as_str();
+                (first, (first != last).then_some(last))
+            });
+            let mut content = part_banner(idx + 1, parts, files);
+            if let Some(table) = tables.get(idx) {
+                if idx == 0 {
+                    content.push_str(TOC_TITLE);
+                }
+                content.push_str(table);
+            }
+            let mut files: Vec<String> = Vec::new();
+            let mut section = None;
+            for piece in &part {
+                if section != Some(piece.file.section) {
+                    section = Some(piece.file.section);
+                    content.push_str(&piece.file.section.banner());
+                }
+                content.push_str(&piece.text);
+                if files.last() != Some(&piece.file.display_path) {
+                    files.push(piece.file.display_path.clone());
+                }
+            }
+            ContextChunk {
+                part: idx + 1,
+                parts,
+                files,
+                tokens: estimate_tokens(&content),
+                content,
+            }
+        })
+        .collect();
+    Ok(chunks)
+}
+
+/// The table of contents, listing every file and the parts it is in, split as
+/// `table_parts` says or whole for part 1 if it is empty
+fn tables_of_contents(packed: &[Vec<Piece>], table_parts: &[usize]) -> Vec<String> {
+    let mut contents: Vec<(&str, usize, usize)> = Vec::new();
+    for (idx, part) in packed.iter().enumerate() {
+        for piece in part {
+            let name = piece.file.display_path.as_str();
+            match contents.last_mut() {
+                Some((last, _, to)) if *last == name => *to = idx + 1,
+                _ => contents.push((name, idx + 1, idx + 1)),
+            }
+        }
+    }
+    let mut lines = contents
+        .into_iter()
+        .map(|(name, from, to)| toc_line(name, from, to));
+    if table_parts.is_empty() {
+        return vec![lines.collect()];
+    }
+    // Each table part takes the lines it was sized for, which are at least as long
+    table_parts
+        .iter()
+        .map(|&count| lines.by_ref().take(count).collect())
+        .collect()
+}
+
+/// Packs pieces into parts holding at most `budget` tokens of files and one section banner
+/// of `banner` tokens, besides `first_used` tokens already taken in the first part. Each
+/// piece costs another section banner when it starts a section.
+fn pack(
+    pieces: Vec<Piece<'_>>,
+    budget: usize,
+    banner: usize,
+    first_used: usize,
+) -> Vec<Vec<Piece<'_>>> {
+    let mut packed: Vec<Vec<Piece>> = vec![Vec::new()];
+    let mut used = first_used;
+    for piece in pieces {
+        let current = packed.last_mut().expect("There is always a current part");
+        let same_section = current
+            .last()
+            .is_some_and(|last| last.file.section == piece.file.section);
+        let tokens = piece.tokens() + if same_section { 0 } else { banner };
+        if used > 0 && used + tokens > budget + banner {
+            used = piece.tokens() + banner;
+            packed.push(vec![piece]);
+        } else {
+            used += tokens;
+            current.push(piece);
+        }
+    }
+    packed
+}
+
+/// How many of `lines`, in order, go in each part so that no part's lines exceed `budget`
+/// tokens, the first part's with the table's title
+fn group_lines(lines: &[String], budget: usize) -> Vec<usize> {
+    let mut groups = vec![0];
+    let mut used = estimate_tokens(TOC_TITLE);
+    for line in lines {
+        let tokens = estimate_tokens(line);
+        let current = groups.last_mut().expect("There is always a current group");
+        if *current > 0 && used + tokens > budget {
+            used = tokens;
+            groups.push(1);
+        } else {
+            used += tokens;
+            *current += 1;
+        }
+    }
+    groups
+}
+
+/// Where part `part` of context meant for `output` is written: `out.md` becomes
+/// `out.part2.md`
+pub fn part_path(output: &Path, part: usize) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match output.extension() {
+        Some(extension) => format!("{stem}.part{part}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.part{part}"),
+    };
+    output.with_file_name(name)
+}
+
+const TOC_TITLE: &str = "Table of Contents:\n";
+
+/// The banner opening a part, naming the first file in it and the last if it is another, or
+/// saying it holds the table of contents when it has no files
+fn part_banner(part: usize, parts: usize, files: Option<(&str, Option<&str>)>) -> String {
+    let files = match files {
+        Some((first, Some(last))) => format!("files {first} – {last}"),
+        Some((first, None)) => format!("file {first}"),
+        None => "table of contents".to_string(),
+    };
+    format!("{SEPARATOR}Part {part}/{parts}, {files}\n")
+}
+
+/// A file's line in the table of contents, with the parts it is in
+fn toc_line(name: &str, from: usize, to: usize) -> String {
+    if from == to {
+        format!("- {name} (part {from})\n")
+    } else {
+        format!("- {name} (parts {from}-{to})\n")
+    }
+}
+
+/// A rendered file as one piece, or split into pieces of at most `budget` tokens
+fn split(file: &RenderedFile, budget: usize) -> Vec<Piece<'_>> {
+    let whole = file.render();
+    if estimate_tokens(&whole) <= budget {
+        return vec![Piece { file, text: whole }];
+    }
+
+    let lines: Vec<&str> = file.content.lines().collect();
+    let total = lines.len();
+    let breaks = break_points(&file.path, &file.content, total);
+    // The header of a piece is at most this, whatever lines it holds
+    let widest = piece_text(file, &[], (total, total), total);
+    let room = budget.saturating_sub(estimate_tokens(&widest)).max(1);
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < total {
+        // The most lines from `start` that fit, at least one
+        let mut chars = 0;
+        let mut end = start;
+        while end < total {
+            let next = chars + lines[end].chars().count() + 1;
+            if end > start && next.div_ceil(4) > room {
+                break;
+            }
+            chars = next;
+            end += 1;
+        }
+        // Back off to a break between functions, preferably after a blank line
+        if end < total {
+            let candidates = (start + 1..=end).rev().filter(|&idx| breaks[idx]);
+            let blank = candidates
+                .clone()
+                .find(|&idx| lines[idx - 1].trim().is_empty());
+            end = blank.or_else(|| candidates.clone().next()).unwrap_or(end);
+        }
+        pieces.push(Piece {
+            file,
+            text: piece_text(file, &lines[start..end], (start + 1, end), total),
+        });
+        start = end;
+    }
+    pieces
+}
+
+fn piece_text(file: &RenderedFile, lines: &[&str], span: (usize, usize), total: usize) -> String {
+    let mut text = file.header.clone();
+    writeln!(text, "Lines: {}-{} of {total}", span.0, span.1)
+        .expect("Writing to a String should not fail");
+    text.push_str(SEPARATOR);
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Whether a piece may start at each line, 0-based, without splitting a function. Every
+/// line may for files that cannot be parsed.
+fn break_points(path: &Path, content: &str, total: usize) -> Vec<bool> {
+    let mut breaks = vec![true; total + 1];
+    let parsed =
+        SourceLanguage::for_path(path).and_then(|language| syntax::parse_source(language, content));
+    let Some(parsed) = parsed else {
+        return breaks;
+    };
+    for component in &parsed.components {
+        let is_function = matches!(
+            component.component_type,
+            ComponentType::Function | ComponentType::Method
+        );
+        if is_function {
+            // Anywhere past its first line
+            let end = component.end_line.min(total);
+            for brk in breaks.iter_mut().take(end).skip(component.start_line) {
+                *brk = false;
+            }
+        }
+    }
+    breaks
+}
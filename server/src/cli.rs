@@ -1,5 +1,13 @@
+use crate::annotations::AnnotationKind;
 use crate::cli_handlers;
 use crate::config::Config;
+use crate::contexter::GatherPreset;
+use crate::dedup::DedupOptions;
+use crate::graph_export::ExportFormat;
+use crate::graph_query::{Direction, GraphQuery};
+use crate::ranking::{parse_weighted_signal, RankSignal};
+use crate::repo_mapper::{EdgeType, MapLevel};
+use crate::truncate::FileLimits;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -16,16 +24,7 @@ pub enum Cli {
     },
 
     #[structopt(name = "gather", about = "Gather context from files")]
-    Gather {
-        #[structopt(parse(from_os_str))]
-        directory: PathBuf,
-
-        #[structopt(short, long, help = "File extensions to include")]
-        extensions: Vec<String>,
-
-        #[structopt(short, long, help = "Patterns to ignore")]
-        ignore: Vec<String>,
-    },
+    Gather(GatherOptions),
 
     #[structopt(name = "config", about = "Manage configuration")]
     Config {
@@ -50,8 +49,226 @@ pub enum Cli {
         #[structopt(short, long, help = "JSON format")]
         json: bool,
 
-        #[structopt(long, help = "Component to focus on")]
+        #[structopt(
+            long,
+            help = "Component to focus on by ID, name or qualified name, or file with --level file"
+        )]
         focus: Option<String>,
+
+        #[structopt(
+            long,
+            help = "Map granularity",
+            default_value = "component",
+            possible_values = &["component", "file"]
+        )]
+        level: MapLevel,
+
+        #[structopt(
+            long,
+            help = "Export the graph in a standard format instead of a map",
+            possible_values = &["dot", "mermaid", "graphml", "jgf"]
+        )]
+        export: Option<ExportFormat>,
+
+        #[structopt(
+            long,
+            help = "With --focus, how many dependency steps around it to show",
+            default_value = "1"
+        )]
+        depth: usize,
+
+        #[structopt(long, help = "With --focus, only show what it depends on")]
+        upstream: bool,
+
+        #[structopt(long, help = "With --focus, only show what depends on it")]
+        downstream: bool,
+
+        #[structopt(
+            long = "edge-type",
+            help = "With --focus, only follow these edges",
+            possible_values = &["function_call", "method_call", "class_inheritance", "module_import", "field_access"]
+        )]
+        edge_types: Vec<EdgeType>,
+    },
+
+    #[structopt(name = "suggest", about = "Suggest files related to the given files")]
+    Suggest {
+        #[structopt(help = "Files to find related files for", required = true)]
+        files: Vec<PathBuf>,
+
+        #[structopt(long, help = "Repository path to analyze", default_value = ".")]
+        path: PathBuf,
+
+        #[structopt(
+            short,
+            long,
+            help = "Maximum number of suggestions",
+            default_value = "10"
+        )]
+        limit: usize,
+
+        #[structopt(short, long, help = "JSON format")]
+        json: bool,
+    },
+
+    #[structopt(name = "analyze", about = "Run a single analysis of a repository")]
+    Analyze {
+        #[structopt(subcommand)]
+        cmd: AnalyzeCommand,
+    },
+
+    #[structopt(
+        name = "impact",
+        about = "Show what depends on the given or changed files"
+    )]
+    Impact {
+        #[structopt(help = "Changed files")]
+        files: Vec<PathBuf>,
+
+        #[structopt(long, help = "Repository path to analyze", default_value = ".")]
+        path: PathBuf,
+
+        #[structopt(
+            long,
+            help = "Also take the files changed since this git revision, e.g. HEAD or main"
+        )]
+        diff: Option<String>,
+
+        #[structopt(long, help = "How many dependency steps to follow; all by default")]
+        depth: Option<usize>,
+
+        #[structopt(short, long, help = "JSON format")]
+        json: bool,
+
+        #[structopt(
+            long,
+            help = "Print the changed and affected files and their tests instead of the report"
+        )]
+        context: bool,
+    },
+
+    #[structopt(
+        name = "todos",
+        about = "List TODO, FIXME, HACK, XXX and SAFETY comments and #[allow] attributes"
+    )]
+    Todos {
+        #[structopt(help = "Repository path to analyze", default_value = ".")]
+        path: PathBuf,
+
+        #[structopt(
+            long,
+            help = "Only list these kinds",
+            possible_values = &["todo", "fixme", "hack", "xxx", "safety", "allow"],
+            case_insensitive = true
+        )]
+        kind: Vec<AnnotationKind>,
+
+        #[structopt(long, help = "Add who last changed each line and when, from git blame")]
+        blame: bool,
+
+        #[structopt(short, long, help = "JSON format")]
+        json: bool,
+    },
+}
+
+/// What `gather` collects and how
+#[derive(Debug, Default, StructOpt)]
+pub struct GatherOptions {
+    #[structopt(parse(from_os_str))]
+    pub directory: PathBuf,
+
+    #[structopt(short, long, help = "File extensions to include")]
+    pub extensions: Vec<String>,
+
+    #[structopt(
+        short,
+        long,
+        help = "Regex patterns of paths to ignore, relative to the directory"
+    )]
+    pub ignore: Vec<String>,
+
+    #[structopt(
+        short,
+        long,
+        help = "Workspace members (Cargo crates, npm packages, Go modules) to include"
+    )]
+    pub member: Vec<String>,
+
+    #[structopt(long, help = "Also include the tests of the gathered files")]
+    pub with_tests: bool,
+
+    #[structopt(
+        long,
+        help = "Gather a selection instead of whole files",
+        possible_values = &["undocumented"]
+    )]
+    pub preset: Option<GatherPreset>,
+
+    #[structopt(
+        long,
+        help = "Only public signatures, doc comments and type definitions, with bodies elided"
+    )]
+    pub signatures_only: bool,
+
+    #[structopt(flatten)]
+    pub limits: FileLimits,
+
+    #[structopt(flatten)]
+    pub dedup: DedupOptions,
+
+    #[structopt(
+        long,
+        parse(try_from_str = parse_weighted_signal),
+        help = "Put important files first by dependencies, fan-in, recency or complexity, optionally weighted as in fan-in=2"
+    )]
+    pub rank: Vec<(RankSignal, f64)>,
+
+    #[structopt(
+        long,
+        help = "Glob patterns of files to put before all others, in order"
+    )]
+    pub priority: Vec<String>,
+
+    #[structopt(
+        short,
+        long,
+        parse(from_os_str),
+        help = "Write the context to this file instead of printing it"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        requires = "output",
+        help = "Split the context into parts of at most this many tokens, written as out.part1.md, out.part2.md and so on for --output out.md"
+    )]
+    pub chunk_tokens: Option<usize>,
+}
+
+#[derive(StructOpt)]
+pub enum AnalyzeCommand {
+    #[structopt(
+        name = "dead-code",
+        about = "Find components nothing uses and imports their file never mentions"
+    )]
+    DeadCode {
+        #[structopt(help = "Repository path to analyze", default_value = ".")]
+        path: PathBuf,
+
+        #[structopt(short, long, help = "JSON format")]
+        json: bool,
+    },
+
+    #[structopt(
+        name = "doc-coverage",
+        about = "Report how much of the public API has doc comments, per file and directory"
+    )]
+    DocCoverage {
+        #[structopt(help = "Repository path to analyze", default_value = ".")]
+        path: PathBuf,
+
+        #[structopt(short, long, help = "JSON format")]
+        json: bool,
     },
 }
 
@@ -62,8 +279,11 @@ pub enum ConfigCommand {
         #[structopt(help = "Project name")]
         name: String,
 
-        #[structopt(help = "Project path")]
-        path: PathBuf,
+        #[structopt(
+            help = "Project path; give several to combine roots, each prefixed by its directory name",
+            required = true
+        )]
+        paths: Vec<PathBuf>,
     },
 
     #[structopt(name = "remove-project", about = "Remove a project")]
@@ -76,6 +296,9 @@ pub enum ConfigCommand {
     GenerateKey {
         #[structopt(help = "API key name")]
         name: String,
+
+        #[structopt(long, help = "Allow this key to use admin endpoints")]
+        admin: bool,
     },
 
     #[structopt(name = "remove-key", about = "Remove an API key")]
@@ -103,6 +326,36 @@ pub enum ConfigCommand {
     List,
 }
 
+fn run_config_command(
+    cmd: ConfigCommand,
+    config: &mut Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        ConfigCommand::AddProject { name, paths } => {
+            cli_handlers::handle_config_add_project(config, &name, &paths)
+        }
+        ConfigCommand::RemoveProject { name } => {
+            cli_handlers::handle_config_remove_project(config, &name)
+        }
+        ConfigCommand::GenerateKey { name, admin } => {
+            cli_handlers::handle_config_generate_key(config, &name, admin)
+        }
+        ConfigCommand::RemoveKey { name } => cli_handlers::handle_config_remove_key(config, &name),
+        ConfigCommand::ListKeys => {
+            cli_handlers::handle_config_list_keys(config);
+            Ok(())
+        }
+        ConfigCommand::SetPort { port } => cli_handlers::handle_config_set_port(config, port),
+        ConfigCommand::SetAddress { address } => {
+            cli_handlers::handle_config_set_address(config, &address)
+        }
+        ConfigCommand::List => {
+            cli_handlers::handle_config_list(config);
+            Ok(())
+        }
+    }
+}
+
 pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::from_args();
     let mut config = Config::load()?;
@@ -119,39 +372,8 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
             // Server logic will be handled in main.rs
             Ok(())
         }
-        Cli::Gather {
-            directory,
-            extensions,
-            ignore,
-        } => cli_handlers::handle_gather(&directory, &extensions, ignore),
-        Cli::Config { cmd } => match cmd {
-            ConfigCommand::AddProject { name, path } => {
-                cli_handlers::handle_config_add_project(&mut config, &name, &path)
-            }
-            ConfigCommand::RemoveProject { name } => {
-                cli_handlers::handle_config_remove_project(&mut config, &name)
-            }
-            ConfigCommand::GenerateKey { name } => {
-                cli_handlers::handle_config_generate_key(&mut config, &name)
-            }
-            ConfigCommand::RemoveKey { name } => {
-                cli_handlers::handle_config_remove_key(&mut config, &name)
-            }
-            ConfigCommand::ListKeys => {
-                cli_handlers::handle_config_list_keys(&config);
-                Ok(())
-            }
-            ConfigCommand::SetPort { port } => {
-                cli_handlers::handle_config_set_port(&mut config, port)
-            }
-            ConfigCommand::SetAddress { address } => {
-                cli_handlers::handle_config_set_address(&mut config, &address)
-            }
-            ConfigCommand::List => {
-                cli_handlers::handle_config_list(&config);
-                Ok(())
-            }
-        },
+        Cli::Gather(options) => cli_handlers::handle_gather(options),
+        Cli::Config { cmd } => run_config_command(cmd, &mut config),
         Cli::Map {
             path,
             dependencies,
@@ -159,13 +381,63 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
             output,
             json,
             focus,
-        } => cli_handlers::handle_repo_map_generate(
-            &path,
-            dependencies,
-            order,
-            output,
+            level,
+            export,
+            depth,
+            upstream,
+            downstream,
+            edge_types,
+        } => {
+            let focus = focus.map(|seed| GraphQuery {
+                seed,
+                depth,
+                direction: Direction::from_flags(upstream, downstream),
+                edge_types,
+            });
+            match export {
+                Some(format) => cli_handlers::handle_repo_map_export(
+                    &path,
+                    format,
+                    level,
+                    focus.as_ref(),
+                    output,
+                ),
+                None => cli_handlers::handle_repo_map_generate(
+                    &path,
+                    dependencies,
+                    order,
+                    output,
+                    json,
+                    focus.as_ref(),
+                    level,
+                ),
+            }
+        }
+        Cli::Suggest {
+            files,
+            path,
+            limit,
+            json,
+        } => cli_handlers::handle_suggest(&path, &files, limit, json),
+        Cli::Analyze { cmd } => match cmd {
+            AnalyzeCommand::DeadCode { path, json } => cli_handlers::handle_dead_code(&path, json),
+            AnalyzeCommand::DocCoverage { path, json } => {
+                cli_handlers::handle_doc_coverage(&path, json)
+            }
+        },
+        Cli::Impact {
+            files,
+            path,
+            diff,
+            depth,
+            json,
+            context,
+        } => cli_handlers::handle_impact(&path, files, diff.as_deref(), depth, json, context),
+        Cli::Todos {
+            path,
+            kind,
+            blame,
             json,
-            focus.as_ref(),
-        ),
+        } => cli_handlers::handle_todos(&path, &kind, blame, json),
     }
 }
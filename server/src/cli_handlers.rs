@@ -1,40 +1,143 @@
 #![allow(clippy::print_stdout)] // CLI tools appropriately use println! for output
 
-use crate::config::Config;
-use crate::contexter::{concatenate_files, gather_relevant_files};
-use crate::repo_mapper::RepositoryMapper;
+use crate::annotations::AnnotationKind;
+use crate::chunking::{chunk_project_files_with, part_path};
+use crate::cli::GatherOptions;
+use crate::complexity::approximate_note;
+use crate::config::{Config, ProjectConfig, ProjectRoot};
+use crate::contexter::{
+    concatenate_files, concatenate_project_files_with, ConcatOptions, GatherPreset, ProjectFile,
+};
+use crate::doc_coverage::{render_undocumented, CoverageStats, DocCoverage};
+use crate::graph_export::ExportFormat;
+use crate::graph_query::GraphQuery;
+use crate::ranking::Ranking;
+use crate::repo_mapper::{MapLevel, RepositoryMapper, Visibility};
 use crate::utils::{generate_api_key, hash_api_key};
+use crate::workspace::gather_project_files_with;
 use log::info;
 use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
-pub fn handle_gather(
-    directory: &Path,
-    extensions: &[String],
-    ignore: Vec<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let files = gather_relevant_files(
-        directory
-            .to_str()
-            .expect("Directory path should be valid UTF-8"),
-        &extensions.iter().map(AsRef::as_ref).collect::<Vec<_>>(),
+pub fn handle_gather(options: GatherOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let GatherOptions {
+        directory,
+        extensions,
         ignore,
-    )?;
-    let (content, _) = concatenate_files(files)?;
-    println!("{content}");
+        member: members,
+        with_tests,
+        preset,
+        signatures_only,
+        limits,
+        dedup,
+        rank,
+        priority,
+        output,
+        chunk_tokens,
+    } = options;
+    let extensions = extensions.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let project = ProjectConfig::Path(directory.clone());
+    let members = (!members.is_empty()).then_some(members.as_slice());
+    let mut files: Vec<PathBuf> =
+        gather_project_files_with(&project, None, members, &extensions, &ignore)?
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+    if let Some(GatherPreset::Undocumented) = preset {
+        let mut mapper = RepositoryMapper::new();
+        mapper.analyze_repository(&directory)?;
+        let files: Vec<ProjectFile> = files.into_iter().map(ProjectFile::from).collect();
+        let content = render_undocumented(&mapper.component_docs(), &files);
+        return write_context(&content, output.as_deref());
+    }
+
+    let ranking = Ranking {
+        signals: rank.into_iter().collect(),
+        priority,
+    }
+    .or(Ranking::configured(std::slice::from_ref(&directory))?);
+    let mut mapper = RepositoryMapper::new();
+    if with_tests || signatures_only || !ranking.is_empty() {
+        mapper.analyze_repository(&directory)?;
+    }
+    if with_tests {
+        let tests = mapper.tests_for_files(&files);
+        info!("Including {} test file(s)", tests.len());
+        files.extend(tests);
+    }
+    let mut files: Vec<ProjectFile> = files.into_iter().map(ProjectFile::from).collect();
+    let ranked = !ranking.is_empty();
+    if ranked {
+        ranking.rank(&mapper, &mut files);
+    }
+    let concat = ConcatOptions {
+        limits,
+        ranked,
+        dedup,
+    };
+    let signatures = mapper.signatures();
+    let transform = |path: &Path, content| {
+        if signatures_only {
+            signatures.for_file(path)
+        } else {
+            Some(content)
+        }
+    };
+    if let (Some(max_tokens), Some(output)) = (chunk_tokens, &output) {
+        let chunks = chunk_project_files_with(files, &concat, max_tokens, transform)?;
+        for chunk in &chunks {
+            let path = part_path(output, chunk.part);
+            std::fs::write(&path, &chunk.content)?;
+            info!(
+                "Wrote part {}/{} with {} file(s), about {} tokens, to {}",
+                chunk.part,
+                chunk.parts,
+                chunk.files.len(),
+                chunk.tokens,
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+    let (content, _) = concatenate_project_files_with(files, &concat, transform)?;
+    write_context(&content, output.as_deref())
+}
+
+/// Writes gathered context to `output`, or prints it when there is none
+fn write_context(content: &str, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(output) = output {
+        std::fs::write(output, content)?;
+        info!("Wrote context to {}", output.display());
+    } else {
+        println!("{content}");
+    }
     Ok(())
 }
 
 pub fn handle_config_add_project(
     config: &mut Config,
     name: &str,
-    path: &Path,
+    paths: &[PathBuf],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    config.add_project(name.to_string(), path.to_path_buf());
+    if let [path] = paths {
+        config.add_project(name.to_string(), path.clone());
+    } else {
+        let roots = paths
+            .iter()
+            .map(|path| ProjectRoot {
+                prefix: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                path: path.clone(),
+            })
+            .collect();
+        config.add_multi_root_project(name.to_string(), roots);
+    }
     config.save()?;
     info!(
         "Project '{name}' added successfully with path {}",
-        path.display()
+        config.projects[name].display_path()
     );
     Ok(())
 }
@@ -55,10 +158,14 @@ pub fn handle_config_remove_project(
 pub fn handle_config_generate_key(
     config: &mut Config,
     name: &str,
+    admin: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let new_key = generate_api_key();
     let hashed_key = hash_api_key(&new_key);
     config.add_api_key(name.to_string(), hashed_key);
+    if admin {
+        config.grant_admin(name);
+    }
     config.save()?;
     println!("New API key generated for '{name}': {new_key}");
     println!("Please store this key securely. It won't be displayed again.");
@@ -79,7 +186,12 @@ pub fn handle_config_remove_key(
 pub fn handle_config_list_keys(config: &Config) {
     println!("API Keys:");
     for name in config.api_keys.keys() {
-        println!("  {}: {}", name, "*".repeat(40)); // Hide the hashed key in the output
+        let scope = if config.is_admin_key(name) {
+            " (admin)"
+        } else {
+            ""
+        };
+        println!("  {}: {}{scope}", name, "*".repeat(40)); // Hide the hashed key in the output
     }
 }
 
@@ -108,8 +220,8 @@ pub fn handle_config_list(config: &Config) {
     println!("Port: {}", config.port);
     println!("Listen Address: {}", config.listen_address);
     println!("Projects:");
-    for (name, path) in &config.projects {
-        println!("  {name}: {}", path.display());
+    for (name, project) in &config.projects {
+        println!("  {name}: {}", project.display_path());
     }
     println!("API Keys:");
     for name in config.api_keys.keys() {
@@ -126,7 +238,8 @@ pub fn handle_repo_map_generate(
     show_order: bool,
     output: Option<PathBuf>,
     json_format: bool,
-    focus_component: Option<&String>,
+    focus: Option<&GraphQuery>,
+    level: MapLevel,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Generating repository map for: {}", path.display());
 
@@ -135,7 +248,16 @@ pub fn handle_repo_map_generate(
 
     let mut result = String::new();
 
-    if json_format {
+    if level == MapLevel::File {
+        result = render_file_map(
+            path,
+            &mapper,
+            show_dependencies,
+            show_order,
+            json_format,
+            focus.map(|query| &query.seed),
+        )?;
+    } else if json_format {
         // JSON output with all the data
         let json_data = serde_json::json!({
             "repository": path,
@@ -143,14 +265,22 @@ pub fn handle_repo_map_generate(
             "entry_points": mapper.insights.entry_points,
             "cycles": mapper.graph.cycles.len(),
             "most_connected": mapper.insights.most_connected_components,
+            "most_complex_files": mapper.complexity_ranking().into_iter().take(10).collect::<Vec<_>>(),
+            "architectural_layers": mapper.insights.architectural_layers,
+            "layering_violations": mapper.insights.layering_violations,
+            "hotspots": mapper.hotspots,
             "processing_order": if show_order { Some(&mapper.topological_order) } else { None },
             "dependency_graph": if show_dependencies { Some(&mapper.graph.edges) } else { None },
-            "focus": focus_component.and_then(|comp| {
-                mapper.graph.components.get(comp).map(|c| serde_json::json!({
-                    "component": c,
-                    "dependencies": mapper.get_dependencies(comp),
-                    "dependents": mapper.get_dependents(comp)
-                }))
+            "focus": focus.map(|query| {
+                match mapper.subgraph(&query.seed, query.depth, query.direction, &query.edge_types) {
+                    Ok(subgraph) => serde_json::json!({
+                        "component": mapper.graph.components.get(&subgraph.seed),
+                        "dependencies": mapper.get_dependencies(&subgraph.seed),
+                        "dependents": mapper.get_dependents(&subgraph.seed),
+                        "subgraph": subgraph,
+                    }),
+                    Err(e) => serde_json::json!({ "error": e.to_string() }),
+                }
             })
         });
         result = serde_json::to_string_pretty(&json_data)?;
@@ -170,54 +300,8 @@ pub fn handle_repo_map_generate(
         )?;
 
         // Focus on specific component if requested
-        if let Some(focus) = focus_component {
-            if let Some(component) = mapper.graph.components.values().find(|c| c.name == *focus) {
-                writeln!(&mut result, "🎯 Focus: {}", component.name)?;
-                writeln!(
-                    &mut result,
-                    "   Type: {:?} | Visibility: {:?}",
-                    component.component_type, component.visibility
-                )?;
-                writeln!(&mut result, "   File: {}", component.file_path.display())?;
-                write!(
-                    &mut result,
-                    "   Dependencies: {} | Used by: {}\n\n",
-                    component.dependencies.len(),
-                    component.dependents.len()
-                )?;
-
-                if !component.dependencies.is_empty() {
-                    result.push_str("Dependencies:\n");
-                    for dep_id in &component.dependencies {
-                        if let Some(dep) = mapper.graph.components.get(dep_id) {
-                            writeln!(
-                                &mut result,
-                                "  → {} ({})",
-                                dep.name,
-                                dep.file_path.display()
-                            )?;
-                        }
-                    }
-                    result.push('\n');
-                }
-
-                if !component.dependents.is_empty() {
-                    result.push_str("Used by:\n");
-                    for dep_id in &component.dependents {
-                        if let Some(dep) = mapper.graph.components.get(dep_id) {
-                            writeln!(
-                                &mut result,
-                                "  ← {} ({})",
-                                dep.name,
-                                dep.file_path.display()
-                            )?;
-                        }
-                    }
-                    result.push('\n');
-                }
-            } else {
-                write!(&mut result, "Component '{focus}' not found\n\n")?;
-            }
+        if let Some(query) = focus {
+            write_focus(&mut result, &mapper, query)?;
         }
 
         // Group components by file for clean overview
@@ -240,6 +324,7 @@ pub fn handle_repo_map_generate(
                     crate::repo_mapper::ComponentType::Function
                     | crate::repo_mapper::ComponentType::Method => "fn",
                     crate::repo_mapper::ComponentType::Class => "struct",
+                    crate::repo_mapper::ComponentType::Enum => "enum",
                     crate::repo_mapper::ComponentType::Module => "mod",
                     crate::repo_mapper::ComponentType::Interface => "trait",
                 };
@@ -263,15 +348,12 @@ pub fn handle_repo_map_generate(
         // Show entry points (important for understanding)
         if !mapper.insights.entry_points.is_empty() {
             result.push_str("Entry Points:\n");
-            for entry_id in &mapper.insights.entry_points {
-                if let Some(entry) = mapper.graph.components.get(entry_id) {
-                    writeln!(
-                        &mut result,
-                        "  {} ({})",
-                        entry.name,
-                        entry.file_path.display()
-                    )?;
-                }
+            for entry in &mapper.insights.entry_points {
+                writeln!(
+                    &mut result,
+                    "  {} [{}] ({}:{})",
+                    entry.name, entry.kind, entry.file, entry.line
+                )?;
             }
             result.push('\n');
         }
@@ -299,6 +381,27 @@ pub fn handle_repo_map_generate(
             result.push('\n');
         }
 
+        // Show where the complex code lives
+        let most_complex = mapper.complexity_ranking();
+        if !most_complex.is_empty() {
+            result.push_str("Most Complex Files:\n");
+            for (i, file) in most_complex.iter().take(5).enumerate() {
+                writeln!(
+                    &mut result,
+                    "  {}. {} (cognitive {}, cyclomatic {}, {} SLOC{})",
+                    i + 1,
+                    file.path,
+                    file.cognitive,
+                    file.cyclomatic,
+                    file.sloc,
+                    approximate_note(file.approximate)
+                )?;
+            }
+            result.push('\n');
+        }
+
+        write_architecture(&mut result, &mapper)?;
+
         // Show processing order if requested
         if show_order {
             result.push_str("Processing Order (dependencies first):\n");
@@ -347,3 +450,503 @@ pub fn handle_repo_map_generate(
 
     Ok(())
 }
+
+pub fn handle_repo_map_export(
+    path: &Path,
+    format: ExportFormat,
+    level: MapLevel,
+    focus: Option<&GraphQuery>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Exporting repository graph for: {}", path.display());
+
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    let exported = mapper.export_graph(level, format, focus)?;
+
+    if let Some(output_path) = output {
+        std::fs::write(output_path, &exported)?;
+        println!("Repository graph saved");
+    } else {
+        print!("{exported}");
+    }
+    Ok(())
+}
+
+pub fn handle_suggest(
+    path: &Path,
+    files: &[PathBuf],
+    limit: usize,
+    json_format: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Suggesting files related to {} file(s)", files.len());
+
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    let mut suggestions = mapper.suggest_related(files, limit)?;
+    for suggestion in &mut suggestions {
+        suggestion.relabel(|file| file.strip_prefix("./").unwrap_or(file).to_string());
+    }
+
+    if json_format {
+        println!("{}", serde_json::to_string_pretty(&suggestions)?);
+        return Ok(());
+    }
+
+    if suggestions.is_empty() {
+        println!("No related files found");
+        return Ok(());
+    }
+    println!("Related files:");
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        println!("  {}. {} ({:.2})", i + 1, suggestion.path, suggestion.score);
+        for reason in &suggestion.reasons {
+            println!("     - {reason}");
+        }
+    }
+    Ok(())
+}
+
+/// The focused component and what it depends on and is used by, within the query's depth
+pub fn handle_dead_code(path: &Path, json_format: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    let mut dead_code = mapper.find_dead_code();
+    let relabel = |file: &mut String| {
+        if let Some(relative) = file.strip_prefix("./") {
+            *file = relative.to_string();
+        }
+    };
+    dead_code
+        .components
+        .iter_mut()
+        .for_each(|c| relabel(&mut c.file));
+    dead_code
+        .imports
+        .iter_mut()
+        .for_each(|i| relabel(&mut i.file));
+
+    if json_format {
+        println!("{}", serde_json::to_string_pretty(&dead_code)?);
+        return Ok(());
+    }
+
+    if dead_code.components.is_empty() {
+        println!("No unused components found");
+    } else {
+        println!("Unused components ({}):", dead_code.components.len());
+        for component in &dead_code.components {
+            let export = if component.visibility == Visibility::Public {
+                ", public"
+            } else {
+                ""
+            };
+            println!(
+                "  {}:{}  {} ({:?}{export})",
+                component.file, component.line, component.name, component.component_type
+            );
+        }
+    }
+    if dead_code.imports.is_empty() {
+        println!("No unused imports found");
+    } else {
+        println!("\nUnused imports ({}):", dead_code.imports.len());
+        for import in &dead_code.imports {
+            println!(
+                "  {}:{}  {} ({})",
+                import.file, import.line, import.name, import.path
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_doc_coverage(
+    path: &Path,
+    json_format: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    let mut docs = mapper.component_docs();
+    for component in &mut docs {
+        if let Some(relative) = component.file.strip_prefix("./") {
+            component.file = relative.to_string();
+        }
+    }
+    let coverage = DocCoverage::from_docs(&docs);
+
+    if json_format {
+        println!("{}", serde_json::to_string_pretty(&coverage)?);
+        return Ok(());
+    }
+
+    let summary = |stats: &CoverageStats| {
+        format!(
+            "{}/{} documented ({:.1}%), {}/{} parameters",
+            stats.documented,
+            stats.public_components,
+            stats.coverage * 100.0,
+            stats.documented_parameters,
+            stats.parameters
+        )
+    };
+    println!("Public API: {}", summary(&coverage.total));
+    println!("\nBy directory:");
+    for (module, stats) in &coverage.modules {
+        println!("  {module}  {}", summary(stats));
+    }
+    println!("\nBy file:");
+    for (file, stats) in &coverage.files {
+        println!("  {file}  {}", summary(stats));
+    }
+    Ok(())
+}
+
+pub fn handle_todos(
+    path: &Path,
+    kinds: &[AnnotationKind],
+    with_blame: bool,
+    json_format: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    let mut annotations = mapper.annotations(with_blame);
+    annotations.retain(|annotation| kinds.is_empty() || kinds.contains(&annotation.kind));
+    for annotation in &mut annotations {
+        if let Some(relative) = annotation.file.strip_prefix("./") {
+            annotation.file = relative.to_string();
+        }
+    }
+
+    if json_format {
+        println!("{}", serde_json::to_string_pretty(&annotations)?);
+        return Ok(());
+    }
+    if annotations.is_empty() {
+        println!("No annotations found");
+        return Ok(());
+    }
+    println!("Annotations ({}):", annotations.len());
+    for annotation in &annotations {
+        let mut line = format!(
+            "  {}:{}  {} {}",
+            annotation.file, annotation.line, annotation.kind, annotation.text
+        );
+        if let Some(component) = annotation
+            .component
+            .as_ref()
+            .and_then(|id| mapper.graph.components.get(id))
+        {
+            write!(&mut line, "  (in {})", component.name)?;
+        }
+        if let Some(blame) = &annotation.blame {
+            write!(&mut line, "  [{}, {}]", blame.author, blame.date)?;
+        }
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+pub fn handle_impact(
+    path: &Path,
+    mut files: Vec<PathBuf>,
+    diff: Option<&str>,
+    depth: Option<usize>,
+    json_format: bool,
+    gather_context: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mapper = RepositoryMapper::new();
+    mapper.analyze_repository(path)?;
+    if let Some(base) = diff {
+        files.extend(mapper.changed_files(base)?);
+    }
+    info!("Analyzing the impact of {} changed file(s)", files.len());
+    let mut report = mapper.impact(&files, depth)?;
+
+    if gather_context {
+        let (content, _) = concatenate_files(report.context_files())?;
+        println!("{content}");
+        return Ok(());
+    }
+    report.relabel(|file| file.strip_prefix("./").unwrap_or(file).to_string());
+    if json_format {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Changed files:");
+    for file in &report.changed_files {
+        println!("  {file}");
+    }
+    let steps = |distance: usize| {
+        if distance == 1 {
+            "1 step".to_string()
+        } else {
+            format!("{distance} steps")
+        }
+    };
+    if report.components.is_empty() && report.files.is_empty() && report.tests.is_empty() {
+        println!("\nNothing depends on the changed files");
+        return Ok(());
+    }
+    println!("\nAffected components:");
+    for component in &report.components {
+        println!(
+            "  {} ({}) [{}]",
+            component.name,
+            component.file,
+            steps(component.distance)
+        );
+    }
+    println!("\nAffected files:");
+    for file in &report.files {
+        println!(
+            "  {} ({:.2}, {})",
+            file.path,
+            file.score,
+            steps(file.distance)
+        );
+    }
+    println!("\nAffected tests:");
+    for test in &report.tests {
+        println!(
+            "  {} ({:.2}, {})",
+            test.path,
+            test.score,
+            steps(test.distance)
+        );
+        for name in &test.tests {
+            println!("     - {name}");
+        }
+    }
+    Ok(())
+}
+
+fn write_focus(
+    result: &mut String,
+    mapper: &RepositoryMapper,
+    query: &GraphQuery,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let subgraph =
+        match mapper.subgraph(&query.seed, query.depth, query.direction, &query.edge_types) {
+            Ok(subgraph) => subgraph,
+            Err(e) => {
+                write!(result, "{e}\n\n")?;
+                return Ok(());
+            }
+        };
+    let component = mapper
+        .graph
+        .components
+        .get(&subgraph.seed)
+        .expect("The subgraph seed should be a component");
+
+    writeln!(result, "🎯 Focus: {}", component.name)?;
+    writeln!(
+        result,
+        "   Type: {:?} | Visibility: {:?}",
+        component.component_type, component.visibility
+    )?;
+    writeln!(result, "   File: {}", component.file_path.display())?;
+    writeln!(
+        result,
+        "   Complexity: cyclomatic {} | cognitive {} | nesting {} | {} params | {} SLOC{}",
+        component.complexity_score,
+        component.metrics.cognitive,
+        component.metrics.max_nesting,
+        component.metrics.parameters,
+        component.metrics.sloc,
+        approximate_note(component.metrics.approximate)
+    )?;
+    write!(
+        result,
+        "   Dependencies: {} | Used by: {}\n\n",
+        component.dependencies.len(),
+        component.dependents.len()
+    )?;
+
+    for (title, arrow, nodes) in [
+        ("Dependencies", "→", subgraph.upstream()),
+        ("Used by", "←", subgraph.downstream()),
+    ] {
+        if nodes.is_empty() {
+            continue;
+        }
+        writeln!(result, "{title}:")?;
+        for node in nodes {
+            write!(
+                result,
+                "  {arrow} {} ({})",
+                node.name,
+                node.file_path.display()
+            )?;
+            if node.distance > 1 {
+                write!(result, " [{} steps]", node.distance)?;
+            }
+            result.push('\n');
+        }
+        result.push('\n');
+    }
+    Ok(())
+}
+
+/// Layers, layering violations and hotspots for the text map
+fn write_architecture(
+    result: &mut String,
+    mapper: &RepositoryMapper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !mapper.insights.architectural_layers.is_empty() {
+        result.push_str("Layers:\n");
+        for layer in &mapper.insights.architectural_layers {
+            writeln!(result, "  {} ({} files)", layer.name, layer.files.len())?;
+        }
+        result.push('\n');
+    }
+
+    if !mapper.insights.layering_violations.is_empty() {
+        result.push_str("Layering Violations:\n");
+        for violation in &mapper.insights.layering_violations {
+            writeln!(
+                result,
+                "  {} ({}) -> {} ({})",
+                violation.from, violation.from_layer, violation.to, violation.to_layer
+            )?;
+        }
+        result.push('\n');
+    }
+
+    if !mapper.hotspots.is_empty() {
+        result.push_str("Hotspots:\n");
+        for (i, hotspot) in mapper.hotspots.iter().take(5).enumerate() {
+            writeln!(
+                result,
+                "  {}. {} (fan-in {}, {} recent commits)",
+                i + 1,
+                hotspot.path,
+                hotspot.fan_in,
+                hotspot.churn
+            )?;
+        }
+        result.push('\n');
+    }
+    Ok(())
+}
+
+/// Render the file-level import graph for `map --level file`
+fn render_file_map(
+    path: &Path,
+    mapper: &RepositoryMapper,
+    show_dependencies: bool,
+    show_order: bool,
+    json_format: bool,
+    focus_file: Option<&String>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let graph = &mapper.file_graph;
+    let focused = focus_file.and_then(|focus| {
+        graph
+            .files
+            .values()
+            .find(|node| node.path == *focus || node.path.ends_with(focus.as_str()))
+    });
+
+    if json_format {
+        let json_data = serde_json::json!({
+            "repository": path,
+            "level": MapLevel::File,
+            "files": graph.files.len(),
+            "imports": graph.edges.len(),
+            "cycles": graph.cycles,
+            "most_imported": graph.most_imported(10).iter().map(|node| &node.path).collect::<Vec<_>>(),
+            "processing_order": if show_order { Some(&graph.topological_order) } else { None },
+            "dependency_graph": if show_dependencies { Some(&graph.edges) } else { None },
+            "focus": focused,
+        });
+        return Ok(serde_json::to_string_pretty(&json_data)?);
+    }
+
+    let mut result = String::new();
+    result.push_str("File Map\n");
+    result.push_str("========\n\n");
+    writeln!(&mut result, "📍 {}", path.display())?;
+    write!(
+        &mut result,
+        "📊 {} files, {} imports, {} cycles\n\n",
+        graph.files.len(),
+        graph.edges.len(),
+        graph.cycles.len()
+    )?;
+
+    if let Some(focus) = focus_file {
+        if let Some(node) = focused {
+            writeln!(&mut result, "🎯 Focus: {}", node.path)?;
+            write!(
+                &mut result,
+                "   Imports: {} | Imported by: {}\n\n",
+                node.fan_out, node.fan_in
+            )?;
+            for import in &node.imports {
+                writeln!(&mut result, "  → {import}")?;
+            }
+            for importer in &node.imported_by {
+                writeln!(&mut result, "  ← {importer}")?;
+            }
+            result.push('\n');
+        } else {
+            write!(&mut result, "File '{focus}' not found\n\n")?;
+        }
+    }
+
+    result.push_str("Structure:\n");
+    for node in graph.files.values() {
+        writeln!(
+            &mut result,
+            "  {} ({}→{})",
+            node.path, node.fan_out, node.fan_in
+        )?;
+    }
+    result.push('\n');
+
+    if !graph.cycles.is_empty() {
+        result.push_str("Import Cycles:\n");
+        for cycle in &graph.cycles {
+            writeln!(&mut result, "  {}", cycle.join(" ↔ "))?;
+        }
+        result.push('\n');
+    }
+
+    let most_imported = graph.most_imported(5);
+    if !most_imported.is_empty() {
+        result.push_str("Most Imported:\n");
+        for (i, node) in most_imported.iter().enumerate() {
+            writeln!(&mut result, "  {}. {} ({})", i + 1, node.path, node.fan_in)?;
+        }
+        result.push('\n');
+    }
+
+    if show_order {
+        result.push_str("Processing Order (imports first):\n");
+        for (i, file) in graph.topological_order.iter().take(10).enumerate() {
+            writeln!(&mut result, "  {}. {file}", i + 1)?;
+        }
+        if graph.topological_order.len() > 10 {
+            writeln!(
+                &mut result,
+                "  ... and {} more",
+                graph.topological_order.len() - 10
+            )?;
+        }
+        result.push('\n');
+    }
+
+    if show_dependencies && !graph.edges.is_empty() {
+        result.push_str("Imports:\n");
+        for edge in graph.edges.iter().take(10) {
+            writeln!(&mut result, "  {} → {}", edge.from, edge.to)?;
+        }
+        if graph.edges.len() > 10 {
+            writeln!(&mut result, "  ... and {} more", graph.edges.len() - 10)?;
+        }
+    }
+
+    Ok(result)
+}
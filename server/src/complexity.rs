@@ -0,0 +1,420 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// Complexity measurements of a component, taken from its parse tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplexityMetrics {
+    /// One plus the number of decision points: branches, loops, cases, catches and `&&`/`||`
+    pub cyclomatic: u32,
+    /// Decision points weighted by how deeply they are nested, after `SonarSource`'s
+    /// cognitive complexity
+    pub cognitive: u32,
+    /// Deepest nesting of control structures and closures
+    pub max_nesting: u32,
+    /// Lines spanned, including blank lines and comments
+    pub loc: usize,
+    /// Lines containing code
+    pub sloc: usize,
+    /// Parameters, not counting `self`, `this` or `cls`
+    pub parameters: usize,
+    /// Estimated from the lines of a language without a bundled grammar, such as Kotlin
+    #[serde(default)]
+    pub approximate: bool,
+}
+
+/// Complexity of a source file, summed over its functions and methods
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileComplexity {
+    pub path: String,
+    pub functions: usize,
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    /// Cyclomatic complexity of the file's most complex function
+    pub max_cyclomatic: u32,
+    pub max_nesting: u32,
+    pub loc: usize,
+    pub sloc: usize,
+    /// Some of the functions' metrics are line-based estimates
+    #[serde(default)]
+    pub approximate: bool,
+}
+
+impl FileComplexity {
+    /// Add a function or method's metrics to the file totals
+    pub fn add(&mut self, metrics: &ComplexityMetrics) {
+        self.functions += 1;
+        self.cyclomatic += metrics.cyclomatic;
+        self.cognitive += metrics.cognitive;
+        self.max_cyclomatic = self.max_cyclomatic.max(metrics.cyclomatic);
+        self.max_nesting = self.max_nesting.max(metrics.max_nesting);
+        self.approximate |= metrics.approximate;
+    }
+
+    /// Cognitive complexity per line of code, for weighing complex code against its size
+    pub fn density(&self) -> f64 {
+        if self.sloc == 0 {
+            0.0
+        } else {
+            f64::from(self.cognitive) / f64::from(u32::try_from(self.sloc).unwrap_or(u32::MAX))
+        }
+    }
+}
+
+/// Marks metrics that are line-based estimates where they are listed
+pub(crate) fn approximate_note(approximate: bool) -> &'static str {
+    if approximate {
+        ", approximate"
+    } else {
+        ""
+    }
+}
+
+/// Measure a component. Functions get every metric; other components only their size,
+/// since their complexity is the sum of their members'.
+pub(crate) fn measure(node: Node, source: &[u8], function: bool) -> ComplexityMetrics {
+    let mut metrics = ComplexityMetrics {
+        loc: node.end_position().row - node.start_position().row + 1,
+        sloc: source_lines(node),
+        ..ComplexityMetrics::default()
+    };
+    if !function {
+        return metrics;
+    }
+
+    metrics.cyclomatic = 1;
+    metrics.parameters = count_parameters(node, source);
+    // `const f = () => ...` is measured from the arrow function, which is not nested
+    let body = node
+        .child_by_field_name("value")
+        .filter(|value| is_closure(value.kind()))
+        .unwrap_or(node);
+    let mut cursor = body.walk();
+    for child in body.named_children(&mut cursor) {
+        walk(child, source, 0, &mut metrics);
+    }
+    metrics
+}
+
+/// Number of lines holding something other than whitespace and comments
+pub(crate) fn source_lines(node: Node) -> usize {
+    let first = node.start_position().row;
+    let mut lines = vec![false; node.end_position().row - first + 1];
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node.kind().contains("comment") {
+            continue;
+        }
+        if node.child_count() == 0 {
+            for row in node.start_position().row..=node.end_position().row {
+                if let Some(line) = lines.get_mut(row - first) {
+                    *line = true;
+                }
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    lines.into_iter().filter(|&line| line).count()
+}
+
+fn is_branch(kind: &str) -> bool {
+    matches!(kind, "if_expression" | "if_statement")
+}
+
+fn is_loop(kind: &str) -> bool {
+    matches!(
+        kind,
+        "for_expression"
+            | "while_expression"
+            | "loop_expression"
+            | "for_statement"
+            | "for_in_statement"
+            | "enhanced_for_statement"
+            | "for_range_loop"
+            | "while_statement"
+            | "do_statement"
+    )
+}
+
+fn is_switch(kind: &str) -> bool {
+    matches!(
+        kind,
+        "match_expression"
+            | "match_statement"
+            | "switch_statement"
+            | "switch_expression"
+            | "expression_switch_statement"
+            | "type_switch_statement"
+            | "select_statement"
+    )
+}
+
+fn is_closure(kind: &str) -> bool {
+    matches!(
+        kind,
+        "closure_expression"
+            | "lambda"
+            | "lambda_expression"
+            | "arrow_function"
+            | "function_expression"
+            | "function"
+            | "generator_function"
+            | "func_literal"
+            // Nested named functions are not components of their own
+            | "function_item"
+            | "function_definition"
+            | "function_declaration"
+    )
+}
+
+/// A switch or match case other than the default one
+fn is_case(node: Node, source: &[u8]) -> bool {
+    let text = |node: Node| {
+        node.utf8_text(source)
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    match node.kind() {
+        "switch_case" | "expression_case" | "type_case" | "communication_case" => true,
+        "match_arm" => node
+            .child_by_field_name("pattern")
+            .is_some_and(|pattern| text(pattern) != "_"),
+        "case_clause" => {
+            let mut cursor = node.walk();
+            let is_wildcard = node
+                .named_children(&mut cursor)
+                .find(|child| child.kind() == "case_pattern")
+                .is_some_and(|pattern| text(pattern) == "_");
+            !is_wildcard
+        }
+        "case_statement" => node.child_by_field_name("value").is_some(),
+        "switch_label" => text(node).starts_with("case"),
+        _ => false,
+    }
+}
+
+/// The `&&`/`||`/`and`/`or` operator of a boolean expression
+fn boolean_operator<'a>(node: Node, source: &'a [u8]) -> Option<&'a str> {
+    if !matches!(node.kind(), "binary_expression" | "boolean_operator") {
+        return None;
+    }
+    let operator = node
+        .child_by_field_name("operator")?
+        .utf8_text(source)
+        .ok()?;
+    matches!(operator, "&&" | "||" | "and" | "or").then_some(operator)
+}
+
+/// Whether an `if` is the `else if` branch of another
+fn is_else_if(node: Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        parent.kind() == "else_clause"
+            || (is_branch(parent.kind()) && parent.child_by_field_name("alternative") == Some(node))
+    })
+}
+
+fn walk(node: Node, source: &[u8], nesting: u32, metrics: &mut ComplexityMetrics) {
+    let kind = node.kind();
+    let mut inner = nesting;
+    if is_branch(kind) {
+        metrics.cyclomatic += 1;
+        if is_else_if(node) {
+            metrics.cognitive += 1;
+        } else {
+            metrics.cognitive += 1 + nesting;
+            inner += 1;
+        }
+        // Java and Go attach a plain `else` block directly as the alternative
+        if node
+            .child_by_field_name("alternative")
+            .is_some_and(|alternative| {
+                !is_branch(alternative.kind())
+                    && !matches!(alternative.kind(), "else_clause" | "elif_clause")
+            })
+        {
+            metrics.cognitive += 1;
+        }
+    } else if is_loop(kind)
+        || matches!(
+            kind,
+            "ternary_expression"
+                | "conditional_expression"
+                | "catch_clause"
+                | "except_clause"
+                | "except_group_clause"
+        )
+    {
+        metrics.cyclomatic += 1;
+        metrics.cognitive += 1 + nesting;
+        inner += 1;
+    } else if is_switch(kind) {
+        metrics.cognitive += 1 + nesting;
+        inner += 1;
+    } else if is_closure(kind) {
+        inner += 1;
+    } else if kind == "elif_clause" {
+        metrics.cyclomatic += 1;
+        metrics.cognitive += 1;
+    } else if kind == "else_clause" {
+        let mut cursor = node.walk();
+        let else_if = node
+            .named_children(&mut cursor)
+            .any(|child| is_branch(child.kind()));
+        if !else_if {
+            metrics.cognitive += 1;
+        }
+    } else if matches!(kind, "for_in_clause" | "if_clause") {
+        // Comprehension clauses
+        metrics.cyclomatic += 1;
+    } else if is_case(node, source) {
+        metrics.cyclomatic += 1;
+    } else if let Some(operator) = boolean_operator(node, source) {
+        metrics.cyclomatic += 1;
+        // A run of the same operator, `a && b && c`, counts once
+        let continues_run = node
+            .parent()
+            .and_then(|parent| boolean_operator(parent, source))
+            .is_some_and(|parent| parent == operator);
+        if !continues_run {
+            metrics.cognitive += 1;
+        }
+    }
+
+    metrics.max_nesting = metrics.max_nesting.max(inner);
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        walk(child, source, inner, metrics);
+    }
+}
+
+fn count_parameters(node: Node, source: &[u8]) -> usize {
+    // `x => x`
+    if node.child_by_field_name("parameter").is_some() {
+        return 1;
+    }
+    if let Some(list) = node.child_by_field_name("parameters") {
+        let mut cursor = list.walk();
+        return list
+            .named_children(&mut cursor)
+            .map(|parameter| parameter_count(parameter, source))
+            .sum();
+    }
+    // Arrow functions assigned to variables, and C declarators
+    ["value", "declarator"]
+        .iter()
+        .find_map(|field| node.child_by_field_name(field))
+        .map_or(0, |inner| count_parameters(inner, source))
+}
+
+fn parameter_count(parameter: Node, source: &[u8]) -> usize {
+    let text = parameter.utf8_text(source).unwrap_or_default();
+    match parameter.kind() {
+        "self_parameter"
+        | "receiver_parameter"
+        | "attribute_item"
+        | "keyword_separator"
+        | "positional_separator"
+        | "comment" => 0,
+        "identifier" if matches!(text, "self" | "cls") => 0,
+        "parameter_declaration" => {
+            // Go declares several names at once: `a, b int`
+            let mut cursor = parameter.walk();
+            let names = parameter
+                .children_by_field_name("name", &mut cursor)
+                .count();
+            if names > 0 {
+                names
+            } else {
+                // C's `f(void)` takes no parameters
+                usize::from(text.trim() != "void")
+            }
+        }
+        _ => 1,
+    }
+}
+
+/// Line-based estimate for languages without a bundled grammar. `lines` are the lines a
+/// component spans, from its declaration to its closing brace, with strings and comments
+/// already blanked out by the caller.
+pub(crate) fn estimate(lines: &[&str], function: bool) -> ComplexityMetrics {
+    let code: Vec<&str> = lines
+        .iter()
+        .map(|line| line.split("//").next().unwrap_or(line).trim())
+        .collect();
+    let mut metrics = ComplexityMetrics {
+        loc: lines.len(),
+        sloc: code
+            .iter()
+            .filter(|line| !line.is_empty() && !line.starts_with('*') && !line.starts_with("/*"))
+            .count(),
+        approximate: true,
+        ..ComplexityMetrics::default()
+    };
+    if !function {
+        return metrics;
+    }
+
+    let decision = Regex::new(r"\b(?:if|for|while|when|catch)\b|&&|\|\|")
+        .expect("Decision regex should be valid");
+    metrics.cyclomatic = 1;
+    metrics.parameters = estimate_parameters(&code.join(" "));
+    // Depth 1 is the function body. Braces and decisions are taken in the order they are
+    // written, so `} else if (...) {` is not counted as nested in the closed block.
+    let mut depth = 0u32;
+    for line in &code {
+        let mut decisions = decision.find_iter(line).peekable();
+        for (at, c) in line.char_indices() {
+            while let Some(found) = decisions.next_if(|found| found.start() == at) {
+                count_decision(found.as_str(), depth.saturating_sub(1), &mut metrics);
+            }
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+    metrics
+}
+
+fn count_decision(decision: &str, nesting: u32, metrics: &mut ComplexityMetrics) {
+    if matches!(decision, "&&" | "||") {
+        metrics.cyclomatic += 1;
+        metrics.cognitive += 1;
+    } else {
+        // `when` is a switch: its branches are not counted
+        if decision != "when" {
+            metrics.cyclomatic += 1;
+        }
+        metrics.cognitive += 1 + nesting;
+        metrics.max_nesting = metrics.max_nesting.max(nesting + 1);
+    }
+}
+
+/// Parameters in the first parenthesized list of a declaration
+fn estimate_parameters(declaration: &str) -> usize {
+    let Some(open) = declaration.find('(') else {
+        return 0;
+    };
+    let mut depth = 0usize;
+    let mut count = 0;
+    let mut empty = true;
+    for c in declaration[open + 1..].chars() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' | '>' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => count += 1,
+            c if !c.is_whitespace() => empty = false,
+            _ => {}
+        }
+    }
+    if empty {
+        0
+    } else {
+        count + 1
+    }
+}
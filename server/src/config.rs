@@ -1,15 +1,89 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
-    pub projects: HashMap<String, PathBuf>,
+    pub projects: HashMap<String, ProjectConfig>,
     pub port: u16,
     pub listen_address: String,
     #[serde(default)]
     pub api_keys: HashMap<String, String>, // Map from name to hashed API key
+    #[serde(default)]
+    pub admin_keys: HashSet<String>, // Names of API keys allowed to use admin endpoints
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+}
+
+/// A registered project: either a single directory or several roots combined into one
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ProjectConfig {
+    Path(PathBuf),
+    MultiRoot { roots: Vec<ProjectRoot> },
+}
+
+/// One root directory of a multi-root project
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ProjectRoot {
+    pub path: PathBuf,
+    /// Prepended to this root's file paths in output, e.g. `backend/src/main.rs`
+    #[serde(default)]
+    pub prefix: String,
+}
+
+impl ProjectConfig {
+    /// Returns the roots of the project; a single-path project has one root with no prefix.
+    pub fn roots(&self) -> Vec<ProjectRoot> {
+        match self {
+            ProjectConfig::Path(path) => vec![ProjectRoot {
+                path: path.clone(),
+                prefix: String::new(),
+            }],
+            ProjectConfig::MultiRoot { roots } => roots.clone(),
+        }
+    }
+
+    /// Human-readable description of where the project lives on disk.
+    pub fn display_path(&self) -> String {
+        match self {
+            ProjectConfig::Path(path) => path.to_string_lossy().into_owned(),
+            ProjectConfig::MultiRoot { roots } => roots
+                .iter()
+                .map(|root| format!("{}={}", root.prefix, root.path.display()))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+impl From<PathBuf> for ProjectConfig {
+    fn from(path: PathBuf) -> Self {
+        ProjectConfig::Path(path)
+    }
+}
+
+/// Limits applied to the expensive endpoints that walk the project on disk
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Steady request rate allowed per API key (0 disables per-key limiting)
+    pub requests_per_minute: u32,
+    /// Number of requests a key may issue back-to-back before being throttled
+    pub burst: u32,
+    /// Expensive operations allowed to run at once across all clients (0 disables the cap)
+    pub max_concurrent_operations: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            requests_per_minute: 30,
+            burst: 10,
+            max_concurrent_operations: 4,
+        }
+    }
 }
 
 impl Default for Config {
@@ -19,6 +93,8 @@ impl Default for Config {
             port: 3030,
             listen_address: "127.0.0.1".to_string(),
             api_keys: HashMap::new(),
+            admin_keys: HashSet::new(),
+            rate_limits: RateLimitConfig::default(),
         }
     }
 }
@@ -39,17 +115,42 @@ impl Config {
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let config_path = Config::get_config_path()?;
+        self.save_to(&Config::get_config_path()?)
+    }
+
+    pub fn save_to(&self, config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         let config_str = serde_json::to_string_pretty(self)?;
-        fs::write(config_path, config_str)?;
+        // Write to a temporary file and rename so a running server never sees a partial file
+        let tmp_path = config_path.with_extension("json.tmp");
+        fs::write(&tmp_path, config_str)?;
+        fs::rename(tmp_path, config_path)?;
+        Ok(())
+    }
+
+    /// Checks that a configuration is safe to swap into a running server.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listen_address.trim().is_empty() {
+            return Err("Listen address must not be empty".to_string());
+        }
+        if self.port == 0 {
+            return Err("Port must not be 0".to_string());
+        }
+        if self.api_keys.is_empty() {
+            return Err("At least one API key must be configured".to_string());
+        }
         Ok(())
     }
 
     pub fn add_project(&mut self, name: String, path: PathBuf) {
-        self.projects.insert(name, path);
+        self.projects.insert(name, ProjectConfig::Path(path));
+    }
+
+    pub fn add_multi_root_project(&mut self, name: String, roots: Vec<ProjectRoot>) {
+        self.projects
+            .insert(name, ProjectConfig::MultiRoot { roots });
     }
 
-    pub fn remove_project(&mut self, name: &str) -> Option<PathBuf> {
+    pub fn remove_project(&mut self, name: &str) -> Option<ProjectConfig> {
         self.projects.remove(name)
     }
 
@@ -59,9 +160,18 @@ impl Config {
 
     pub fn remove_api_key(&mut self, name: &str) {
         self.api_keys.remove(name);
+        self.admin_keys.remove(name);
+    }
+
+    pub fn grant_admin(&mut self, name: &str) {
+        self.admin_keys.insert(name.to_string());
+    }
+
+    pub fn is_admin_key(&self, name: &str) -> bool {
+        self.admin_keys.contains(name)
     }
 
-    fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    pub(crate) fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
         let mut path = dirs::config_dir().ok_or("Could not find config directory")?;
         path.push("contexter");
         fs::create_dir_all(&path)?;
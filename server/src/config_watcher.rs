@@ -0,0 +1,86 @@
+use crate::config::Config;
+use crate::server::AppState;
+use actix_web::web;
+use log::{debug, error, info, warn};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Errors that prevent a new configuration from being swapped in.
+///
+/// On any of these the running configuration is left untouched.
+#[derive(Debug, Error)]
+pub enum ReloadError {
+    #[error("Config reloading is not available: the server has no config file path")]
+    Unavailable,
+    #[error("Failed to read config file: {0}")]
+    Read(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Re-read the config file and atomically replace the server's configuration.
+///
+/// The new configuration is parsed and validated before the write lock is taken, so a
+/// broken file leaves the previous configuration in place.
+pub async fn reload_config(state: &AppState) -> Result<Config, ReloadError> {
+    let path = state
+        .config_path
+        .as_deref()
+        .ok_or(ReloadError::Unavailable)?;
+    let new_config = read_config(path)?;
+    new_config.validate().map_err(ReloadError::Invalid)?;
+
+    let mut config = state.config.write().await;
+    if config.port != new_config.port || config.listen_address != new_config.listen_address {
+        warn!("Listen address or port changed; restart the server for this to take effect");
+    }
+    *config = new_config.clone();
+    drop(config);
+
+    info!(
+        "Configuration reloaded: {} projects, {} API keys",
+        new_config.projects.len(),
+        new_config.api_keys.len()
+    );
+    Ok(new_config)
+}
+
+fn read_config(path: &Path) -> Result<Config, ReloadError> {
+    let config_str = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&config_str)?)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Poll the config file and reload it whenever its modification time changes.
+pub fn watch_config(state: web::Data<AppState>, interval: Duration) {
+    let Some(path) = state.config_path.clone() else {
+        debug!("No config file path; not watching for changes");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut last_modified = modified_time(&path);
+        let mut ticker = actix_web::rt::time::interval(interval);
+        info!("Watching {} for configuration changes", path.display());
+
+        loop {
+            ticker.tick().await;
+            let modified = modified_time(&path);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            if let Err(e) = reload_config(&state).await {
+                error!("Keeping previous configuration: {e}");
+            }
+        }
+    });
+}
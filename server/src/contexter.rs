@@ -1,13 +1,61 @@
+use crate::dedup::{strip_license_header, DedupOptions, Deduplicator, Duplicate};
+use crate::truncate::{truncate, FileLimits};
 use ignore::WalkBuilder;
 use regex::Regex;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::fs::{metadata, read_to_string};
-use std::hash::{Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
 
+/// Ready-made selections of what to gather from the matched files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GatherPreset {
+    /// Public components without doc comments or with undocumented parameters, with the
+    /// source of each, for "write docs for these" prompts
+    Undocumented,
+}
+
+impl std::str::FromStr for GatherPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "undocumented" => Ok(Self::Undocumented),
+            _ => Err(format!("Unknown preset '{s}', expected undocumented")),
+        }
+    }
+}
+
+/// How files are put together into one context
+#[derive(Debug, Clone, Default)]
+pub struct ConcatOptions {
+    /// Size caps each file is elided down to
+    pub limits: FileLimits,
+    /// Keep files in the order given, e.g. by [`crate::ranking::Ranking::rank`], instead of
+    /// grouping them by section and sorting them by path. A section banner then opens
+    /// wherever the section changes.
+    pub ranked: bool,
+    /// Near-duplicate collapsing and license header stripping
+    pub dedup: DedupOptions,
+}
+
+/// A file on disk paired with the path shown for it in generated context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub display_path: String,
+}
+
+impl From<PathBuf> for ProjectFile {
+    fn from(path: PathBuf) -> Self {
+        let display_path = path.display().to_string();
+        ProjectFile { path, display_path }
+    }
+}
+
 /// Gathers relevant files from a directory based on specified extensions and exclusion patterns.
 ///
 /// # Arguments
@@ -67,7 +115,8 @@ pub fn gather_relevant_files(
             Ok(entry) => {
                 if entry.file_type().is_some_and(|ft| ft.is_file()) {
                     let path = entry.path();
-                    if !is_excluded(path, &exclude_patterns)
+                    let relative_path = path.strip_prefix(&project_dir).unwrap_or(path);
+                    if !is_excluded(relative_path, &exclude_patterns)
                         && !is_likely_binary(path)?
                         && (extensions.is_empty()
                             || extensions
@@ -118,13 +167,6 @@ fn is_likely_binary(path: &Path) -> io::Result<bool> {
     Ok(buffer[..bytes_read].contains(&0))
 }
 
-/// Calculates a hash for the given value.
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
-}
-
 /// Concatenates the contents of multiple files, categorizing them and removing duplicates.
 ///
 /// # Arguments
@@ -134,82 +176,188 @@ fn calculate_hash<T: Hash>(t: &T) -> u64 {
 /// # Returns
 ///
 /// A Result containing a tuple of the concatenated content string and a vector of processed filenames.
-pub fn concatenate_files(mut files: Vec<PathBuf>) -> io::Result<(String, Vec<String>)> {
+pub fn concatenate_files(files: Vec<PathBuf>) -> io::Result<(String, Vec<String>)> {
+    concatenate_project_files(files.into_iter().map(ProjectFile::from).collect())
+}
+
+/// Concatenates project files like [`concatenate_files`], labelling each file with its
+/// `display_path` instead of its location on disk.
+pub fn concatenate_project_files(files: Vec<ProjectFile>) -> io::Result<(String, Vec<String>)> {
+    concatenate_project_files_with(files, &ConcatOptions::default(), |_, content| Some(content))
+}
+
+/// Where a file goes in concatenated context, in output order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Section {
+    Configuration,
+    Documentation,
+    Source,
+    Tests,
+}
+
+impl Section {
+    fn of(path: &Path) -> Self {
+        let file_ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match file_ext.as_str() {
+            "toml" | "json" | "yaml" | "yml" => Self::Configuration,
+            "md" | "txt" => Self::Documentation,
+            _ if file_ext.contains("test") => Self::Tests,
+            _ => Self::Source,
+        }
+    }
+
+    /// The banner starting the section
+    pub(crate) fn banner(self) -> String {
+        let name = match self {
+            Self::Configuration => "Configuration Files",
+            Self::Documentation => "Documentation",
+            Self::Source => "Source Files",
+            Self::Tests => "Tests",
+        };
+        format!("{SEPARATOR}Section: {name}\n{SEPARATOR}")
+    }
+}
+
+/// A gathered file with its metadata header, ready to be concatenated
+#[derive(Debug, Clone)]
+pub(crate) struct RenderedFile {
+    pub path: PathBuf,
+    pub display_path: String,
+    pub section: Section,
+    /// Metadata lines, opened by a separator and not yet closed by one
+    pub header: String,
+    pub content: String,
+}
+
+impl RenderedFile {
+    /// The header closed by a separator, then the content
+    pub(crate) fn render(&self) -> String {
+        format!("{}{SEPARATOR}{}\n", self.header, self.content)
+    }
+}
+
+pub(crate) const SEPARATOR: &str = "========================================\n";
+
+/// Concatenates project files like [`concatenate_project_files`], passing each file's path
+/// and content through `transform` first and eliding it down to `options.limits`. Files
+/// `transform` returns `None` for are left out.
+pub fn concatenate_project_files_with<F>(
+    files: Vec<ProjectFile>,
+    options: &ConcatOptions,
+    transform: F,
+) -> io::Result<(String, Vec<String>)>
+where
+    F: FnMut(&Path, String) -> Option<String>,
+{
+    let rendered = render_project_files(files, options, transform)?;
     let mut content = String::new();
-    let mut filenames = Vec::new();
-    let mut seen_hashes = HashSet::new();
+    let mut section = None;
+    for file in &rendered {
+        if section != Some(file.section) {
+            section = Some(file.section);
+            content.push_str(&file.section.banner());
+        }
+        content.push_str(&file.render());
+    }
+    let filenames = rendered.into_iter().map(|file| file.display_path).collect();
+    Ok((content, filenames))
+}
 
-    // Sort files alphabetically by their file name
-    files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+/// Reads, deduplicates, transforms and elides project files, and renders each with its
+/// metadata header. Files come back in output order: ranked, or by section and then by path.
+/// A duplicate is left out, and listed in the header of the first file it duplicates.
+pub(crate) fn render_project_files<F>(
+    mut files: Vec<ProjectFile>,
+    options: &ConcatOptions,
+    mut transform: F,
+) -> io::Result<Vec<RenderedFile>>
+where
+    F: FnMut(&Path, String) -> Option<String>,
+{
+    options.dedup.validate()?;
+    let mut rendered: Vec<RenderedFile> = Vec::new();
+    let mut deduplicator = Deduplicator::new(&options.dedup);
+    let mut duplicates: BTreeMap<usize, Vec<Duplicate>> = BTreeMap::new();
 
-    // Initialize strings for different file categories
-    let mut config_files = String::new();
-    let mut source_files = String::new();
-    let mut doc_files = String::new();
-    let mut test_files = String::new();
+    if !options.ranked {
+        files.sort_by(|a, b| a.display_path.cmp(&b.display_path));
+    }
 
     // Process each file
-    for path in files {
-        let file_content = read_to_string(&path)?;
-        let file_hash = calculate_hash(&file_content);
+    for ProjectFile { path, display_path } in files {
+        let mut file_content = read_to_string(&path)?;
+        let mut stripped_lines = None;
+        if options.dedup.strip_license_headers {
+            if let Some((stripped, lines)) = strip_license_header(&file_content) {
+                file_content = stripped;
+                stripped_lines = Some(lines);
+            }
+        }
 
-        // Only process the file if its content hasn't been seen before
-        if !seen_hashes.contains(&file_hash) {
-            seen_hashes.insert(file_hash);
+        // Only process the file if it doesn't duplicate one already kept
+        let key = deduplicator.key(&file_content);
+        if let Some((kept, likeness)) = deduplicator.find(&key) {
+            duplicates.entry(kept).or_default().push(Duplicate {
+                display_path,
+                likeness,
+            });
+        } else {
+            let Some(file_content) = transform(&path, file_content) else {
+                continue;
+            };
+            let (content, truncation) = truncate(&path, &file_content, &options.limits);
 
             // Prepare file metadata
-            let mut file_info = String::new();
+            let mut header = String::new();
             let metadata = metadata(&path)?;
             write!(
-                &mut file_info,
-                "========================================\n\
+                &mut header,
+                "{SEPARATOR}\
                 File: {}\n\
                 Size: {} bytes\n\
-                Last Modified: {:?}\n\
-                ========================================\n",
-                path.display(),
+                Last Modified: {:?}\n",
+                display_path,
                 metadata.len(),
                 metadata.modified()?
             )
-            .unwrap();
-
-            file_info.push_str(&file_content);
-            file_info.push('\n');
-
-            // Categorize the file based on its extension
-            let file_ext = path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            match file_ext.as_str() {
-                "toml" | "json" | "yaml" | "yml" => config_files.push_str(&file_info),
-                "rs" => source_files.push_str(&file_info),
-                "md" | "txt" => doc_files.push_str(&file_info),
-                _ if file_ext.contains("test") => test_files.push_str(&file_info),
-                _ => source_files.push_str(&file_info),
+            .expect("Writing to a String should not fail");
+            if let Some(lines) = stripped_lines {
+                writeln!(&mut header, "License Header: {lines} line(s) stripped")
+                    .expect("Writing to a String should not fail");
+            }
+            if let Some(truncation) = truncation {
+                writeln!(&mut header, "Truncated: {truncation}")
+                    .expect("Writing to a String should not fail");
             }
 
-            filenames.push(path.to_string_lossy().to_string());
+            deduplicator.insert(key, rendered.len());
+            rendered.push(RenderedFile {
+                section: Section::of(&path),
+                path,
+                display_path,
+                header,
+                content,
+            });
         }
     }
 
-    // Append sections in the desired order
-    let sections = [
-        ("Configuration Files", &config_files),
-        ("Documentation", &doc_files),
-        ("Source Files", &source_files),
-        ("Tests", &test_files),
-    ];
-
-    // Construct the final content string
-    for (section_name, section_content) in &sections {
-        if !section_content.is_empty() {
-            use std::fmt::Write;
-            write!(&mut content, "========================================\nSection: {section_name}\n========================================\n").unwrap();
-            content.push_str(section_content);
-        }
+    for (kept, duplicates) in duplicates {
+        let duplicates: Vec<String> = duplicates.iter().map(ToString::to_string).collect();
+        writeln!(
+            &mut rendered[kept].header,
+            "Duplicates: {}",
+            duplicates.join(", ")
+        )
+        .expect("Writing to a String should not fail");
     }
 
-    Ok((content, filenames))
+    // Ranked files stay in rank order, so the most important come first whatever their section
+    if !options.ranked {
+        rendered.sort_by_key(|file| file.section);
+    }
+    Ok(rendered)
 }
@@ -0,0 +1,569 @@
+use crate::repo_mapper::{CodeComponent, ComponentType, RepositoryMapper, Visibility};
+use crate::repo_settings::DeadCodeSettings;
+use crate::syntax::use_tree_bindings;
+use crate::test_correlation::{in_test_module, test_subject};
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+/// Rust traits imported for their methods or macros, which never name them, besides the
+/// `*Ext` extension traits
+const METHOD_TRAITS: &[&str] = &[
+    "BufRead",
+    "BuildHasher",
+    "Digest",
+    "FromStr",
+    "Hash",
+    "Hasher",
+    "IntoDeserializer",
+    "Itertools",
+    "Read",
+    "Rng",
+    "RngCore",
+    "Seek",
+    "SerializeMap",
+    "SerializeSeq",
+    "SerializeStruct",
+    "StructOpt",
+    "ToOwned",
+    "ToString",
+    "VariantAccess",
+    "Write",
+];
+
+/// Attributes and decorators that do not hand a function to a framework
+const INERT_ATTRIBUTES: &[&str] = &[
+    "abstractmethod",
+    "allow",
+    "cfg",
+    "cfg_attr",
+    "classmethod",
+    "Deprecated",
+    "deny",
+    "deprecated",
+    "doc",
+    "expect",
+    "inline",
+    "must_use",
+    "Nullable",
+    "rustfmt",
+    "staticmethod",
+    "SuppressWarnings",
+    "warn",
+];
+
+/// A component nothing depends on or mentions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnusedComponent {
+    pub id: String,
+    pub name: String,
+    pub component_type: ComponentType,
+    /// Public components are unused exports
+    pub visibility: Visibility,
+    pub file: String,
+    pub line: usize,
+}
+
+/// An imported name the importing file never mentions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnusedImport {
+    pub file: String,
+    pub line: usize,
+    pub name: String,
+    /// The imported path as written, e.g. `std::collections::HashMap` or `os.path`
+    pub path: String,
+}
+
+/// Whether the Rust item at `line` of `content` is inside an `impl Trait for Type` block
+pub(crate) fn in_trait_impl(content: &str, line: usize) -> bool {
+    let above: Vec<&str> = content.lines().take(line.saturating_sub(1)).collect();
+    above
+        .into_iter()
+        .rev()
+        .map(str::trim_start)
+        .find(|line| line.starts_with("impl") && !line.starts_with("impl_"))
+        .is_some_and(|line| line.contains(" for "))
+}
+
+/// Components and imports that look unused
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadCode {
+    pub components: Vec<UnusedComponent>,
+    pub imports: Vec<UnusedImport>,
+}
+
+/// Find components with no dependents whose name is not mentioned anywhere else and
+/// that are not entry points, and imports their file never mentions
+pub(crate) fn find_dead_code(mapper: &RepositoryMapper) -> DeadCode {
+    let identifier = Regex::new(r"[A-Za-z_$][A-Za-z0-9_$]*").expect("Invalid identifier regex");
+    let mut sources: BTreeMap<String, String> = BTreeMap::new();
+    for file in mapper.files.values() {
+        match std::fs::read_to_string(file) {
+            Ok(content) => {
+                sources.insert(file.display().to_string(), content);
+            }
+            Err(e) => debug!("Skipping unreadable file {}: {e}", file.display()),
+        }
+    }
+
+    let entry_points = EntryPoints::new(mapper, &sources);
+    let mentions = count_mentions(mapper, &sources, &identifier);
+    let mut components: Vec<UnusedComponent> = mapper
+        .graph
+        .components
+        .values()
+        .filter(|component| {
+            component.component_type != ComponentType::Module
+                && component.dependents.is_empty()
+                && mentions.get(component.name.as_str()).copied().unwrap_or(0) == 0
+                && !entry_points.contains(mapper, component)
+        })
+        .map(|component| UnusedComponent {
+            id: component.id.clone(),
+            name: component.name.clone(),
+            component_type: component.component_type.clone(),
+            visibility: component.visibility.clone(),
+            file: component.file_path.display().to_string(),
+            line: component.start_line,
+        })
+        .collect();
+    components.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+    // Traits of the repository are imported for their methods too
+    let traits: HashSet<&str> = mapper
+        .graph
+        .components
+        .values()
+        .filter(|component| component.component_type == ComponentType::Interface)
+        .map(|component| component.name.as_str())
+        .collect();
+    let mut imports = Vec::new();
+    for (file, content) in &sources {
+        let Some(settings) = mapper.settings_for(Path::new(file)) else {
+            continue;
+        };
+        let is_rust = Path::new(file).extension().is_some_and(|ext| ext == "rs");
+        imports.extend(
+            unused_imports(file, content, &identifier)
+                .into_iter()
+                .filter(|import| !(is_rust && traits.contains(import.name.as_str())))
+                .filter(|import| !settings.dead_code.is_used_import(&import.name)),
+        );
+    }
+    DeadCode {
+        components,
+        imports,
+    }
+}
+
+/// How often each name appears outside the definitions of components with that name
+fn count_mentions<'a>(
+    mapper: &RepositoryMapper,
+    sources: &'a BTreeMap<String, String>,
+    identifier: &Regex,
+) -> HashMap<&'a str, usize> {
+    let mut definitions: HashSet<(&str, usize, &str)> = HashSet::new();
+    for component in mapper.graph.components.values() {
+        let Some((file, content)) =
+            sources.get_key_value(&component.file_path.display().to_string())
+        else {
+            continue;
+        };
+        // The first line of the component naming it, after any attributes or decorators
+        let line = content
+            .lines()
+            .enumerate()
+            .skip(component.start_line.saturating_sub(1))
+            .take(component.end_line.saturating_sub(component.start_line) + 1)
+            .find(|(_, line)| {
+                identifier
+                    .find_iter(line)
+                    .any(|token| token.as_str() == component.name)
+            })
+            .map(|(idx, _)| idx + 1);
+        if let Some(line) = line {
+            definitions.insert((file.as_str(), line, component.name.as_str()));
+        }
+    }
+
+    let mut mentions: HashMap<&str, usize> = HashMap::new();
+    for (file, content) in sources {
+        for (idx, line) in content.lines().enumerate() {
+            for token in identifier.find_iter(line) {
+                let token = token.as_str();
+                if !definitions.contains(&(file.as_str(), idx + 1, token)) {
+                    *mentions.entry(token).or_default() += 1;
+                }
+            }
+        }
+    }
+    mentions
+}
+
+/// Components run by something other than a call in the repository
+struct EntryPoints<'a> {
+    sources: &'a BTreeMap<String, String>,
+    /// Names of methods declared by traits and interfaces in the repository
+    interface_methods: HashSet<&'a str>,
+    /// Names a Rust `lib.rs` re-exports with `pub use`, and the files of its `pub mod`s
+    exported_names: HashSet<String>,
+    exported_modules: Vec<std::path::PathBuf>,
+}
+
+impl<'a> EntryPoints<'a> {
+    fn new(mapper: &'a RepositoryMapper, sources: &'a BTreeMap<String, String>) -> Self {
+        let interface_methods = mapper
+            .graph
+            .components
+            .values()
+            .filter(|component| {
+                component
+                    .parent
+                    .as_ref()
+                    .and_then(|parent| mapper.graph.components.get(parent))
+                    .is_some_and(|parent| parent.component_type == ComponentType::Interface)
+            })
+            .map(|component| component.name.as_str())
+            .collect();
+
+        let public_use = Regex::new(r"(?m)^\s*pub\s+use\s+([^;]+);").expect("Invalid use regex");
+        let public_mod = Regex::new(r"(?m)^\s*pub\s+mod\s+(\w+)\s*;").expect("Invalid mod regex");
+        let mut exported_names = HashSet::new();
+        let mut exported_modules = Vec::new();
+        for (file, content) in sources {
+            let path = Path::new(file);
+            if path.file_name().and_then(|name| name.to_str()) != Some("lib.rs") {
+                continue;
+            }
+            let dir = path.parent().unwrap_or(path);
+            exported_modules.push(path.to_path_buf());
+            for captures in public_use.captures_iter(content) {
+                exported_names.extend(
+                    use_tree_bindings(&captures[1])
+                        .into_iter()
+                        .map(|(_, name)| name),
+                );
+            }
+            for captures in public_mod.captures_iter(content) {
+                exported_modules.push(dir.join(format!("{}.rs", &captures[1])));
+                exported_modules.push(dir.join(&captures[1]));
+            }
+        }
+
+        Self {
+            sources,
+            interface_methods,
+            exported_names,
+            exported_modules,
+        }
+    }
+
+    fn contains(&self, mapper: &RepositoryMapper, component: &CodeComponent) -> bool {
+        let file = component.file_path.display().to_string();
+        let parent = component
+            .parent
+            .as_ref()
+            .and_then(|parent| mapper.graph.components.get(parent));
+        let is_method = component.component_type == ComponentType::Method;
+        let extension = component
+            .file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        let special = component.name == "main"
+            || (component.name.starts_with("__") && component.name.ends_with("__"))
+            || component.name == "constructor"
+            || parent.is_some_and(|parent| {
+                parent.name == component.name || parent.component_type == ComponentType::Interface
+            });
+        let is_test = test_subject(mapper.relative_to_root(&component.file_path)).is_some()
+            || in_test_module(mapper, component);
+        // Trait and interface implementations are called through the trait; Go interfaces
+        // are satisfied implicitly
+        let implements = is_method
+            && (self.interface_methods.contains(component.name.as_str())
+                || extension == "go"
+                || (extension == "rs" && self.in_trait_impl(&file, component.start_line)));
+
+        special
+            || is_test
+            || implements
+            || self.is_registered(&file, component.start_line)
+            || self.is_exported(mapper, component)
+            || mapper
+                .settings_for(&component.file_path)
+                .is_some_and(|settings| {
+                    is_configured_entry_point(&settings.dead_code, mapper, component)
+                })
+    }
+
+    /// Whether the Rust method at `line` is inside an `impl Trait for Type` block
+    fn in_trait_impl(&self, file: &str, line: usize) -> bool {
+        self.sources
+            .get(file)
+            .is_some_and(|content| in_trait_impl(content, line))
+    }
+
+    /// Whether the component carries an attribute or decorator, other than lints and the
+    /// like, through which a framework runs it: `#[test]`, `#[get("/")]`, `@app.route(...)`
+    fn is_registered(&self, file: &str, line: usize) -> bool {
+        let Some(content) = self.sources.get(file) else {
+            return false;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let start = line.saturating_sub(1).min(lines.len());
+        // The component may start at its first attribute, or just after its attributes
+        let below = lines[start..]
+            .iter()
+            .map(|line| line.trim_start())
+            .take_while(|line| is_attribute(line));
+        let above = lines[..start]
+            .iter()
+            .rev()
+            .map(|line| line.trim_start())
+            .take_while(|line| is_attribute(line) || line.starts_with("//"));
+        below
+            .chain(above)
+            .filter(|line| is_attribute(line))
+            .any(|line| !INERT_ATTRIBUTES.contains(&attribute_name(line)))
+    }
+
+    /// Whether a public Rust component is part of a library's API: in `lib.rs`, in a module
+    /// it declares with `pub mod`, or re-exported with `pub use`, or a member of such a type
+    fn is_exported(&self, mapper: &RepositoryMapper, component: &CodeComponent) -> bool {
+        if component.visibility != Visibility::Public {
+            return false;
+        }
+        if self.exported_names.contains(&component.name)
+            || self
+                .exported_modules
+                .iter()
+                .any(|module| component.file_path.starts_with(module))
+        {
+            return true;
+        }
+        // Methods of an exported type, which may be an alias or defined elsewhere
+        let owner_exported = component
+            .id
+            .rsplit("::")
+            .nth(1)
+            .is_some_and(|owner| self.exported_names.contains(owner));
+        owner_exported
+            || component
+                .parent
+                .as_ref()
+                .and_then(|parent| mapper.graph.components.get(parent))
+                .is_some_and(|parent| self.is_exported(mapper, parent))
+    }
+}
+
+fn is_attribute(line: &str) -> bool {
+    line.starts_with("#[") || line.starts_with('@')
+}
+
+/// `get` for `#[actix_web::get("/")]`, `route` for `@app.route("/")`
+fn attribute_name(line: &str) -> &str {
+    let body = line.trim_start_matches("#[").trim_start_matches('@');
+    let end = body
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | ':' | '.')))
+        .unwrap_or(body.len());
+    body[..end].rsplit([':', '.']).next().unwrap_or_default()
+}
+
+fn is_configured_entry_point(
+    settings: &DeadCodeSettings,
+    mapper: &RepositoryMapper,
+    component: &CodeComponent,
+) -> bool {
+    let file = component.file_path.display().to_string();
+    let qualified = component
+        .id
+        .strip_prefix(&format!("{file}::"))
+        .unwrap_or(&component.name);
+    let relative = format!(
+        "{}::{qualified}",
+        mapper.relative_to_root(&component.file_path).display()
+    );
+    settings.is_entry_point(&[&component.name, qualified, &relative])
+}
+
+/// Imports in `content` whose bound name is not mentioned elsewhere in the file
+fn unused_imports(file: &str, content: &str, identifier: &Regex) -> Vec<UnusedImport> {
+    let path = Path::new(file);
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    // Packages import names to re-export them
+    if matches!(file_name, "__init__.py" | "lib.rs" | "mod.rs") {
+        return Vec::new();
+    }
+    let (imports, spans) = match extension {
+        "rs" => rust_imports(content),
+        "py" | "pyi" => python_imports(content),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => js_imports(content),
+        "java" | "kt" | "kts" => jvm_imports(content),
+        _ => return Vec::new(),
+    };
+    if imports.is_empty() {
+        return Vec::new();
+    }
+
+    // Mentions outside the import statements themselves
+    let mut rest = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end) in spans {
+        rest.push_str(&content[last..start]);
+        last = end;
+    }
+    rest.push_str(&content[last..]);
+    let mentioned: BTreeSet<&str> = identifier.find_iter(&rest).map(|m| m.as_str()).collect();
+
+    imports
+        .into_iter()
+        .filter(|(_, name, _)| name != "*" && name != "_" && !mentioned.contains(name.as_str()))
+        .filter(|(_, _, import_path)| {
+            // By the imported name, as traits are often imported under an alias
+            let imported = import_path.rsplit("::").next().unwrap_or_default();
+            extension != "rs" || !(imported.ends_with("Ext") || METHOD_TRAITS.contains(&imported))
+        })
+        .map(|(offset, name, import_path)| UnusedImport {
+            file: file.to_string(),
+            line: content[..offset].matches('\n').count() + 1,
+            name,
+            path: import_path,
+        })
+        .collect()
+}
+
+/// Imports as `(offset, bound name, path)`, and the byte ranges of the import statements
+type Imports = (Vec<(usize, String, String)>, Vec<(usize, usize)>);
+
+/// Private file-level `use` declarations. `pub use` re-exports are left alone, as are `use`
+/// declarations in functions, which mostly bring traits into scope for their methods.
+fn rust_imports(content: &str) -> Imports {
+    let declaration =
+        Regex::new(r"(?m)^(pub(\([^)]*\))?\s+)?use\s+([^;]+);").expect("Invalid use regex");
+    let mut imports = Vec::new();
+    let mut spans = Vec::new();
+    for captures in declaration.captures_iter(content) {
+        let whole = captures.get(0).expect("Match should have a span");
+        spans.push((whole.start(), whole.end()));
+        if captures.get(1).is_some() {
+            continue;
+        }
+        for (path, name) in use_tree_bindings(&captures[3]) {
+            imports.push((whole.start(), name, path));
+        }
+    }
+    (imports, spans)
+}
+
+/// `import a.b` binds `a`; `import a.b as c` and `from a import b as c` bind `c`
+fn python_imports(content: &str) -> Imports {
+    let statement = Regex::new(
+        r"(?m)^[ \t]*(?:from[ \t]+([\w.]+)[ \t]+import[ \t]+(\([^)]*\)|[^\n#]+)|import[ \t]+([^\n#]+))",
+    )
+    .expect("Invalid import regex");
+    let mut imports = Vec::new();
+    let mut spans = Vec::new();
+    for captures in statement.captures_iter(content) {
+        let whole = captures.get(0).expect("Match should have a span");
+        spans.push((whole.start(), whole.end()));
+        if let (Some(module), Some(names)) = (captures.get(1), captures.get(2)) {
+            if module.as_str() == "__future__" {
+                continue;
+            }
+            let names = names.as_str().trim_matches(|c| matches!(c, '(' | ')'));
+            for item in names
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+            {
+                let mut parts = item.split(" as ").map(str::trim);
+                let name = parts.next().unwrap_or(item);
+                let bound = parts.next().unwrap_or(name);
+                imports.push((
+                    whole.start(),
+                    bound.to_string(),
+                    format!("{}.{name}", module.as_str()),
+                ));
+            }
+        } else if let Some(modules) = captures.get(3) {
+            for item in modules.as_str().split(',').map(str::trim) {
+                let mut parts = item.split(" as ").map(str::trim);
+                let module = parts.next().unwrap_or(item);
+                let bound = parts
+                    .next()
+                    .unwrap_or_else(|| module.split('.').next().unwrap_or(module));
+                imports.push((whole.start(), bound.to_string(), module.to_string()));
+            }
+        }
+    }
+    (imports, spans)
+}
+
+/// `import X, { a, b as c } from "m"` and `import * as ns from "m"`
+fn js_imports(content: &str) -> Imports {
+    let statement =
+        Regex::new(r#"(?m)^[ \t]*import\s+(?:type\s+)?([^'"`;]+?)\s+from\s+['"]([^'"]+)['"]"#)
+            .expect("Invalid import regex");
+    let mut imports = Vec::new();
+    let mut spans = Vec::new();
+    for captures in statement.captures_iter(content) {
+        let whole = captures.get(0).expect("Match should have a span");
+        spans.push((whole.start(), whole.end()));
+        let clause = &captures[1];
+        let module = &captures[2];
+        let (default, named) = match (clause.find('{'), clause.rfind('}')) {
+            (Some(open), Some(close)) => (&clause[..open], &clause[open + 1..close]),
+            _ => (clause, ""),
+        };
+        for item in default
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let bound = item.rsplit(" as ").next().unwrap_or(item).trim();
+            imports.push((whole.start(), bound.to_string(), module.to_string()));
+        }
+        for item in named
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let item = item.trim_start_matches("type ").trim();
+            let mut parts = item.split(" as ").map(str::trim);
+            let name = parts.next().unwrap_or(item);
+            let bound = parts.next().unwrap_or(name);
+            imports.push((whole.start(), bound.to_string(), format!("{module}.{name}")));
+        }
+    }
+    (imports, spans)
+}
+
+/// `import a.b.C;`, binding `C`, or Kotlin's `import a.b.C as D`
+fn jvm_imports(content: &str) -> Imports {
+    let statement = Regex::new(r"(?m)^[ \t]*import[ \t]+(?:static[ \t]+)?([\w.]+(?:\.\*)?)(?:[ \t]+as[ \t]+(\w+))?[ \t]*;?")
+        .expect("Invalid import regex");
+    let mut imports = Vec::new();
+    let mut spans = Vec::new();
+    for captures in statement.captures_iter(content) {
+        let whole = captures.get(0).expect("Match should have a span");
+        spans.push((whole.start(), whole.end()));
+        let path = &captures[1];
+        let bound = captures.get(2).map_or_else(
+            || path.rsplit('.').next().unwrap_or(path),
+            |alias| alias.as_str(),
+        );
+        imports.push((whole.start(), bound.to_string(), path.to_string()));
+    }
+    (imports, spans)
+}
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use structopt::StructOpt;
+
+/// Words per shingle compared between files
+const SHINGLE_WORDS: usize = 5;
+/// Minimum hashes kept per file
+const MINHASH_SIZE: usize = 128;
+/// Minimum hashes per band; files sharing a whole band are compared
+const BAND_ROWS: usize = 4;
+
+/// How gathered files are deduplicated beyond dropping exact copies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, StructOpt)]
+pub struct DedupOptions {
+    /// Estimated share of word shingles two files have in common, from 0 to 1
+    #[structopt(
+        long,
+        help = "Collapse files at least this similar, from 0 to 1, into the first of them, e.g. 0.9"
+    )]
+    pub near_duplicates: Option<f64>,
+
+    #[structopt(
+        long,
+        help = "Strip license and copyright comments from the start of files"
+    )]
+    #[serde(default)]
+    pub strip_license_headers: bool,
+}
+
+impl DedupOptions {
+    pub(crate) fn validate(&self) -> io::Result<()> {
+        match self.near_duplicates {
+            Some(threshold) if !(threshold > 0.0 && threshold <= 1.0) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Near-duplicate similarity {threshold} is not between 0 and 1"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The hex SHA-256 of `content`, stable across builds and platforms
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Phrases only license and copyright notices are expected to use
+const LICENSE_PHRASES: &[&str] = &[
+    "copyright",
+    "spdx-license-identifier",
+    "licensed under",
+    "permission is hereby granted",
+    "all rights reserved",
+    "general public license",
+    "apache license",
+];
+
+/// `content` without the license comments at its start, and how many lines were removed.
+/// A shebang line is kept, and `None` is returned when there is nothing to strip.
+pub fn strip_license_header(content: &str) -> Option<(String, usize)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = usize::from(lines.first().is_some_and(|line| line.starts_with("#!")));
+    let mut end = start;
+    while let Some(block) = comment_block(&lines[end..]) {
+        let text = lines[end..end + block].join("\n").to_lowercase();
+        if !LICENSE_PHRASES.iter().any(|phrase| text.contains(phrase)) {
+            break;
+        }
+        end += block;
+        while lines.get(end).is_some_and(|line| line.trim().is_empty()) {
+            end += 1;
+        }
+    }
+    if end == start {
+        return None;
+    }
+
+    let mut kept: Vec<&str> = lines[..start].to_vec();
+    kept.extend(&lines[end..]);
+    let mut stripped = kept.join("\n");
+    if content.ends_with('\n') && !stripped.is_empty() {
+        stripped.push('\n');
+    }
+    Some((stripped, end - start))
+}
+
+/// The length of the comment starting at the first of `lines`, past blank lines before it
+fn comment_block(lines: &[&str]) -> Option<usize> {
+    let blank = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty())
+        .count();
+    let first = lines.get(blank)?.trim_start();
+    let closed_by = |close: &str| {
+        lines[blank..]
+            .iter()
+            .position(|line| line.contains(close))
+            .map(|idx| blank + idx + 1)
+    };
+    if first.starts_with("/*") {
+        return closed_by("*/");
+    }
+    if first.starts_with("<!--") {
+        return closed_by("-->");
+    }
+    let prefix = ["//", "#", "--", ";"]
+        .into_iter()
+        .find(|prefix| is_line_comment(first, prefix))?;
+    let length = lines[blank..]
+        .iter()
+        .take_while(|line| is_line_comment(line.trim_start(), prefix))
+        .count();
+    Some(blank + length)
+}
+
+/// Whether `line` is a comment opened by `prefix`, and not a doc comment, attribute or
+/// preprocessor directive that merely starts like one
+fn is_line_comment(line: &str, prefix: &str) -> bool {
+    let Some(rest) = line.strip_prefix(prefix) else {
+        return false;
+    };
+    match prefix {
+        "//" => !rest.starts_with('/') && !rest.starts_with('!'),
+        "#" => rest
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_alphanumeric() && c != '[' && c != '!'),
+        _ => true,
+    }
+}
+
+/// `MinHash` signature of a file's word shingles, whitespace ignored
+#[derive(Debug, Clone)]
+struct Fingerprint {
+    minimums: Vec<u64>,
+}
+
+impl Fingerprint {
+    /// `None` for files too short to hold a shingle
+    fn of(content: &str) -> Option<Self> {
+        let words: Vec<&str> = content.split_whitespace().collect();
+        if words.len() < SHINGLE_WORDS {
+            return None;
+        }
+        let mut minimums = vec![u64::MAX; MINHASH_SIZE];
+        for shingle in words.windows(SHINGLE_WORDS) {
+            let hash = fnv1a(shingle);
+            let mut seed = 0u64;
+            for minimum in &mut minimums {
+                seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                *minimum = (*minimum).min(mix(hash ^ seed));
+            }
+        }
+        Some(Self { minimums })
+    }
+
+    /// Estimated share of shingles the two files have in common
+    fn similarity(&self, other: &Self) -> f64 {
+        let matching = self
+            .minimums
+            .iter()
+            .zip(&other.minimums)
+            .filter(|(a, b)| a == b)
+            .count();
+        f64::from(u32::try_from(matching).unwrap_or(u32::MAX))
+            / f64::from(u32::try_from(MINHASH_SIZE).unwrap_or(u32::MAX))
+    }
+
+    /// A hash of each band of minimums
+    fn bands(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.minimums
+            .chunks(BAND_ROWS)
+            .enumerate()
+            .map(|(band, rows)| (band, rows.iter().fold(0, |hash, row| mix(hash ^ row))))
+    }
+}
+
+/// 64-bit FNV-1a over words separated by spaces
+fn fnv1a(words: &[&str]) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325_u64;
+    for (idx, word) in words.iter().enumerate() {
+        let separator = if idx == 0 { &[][..] } else { &b" "[..] };
+        for &byte in separator.iter().chain(word.as_bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+    hash
+}
+
+/// The `SplitMix64` finalizer
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// How a file duplicates another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Likeness {
+    Identical,
+    /// Estimated share of shingles in common
+    Similar(f64),
+}
+
+/// A file collapsed into the file kept in its place
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Duplicate {
+    pub display_path: String,
+    pub likeness: Likeness,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.likeness {
+            Likeness::Identical => write!(f, "{} (identical)", self.display_path),
+            Likeness::Similar(similarity) => {
+                write!(
+                    f,
+                    "{} ({:.0}% similar)",
+                    self.display_path,
+                    similarity * 100.0
+                )
+            }
+        }
+    }
+}
+
+/// What a file's content is compared by
+pub(crate) struct ContentKey {
+    hash: String,
+    fingerprint: Option<Fingerprint>,
+}
+
+/// Remembers the files kept so far and finds which of them a new file duplicates
+pub(crate) struct Deduplicator {
+    threshold: Option<f64>,
+    hashes: HashMap<String, usize>,
+    fingerprints: Vec<(usize, Fingerprint)>,
+    /// Indexes into `fingerprints` by band and band hash
+    buckets: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl Deduplicator {
+    pub(crate) fn new(options: &DedupOptions) -> Self {
+        Self {
+            threshold: options.near_duplicates,
+            hashes: HashMap::new(),
+            fingerprints: Vec::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn key(&self, content: &str) -> ContentKey {
+        ContentKey {
+            hash: content_hash(content),
+            fingerprint: self.threshold.and_then(|_| Fingerprint::of(content)),
+        }
+    }
+
+    /// The kept file `key` duplicates, if any, and how closely
+    pub(crate) fn find(&self, key: &ContentKey) -> Option<(usize, Likeness)> {
+        if let Some(&kept) = self.hashes.get(&key.hash) {
+            return Some((kept, Likeness::Identical));
+        }
+        let threshold = self.threshold?;
+        let fingerprint = key.fingerprint.as_ref()?;
+        let mut candidates: Vec<usize> = fingerprint
+            .bands()
+            .filter_map(|band| self.buckets.get(&band))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .map(|idx| {
+                let (kept, other) = &self.fingerprints[idx];
+                (*kept, fingerprint.similarity(other))
+            })
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(kept, similarity)| (kept, Likeness::Similar(similarity)))
+    }
+
+    /// Remembers `key` as the content of kept file `kept`
+    pub(crate) fn insert(&mut self, key: ContentKey, kept: usize) {
+        self.hashes.insert(key.hash, kept);
+        if let Some(fingerprint) = key.fingerprint {
+            let idx = self.fingerprints.len();
+            for band in fingerprint.bands() {
+                self.buckets.entry(band).or_default().push(idx);
+            }
+            self.fingerprints.push((kept, fingerprint));
+        }
+    }
+}
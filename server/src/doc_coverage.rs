@@ -0,0 +1,484 @@
+use crate::contexter::ProjectFile;
+use crate::dead_code::in_trait_impl;
+use crate::imports::normalize_path;
+use crate::repo_mapper::{CodeComponent, ComponentType, RepositoryMapper, Visibility};
+use crate::test_correlation::{in_test_module, test_subject};
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a language writes doc comments and parameter lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocStyle {
+    /// `///` and `/** */` above, `name: Type` parameters
+    Rust,
+    /// A docstring below the signature, `name: Type = default` parameters
+    Python,
+    /// `JSDoc` above, `name?: Type = default` parameters
+    JavaScript,
+    /// `//` comments directly above, `a, b Type` parameters
+    Go,
+    /// Javadoc or `KDoc` above, `Type name` or `name: Type` parameters
+    Jvm,
+    /// `/** */` or `///` above, `Type name` parameters
+    C,
+}
+
+impl DocStyle {
+    fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Some(match extension {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "mts" | "cts" | "tsx" => Self::JavaScript,
+            "go" => Self::Go,
+            "java" | "kt" | "kts" => Self::Jvm,
+            "c" | "h" | "hh" | "hpp" | "hxx" | "cc" | "cpp" | "cxx" => Self::C,
+            _ => return None,
+        })
+    }
+}
+
+/// The documentation of a public component
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentDocs {
+    pub id: String,
+    pub name: String,
+    pub component_type: ComponentType,
+    pub file: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub documented: bool,
+    /// Non-empty lines of doc comment or docstring
+    pub doc_lines: usize,
+    /// Parameter names of functions and methods, without `self`, `cls` or `this`
+    pub parameters: Vec<String>,
+    /// Parameters the documentation never mentions
+    pub undocumented_parameters: Vec<String>,
+}
+
+/// How much of a set of public components is documented
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageStats {
+    pub public_components: usize,
+    pub documented: usize,
+    /// Share of public components with documentation, from 0 to 1
+    pub coverage: f64,
+    pub parameters: usize,
+    pub documented_parameters: usize,
+}
+
+impl CoverageStats {
+    fn add(&mut self, docs: &ComponentDocs) {
+        self.public_components += 1;
+        self.documented += usize::from(docs.documented);
+        self.parameters += docs.parameters.len();
+        self.documented_parameters += docs.parameters.len() - docs.undocumented_parameters.len();
+        let count = |n: usize| f64::from(u32::try_from(n).unwrap_or(u32::MAX));
+        self.coverage = count(self.documented) / count(self.public_components);
+    }
+}
+
+/// Documentation coverage of the public API, overall, per file and per module (directory)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocCoverage {
+    pub total: CoverageStats,
+    pub files: BTreeMap<String, CoverageStats>,
+    pub modules: BTreeMap<String, CoverageStats>,
+}
+
+impl DocCoverage {
+    pub fn from_docs(docs: &[ComponentDocs]) -> Self {
+        let mut coverage = Self::default();
+        for component in docs {
+            coverage.total.add(component);
+            coverage
+                .files
+                .entry(component.file.clone())
+                .or_default()
+                .add(component);
+            let module = Path::new(&component.file)
+                .parent()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default();
+            coverage.modules.entry(module).or_default().add(component);
+        }
+        coverage
+    }
+}
+
+fn is_public_api(mapper: &RepositoryMapper, component: &CodeComponent) -> bool {
+    component.visibility == Visibility::Public
+        && matches!(
+            component.component_type,
+            ComponentType::Function
+                | ComponentType::Method
+                | ComponentType::Class
+                | ComponentType::Enum
+                | ComponentType::Interface
+        )
+        && test_subject(mapper.relative_to_root(&component.file_path)).is_none()
+        && !in_test_module(mapper, component)
+}
+
+/// Documentation of every public function, method and type outside tests, by file and line.
+/// Rust trait implementations are left out, as they share the trait's documentation.
+pub(crate) fn component_docs(mapper: &RepositoryMapper) -> Vec<ComponentDocs> {
+    let mut by_file: BTreeMap<&Path, Vec<&CodeComponent>> = BTreeMap::new();
+    for component in mapper.graph.components.values() {
+        if is_public_api(mapper, component) {
+            by_file
+                .entry(component.file_path.as_path())
+                .or_default()
+                .push(component);
+        }
+    }
+
+    let mut docs = Vec::new();
+    for (file, mut components) in by_file {
+        let Some(style) = DocStyle::for_path(file) else {
+            continue;
+        };
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("Skipping unreadable file {}: {e}", file.display());
+                continue;
+            }
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        components.sort_by_key(|c| (c.start_line, c.id.clone()));
+        for component in components {
+            let implements_trait = style == DocStyle::Rust
+                && component.component_type == ComponentType::Method
+                && in_trait_impl(&content, component.start_line);
+            if !implements_trait {
+                docs.push(document(component, style, &lines));
+            }
+        }
+    }
+    docs
+}
+
+fn document(component: &CodeComponent, style: DocStyle, lines: &[&str]) -> ComponentDocs {
+    let doc = match style {
+        DocStyle::Python => docstring(lines, component.start_line, component.end_line),
+        _ => leading_comment(lines, component.start_line, style),
+    };
+    let parameters = if matches!(
+        component.component_type,
+        ComponentType::Function | ComponentType::Method
+    ) {
+        parameter_names(lines, component, style)
+    } else {
+        Vec::new()
+    };
+    let undocumented_parameters = parameters
+        .iter()
+        .filter(|name| !mentions(&doc, name))
+        .cloned()
+        .collect();
+    let doc_lines = doc.iter().filter(|line| !line.is_empty()).count();
+    ComponentDocs {
+        id: component.id.clone(),
+        name: component.name.clone(),
+        component_type: component.component_type.clone(),
+        file: component.file_path.display().to_string(),
+        line: component.start_line,
+        end_line: component.end_line,
+        documented: doc_lines > 0,
+        doc_lines,
+        parameters,
+        undocumented_parameters,
+    }
+}
+
+fn mentions(doc: &[String], name: &str) -> bool {
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(name)))
+        .expect("Escaped parameter name should form a valid regex");
+    doc.iter().any(|line| word.is_match(line))
+}
+
+/// Text of the doc comment directly above `start_line`, past any attributes or annotations
+fn leading_comment(lines: &[&str], start_line: usize, style: DocStyle) -> Vec<String> {
+    let mut doc = Vec::new();
+    let mut idx = start_line.saturating_sub(1);
+    while idx > 0 {
+        let line = lines.get(idx - 1).map_or("", |line| line.trim());
+        if line.starts_with("#[") || (line.starts_with('@') && style != DocStyle::Rust) {
+            idx -= 1;
+        } else if line.starts_with("///") || (style == DocStyle::Go && line.starts_with("//")) {
+            let text = line.trim_start_matches('/');
+            doc.push(text.trim().to_string());
+            idx -= 1;
+        } else if line.ends_with("*/") {
+            // Walk up to the opening of the block, which must be a doc comment
+            let mut block = Vec::new();
+            let mut open = idx;
+            while open > 0 && !lines[open - 1].trim_start().starts_with("/*") {
+                block.push(lines[open - 1].trim());
+                open -= 1;
+            }
+            let Some(first) = open.checked_sub(1).map(|n| lines[n].trim()) else {
+                break;
+            };
+            if !first.starts_with("/**") && style != DocStyle::Go {
+                break;
+            }
+            block.push(first);
+            doc.extend(block.into_iter().map(|text| {
+                text.trim_start_matches("/**")
+                    .trim_start_matches("/*")
+                    .trim_end_matches("*/")
+                    .trim_start_matches('*')
+                    .trim()
+                    .to_string()
+            }));
+            break;
+        } else {
+            break;
+        }
+    }
+    doc.reverse();
+    doc
+}
+
+/// Text of the docstring opening the body of a Python function or class
+fn docstring(lines: &[&str], start_line: usize, end_line: usize) -> Vec<String> {
+    let Some((first, last)) = python_signature_end(lines, start_line, end_line)
+        .and_then(|signature_end| docstring_span(lines, signature_end, end_line))
+    else {
+        return Vec::new();
+    };
+    let Some((quote, opening)) = docstring_opening(lines[first]) else {
+        return Vec::new();
+    };
+    (first..=last)
+        .map(|idx| {
+            let text = if idx == first {
+                opening
+            } else {
+                lines[idx].trim()
+            };
+            text.split(quote).next().unwrap_or(text).trim().to_string()
+        })
+        .collect()
+}
+
+/// Index of the last line of the Python signature starting at `start_line`: the first one
+/// ending in `:`, past any decorators
+pub(crate) fn python_signature_end(
+    lines: &[&str],
+    start_line: usize,
+    end_line: usize,
+) -> Option<usize> {
+    let end = end_line.min(lines.len());
+    (start_line.saturating_sub(1)..end).find(|&idx| {
+        let code = lines[idx]
+            .split('#')
+            .next()
+            .unwrap_or(lines[idx])
+            .trim_end();
+        code.ends_with(':') && !code.trim_start().starts_with('@')
+    })
+}
+
+/// Indices of the first and last lines of the docstring opening the body after
+/// `signature_end`, if the body starts with one
+pub(crate) fn docstring_span(
+    lines: &[&str],
+    signature_end: usize,
+    end_line: usize,
+) -> Option<(usize, usize)> {
+    let end = end_line.min(lines.len());
+    let first = (signature_end + 1..end).find(|&idx| !lines[idx].trim().is_empty())?;
+    let (quote, opening) = docstring_opening(lines[first])?;
+    if opening.contains(quote) {
+        return Some((first, first));
+    }
+    let last = (first + 1..end)
+        .find(|&idx| lines[idx].contains(quote))
+        .unwrap_or(end - 1);
+    Some((first, last))
+}
+
+/// The quote a docstring line opens with and the text after it
+fn docstring_opening(line: &str) -> Option<(&'static str, &str)> {
+    let opening = line.trim_start().trim_start_matches(['r', 'R', 'u', 'U']);
+    ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quote| opening.starts_with(quote))
+        .map(|quote| (quote, &opening[quote.len()..]))
+}
+
+/// Names in the parameter list following the component's name, skipping generics
+fn parameter_names(lines: &[&str], component: &CodeComponent, style: DocStyle) -> Vec<String> {
+    let end = component.end_line.min(lines.len());
+    let Some(span) = lines.get(component.start_line.saturating_sub(1)..end) else {
+        return Vec::new();
+    };
+    let source = span.join("\n");
+    let Some(name_at) = Regex::new(&format!(r"\b{}\b", regex::escape(&component.name)))
+        .expect("Escaped component name should form a valid regex")
+        .find(&source)
+    else {
+        return Vec::new();
+    };
+
+    let Some(list) = parenthesized(&source[name_at.end()..]) else {
+        return Vec::new();
+    };
+    let parts = split_top_level(list);
+    // Go lists like `a, b int` name every parameter only if some part has a type
+    if style == DocStyle::Go && !parts.iter().any(|part| part.contains(char::is_whitespace)) {
+        return Vec::new();
+    }
+    parts
+        .iter()
+        .filter_map(|part| parameter_name(part, style))
+        .filter(|name| !matches!(name.as_str(), "self" | "cls" | "this" | "_"))
+        .collect()
+}
+
+/// Contents of the first balanced parenthesis group, skipping `<...>` generics before it
+fn parenthesized(text: &str) -> Option<&str> {
+    let open = text.find('(')?;
+    if text[..open].contains(['{', ';', '=']) {
+        return None;
+    }
+    let mut depth = 0usize;
+    for (idx, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[open + 1..open + idx]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a parameter list on commas outside brackets, generics and strings
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (idx, c) in list.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '>' if !list[..idx].ends_with('-') && !list[..idx].ends_with('=') => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(list[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(list[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn parameter_name(part: &str, style: DocStyle) -> Option<String> {
+    let part = part.trim();
+    let name = match style {
+        DocStyle::Rust => {
+            let pattern = part.split(':').next()?.trim();
+            let pattern = pattern.trim_start_matches('&').trim();
+            let pattern = pattern.strip_prefix("mut ").unwrap_or(pattern);
+            if part.contains("self") && !part.contains(':') {
+                return None;
+            }
+            pattern.trim()
+        }
+        DocStyle::Python => {
+            let name = part.split([':', '=']).next()?.trim();
+            name.trim_start_matches('*')
+        }
+        DocStyle::JavaScript => {
+            let name = part.split([':', '=']).next()?.trim();
+            name.trim_start_matches("...").trim_end_matches('?')
+        }
+        DocStyle::Go => part.split_whitespace().next()?,
+        DocStyle::Jvm if part.contains(':') => part.split(':').next()?.split_whitespace().last()?,
+        DocStyle::Jvm | DocStyle::C => {
+            let declaration = part.split('=').next()?.trim();
+            let declaration = declaration.split('[').next()?.trim_end();
+            let mut tokens = declaration.split_whitespace();
+            let last = tokens.next_back()?;
+            // A lone type, as in `void` or an unnamed prototype parameter, names nothing
+            tokens.next()?;
+            last.trim_start_matches(['*', '&'])
+        }
+    };
+    let is_identifier = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    is_identifier.then(|| name.to_string())
+}
+
+/// Source of each component in `files` that is undocumented or has undocumented
+/// parameters, headed by where it is and what it is missing, for "write docs for these"
+/// prompts
+pub fn render_undocumented(docs: &[ComponentDocs], files: &[ProjectFile]) -> String {
+    let labels: HashMap<PathBuf, &str> = files
+        .iter()
+        .map(|file| (normalize_path(&file.path), file.display_path.as_str()))
+        .collect();
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    let mut content = String::new();
+    for component in docs {
+        if component.documented && component.undocumented_parameters.is_empty() {
+            continue;
+        }
+        let Some(label) = labels.get(&normalize_path(Path::new(&component.file))) else {
+            continue;
+        };
+        let source = sources
+            .entry(component.file.as_str())
+            .or_insert_with(|| fs::read_to_string(&component.file).ok());
+        let Some(source) = source else {
+            continue;
+        };
+        let body: Vec<&str> = source
+            .lines()
+            .skip(component.line - 1)
+            .take(component.end_line + 1 - component.line)
+            .collect();
+        let missing = if component.documented {
+            format!(
+                "Undocumented parameters: {}",
+                component.undocumented_parameters.join(", ")
+            )
+        } else {
+            "Undocumented".to_string()
+        };
+        write!(
+            &mut content,
+            "========================================\n\
+            File: {}:{}-{}\n\
+            Component: {} ({:?})\n\
+            {missing}\n\
+            ========================================\n\
+            {}\n\n",
+            label,
+            component.line,
+            component.end_line,
+            component.name,
+            component.component_type,
+            body.join("\n"),
+        )
+        .expect("Writing to a String should not fail");
+    }
+    content
+}
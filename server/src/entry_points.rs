@@ -0,0 +1,618 @@
+use crate::repo_mapper::{CodeComponent, ComponentType, RepositoryMapper};
+use crate::test_correlation::test_subject;
+use log::{debug, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Languages whose programs start at a function named `main`
+const MAIN_EXTENSIONS: &[&str] = &[
+    "rs", "go", "java", "kt", "kts", "c", "h", "cc", "cpp", "cxx",
+];
+
+/// How a program or request gets into the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryPointKind {
+    /// A binary target of a `Cargo.toml`, implicit or declared with `[[bin]]`
+    Binary,
+    /// A `main` function outside of any binary target
+    Main,
+    /// A `main` function started by an async runtime, such as `#[tokio::main]`
+    AsyncMain,
+    /// A Python module run as a script, through `if __name__ == "__main__"` or `__main__.py`
+    Script,
+    /// The `main` module of a `package.json`
+    PackageMain,
+    /// A command declared in the `bin` of a `package.json`
+    PackageBin,
+    /// A handler registered for an HTTP route
+    HttpRoute,
+}
+
+impl fmt::Display for EntryPointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Binary => "binary",
+            Self::Main => "main",
+            Self::AsyncMain => "async main",
+            Self::Script => "script",
+            Self::PackageMain => "package main",
+            Self::PackageBin => "package bin",
+            Self::HttpRoute => "HTTP route",
+        })
+    }
+}
+
+/// A place where execution starts
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub kind: EntryPointKind,
+    /// The binary, command or package name, `main`, the script module, or the method and
+    /// path of a route such as `GET /users`
+    pub name: String,
+    /// The file that runs, or that registers the route
+    pub file: String,
+    pub line: usize,
+    /// ID of the component that runs, when known
+    pub component: Option<String>,
+}
+
+/// Analyzed source files and their components, by file
+struct Sources<'a> {
+    contents: BTreeMap<&'a Path, String>,
+    components: HashMap<&'a Path, Vec<&'a CodeComponent>>,
+}
+
+impl<'a> Sources<'a> {
+    fn new(mapper: &'a RepositoryMapper) -> Self {
+        let mut contents = BTreeMap::new();
+        for file in mapper.files.values() {
+            match fs::read_to_string(file) {
+                Ok(content) => {
+                    contents.insert(file.as_path(), content);
+                }
+                Err(e) => debug!("Skipping unreadable file {}: {e}", file.display()),
+            }
+        }
+        let mut components: HashMap<&Path, Vec<&CodeComponent>> = HashMap::new();
+        for component in mapper.graph.components.values() {
+            components
+                .entry(component.file_path.as_path())
+                .or_default()
+                .push(component);
+        }
+        Self {
+            contents,
+            components,
+        }
+    }
+
+    fn functions(&self, file: &Path) -> impl Iterator<Item = &'a CodeComponent> + '_ {
+        self.components
+            .get(file)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|component| {
+                matches!(
+                    component.component_type,
+                    ComponentType::Function | ComponentType::Method
+                )
+            })
+    }
+
+    /// The function defined first at or after `line`, such as the one under a decorator
+    fn function_after(&self, file: &Path, line: usize) -> Option<&'a CodeComponent> {
+        self.functions(file)
+            .filter(|component| component.start_line >= line)
+            .min_by_key(|component| component.start_line)
+    }
+
+    /// Whether `line` is inside an inline test module such as Rust's `mod tests`
+    fn in_test_module(&self, file: &Path, line: usize) -> bool {
+        self.components
+            .get(file)
+            .into_iter()
+            .flatten()
+            .any(|component| {
+                component.component_type == ComponentType::Module
+                    && matches!(component.name.as_str(), "tests" | "test")
+                    && (component.start_line..=component.end_line).contains(&line)
+            })
+    }
+
+    /// The function a handler expression such as `handlers::index` names, preferably in
+    /// `file`, otherwise anywhere if the name is unique
+    fn resolve_handler(
+        &self,
+        mapper: &'a RepositoryMapper,
+        file: &Path,
+        handler: &str,
+    ) -> Option<&'a CodeComponent> {
+        let name = handler.rsplit([':', '.']).next().unwrap_or(handler);
+        if let Some(local) = self
+            .functions(file)
+            .find(|component| component.name == name)
+        {
+            return Some(local);
+        }
+        let mut matching = mapper.graph.components.values().filter(|component| {
+            component.name == name
+                && matches!(
+                    component.component_type,
+                    ComponentType::Function | ComponentType::Method
+                )
+        });
+        let found = matching.next();
+        if matching.next().is_some() {
+            None
+        } else {
+            found
+        }
+    }
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Whether `offset` is in a line comment or on a line continuing a block comment
+fn is_commented(content: &str, offset: usize) -> bool {
+    let line = content[..offset]
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .trim_start();
+    line.contains("//")
+        || line.starts_with('*')
+        || (line.starts_with('#') && !line.starts_with("#["))
+}
+
+/// Binaries, `main` functions, scripts, package entry modules and HTTP routes, by kind
+/// and then by name
+pub(crate) fn detect_entry_points(mapper: &RepositoryMapper) -> Vec<EntryPoint> {
+    let sources = Sources::new(mapper);
+    let mut entry_points = BTreeSet::new();
+
+    let mut binary_files = BTreeSet::new();
+    for dir in manifest_dirs(mapper) {
+        let cargo = dir.join("Cargo.toml");
+        if cargo.is_file() {
+            for (name, file) in cargo_binaries(&dir, &cargo) {
+                let main = sources.functions(&file).find(|c| c.name == "main");
+                entry_points.insert(EntryPoint {
+                    kind: EntryPointKind::Binary,
+                    name,
+                    file: file.display().to_string(),
+                    line: main.map_or(1, |main| main.start_line),
+                    component: main.map(|main| main.id.clone()),
+                });
+                binary_files.insert(file);
+            }
+        }
+        let package = dir.join("package.json");
+        if package.is_file() {
+            entry_points.extend(package_entry_points(&dir, &package));
+        }
+    }
+
+    for (&file, content) in &sources.contents {
+        if !binary_files.contains(file) {
+            entry_points.extend(main_functions(&sources, file, content));
+        }
+        entry_points.extend(python_script(&sources, file, content));
+        // Tests spell out routes in fixtures and requests without registering them
+        if test_subject(mapper.relative_to_root(file)).is_none() {
+            entry_points.extend(http_routes(mapper, &sources, file, content));
+        }
+    }
+    entry_points.into_iter().collect()
+}
+
+/// Directories of the analyzed files and their ancestors within the analyzed roots, where
+/// `Cargo.toml` and `package.json` manifests may declare binaries
+fn manifest_dirs(mapper: &RepositoryMapper) -> BTreeSet<PathBuf> {
+    let mut dirs = BTreeSet::new();
+    for file in mapper.files.values() {
+        for dir in file.ancestors().skip(1) {
+            if !mapper.roots.iter().any(|root| dir.starts_with(root)) || !dirs.insert(dir.into()) {
+                break;
+            }
+        }
+    }
+    dirs
+}
+
+/// Names and main files of a Cargo package's binaries: `src/main.rs`, `src/bin/*.rs`,
+/// `src/bin/*/main.rs` and the `[[bin]]` targets
+fn cargo_binaries(dir: &Path, manifest: &Path) -> Vec<(String, PathBuf)> {
+    let value: toml::Value = match fs::read_to_string(manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|content| toml::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Skipping unreadable manifest {}: {e}", manifest.display());
+            return Vec::new();
+        }
+    };
+    let Some(package) = value.get("package") else {
+        // A virtual workspace manifest
+        return Vec::new();
+    };
+    let package_name = package
+        .get("name")
+        .and_then(toml::Value::as_str)
+        .unwrap_or_default();
+
+    let mut binaries: BTreeMap<PathBuf, String> = BTreeMap::new();
+    if package.get("autobins").and_then(toml::Value::as_bool) != Some(false) {
+        let main = dir.join("src").join("main.rs");
+        if main.is_file() {
+            binaries.insert(main, package_name.to_string());
+        }
+        let bin_dir = dir.join("src").join("bin");
+        for entry in fs::read_dir(&bin_dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if path.extension().is_some_and(|ext| ext == "rs") {
+                binaries.insert(path, name);
+            } else if path.join("main.rs").is_file() {
+                binaries.insert(path.join("main.rs"), name);
+            }
+        }
+    }
+    for target in value
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(name) = target.get("name").and_then(toml::Value::as_str) else {
+            continue;
+        };
+        let path = match target.get("path").and_then(toml::Value::as_str) {
+            Some(path) => dir.join(path.trim_start_matches("./")),
+            None if name == package_name => dir.join("src").join("main.rs"),
+            None => dir.join("src").join("bin").join(format!("{name}.rs")),
+        };
+        binaries.insert(path, name.to_string());
+    }
+    binaries
+        .into_iter()
+        .map(|(path, name)| (name, path))
+        .collect()
+}
+
+/// The `main` module and `bin` commands of a `package.json`
+fn package_entry_points(dir: &Path, manifest: &Path) -> Vec<EntryPoint> {
+    let value: serde_json::Value = match fs::read_to_string(manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Skipping unreadable manifest {}: {e}", manifest.display());
+            return Vec::new();
+        }
+    };
+    let package_name = value["name"]
+        .as_str()
+        .or_else(|| dir.file_name().and_then(|name| name.to_str()))
+        .unwrap_or_default();
+    let entry_point = |kind, name: &str, file: &str| EntryPoint {
+        kind,
+        name: name.to_string(),
+        file: dir
+            .join(file.trim_start_matches("./"))
+            .display()
+            .to_string(),
+        line: 1,
+        component: None,
+    };
+
+    let mut entry_points = Vec::new();
+    if let Some(main) = value["main"].as_str() {
+        entry_points.push(entry_point(EntryPointKind::PackageMain, package_name, main));
+    }
+    match &value["bin"] {
+        serde_json::Value::String(file) => {
+            entry_points.push(entry_point(EntryPointKind::PackageBin, package_name, file));
+        }
+        serde_json::Value::Object(commands) => {
+            for (name, file) in commands {
+                if let Some(file) = file.as_str() {
+                    entry_points.push(entry_point(EntryPointKind::PackageBin, name, file));
+                }
+            }
+        }
+        _ => {}
+    }
+    entry_points
+}
+
+/// `main` functions of languages that start there; Go ones only in `package main`
+fn main_functions(sources: &Sources, file: &Path, content: &str) -> Vec<EntryPoint> {
+    let extension = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    if !MAIN_EXTENSIONS.contains(&extension)
+        || (extension == "go" && !content.lines().any(|line| line.trim() == "package main"))
+    {
+        return Vec::new();
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    sources
+        .functions(file)
+        .filter(|component| component.name == "main")
+        .map(|main| {
+            // `#[tokio::main]`, `#[actix_web::main]`, `#[async_std::main]`
+            let is_async = lines[..main.start_line.saturating_sub(1).min(lines.len())]
+                .iter()
+                .rev()
+                .map(|line| line.trim())
+                .take_while(|line| line.starts_with("#[") || line.starts_with("//"))
+                .any(|line| line.starts_with("#[") && line.contains("::main"));
+            EntryPoint {
+                kind: if is_async {
+                    EntryPointKind::AsyncMain
+                } else {
+                    EntryPointKind::Main
+                },
+                name: main.name.clone(),
+                file: file.display().to_string(),
+                line: main.start_line,
+                component: Some(main.id.clone()),
+            }
+        })
+        .collect()
+}
+
+/// A Python module run as a script, and the function its `__main__` block calls first
+fn python_script(sources: &Sources, file: &Path, content: &str) -> Option<EntryPoint> {
+    if file.extension().is_none_or(|ext| ext != "py") {
+        return None;
+    }
+    let guard = Regex::new(r#"(?m)^if\s+__name__\s*==\s*['"]__main__['"]\s*:"#)
+        .expect("Invalid __main__ regex");
+    let is_main_module = file.file_name().is_some_and(|name| name == "__main__.py");
+    let (line, body) = match guard.find(content) {
+        Some(found) => (line_of(content, found.start()), &content[found.end()..]),
+        None if is_main_module => (1, content),
+        None => return None,
+    };
+
+    let call = Regex::new(r"\b([A-Za-z_]\w*)\s*\(").expect("Invalid call regex");
+    let component = call
+        .captures_iter(body)
+        .find_map(|captures| sources.functions(file).find(|c| c.name == captures[1]))
+        .map(|component| component.id.clone());
+    let module = if is_main_module {
+        file.parent().and_then(Path::file_name)
+    } else {
+        file.file_stem()
+    };
+    Some(EntryPoint {
+        kind: EntryPointKind::Script,
+        name: module
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        file: file.display().to_string(),
+        line,
+        component,
+    })
+}
+
+/// A registered route: its offset in the file, method, path and handler expression, if any
+type Route = (usize, String, String, Option<String>);
+
+/// Handlers registered for HTTP routes with actix-web, axum, Flask, `FastAPI`, Express,
+/// Spring or Go's `net/http`
+fn http_routes(
+    mapper: &RepositoryMapper,
+    sources: &Sources,
+    file: &Path,
+    content: &str,
+) -> Vec<EntryPoint> {
+    let extension = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let (routes, decorated) = match extension {
+        "rs" => rust_routes(content),
+        "py" => (Vec::new(), python_routes(content)),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => (js_routes(content), Vec::new()),
+        "java" | "kt" => (Vec::new(), spring_routes(content)),
+        "go" => (go_routes(content), Vec::new()),
+        _ => return Vec::new(),
+    };
+
+    let entry_point =
+        |offset: usize, method: &str, path: &str, component: Option<&CodeComponent>| EntryPoint {
+            kind: EntryPointKind::HttpRoute,
+            name: format!("{method} {path}").trim_end().to_string(),
+            file: file.display().to_string(),
+            line: line_of(content, offset),
+            component: component.map(|component| component.id.clone()),
+        };
+    let registered = |offset: usize| {
+        !is_commented(content, offset) && !sources.in_test_module(file, line_of(content, offset))
+    };
+    // Routes registered by calls name their handler
+    let mut entry_points: Vec<EntryPoint> = routes
+        .into_iter()
+        .filter(|(offset, ..)| registered(*offset))
+        .map(|(offset, method, path, handler)| {
+            let component = handler.and_then(|h| sources.resolve_handler(mapper, file, &h));
+            entry_point(offset, &method, &path, component)
+        })
+        .collect();
+    // Routes declared by attributes or decorators are handled by the function below them
+    entry_points.extend(
+        decorated
+            .into_iter()
+            .filter(|(offset, ..)| registered(*offset))
+            .map(|(offset, method, path, _)| {
+                let component = sources.function_after(file, line_of(content, offset));
+                entry_point(offset, &method, &path, component)
+            }),
+    );
+    entry_points
+}
+
+/// actix-web `web::get().to(handler)` registrations, under the closest preceding path of
+/// their statement, axum `.route("/path", get(handler))`, and `#[get("/path")]` handlers
+fn rust_routes(content: &str) -> (Vec<Route>, Vec<Route>) {
+    let actix =
+        Regex::new(r"web::(get|post|put|patch|delete|head)\(\)\s*\.to\(\s*([A-Za-z_][\w:]*)")
+            .expect("Invalid actix route regex");
+    let axum = Regex::new(
+        r#"\.route\(\s*"([^"]*)"\s*,\s*(get|post|put|patch|delete|head)\(\s*([A-Za-z_][\w:]*)\s*\)"#,
+    )
+    .expect("Invalid axum route regex");
+    let attribute =
+        Regex::new(r#"#\[(?:actix_web::)?(get|post|put|patch|delete|head)\(\s*"([^"]*)""#)
+            .expect("Invalid route attribute regex");
+    let literal = Regex::new(r#""(/[^"]*)""#).expect("Invalid path regex");
+
+    let mut routes = Vec::new();
+    for captures in actix.captures_iter(content) {
+        let start = captures.get(0).map_or(0, |m| m.start());
+        let statement = &content[content[..start].rfind(';').map_or(0, |end| end + 1)..start];
+        let path = literal
+            .captures_iter(statement)
+            .last()
+            .map(|path| path[1].to_string())
+            .unwrap_or_default();
+        routes.push((
+            start,
+            captures[1].to_uppercase(),
+            path,
+            Some(captures[2].to_string()),
+        ));
+    }
+    for captures in axum.captures_iter(content) {
+        routes.push((
+            captures.get(0).map_or(0, |m| m.start()),
+            captures[2].to_uppercase(),
+            captures[1].to_string(),
+            Some(captures[3].to_string()),
+        ));
+    }
+    let decorated = attribute
+        .captures_iter(content)
+        .map(|captures| {
+            (
+                captures.get(0).map_or(0, |m| m.start()),
+                captures[1].to_uppercase(),
+                captures[2].to_string(),
+                None,
+            )
+        })
+        .collect();
+    (routes, decorated)
+}
+
+/// Flask `@app.route("/path", methods=["POST"])` and `@app.get("/path")` style decorators
+fn python_routes(content: &str) -> Vec<Route> {
+    let decorator = Regex::new(
+        r#"(?m)^\s*@\w+(?:\.\w+)*\.(route|get|post|put|patch|delete|head)\(\s*['"]([^'"]*)['"]([^\n]*)"#,
+    )
+    .expect("Invalid route decorator regex");
+    let methods = Regex::new(r#"methods\s*=\s*[\[(]\s*['"](\w+)"#).expect("Invalid methods regex");
+    decorator
+        .captures_iter(content)
+        .map(|captures| {
+            let method = if &captures[1] == "route" {
+                methods
+                    .captures(&captures[3])
+                    .map_or_else(|| "GET".to_string(), |m| m[1].to_uppercase())
+            } else {
+                captures[1].to_uppercase()
+            };
+            (
+                captures.get(0).map_or(0, |m| m.start()),
+                method,
+                captures[2].to_string(),
+                None,
+            )
+        })
+        .collect()
+}
+
+/// Express `app.get("/path", ..., handler)` registrations on `app` or a router
+fn js_routes(content: &str) -> Vec<Route> {
+    let registration = Regex::new(
+        r#"\b(?:app|router|server)\.(get|post|put|patch|delete|all)\(\s*['"`]([^'"`]+)['"`]"#,
+    )
+    .expect("Invalid route regex");
+    // The last argument, when it names a function rather than defining one
+    let handler = Regex::new(r"^\s*,\s*(?:[\w$.]+\s*,\s*)*([A-Za-z_$][\w$.]*)\s*\)")
+        .expect("Invalid handler regex");
+    registration
+        .captures_iter(content)
+        .map(|captures| {
+            let whole = captures.get(0).expect("Match should have a span");
+            let method = match &captures[1] {
+                "all" => "ANY".to_string(),
+                method => method.to_uppercase(),
+            };
+            let handler = handler
+                .captures(&content[whole.end()..])
+                .map(|handler| handler[1].to_string());
+            (whole.start(), method, captures[2].to_string(), handler)
+        })
+        .collect()
+}
+
+/// Spring `@GetMapping("/path")` style annotations
+fn spring_routes(content: &str) -> Vec<Route> {
+    let annotation = Regex::new(
+        r#"@(Get|Post|Put|Patch|Delete|Request)Mapping\(\s*(?:(?:value|path)\s*=\s*)?"([^"]*)""#,
+    )
+    .expect("Invalid mapping regex");
+    annotation
+        .captures_iter(content)
+        .map(|captures| {
+            let method = match &captures[1] {
+                "Request" => "ANY".to_string(),
+                method => method.to_uppercase(),
+            };
+            (
+                captures.get(0).map_or(0, |m| m.start()),
+                method,
+                captures[2].to_string(),
+                None,
+            )
+        })
+        .collect()
+}
+
+/// `net/http` `HandleFunc("/path", handler)` registrations, whose pattern may start with
+/// a method
+fn go_routes(content: &str) -> Vec<Route> {
+    let registration = Regex::new(r#"\bHandleFunc\(\s*"(?:([A-Z]+)\s+)?([^"]*)"\s*,\s*([\w.]+)"#)
+        .expect("Invalid HandleFunc regex");
+    registration
+        .captures_iter(content)
+        .map(|captures| {
+            (
+                captures.get(0).map_or(0, |m| m.start()),
+                captures
+                    .get(1)
+                    .map_or_else(|| "ANY".to_string(), |m| m.as_str().to_string()),
+                captures[2].to_string(),
+                Some(captures[3].to_string()),
+            )
+        })
+        .collect()
+}
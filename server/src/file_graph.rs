@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// A source file and the files it imports or is imported by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileNode {
+    pub path: String,
+    pub imports: Vec<String>,
+    pub imported_by: Vec<String>,
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+/// An import of one file by another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// File-level import graph, built alongside the component graph
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileGraph {
+    pub files: BTreeMap<String, FileNode>,
+    pub edges: Vec<FileEdge>,
+    /// Groups of files that import each other, directly or indirectly
+    pub cycles: Vec<Vec<String>>,
+    /// Files ordered so that imported files come before the files importing them
+    pub topological_order: Vec<String>,
+}
+
+impl FileGraph {
+    /// Build the graph from every analyzed file and the resolved `(importer, imported)` pairs
+    pub fn build(
+        files: impl IntoIterator<Item = String>,
+        imports: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut nodes: BTreeMap<String, FileNode> = files
+            .into_iter()
+            .map(|path| {
+                let node = FileNode {
+                    path: path.clone(),
+                    imports: Vec::new(),
+                    imported_by: Vec::new(),
+                    fan_in: 0,
+                    fan_out: 0,
+                };
+                (path, node)
+            })
+            .collect();
+
+        let pairs: BTreeSet<(String, String)> = imports
+            .into_iter()
+            .filter(|(from, to)| from != to && nodes.contains_key(from) && nodes.contains_key(to))
+            .collect();
+        let mut edges = Vec::with_capacity(pairs.len());
+        for (from, to) in pairs {
+            if let Some(node) = nodes.get_mut(&from) {
+                node.imports.push(to.clone());
+                node.fan_out += 1;
+            }
+            if let Some(node) = nodes.get_mut(&to) {
+                node.imported_by.push(from.clone());
+                node.fan_in += 1;
+            }
+            edges.push(FileEdge { from, to });
+        }
+
+        let paths: Vec<&String> = nodes.keys().collect();
+        let index: BTreeMap<&str, usize> = paths
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| (path.as_str(), idx))
+            .collect();
+        let adjacency: Vec<Vec<usize>> = nodes
+            .values()
+            .map(|node| {
+                node.imports
+                    .iter()
+                    .filter_map(|to| index.get(to.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+
+        let components = strongly_connected_components(&adjacency);
+        let mut cycles = Vec::new();
+        let mut topological_order = Vec::with_capacity(paths.len());
+        for component in components {
+            let mut members: Vec<String> = component
+                .into_iter()
+                .map(|idx| paths[idx].clone())
+                .collect();
+            members.sort();
+            if members.len() > 1 {
+                cycles.push(members.clone());
+            }
+            topological_order.extend(members);
+        }
+
+        Self {
+            files: nodes,
+            edges,
+            cycles,
+            topological_order,
+        }
+    }
+
+    /// Files imported by the most other files
+    pub fn most_imported(&self, limit: usize) -> Vec<&FileNode> {
+        let mut nodes: Vec<&FileNode> = self.files.values().filter(|n| n.fan_in > 0).collect();
+        nodes.sort_by_key(|node| std::cmp::Reverse(node.fan_in));
+        nodes.truncate(limit);
+        nodes
+    }
+
+    /// Files importing the most other files
+    pub fn most_importing(&self, limit: usize) -> Vec<&FileNode> {
+        let mut nodes: Vec<&FileNode> = self.files.values().filter(|n| n.fan_out > 0).collect();
+        nodes.sort_by_key(|node| std::cmp::Reverse(node.fan_out));
+        nodes.truncate(limit);
+        nodes
+    }
+
+    /// Generate a text overview of the file graph
+    pub fn render(&self) -> String {
+        let mut map = String::new();
+        map.push_str("File Structure\n");
+        map.push_str("==============\n\n");
+        write!(
+            &mut map,
+            "Files: {} | Imports: {} | Cycles: {}\n\n",
+            self.files.len(),
+            self.edges.len(),
+            self.cycles.len()
+        )
+        .expect("Writing to a String should not fail");
+
+        for node in self.files.values() {
+            writeln!(
+                &mut map,
+                "{} (imports: {}, imported by: {})",
+                node.path, node.fan_out, node.fan_in
+            )
+            .expect("Writing to a String should not fail");
+            for import in &node.imports {
+                writeln!(&mut map, "  → {import}").expect("Writing to a String should not fail");
+            }
+        }
+        map.push('\n');
+
+        if !self.cycles.is_empty() {
+            map.push_str("Import Cycles:\n");
+            for cycle in &self.cycles {
+                writeln!(&mut map, "  {}", cycle.join(" ↔ "))
+                    .expect("Writing to a String should not fail");
+            }
+            map.push('\n');
+        }
+
+        let most_imported = self.most_imported(5);
+        if !most_imported.is_empty() {
+            map.push_str("Most Imported:\n");
+            for (i, node) in most_imported.iter().enumerate() {
+                writeln!(&mut map, "  {}. {} ({})", i + 1, node.path, node.fan_in)
+                    .expect("Writing to a String should not fail");
+            }
+            map.push('\n');
+        }
+
+        let most_importing = self.most_importing(5);
+        if !most_importing.is_empty() {
+            map.push_str("Most Dependencies:\n");
+            for (i, node) in most_importing.iter().enumerate() {
+                writeln!(&mut map, "  {}. {} ({})", i + 1, node.path, node.fan_out)
+                    .expect("Writing to a String should not fail");
+            }
+        }
+
+        map
+    }
+}
+
+/// Tarjan's strongly connected components algorithm, without recursion.
+///
+/// Components are returned in reverse topological order: when edges point from a node to
+/// what it depends on, every component comes after the components it depends on.
+pub fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let count = adjacency.len();
+    let mut index: Vec<Option<usize>> = vec![None; count];
+    let mut lowlink = vec![0; count];
+    let mut on_stack = vec![false; count];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..count {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(next_index);
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        // Each frame is a node and the position of the next neighbor to visit
+        let mut call_stack = vec![(root, 0usize)];
+
+        while let Some(&(node, position)) = call_stack.last() {
+            if let Some(&next) = adjacency[node].get(position) {
+                if let Some(frame) = call_stack.last_mut() {
+                    frame.1 += 1;
+                }
+                match index[next] {
+                    None => {
+                        index[next] = Some(next_index);
+                        lowlink[next] = next_index;
+                        next_index += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        call_stack.push((next, 0));
+                    }
+                    Some(next_index_value) if on_stack[next] => {
+                        lowlink[node] = lowlink[node].min(next_index_value);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if Some(lowlink[node]) == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
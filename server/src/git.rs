@@ -0,0 +1,185 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("Failed to run git: {0}")]
+    Io(#[from] io::Error),
+    #[error("git {command} failed: {stderr}")]
+    Command { command: String, stderr: String },
+    #[error("Invalid revision '{0}'")]
+    InvalidRevision(String),
+}
+
+/// A commit and the files it touched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    /// Changed files, relative to the directory the history was read from
+    pub files: Vec<PathBuf>,
+}
+
+/// The commit that last changed a line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blame {
+    pub commit: String,
+    pub author: String,
+    /// `YYYY-MM-DD` in UTC
+    pub date: String,
+}
+
+/// Run git in `dir`, returning its standard output
+fn run(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(GitError::Command {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether `dir` is inside a git work tree
+pub fn is_repository(dir: &Path) -> bool {
+    run(dir, &["rev-parse", "--is-inside-work-tree"]).is_ok_and(|out| out.trim() == "true")
+}
+
+/// The most recent non-merge commits touching files under `dir`, newest first.
+///
+/// File paths are relative to `dir`. Returns an empty history when `dir` is not in a
+/// git repository.
+pub fn recent_commits(dir: &Path, limit: usize) -> Result<Vec<Commit>, GitError> {
+    if !is_repository(dir) {
+        debug!("{} is not in a git repository", dir.display());
+        return Ok(Vec::new());
+    }
+
+    let limit = limit.to_string();
+    let log = run(
+        dir,
+        &[
+            "log",
+            "--no-merges",
+            "--name-only",
+            "--relative",
+            "--format=%x1e%H",
+            "-n",
+            &limit,
+            "--",
+            ".",
+        ],
+    )?;
+
+    Ok(log
+        .split('\x1e')
+        .filter_map(|entry| {
+            let mut lines = entry.lines().map(str::trim).filter(|line| !line.is_empty());
+            let hash = lines.next()?.to_string();
+            let files = lines.map(PathBuf::from).collect();
+            Some(Commit { hash, files })
+        })
+        .collect())
+}
+
+/// Files under `dir` that differ between `base` and the work tree, staged or not.
+///
+/// File paths are relative to `dir`. Returns no files when `dir` is not in a git repository.
+pub fn changed_files(dir: &Path, base: &str) -> Result<Vec<PathBuf>, GitError> {
+    // Anything starting with a dash would be taken as an option
+    if base.is_empty() || base.starts_with('-') {
+        return Err(GitError::InvalidRevision(base.to_string()));
+    }
+    if !is_repository(dir) {
+        debug!("{} is not in a git repository", dir.display());
+        return Ok(Vec::new());
+    }
+
+    let diff = run(dir, &["diff", "--name-only", "--relative", base, "--", "."])?;
+    Ok(diff
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The commits that last changed `lines` of `file`, keyed by line number.
+///
+/// Uncommitted lines are left out. Returns nothing when `file` is not in a git repository.
+pub fn blame(file: &Path, lines: &[usize]) -> Result<HashMap<usize, Blame>, GitError> {
+    let dir = file.parent().unwrap_or_else(|| Path::new("."));
+    let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+        return Ok(HashMap::new());
+    };
+    if lines.is_empty() || !is_repository(dir) {
+        return Ok(HashMap::new());
+    }
+
+    let ranges: Vec<String> = lines
+        .iter()
+        .map(|line| format!("-L{line},{line}"))
+        .collect();
+    let mut args = vec!["blame", "--line-porcelain"];
+    args.extend(ranges.iter().map(String::as_str));
+    args.extend(["--", name]);
+    let output = run(dir, &args)?;
+
+    let mut blamed = HashMap::new();
+    let mut current: Option<(usize, Blame)> = None;
+    for line in output.lines() {
+        if let Some((_, blame)) = current.as_mut() {
+            if let Some(author) = line.strip_prefix("author ") {
+                blame.author = author.to_string();
+            } else if let Some(time) = line.strip_prefix("author-time ") {
+                blame.date = time.parse().map(utc_date).unwrap_or_default();
+            } else if line.starts_with('\t') {
+                // The line itself ends each entry; uncommitted lines have an all-zero commit
+                let (line_number, blame) = current.take().expect("Entry should be in progress");
+                if blame.commit.bytes().any(|b| b != b'0') {
+                    blamed.insert(line_number, blame);
+                }
+            }
+            continue;
+        }
+        // `<commit> <original line> <final line> [<lines in group>]`
+        let mut fields = line.split(' ');
+        let commit = fields.next().unwrap_or_default();
+        if let Some(final_line) = fields.nth(1).and_then(|n| n.parse().ok()) {
+            current = Some((
+                final_line,
+                Blame {
+                    commit: commit.to_string(),
+                    author: String::new(),
+                    date: String::new(),
+                },
+            ));
+        }
+    }
+    Ok(blamed)
+}
+
+/// `YYYY-MM-DD` of a Unix timestamp, in UTC
+fn utc_date(timestamp: i64) -> String {
+    // Days since 1970-01-01 to a civil date, after Howard Hinnant's `civil_from_days`
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
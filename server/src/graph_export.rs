@@ -0,0 +1,396 @@
+use crate::graph_query::{reachable, GraphQuery, QueryError};
+use crate::repo_mapper::{ComponentType, MapLevel, RepositoryMapper};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Component(#[from] QueryError),
+    #[error("File '{0}' not found")]
+    UnknownFile(String),
+}
+
+/// Standard graph formats a map can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart, for Markdown
+    Mermaid,
+    /// `GraphML`, for Gephi, yEd and Cytoscape
+    #[serde(rename = "graphml")]
+    GraphMl,
+    /// JSON Graph Format, version 2
+    Jgf,
+}
+
+impl ExportFormat {
+    /// MIME type of the exported document
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Dot => "text/vnd.graphviz; charset=utf-8",
+            Self::Mermaid => "text/plain; charset=utf-8",
+            Self::GraphMl => "application/graphml+xml; charset=utf-8",
+            Self::Jgf => "application/json",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            "graphml" => Ok(Self::GraphMl),
+            "jgf" => Ok(Self::Jgf),
+            other => Err(format!(
+                "Unknown export format '{other}', expected 'dot', 'mermaid', 'graphml' or 'jgf'"
+            )),
+        }
+    }
+}
+
+/// A component or file in an exported graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportNode {
+    pub id: String,
+    pub label: String,
+    /// `function`, `method`, `class`, `enum`, `module`, `interface` or `file`
+    pub kind: String,
+    pub file: String,
+    /// First line of a component; files have none
+    pub line: Option<usize>,
+}
+
+/// A dependency between two nodes of an exported graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExportEdge {
+    pub from: String,
+    pub to: String,
+    /// `function_call`, `method_call`, `class_inheritance`, `module_import`, `field_access`,
+    /// or `import` between files
+    pub relation: String,
+}
+
+/// The component or file graph of a repository, or the part of it around a focus, ready to
+/// be written in a standard format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportGraph {
+    pub level: MapLevel,
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<ExportEdge>,
+}
+
+fn component_kind(component_type: &ComponentType) -> &'static str {
+    match component_type {
+        ComponentType::Function => "function",
+        ComponentType::Method => "method",
+        ComponentType::Class => "class",
+        ComponentType::Enum => "enum",
+        ComponentType::Module => "module",
+        ComponentType::Interface => "interface",
+    }
+}
+
+impl ExportGraph {
+    /// Components and their dependencies, or with a focus only its
+    /// [`RepositoryMapper::subgraph`]
+    pub fn components(
+        mapper: &RepositoryMapper,
+        focus: Option<&GraphQuery>,
+    ) -> Result<Self, ExportError> {
+        let components = &mapper.graph.components;
+        let mut nodes: Vec<ExportNode> = components
+            .values()
+            .map(|component| ExportNode {
+                id: component.id.clone(),
+                label: component.name.clone(),
+                kind: component_kind(&component.component_type).to_string(),
+                file: component.file_path.display().to_string(),
+                line: Some(component.start_line),
+            })
+            .collect();
+        let subgraph = focus
+            .map(|query| {
+                mapper.subgraph(&query.seed, query.depth, query.direction, &query.edge_types)
+            })
+            .transpose()?;
+        let graph_edges = subgraph
+            .as_ref()
+            .map_or(mapper.graph.edges.as_slice(), |subgraph| {
+                subgraph.edges.as_slice()
+            });
+        let edges: BTreeSet<ExportEdge> = graph_edges
+            .iter()
+            .filter(|edge| components.contains_key(&edge.from) && components.contains_key(&edge.to))
+            .map(|edge| ExportEdge {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                relation: edge.edge_type.as_str().to_string(),
+            })
+            .collect();
+        let edges: Vec<ExportEdge> = edges.into_iter().collect();
+
+        if let Some(subgraph) = &subgraph {
+            nodes.retain(|node| subgraph.nodes.iter().any(|kept| kept.id == node.id));
+        }
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(Self {
+            level: MapLevel::Component,
+            nodes,
+            edges,
+        })
+    }
+
+    /// Files and their imports. With a focus, only the files within its depth of the file
+    /// given by the seed's path or path suffix, following imports in its direction.
+    pub fn files(
+        mapper: &RepositoryMapper,
+        focus: Option<&GraphQuery>,
+    ) -> Result<Self, ExportError> {
+        let graph = &mapper.file_graph;
+        let mut nodes: Vec<ExportNode> = graph
+            .files
+            .keys()
+            .map(|path| ExportNode {
+                id: path.clone(),
+                label: path.strip_prefix("./").unwrap_or(path).to_string(),
+                kind: "file".to_string(),
+                file: path.clone(),
+                line: None,
+            })
+            .collect();
+        let mut edges: Vec<ExportEdge> = graph
+            .edges
+            .iter()
+            .map(|edge| ExportEdge {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                relation: "import".to_string(),
+            })
+            .collect();
+
+        if let Some(query) = focus {
+            let focus = query.seed.as_str();
+            let seed = graph
+                .files
+                .keys()
+                .find(|path| *path == focus)
+                .or_else(|| graph.files.keys().find(|path| path.ends_with(focus)))
+                .ok_or_else(|| ExportError::UnknownFile(focus.to_string()))?;
+            let kept: BTreeSet<String> = reachable(
+                edges
+                    .iter()
+                    .map(|edge| (edge.from.as_str(), edge.to.as_str())),
+                &[seed.as_str()],
+                query.depth,
+                query.direction,
+            )
+            .into_keys()
+            .map(str::to_string)
+            .collect();
+            nodes.retain(|node| kept.contains(&node.id));
+            edges.retain(|edge| kept.contains(&edge.from) && kept.contains(&edge.to));
+        }
+        Ok(Self {
+            level: MapLevel::File,
+            nodes,
+            edges,
+        })
+    }
+
+    /// Write the graph in `format`
+    pub fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Dot => self.to_dot(),
+            ExportFormat::Mermaid => self.to_mermaid(),
+            ExportFormat::GraphMl => self.to_graphml(),
+            ExportFormat::Jgf => self.to_jgf(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph contexter {\n");
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            let shape = match node.kind.as_str() {
+                "file" => "note",
+                "module" => "folder",
+                "class" | "enum" | "interface" => "box",
+                _ => "ellipse",
+            };
+            let tooltip = match node.line {
+                Some(line) => format!("{}:{line}", node.file),
+                None => node.file.clone(),
+            };
+            writeln!(
+                dot,
+                "  {} [label={}, shape={shape}, tooltip={}];",
+                dot_quote(&node.id),
+                dot_quote(&node.label),
+                dot_quote(&tooltip)
+            )
+            .expect("Writing to a String should not fail");
+        }
+        for edge in &self.edges {
+            write!(
+                dot,
+                "  {} -> {}",
+                dot_quote(&edge.from),
+                dot_quote(&edge.to)
+            )
+            .expect("Writing to a String should not fail");
+            if edge.relation != "import" {
+                write!(dot, " [label={}]", dot_quote(&edge.relation))
+                    .expect("Writing to a String should not fail");
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_mermaid(&self) -> String {
+        // Mermaid IDs cannot hold paths, so nodes are numbered
+        let ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id.as_str(), format!("n{idx}")))
+            .collect();
+        let mut mermaid = String::from("graph LR\n");
+        for node in &self.nodes {
+            let label = node.label.replace('"', "#quot;");
+            let (open, close) = match node.kind.as_str() {
+                "file" | "module" => ("[\"", "\"]"),
+                "class" | "enum" | "interface" => ("[[\"", "\"]]"),
+                _ => ("(\"", "\")"),
+            };
+            writeln!(mermaid, "  {}{open}{label}{close}", ids[node.id.as_str()])
+                .expect("Writing to a String should not fail");
+        }
+        for edge in &self.edges {
+            let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str()))
+            else {
+                continue;
+            };
+            if edge.relation == "import" {
+                writeln!(mermaid, "  {from} --> {to}")
+            } else {
+                writeln!(mermaid, "  {from} -->|{}| {to}", edge.relation)
+            }
+            .expect("Writing to a String should not fail");
+        }
+        mermaid
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, domain, kind) in [
+            ("label", "node", "string"),
+            ("kind", "node", "string"),
+            ("file", "node", "string"),
+            ("line", "node", "int"),
+            ("relation", "edge", "string"),
+        ] {
+            writeln!(
+                xml,
+                "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{id}\" attr.type=\"{kind}\"/>"
+            )
+            .expect("Writing to a String should not fail");
+        }
+        xml.push_str("  <graph id=\"contexter\" edgedefault=\"directed\">\n");
+        for node in &self.nodes {
+            writeln!(xml, "    <node id=\"{}\">", xml_escape(&node.id))
+                .expect("Writing to a String should not fail");
+            for (key, value) in [
+                ("label", node.label.as_str()),
+                ("kind", node.kind.as_str()),
+                ("file", node.file.as_str()),
+            ] {
+                writeln!(
+                    xml,
+                    "      <data key=\"{key}\">{}</data>",
+                    xml_escape(value)
+                )
+                .expect("Writing to a String should not fail");
+            }
+            if let Some(line) = node.line {
+                writeln!(xml, "      <data key=\"line\">{line}</data>")
+                    .expect("Writing to a String should not fail");
+            }
+            xml.push_str("    </node>\n");
+        }
+        for (idx, edge) in self.edges.iter().enumerate() {
+            writeln!(
+                xml,
+                "    <edge id=\"e{idx}\" source=\"{}\" target=\"{}\">\n      <data key=\"relation\">{}</data>\n    </edge>",
+                xml_escape(&edge.from),
+                xml_escape(&edge.to),
+                xml_escape(&edge.relation)
+            )
+            .expect("Writing to a String should not fail");
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    fn to_jgf(&self) -> String {
+        let nodes: serde_json::Map<String, serde_json::Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let value = serde_json::json!({
+                    "label": node.label,
+                    "metadata": {
+                        "kind": node.kind,
+                        "file": node.file,
+                        "line": node.line,
+                    }
+                });
+                (node.id.clone(), value)
+            })
+            .collect();
+        let edges: Vec<serde_json::Value> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                serde_json::json!({
+                    "source": edge.from,
+                    "target": edge.to,
+                    "relation": edge.relation,
+                })
+            })
+            .collect();
+        let graph = serde_json::json!({
+            "graph": {
+                "id": "contexter",
+                "type": self.level,
+                "directed": true,
+                "nodes": nodes,
+                "edges": edges,
+            }
+        });
+        serde_json::to_string_pretty(&graph).expect("Serializing a JSON value should not fail")
+    }
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
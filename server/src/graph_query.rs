@@ -0,0 +1,290 @@
+use crate::repo_mapper::{
+    CodeComponent, ComponentType, DependencyEdge, EdgeType, RepositoryMapper,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Candidates listed when a query matches several components
+const MAX_CANDIDATES: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("Component '{0}' not found")]
+    NotFound(String),
+    #[error("'{query}' matches {} components; use one of their IDs: {}", candidates.len(), candidates.join(", "))]
+    Ambiguous {
+        query: String,
+        candidates: Vec<String>,
+    },
+}
+
+/// Which edges to follow from the seed of a query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// What the seed depends on
+    Upstream,
+    /// What depends on the seed
+    Downstream,
+    /// Both, each followed separately
+    #[default]
+    Both,
+}
+
+impl Direction {
+    /// The direction selected by `--upstream` and `--downstream`; both or neither mean both
+    pub fn from_flags(upstream: bool, downstream: bool) -> Self {
+        match (upstream, downstream) {
+            (true, false) => Self::Upstream,
+            (false, true) => Self::Downstream,
+            _ => Self::Both,
+        }
+    }
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+/// A subgraph query: the components within `depth` edges of a seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQuery {
+    /// Component ID, name, qualified name such as `Config::merge`, or path-qualified name
+    /// such as `config.rs::Config::merge`
+    pub seed: String,
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    #[serde(default)]
+    pub direction: Direction,
+    /// Edge types to follow; all when empty
+    #[serde(default)]
+    pub edge_types: Vec<EdgeType>,
+}
+
+/// A component reached by a subgraph query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubgraphNode {
+    pub id: String,
+    pub name: String,
+    pub component_type: ComponentType,
+    pub file_path: PathBuf,
+    pub start_line: usize,
+    /// Edges between the seed and this component; 0 for the seed
+    pub distance: usize,
+}
+
+/// The components around a seed and the edges between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subgraph {
+    /// ID of the component the query was resolved to
+    pub seed: String,
+    pub depth: usize,
+    pub direction: Direction,
+    /// The seed first, then by distance and ID
+    pub nodes: Vec<SubgraphNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+impl Subgraph {
+    /// Nodes other than the seed that the seed depends on, directly or not
+    pub fn upstream(&self) -> Vec<&SubgraphNode> {
+        self.reached(Direction::Upstream)
+    }
+
+    /// Nodes other than the seed that depend on the seed, directly or not
+    pub fn downstream(&self) -> Vec<&SubgraphNode> {
+        self.reached(Direction::Downstream)
+    }
+
+    fn reached(&self, direction: Direction) -> Vec<&SubgraphNode> {
+        let pairs = self
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()));
+        let reached = reachable(pairs, &[self.seed.as_str()], self.depth, direction);
+        self.nodes
+            .iter()
+            .filter(|node| node.id != self.seed && reached.contains_key(node.id.as_str()))
+            .collect()
+    }
+}
+
+/// How closely a component matches a query; higher is closer
+fn match_score(component: &CodeComponent, query: &str) -> Option<u8> {
+    if component.id == query {
+        return Some(100);
+    }
+    if component.name == query {
+        return Some(90);
+    }
+    // `Config::merge`, `Config.merge` or `config.rs::Config::merge`
+    let dotted = query.replace('.', "::");
+    if [query, dotted.as_str()]
+        .iter()
+        .any(|qualified| component.id.ends_with(&format!("::{qualified}")))
+        || component.id.ends_with(&format!("/{query}"))
+    {
+        return Some(80);
+    }
+
+    let name = component.name.to_lowercase();
+    let query = query.to_lowercase();
+    if name == query {
+        Some(70)
+    } else if name.contains(&query) {
+        Some(50)
+    } else if is_subsequence(&query, &name) {
+        Some(30)
+    } else {
+        None
+    }
+}
+
+/// Whether the characters of `needle` appear in order in `haystack`, e.g. `cfgld` in
+/// `config_load`
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+/// The component best matching `query`: by ID, by name, by qualified or path-qualified
+/// name, case-insensitively, by substring and by subsequence, in that order
+pub(crate) fn resolve_component<'a>(
+    mapper: &'a RepositoryMapper,
+    query: &str,
+) -> Result<&'a CodeComponent, QueryError> {
+    let mut best: Vec<&CodeComponent> = Vec::new();
+    let mut best_score = 0;
+    for component in mapper.graph.components.values() {
+        let Some(score) = match_score(component, query) else {
+            continue;
+        };
+        if score > best_score {
+            best_score = score;
+            best.clear();
+        }
+        if score == best_score {
+            best.push(component);
+        }
+    }
+    match best.as_slice() {
+        [] => Err(QueryError::NotFound(query.to_string())),
+        [component] => Ok(component),
+        _ => {
+            let mut candidates: Vec<String> = best.iter().map(|c| c.id.clone()).collect();
+            candidates.sort();
+            candidates.truncate(MAX_CANDIDATES);
+            Err(QueryError::Ambiguous {
+                query: query.to_string(),
+                candidates,
+            })
+        }
+    }
+}
+
+/// Distances from the seeds to every node within `depth` edges, following `(from, to)`
+/// pairs forwards for upstream, backwards for downstream, or each separately for both
+pub(crate) fn reachable<'a>(
+    pairs: impl Iterator<Item = (&'a str, &'a str)>,
+    seeds: &[&'a str],
+    depth: usize,
+    direction: Direction,
+) -> HashMap<&'a str, usize> {
+    let mut forward: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut backward: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in pairs {
+        forward.entry(from).or_default().push(to);
+        backward.entry(to).or_default().push(from);
+    }
+    let mut adjacencies = Vec::new();
+    if direction != Direction::Downstream {
+        adjacencies.push(&forward);
+    }
+    if direction != Direction::Upstream {
+        adjacencies.push(&backward);
+    }
+
+    let mut distances: HashMap<&str, usize> = seeds.iter().map(|&seed| (seed, 0)).collect();
+    for adjacency in adjacencies {
+        let mut seen: HashMap<&str, usize> = seeds.iter().map(|&seed| (seed, 0)).collect();
+        let mut queue: VecDeque<(&str, usize)> = seeds.iter().map(|&seed| (seed, 0)).collect();
+        while let Some((id, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for &next in adjacency.get(id).into_iter().flatten() {
+                if !seen.contains_key(next) {
+                    seen.insert(next, distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        for (id, distance) in seen {
+            distances
+                .entry(id)
+                .and_modify(|known| *known = (*known).min(distance))
+                .or_insert(distance);
+        }
+    }
+    distances
+}
+
+/// The components within `depth` edges of the component matching `seed`
+pub(crate) fn subgraph(
+    mapper: &RepositoryMapper,
+    seed: &str,
+    depth: usize,
+    direction: Direction,
+    edge_types: &[EdgeType],
+) -> Result<Subgraph, QueryError> {
+    let seed = resolve_component(mapper, seed)?;
+    let edges: Vec<&DependencyEdge> = mapper
+        .graph
+        .edges
+        .iter()
+        .filter(|edge| edge_types.is_empty() || edge_types.contains(&edge.edge_type))
+        .collect();
+    let distances = reachable(
+        edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str())),
+        &[seed.id.as_str()],
+        depth,
+        direction,
+    );
+
+    let ordered: BTreeMap<(usize, &str), &CodeComponent> = distances
+        .iter()
+        .filter_map(|(&id, &distance)| {
+            let component = mapper.graph.components.get(id)?;
+            Some(((distance, id), component))
+        })
+        .collect();
+    let nodes = ordered
+        .into_iter()
+        .map(|((distance, _), component)| SubgraphNode {
+            id: component.id.clone(),
+            name: component.name.clone(),
+            component_type: component.component_type.clone(),
+            file_path: component.file_path.clone(),
+            start_line: component.start_line,
+            distance,
+        })
+        .collect();
+    let edges = edges
+        .into_iter()
+        .filter(|edge| {
+            distances.contains_key(edge.from.as_str()) && distances.contains_key(edge.to.as_str())
+        })
+        .cloned()
+        .collect();
+    Ok(Subgraph {
+        seed: seed.id.clone(),
+        depth,
+        direction,
+        nodes,
+        edges,
+    })
+}
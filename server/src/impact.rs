@@ -0,0 +1,318 @@
+use crate::git::{self, GitError};
+use crate::graph_query::{reachable, Direction};
+use crate::repo_mapper::RepositoryMapper;
+use crate::suggest::resolve_file;
+use crate::test_correlation::{test_subject, Exercised, TestCorrelation};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImpactError {
+    #[error("No changed files to analyze the impact of")]
+    NoChanges,
+    #[error("File '{0}' not found")]
+    UnknownFile(String),
+    #[error(transparent)]
+    Git(#[from] GitError),
+}
+
+/// A component depending on the change, directly or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedComponent {
+    pub id: String,
+    pub name: String,
+    pub file: String,
+    /// Dependency edges between the change and the component
+    pub distance: usize,
+    pub score: f64,
+}
+
+/// A file whose components or imports depend on the change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedFile {
+    pub path: String,
+    pub distance: usize,
+    pub score: f64,
+}
+
+/// A test file exercising the change, directly or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedTest {
+    pub path: String,
+    /// Names of the affected test functions
+    pub tests: Vec<String>,
+    pub distance: usize,
+    pub score: f64,
+}
+
+/// What a change to some files may break: everything depending on them, closest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub changed_files: Vec<String>,
+    /// IDs of the components defined in the changed files
+    pub changed_components: Vec<String>,
+    pub components: Vec<AffectedComponent>,
+    /// Source files other than the changed ones
+    pub files: Vec<AffectedFile>,
+    pub tests: Vec<AffectedTest>,
+}
+
+impl ImpactReport {
+    /// The changed files, the affected files and their tests, to review the change with
+    pub fn context_files(&self) -> Vec<PathBuf> {
+        let mut seen = BTreeSet::new();
+        self.changed_files
+            .iter()
+            .chain(self.files.iter().map(|file| &file.path))
+            .chain(self.tests.iter().map(|test| &test.path))
+            .filter(|path| seen.insert(path.as_str()))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Rewrite every file path in the report, e.g. into project-relative labels
+    pub fn relabel(&mut self, label: impl Fn(&str) -> String) {
+        for path in &mut self.changed_files {
+            *path = label(path);
+        }
+        for component in &mut self.components {
+            component.file = label(&component.file);
+        }
+        for file in &mut self.files {
+            file.path = label(&file.path);
+        }
+        for test in &mut self.tests {
+            test.path = label(&test.path);
+        }
+    }
+}
+
+/// 1 for the change itself, 1/2 for its direct dependents, 1/3 for theirs and so on
+fn proximity(distance: usize) -> f64 {
+    1.0 / (f64::from(u32::try_from(distance).unwrap_or(u32::MAX)) + 1.0)
+}
+
+/// Changed files under the analyzed roots between `base` and the work tree; deleted files
+/// are left out
+pub(crate) fn changed_files(
+    mapper: &RepositoryMapper,
+    base: &str,
+) -> Result<Vec<PathBuf>, ImpactError> {
+    let mut changed = Vec::new();
+    for root in &mapper.roots {
+        for file in git::changed_files(root, base)? {
+            let path = root.join(file);
+            if resolve_file(mapper, &path).is_some() {
+                changed.push(path);
+            } else {
+                debug!("Skipping deleted file {}", path.display());
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// The components, files and tests depending on `changed`, within `depth` edges
+pub(crate) fn analyze_impact(
+    mapper: &RepositoryMapper,
+    changed: &[PathBuf],
+    depth: Option<usize>,
+) -> Result<ImpactReport, ImpactError> {
+    if changed.is_empty() {
+        return Err(ImpactError::NoChanges);
+    }
+    let changed_files: BTreeSet<String> = changed
+        .iter()
+        .map(|file| {
+            resolve_file(mapper, file)
+                .ok_or_else(|| ImpactError::UnknownFile(file.display().to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+    let depth = depth.unwrap_or(usize::MAX);
+
+    let mut changed_components: Vec<&str> = mapper
+        .graph
+        .components
+        .values()
+        .filter(|component| changed_files.contains(&component.file_path.display().to_string()))
+        .map(|component| component.id.as_str())
+        .collect();
+    changed_components.sort_unstable();
+    let component_distances = reachable(
+        mapper
+            .graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str())),
+        &changed_components,
+        depth,
+        Direction::Downstream,
+    );
+
+    // Files are affected through their components and through importing affected files
+    let seeds: Vec<&str> = changed_files.iter().map(String::as_str).collect();
+    let mut file_distances: HashMap<String, usize> = reachable(
+        mapper
+            .file_graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str())),
+        &seeds,
+        depth,
+        Direction::Downstream,
+    )
+    .into_iter()
+    .map(|(path, distance)| (path.to_string(), distance))
+    .collect();
+
+    let correlation = mapper.correlate_tests();
+    let test_cases: BTreeSet<&str> = correlation
+        .test_files
+        .iter()
+        .flat_map(|test_file| &test_file.tests)
+        .map(|test| test.id.as_str())
+        .collect();
+    let components = affected_components(
+        mapper,
+        &component_distances,
+        &test_cases,
+        &mut file_distances,
+    );
+
+    let is_test_file =
+        |path: &str| test_subject(mapper.relative_to_root(Path::new(path))).is_some();
+    let tests = affected_tests(
+        &correlation,
+        &component_distances,
+        &file_distances,
+        depth,
+        is_test_file,
+    );
+    let mut files: Vec<AffectedFile> = file_distances
+        .into_iter()
+        .filter(|(path, _)| !changed_files.contains(path) && !is_test_file(path))
+        .map(|(path, distance)| AffectedFile {
+            path,
+            distance,
+            score: proximity(distance),
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    Ok(ImpactReport {
+        changed_files: changed_files.into_iter().collect(),
+        changed_components: changed_components.into_iter().map(String::from).collect(),
+        components,
+        files,
+        tests,
+    })
+}
+
+/// Affected components other than the changed ones and tests, also marking their files as
+/// affected
+fn affected_components(
+    mapper: &RepositoryMapper,
+    distances: &HashMap<&str, usize>,
+    test_cases: &BTreeSet<&str>,
+    file_distances: &mut HashMap<String, usize>,
+) -> Vec<AffectedComponent> {
+    let mut components = Vec::new();
+    for (&id, &distance) in distances {
+        let Some(component) = mapper.graph.components.get(id) else {
+            continue;
+        };
+        let file = component.file_path.display().to_string();
+        file_distances
+            .entry(file.clone())
+            .and_modify(|known| *known = (*known).min(distance))
+            .or_insert(distance);
+        if distance > 0 && !test_cases.contains(id) {
+            components.push(AffectedComponent {
+                id: id.to_string(),
+                name: component.name.clone(),
+                file,
+                distance,
+                score: proximity(distance),
+            });
+        }
+    }
+    components.sort_by(|a, b| a.distance.cmp(&b.distance).then_with(|| a.id.cmp(&b.id)));
+    components
+}
+
+/// Test files whose tests call, are named after or import an affected component, and
+/// dedicated test files that import or are named after an affected file
+fn affected_tests(
+    correlation: &TestCorrelation,
+    components: &HashMap<&str, usize>,
+    files: &HashMap<String, usize>,
+    depth: usize,
+    is_test_file: impl Fn(&str) -> bool,
+) -> Vec<AffectedTest> {
+    // One step further than the closest affected component a test exercises
+    let through = |exercised: &[Exercised]| {
+        exercised
+            .iter()
+            .filter_map(|e| components.get(e.component.as_str()))
+            .min()
+            .map(|distance| distance + 1)
+    };
+
+    let mut tests = Vec::new();
+    for test_file in &correlation.test_files {
+        let mut affected: BTreeMap<&str, usize> = BTreeMap::new();
+        for test in &test_file.tests {
+            let distance = components
+                .get(test.id.as_str())
+                .copied()
+                .into_iter()
+                .chain(through(&test.exercises))
+                .min();
+            if let Some(distance) = distance.filter(|&distance| distance <= depth) {
+                affected.insert(test.name.as_str(), distance);
+            }
+        }
+
+        let mut file_distance = through(&test_file.exercises);
+        if is_test_file(&test_file.path) {
+            let paired = test_file
+                .sources
+                .iter()
+                .filter_map(|source| files.get(source))
+                .map(|distance| distance + 1);
+            file_distance = file_distance
+                .into_iter()
+                .chain(files.get(&test_file.path).copied())
+                .chain(paired)
+                .min();
+        }
+        let Some(distance) = affected
+            .values()
+            .copied()
+            .chain(file_distance)
+            .min()
+            .filter(|&distance| distance <= depth)
+        else {
+            continue;
+        };
+        tests.push(AffectedTest {
+            path: test_file.path.clone(),
+            tests: affected.into_keys().map(String::from).collect(),
+            distance,
+            score: proximity(distance),
+        });
+    }
+    tests.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    tests
+}
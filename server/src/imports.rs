@@ -0,0 +1,346 @@
+use crate::language::SymbolIndex;
+use std::path::{Path, PathBuf};
+
+/// Extensions tried, in order, for extensionless JavaScript/TypeScript specifiers
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
+/// Path aliases from the nearest `tsconfig.json` or `jsconfig.json`
+#[derive(Debug, Clone, Default)]
+pub struct JsConfig {
+    dir: PathBuf,
+    base_url: Option<PathBuf>,
+    paths: Vec<(String, Vec<String>)>,
+}
+
+impl JsConfig {
+    /// Find and parse the config governing `dir`
+    pub fn find(dir: &Path) -> Option<Self> {
+        dir.ancestors().find_map(|ancestor| {
+            ["tsconfig.json", "jsconfig.json"]
+                .iter()
+                .find_map(|name| std::fs::read_to_string(ancestor.join(name)).ok())
+                .map(|content| Self::parse(ancestor, &content))
+        })
+    }
+
+    /// Parse a config, tolerating the comments and trailing commas `tsc` accepts
+    pub fn parse(dir: &Path, content: &str) -> Self {
+        let mut config = Self {
+            dir: dir.to_path_buf(),
+            ..Self::default()
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&strip_json_comments(content))
+        else {
+            return config;
+        };
+        let Some(options) = value.get("compilerOptions") else {
+            return config;
+        };
+        config.base_url = options
+            .get("baseUrl")
+            .and_then(|url| url.as_str())
+            .map(|url| normalize_path(&dir.join(url)));
+        if let Some(paths) = options.get("paths").and_then(|paths| paths.as_object()) {
+            config.paths = paths
+                .iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets
+                        .as_array()
+                        .map(|targets| {
+                            targets
+                                .iter()
+                                .filter_map(|target| target.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    (pattern.clone(), targets)
+                })
+                .collect();
+        }
+        config
+    }
+
+    /// Candidate paths for a bare specifier, from `paths` aliases then `baseUrl`
+    fn candidates(&self, specifier: &str) -> Vec<PathBuf> {
+        let base = self.base_url.as_deref().unwrap_or(&self.dir);
+        let mut candidates = Vec::new();
+        for (pattern, targets) in &self.paths {
+            let capture = match pattern.split_once('*') {
+                Some((prefix, suffix)) => specifier
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix)),
+                None => (specifier == pattern).then_some(""),
+            };
+            if let Some(capture) = capture {
+                candidates.extend(
+                    targets
+                        .iter()
+                        .map(|target| base.join(target.replacen('*', capture, 1))),
+                );
+            }
+        }
+        if let Some(base_url) = &self.base_url {
+            candidates.push(base_url.join(specifier));
+        }
+        candidates
+    }
+}
+
+/// Resolve a `use` path or `mod` declaration (recorded as `self::name`) to a file
+pub fn resolve_rust(
+    path: &str,
+    file: &Path,
+    krate: Option<&RustCrate>,
+    index: &SymbolIndex,
+) -> Option<PathBuf> {
+    let crate_src = krate.map(|krate| krate.src.as_path());
+    let crate_name = krate.and_then(|krate| krate.name.as_deref());
+    let mut segments: Vec<&str> = path.split("::").filter(|s| !s.is_empty()).collect();
+    while matches!(segments.last(), Some(&"self" | &"*")) {
+        segments.pop();
+    }
+    let module_dir = rust_module_dir(file)?;
+
+    let (bases, rest, keyword) = match segments.first() {
+        // Integration tests and binaries name their own library
+        Some(&first) if first == "crate" || Some(first) == crate_name => {
+            (vec![crate_src?.to_path_buf()], &segments[1..], true)
+        }
+        Some(&"self") => (vec![module_dir], &segments[1..], true),
+        Some(&"super") => {
+            let supers = segments.iter().take_while(|s| **s == "super").count();
+            let mut dir = module_dir;
+            for _ in 0..supers {
+                dir = dir.parent()?.to_path_buf();
+            }
+            (vec![dir], &segments[supers..], true)
+        }
+        Some(_) => {
+            let mut bases = vec![module_dir];
+            bases.extend(crate_src.map(Path::to_path_buf));
+            (bases, &segments[..], false)
+        }
+        None => return None,
+    };
+
+    for base in &bases {
+        for len in (1..=rest.len()).rev() {
+            let module = rest[..len].iter().collect::<PathBuf>();
+            let candidates = [
+                base.join(&module).with_extension("rs"),
+                base.join(&module).join("mod.rs"),
+            ];
+            if let Some(found) = candidates.iter().find_map(|c| index.file(c)) {
+                return Some(found.to_path_buf());
+            }
+        }
+    }
+
+    // Items of `crate`, `self` or `super` that live in the module file itself
+    if keyword {
+        let base = &bases[0];
+        let mut candidates = vec![base.join("mod.rs"), base.with_extension("rs")];
+        if crate_src == Some(base.as_path()) {
+            candidates.extend([base.join("lib.rs"), base.join("main.rs")]);
+        }
+        return candidates
+            .iter()
+            .find_map(|c| index.file(c))
+            .map(Path::to_path_buf);
+    }
+    None
+}
+
+/// Directory holding the submodules of the module defined by `file`
+fn rust_module_dir(file: &Path) -> Option<PathBuf> {
+    let parent = file.parent()?;
+    let stem = file.file_stem()?.to_str()?;
+    if matches!(stem, "lib" | "main" | "mod") || parent.ends_with("bin") {
+        Some(parent.to_path_buf())
+    } else {
+        Some(parent.join(stem))
+    }
+}
+
+/// The crate a Rust file belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustCrate {
+    /// The crate's `src` directory
+    pub src: PathBuf,
+    /// Library name, as used by integration tests, examples and binaries
+    pub name: Option<String>,
+}
+
+/// Find the crate containing `file` from the nearest `Cargo.toml`
+pub fn find_crate(file: &Path) -> Option<RustCrate> {
+    let dir = file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())?;
+    let manifest = std::fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok());
+    let name = manifest.and_then(|manifest| {
+        let name_in = |section: &str| {
+            manifest
+                .get(section)
+                .and_then(|table| table.get("name"))
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        };
+        name_in("lib")
+            .or_else(|| name_in("package"))
+            .map(|name| name.replace('-', "_"))
+    });
+    Some(RustCrate {
+        src: dir.join("src"),
+        name,
+    })
+}
+
+/// Resolve an `import` or `from ... import` path to a module or package file
+pub fn resolve_python(path: &str, file: &Path, index: &SymbolIndex) -> Option<PathBuf> {
+    let dots = path.chars().take_while(|c| *c == '.').count();
+    let segments: Vec<&str> = path[dots..].split('.').filter(|s| !s.is_empty()).collect();
+    let dir = file.parent()?;
+
+    let bases: Vec<&Path> = if dots > 0 {
+        let mut base = dir;
+        for _ in 1..dots {
+            base = base.parent()?;
+        }
+        vec![base]
+    } else {
+        // Absolute imports: the nearest directory containing the module wins
+        dir.ancestors().collect()
+    };
+
+    for base in bases {
+        for len in (1..=segments.len()).rev() {
+            let module = segments[..len].iter().collect::<PathBuf>();
+            let candidates = [
+                base.join(&module).with_extension("py"),
+                base.join(&module).join("__init__.py"),
+            ];
+            if let Some(found) = candidates.iter().find_map(|c| index.file(c)) {
+                return Some(found.to_path_buf());
+            }
+        }
+        if dots > 0 {
+            return index.file(&base.join("__init__.py")).map(Path::to_path_buf);
+        }
+    }
+    None
+}
+
+/// Resolve a JavaScript/TypeScript module specifier to a file
+pub fn resolve_js(
+    specifier: &str,
+    file: &Path,
+    config: Option<&JsConfig>,
+    index: &SymbolIndex,
+) -> Option<PathBuf> {
+    let bases = if specifier.starts_with('.') {
+        vec![file.parent()?.join(specifier)]
+    } else {
+        config.map(|config| config.candidates(specifier))?
+    };
+    bases.iter().find_map(|base| resolve_js_path(base, index))
+}
+
+/// Try a path as written, with each extension, and as a directory with an index file
+fn resolve_js_path(base: &Path, index: &SymbolIndex) -> Option<PathBuf> {
+    let mut candidates = vec![base.to_path_buf()];
+    // TypeScript sources are imported with the extension of their compiled output
+    if let Some(ext) = base.extension().and_then(|ext| ext.to_str()) {
+        let sources: &[&str] = match ext {
+            "js" => &["ts", "tsx"],
+            "jsx" => &["tsx"],
+            "mjs" => &["mts"],
+            "cjs" => &["cts"],
+            _ => &[],
+        };
+        candidates.extend(sources.iter().map(|source| base.with_extension(source)));
+    }
+    let name = base.as_os_str().to_string_lossy();
+    candidates.extend(
+        JS_EXTENSIONS
+            .iter()
+            .map(|ext| PathBuf::from(format!("{name}.{ext}"))),
+    );
+    candidates.extend(
+        JS_EXTENSIONS
+            .iter()
+            .map(|ext| base.join(format!("index.{ext}"))),
+    );
+    candidates
+        .iter()
+        .find_map(|c| index.file(c))
+        .map(Path::to_path_buf)
+}
+
+/// Remove `//` and `/* */` comments and trailing commas outside of strings
+fn strip_json_comments(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            ('}' | ']', _) => {
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.truncate(trimmed - 1);
+                }
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
@@ -0,0 +1,383 @@
+use crate::imports::{self, normalize_path, JsConfig, RustCrate};
+use crate::repo_mapper::CodeComponent;
+use crate::syntax::{self, CallSite, FileSyntax, ImportDecl, SourceLanguage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// What a file's top-level declarations are grouped under in the dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Container {
+    /// A package shared by every file that declares the same `key`
+    Package {
+        key: String,
+        name: String,
+        line: usize,
+    },
+    /// The file itself, as with C and C++ translation units and headers
+    File,
+}
+
+/// Which components an import makes dependent on its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// The file's container, or its top-level components if it has none
+    Container,
+    /// The file's top-level types, or its container if it declares none
+    TopLevelTypes,
+}
+
+/// Lookup tables available while resolving imports, built once every file is parsed
+pub struct SymbolIndex<'a> {
+    pub(crate) packages: &'a HashMap<String, String>,
+    pub(crate) types: &'a HashMap<String, String>,
+    pub(crate) file_modules: &'a HashMap<PathBuf, String>,
+    pub(crate) components: &'a HashMap<String, CodeComponent>,
+    /// Analyzed files by normalized path
+    pub(crate) files: &'a HashMap<PathBuf, PathBuf>,
+}
+
+impl SymbolIndex<'_> {
+    /// Component ID of the package registered under `key`
+    pub fn package(&self, key: &str) -> Option<&str> {
+        self.packages.get(key).map(String::as_str)
+    }
+
+    /// Component ID of a type by its package-qualified name, e.g. `com.acme.Store`
+    pub fn type_id(&self, qualified_name: &str) -> Option<&str> {
+        self.types.get(qualified_name).map(String::as_str)
+    }
+
+    /// Component ID of the module standing for a file, for `Container::File` languages
+    pub fn file_module(&self, path: &Path) -> Option<&str> {
+        self.file_modules.get(path).map(String::as_str)
+    }
+
+    /// All files with a module component, with their component IDs
+    pub fn file_modules(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.file_modules
+            .iter()
+            .map(|(path, id)| (path.as_path(), id.as_str()))
+    }
+
+    /// The analyzed file at `path`, as it was discovered, if there is one
+    pub fn file(&self, path: &Path) -> Option<&Path> {
+        self.files.get(&normalize_path(path)).map(PathBuf::as_path)
+    }
+
+    /// Files a component is declared in: its own file and those of its direct children,
+    /// so that a package spans every file declaring it
+    pub fn component_files(&self, id: &str) -> Vec<PathBuf> {
+        let Some(component) = self.components.get(id) else {
+            return Vec::new();
+        };
+        let mut files = vec![component.file_path.clone()];
+        for child in &component.children {
+            if let Some(child) = self.components.get(child) {
+                if !files.contains(&child.file_path) {
+                    files.push(child.file_path.clone());
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Teaches `RepositoryMapper` to understand a language.
+///
+/// Register implementations with `RepositoryMapper::register_analyzer`. Analyzers
+/// registered later take precedence, so a built-in language can be overridden.
+pub trait LanguageAnalyzer: Send + Sync {
+    /// Short identifier, e.g. `"rust"`
+    fn name(&self) -> &str;
+
+    /// File extensions (without the dot) used to discover candidate files
+    fn extensions(&self) -> &[&str];
+
+    /// Whether this analyzer handles `path`; defaults to matching `extensions`
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions().contains(&ext))
+    }
+
+    /// Extract components, imports and the package declaration from a file.
+    ///
+    /// Returning `None` lets the mapper fall back to its regex extractor where one exists.
+    fn extract(&self, path: &Path, source: &str) -> Option<FileSyntax>;
+
+    /// Extract the function and method calls made in a file
+    fn extract_call_sites(&self, _path: &Path, _source: &str) -> Vec<CallSite> {
+        Vec::new()
+    }
+
+    /// What the file's top-level declarations are grouped under
+    fn container(&self, _path: &Path, syntax: &FileSyntax) -> Option<Container> {
+        syntax.package.as_ref().map(|package| Container::Package {
+            key: format!("{}:{}", self.name(), package.name),
+            name: package.name.clone(),
+            line: package.line,
+        })
+    }
+
+    /// Which components depend on the targets of the file's imports
+    fn import_source(&self) -> ImportSource {
+        ImportSource::Container
+    }
+
+    /// Resolve an import to the ID of the component it refers to
+    fn resolve_import(
+        &self,
+        _import: &ImportDecl,
+        _file: &Path,
+        _index: &SymbolIndex,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Resolve an import to the files it refers to, for the file-level graph.
+    ///
+    /// Defaults to the files declaring the component found by `resolve_import`.
+    fn resolve_import_files(
+        &self,
+        import: &ImportDecl,
+        file: &Path,
+        index: &SymbolIndex,
+    ) -> Vec<PathBuf> {
+        self.resolve_import(import, file, index)
+            .map(|id| index.component_files(&id))
+            .unwrap_or_default()
+    }
+}
+
+/// A language supported out of the box, backed by the extractors in `syntax`
+pub struct BuiltinAnalyzer {
+    language: SourceLanguage,
+    /// `go.mod` lookups by directory, for Go import resolution
+    go_modules: Mutex<HashMap<PathBuf, Option<(PathBuf, String)>>>,
+    /// Crates by directory, for Rust `crate::` paths
+    crates: Mutex<HashMap<PathBuf, Option<RustCrate>>>,
+    /// `tsconfig.json`/`jsconfig.json` lookups by directory, for path aliases
+    js_configs: Mutex<HashMap<PathBuf, Option<JsConfig>>>,
+}
+
+impl BuiltinAnalyzer {
+    pub fn new(language: SourceLanguage) -> Self {
+        Self {
+            language,
+            go_modules: Mutex::new(HashMap::new()),
+            crates: Mutex::new(HashMap::new()),
+            js_configs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Analyzers for every built-in language
+    pub fn all() -> Vec<Self> {
+        SourceLanguage::ALL.into_iter().map(Self::new).collect()
+    }
+
+    fn resolve_go_import(
+        &self,
+        import: &ImportDecl,
+        file: &Path,
+        index: &SymbolIndex,
+    ) -> Option<String> {
+        let dir = file.parent()?;
+        let (module_dir, module_path) = self
+            .go_modules
+            .lock()
+            .expect("Go module cache mutex should not be poisoned")
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| find_go_module(dir))
+            .clone()?;
+
+        let relative = if import.path == module_path {
+            ""
+        } else {
+            import.path.strip_prefix(&module_path)?.strip_prefix('/')?
+        };
+        let package_dir = if relative.is_empty() {
+            module_dir
+        } else {
+            module_dir.join(relative)
+        };
+        index
+            .package(&format!("go:{}", package_dir.display()))
+            .map(str::to_string)
+    }
+
+    /// An import names a type or, for wildcards, a package. Static imports and nested types
+    /// are resolved to the known type enclosing them, and a Kotlin top-level function to its
+    /// exact package; anything else is unresolved rather than taken for an enclosing package.
+    fn resolve_jvm_import(import: &ImportDecl, index: &SymbolIndex) -> Option<String> {
+        let path = import.path.as_str();
+        if let Some(id) = index
+            .type_id(path)
+            .or_else(|| index.package(&format!("jvm:{path}")))
+        {
+            return Some(id.to_string());
+        }
+
+        let (parent, member) = path.rsplit_once('.')?;
+        if member.starts_with(char::is_lowercase) {
+            if let Some(id) = index.package(&format!("jvm:{parent}")) {
+                return Some(id.to_string());
+            }
+        }
+        let mut candidate = parent;
+        loop {
+            if let Some(id) = index.type_id(candidate) {
+                return Some(id.to_string());
+            }
+            candidate = &candidate[..candidate.rfind('.')?];
+        }
+    }
+
+    /// Quoted includes are tried relative to the including file, then by path suffix
+    fn resolve_include(import: &ImportDecl, file: &Path, index: &SymbolIndex) -> Option<String> {
+        let include = Path::new(&import.path);
+        if !import.system {
+            if let Some(dir) = file.parent() {
+                if let Some(id) = index.file_module(&normalize_path(&dir.join(include))) {
+                    return Some(id.to_string());
+                }
+            }
+        }
+
+        // Headers found through include directories: pick the shortest matching path
+        index
+            .file_modules()
+            .filter(|(path, _)| path.ends_with(include))
+            .min_by_key(|(path, _)| (path.components().count(), path.to_path_buf()))
+            .map(|(_, id)| id.to_string())
+    }
+}
+
+impl LanguageAnalyzer for BuiltinAnalyzer {
+    fn name(&self) -> &str {
+        match self.language {
+            SourceLanguage::Rust => "rust",
+            SourceLanguage::Python => "python",
+            SourceLanguage::JavaScript => "javascript",
+            SourceLanguage::TypeScript => "typescript",
+            SourceLanguage::Tsx => "tsx",
+            SourceLanguage::Go => "go",
+            SourceLanguage::Java => "java",
+            SourceLanguage::Kotlin => "kotlin",
+            SourceLanguage::C => "c",
+            SourceLanguage::Cpp => "cpp",
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.language.extensions()
+    }
+
+    fn extract(&self, _path: &Path, source: &str) -> Option<FileSyntax> {
+        syntax::parse_source(self.language, source)
+    }
+
+    fn extract_call_sites(&self, _path: &Path, source: &str) -> Vec<CallSite> {
+        syntax::extract_call_sites(self.language, source)
+    }
+
+    fn container(&self, path: &Path, syntax: &FileSyntax) -> Option<Container> {
+        let package = syntax.package.as_ref();
+        match self.language {
+            // Go packages are directories
+            SourceLanguage::Go => package.map(|package| Container::Package {
+                key: format!(
+                    "go:{}",
+                    path.parent().unwrap_or_else(|| Path::new("")).display()
+                ),
+                name: package.name.clone(),
+                line: package.line,
+            }),
+            // Java and Kotlin share packages, which can span several source roots
+            SourceLanguage::Java | SourceLanguage::Kotlin => {
+                package.map(|package| Container::Package {
+                    key: format!("jvm:{}", package.name),
+                    name: package.name.clone(),
+                    line: package.line,
+                })
+            }
+            SourceLanguage::C | SourceLanguage::Cpp => Some(Container::File),
+            _ => None,
+        }
+    }
+
+    fn import_source(&self) -> ImportSource {
+        match self.language {
+            SourceLanguage::Java | SourceLanguage::Kotlin => ImportSource::TopLevelTypes,
+            _ => ImportSource::Container,
+        }
+    }
+
+    fn resolve_import(
+        &self,
+        import: &ImportDecl,
+        file: &Path,
+        index: &SymbolIndex,
+    ) -> Option<String> {
+        match self.language {
+            SourceLanguage::Go => self.resolve_go_import(import, file, index),
+            SourceLanguage::Java | SourceLanguage::Kotlin => {
+                Self::resolve_jvm_import(import, index)
+            }
+            SourceLanguage::C | SourceLanguage::Cpp => Self::resolve_include(import, file, index),
+            _ => None,
+        }
+    }
+
+    fn resolve_import_files(
+        &self,
+        import: &ImportDecl,
+        file: &Path,
+        index: &SymbolIndex,
+    ) -> Vec<PathBuf> {
+        let dir = file.parent().unwrap_or_else(|| Path::new(""));
+        let resolved = match self.language {
+            SourceLanguage::Rust => {
+                let krate = self
+                    .crates
+                    .lock()
+                    .expect("Crate cache mutex should not be poisoned")
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| imports::find_crate(file))
+                    .clone();
+                imports::resolve_rust(&import.path, file, krate.as_ref(), index)
+            }
+            SourceLanguage::Python => imports::resolve_python(&import.path, file, index),
+            SourceLanguage::JavaScript | SourceLanguage::TypeScript | SourceLanguage::Tsx => {
+                let config = self
+                    .js_configs
+                    .lock()
+                    .expect("JS config cache mutex should not be poisoned")
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| JsConfig::find(dir))
+                    .clone();
+                imports::resolve_js(&import.path, file, config.as_ref(), index)
+            }
+            _ => {
+                return self
+                    .resolve_import(import, file, index)
+                    .map(|id| index.component_files(&id))
+                    .unwrap_or_default()
+            }
+        };
+        resolved.into_iter().collect()
+    }
+}
+
+/// Find the `go.mod` governing `dir`, returning its directory and module path
+fn find_go_module(dir: &Path) -> Option<(PathBuf, String)> {
+    dir.ancestors().find_map(|ancestor| {
+        let content = std::fs::read_to_string(ancestor.join("go.mod")).ok()?;
+        let module_path = content.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix("module ")
+                .map(|path| path.trim().trim_matches('"').to_string())
+        })?;
+        Some((ancestor.to_path_buf(), module_path))
+    })
+}
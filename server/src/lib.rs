@@ -1,9 +1,35 @@
+pub mod annotations;
+pub mod architecture;
+pub mod chunking;
 pub mod cli;
+pub mod complexity;
 pub mod config;
+pub mod config_watcher;
 pub mod contexter;
+pub mod dead_code;
+pub mod dedup;
+pub mod doc_coverage;
+pub mod entry_points;
+pub mod file_graph;
+pub mod git;
+pub mod graph_export;
+pub mod graph_query;
+pub mod impact;
+pub mod imports;
+pub mod language;
+pub mod metrics;
+pub mod ranking;
+pub mod rate_limit;
 pub mod repo_mapper;
+pub mod repo_settings;
 pub mod server;
+pub mod signatures;
+pub mod suggest;
+pub mod syntax;
+pub mod test_correlation;
+pub mod truncate;
 pub mod utils;
+pub mod workspace;
 
 // These modules are not public, but their contents are used internally
 mod cli_handlers;
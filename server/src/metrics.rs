@@ -0,0 +1,222 @@
+use crate::server::AppState;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds (in seconds) of the latency histogram buckets.
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Upper bounds (in seconds) of the repository analysis duration buckets.
+const ANALYSIS_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// A cumulative Prometheus-style histogram.
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            writeln!(
+                out,
+                "{name}_bucket{{{labels}{separator}le=\"{bound}\"}} {count}"
+            )
+            .expect("Writing to a String should not fail");
+        }
+        writeln!(
+            out,
+            "{name}_bucket{{{labels}{separator}le=\"+Inf\"}} {}",
+            self.count
+        )
+        .expect("Writing to a String should not fail");
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        writeln!(out, "{name}_sum{labels} {}", self.sum)
+            .expect("Writing to a String should not fail");
+        writeln!(out, "{name}_count{labels} {}", self.count)
+            .expect("Writing to a String should not fail");
+    }
+}
+
+/// Server-wide counters and histograms exported in the Prometheus text format.
+pub struct Metrics {
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    latencies: Mutex<BTreeMap<String, Histogram>>,
+    analysis_durations: Mutex<Histogram>,
+    context_bytes: AtomicU64,
+    files_processed: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            requests: Mutex::new(BTreeMap::new()),
+            latencies: Mutex::new(BTreeMap::new()),
+            analysis_durations: Mutex::new(Histogram::new(ANALYSIS_BUCKETS)),
+            context_bytes: AtomicU64::new(0),
+            files_processed: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a completed HTTP request against its route pattern.
+    pub fn record_request(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        *self
+            .requests
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .entry((route.to_string(), method.to_string(), status))
+            .or_insert(0) += 1;
+        self.latencies
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .entry(route.to_string())
+            .or_insert_with(|| Histogram::new(LATENCY_BUCKETS))
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Record a context response of `bytes` built from `files` files.
+    pub fn record_context_served(&self, bytes: usize, files: usize) {
+        self.context_bytes
+            .fetch_add(u64::try_from(bytes).unwrap_or(u64::MAX), Ordering::Relaxed);
+        self.files_processed
+            .fetch_add(u64::try_from(files).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Record how long a repository analysis took.
+    pub fn record_analysis(&self, elapsed: Duration) {
+        self.analysis_durations
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP contexter_http_requests_total Total HTTP requests by route, method and status\n");
+        out.push_str("# TYPE contexter_http_requests_total counter\n");
+        for ((route, method, status), count) in self
+            .requests
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .iter()
+        {
+            writeln!(
+                &mut out,
+                "contexter_http_requests_total{{route=\"{route}\",method=\"{method}\",status=\"{status}\"}} {count}"
+            )
+            .expect("Writing to a String should not fail");
+        }
+
+        out.push_str(
+            "# HELP contexter_http_request_duration_seconds HTTP request latency by route\n",
+        );
+        out.push_str("# TYPE contexter_http_request_duration_seconds histogram\n");
+        for (route, histogram) in self
+            .latencies
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .iter()
+        {
+            histogram.render(
+                &mut out,
+                "contexter_http_request_duration_seconds",
+                &format!("route=\"{route}\""),
+            );
+        }
+
+        out.push_str("# HELP contexter_context_bytes_total Bytes of context served\n");
+        out.push_str("# TYPE contexter_context_bytes_total counter\n");
+        writeln!(
+            &mut out,
+            "contexter_context_bytes_total {}",
+            self.context_bytes.load(Ordering::Relaxed)
+        )
+        .expect("Writing to a String should not fail");
+
+        out.push_str("# HELP contexter_files_processed_total Files included in served context\n");
+        out.push_str("# TYPE contexter_files_processed_total counter\n");
+        writeln!(
+            &mut out,
+            "contexter_files_processed_total {}",
+            self.files_processed.load(Ordering::Relaxed)
+        )
+        .expect("Writing to a String should not fail");
+
+        out.push_str("# HELP contexter_repository_analysis_duration_seconds Time spent analyzing repositories\n");
+        out.push_str("# TYPE contexter_repository_analysis_duration_seconds histogram\n");
+        self.analysis_durations
+            .lock()
+            .expect("Metrics mutex should not be poisoned")
+            .render(
+                &mut out,
+                "contexter_repository_analysis_duration_seconds",
+                "",
+            );
+
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Middleware recording request counts and latencies per matched route.
+#[allow(clippy::future_not_send)]
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+    let metrics = req
+        .app_data::<web::Data<AppState>>()
+        .map(|data| Arc::clone(&data.metrics));
+    let start = Instant::now();
+
+    let res = next.call(req).await?;
+
+    if let Some(metrics) = metrics {
+        metrics.record_request(&route, &method, res.status().as_u16(), start.elapsed());
+    }
+    Ok(res)
+}
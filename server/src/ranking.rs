@@ -0,0 +1,228 @@
+use crate::complexity::FileComplexity;
+use crate::contexter::ProjectFile;
+use crate::git;
+use crate::imports::normalize_path;
+use crate::repo_mapper::RepositoryMapper;
+use crate::repo_settings::{matches_path, RepoSettings, SettingsError};
+use crate::suggest::{HISTORY_DEPTH, MAX_COMMIT_FILES};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Something about a file that makes it more important to show first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RankSignal {
+    /// Imported files before the files importing them
+    Dependencies,
+    /// Files imported by many others
+    FanIn,
+    /// Files changed in recent commits
+    Recency,
+    /// Files with the most complex code for their size
+    Complexity,
+}
+
+impl std::str::FromStr for RankSignal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dependencies" => Ok(Self::Dependencies),
+            "fan-in" => Ok(Self::FanIn),
+            "recency" => Ok(Self::Recency),
+            "complexity" => Ok(Self::Complexity),
+            _ => Err(format!(
+                "Unknown ranking signal '{s}', expected dependencies, fan-in, recency or complexity"
+            )),
+        }
+    }
+}
+
+/// Parses a ranking signal with an optional weight, e.g. `fan-in` or `fan-in=2`
+pub fn parse_weighted_signal(s: &str) -> Result<(RankSignal, f64), String> {
+    let (name, weight) = s.split_once('=').unwrap_or((s, "1"));
+    let weight = weight
+        .parse::<f64>()
+        .ok()
+        .filter(|weight| weight.is_finite() && *weight >= 0.0)
+        .ok_or_else(|| format!("Invalid weight '{weight}' for ranking signal '{name}'"))?;
+    Ok((name.parse()?, weight))
+}
+
+/// How gathered files are ordered, most important first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ranking {
+    /// The weight of each signal in a file's score. Each signal scores a file from 0 to 1.
+    #[serde(default)]
+    pub signals: BTreeMap<RankSignal, f64>,
+    /// Glob patterns, relative to the repository root, of files to put before all others,
+    /// in the order given
+    #[serde(default)]
+    pub priority: Vec<String>,
+}
+
+impl Ranking {
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty() && self.priority.is_empty()
+    }
+
+    /// `self`, with its signals and priorities taken from `fallback` where it has none
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            signals: if self.signals.is_empty() {
+                fallback.signals
+            } else {
+                self.signals
+            },
+            priority: if self.priority.is_empty() {
+                fallback.priority
+            } else {
+                self.priority
+            },
+        }
+    }
+
+    /// The ranking in the `.contexter.toml` of the first of `roots` that configures one
+    pub fn configured(roots: &[PathBuf]) -> Result<Self, SettingsError> {
+        for root in roots {
+            let ranking = RepoSettings::load(root)?.ranking;
+            if !ranking.is_empty() {
+                return Ok(ranking);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Orders `files`: those matching a priority pattern first, by the first pattern they
+    /// match, then by weighted signal score, highest first, then by path
+    pub fn rank(&self, mapper: &RepositoryMapper, files: &mut [ProjectFile]) {
+        let scores = self.scores(mapper, files);
+        let tier = |file: &ProjectFile| {
+            let relative = mapper.relative_to_root(&file.path);
+            self.priority
+                .iter()
+                .position(|pattern| matches_path(pattern, relative))
+                .unwrap_or(self.priority.len())
+        };
+        let mut keyed: Vec<(usize, f64, ProjectFile)> = files
+            .iter()
+            .zip(scores)
+            .map(|(file, score)| (tier(file), score, file.clone()))
+            .collect();
+        keyed.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then_with(|| b.1.total_cmp(&a.1))
+                .then_with(|| a.2.display_path.cmp(&b.2.display_path))
+        });
+        for (slot, (_, _, file)) in files.iter_mut().zip(keyed) {
+            *slot = file;
+        }
+    }
+
+    /// The weighted score of each of `files`
+    fn scores(&self, mapper: &RepositoryMapper, files: &[ProjectFile]) -> Vec<f64> {
+        let keys: Vec<String> = files
+            .iter()
+            .map(|file| {
+                let normalized = normalize_path(&file.path);
+                let analyzed = mapper.files.get(&normalized).unwrap_or(&normalized);
+                analyzed.display().to_string()
+            })
+            .collect();
+        let mut scores = vec![0.0; files.len()];
+        for (&signal, &weight) in &self.signals {
+            let signal_scores = match signal {
+                RankSignal::Dependencies => dependency_scores(mapper),
+                RankSignal::FanIn => fan_in_scores(mapper),
+                RankSignal::Recency => recency_scores(mapper),
+                RankSignal::Complexity => complexity_scores(mapper),
+            };
+            for (score, key) in scores.iter_mut().zip(&keys) {
+                *score += weight * signal_scores.get(key).copied().unwrap_or_default();
+            }
+        }
+        scores
+    }
+}
+
+/// `position` of `count` as a score from 1 for the first to 0 for the last
+fn position_score(position: usize, count: usize) -> f64 {
+    if count <= 1 {
+        return 1.0;
+    }
+    let from_end = u32::try_from(count - 1 - position).unwrap_or(u32::MAX);
+    let last = u32::try_from(count - 1).unwrap_or(u32::MAX);
+    f64::from(from_end) / f64::from(last)
+}
+
+/// Files by their place in the file graph's topological order, imported files highest
+fn dependency_scores(mapper: &RepositoryMapper) -> HashMap<String, f64> {
+    let order = &mapper.file_graph.topological_order;
+    order
+        .iter()
+        .enumerate()
+        .map(|(position, path)| (path.clone(), position_score(position, order.len())))
+        .collect()
+}
+
+/// Files by how many files import them, relative to the most imported
+fn fan_in_scores(mapper: &RepositoryMapper) -> HashMap<String, f64> {
+    let nodes = &mapper.file_graph.files;
+    let most = nodes.values().map(|node| node.fan_in).max().unwrap_or(0);
+    let most = f64::from(u32::try_from(most).unwrap_or(u32::MAX));
+    nodes
+        .iter()
+        .filter(|(_, node)| node.fan_in > 0)
+        .map(|(path, node)| {
+            let fan_in = f64::from(u32::try_from(node.fan_in).unwrap_or(u32::MAX));
+            (path.clone(), fan_in / most)
+        })
+        .collect()
+}
+
+/// Files by cognitive complexity per line of code, relative to the densest
+fn complexity_scores(mapper: &RepositoryMapper) -> HashMap<String, f64> {
+    let densest = mapper
+        .file_complexity
+        .values()
+        .map(FileComplexity::density)
+        .fold(0.0, f64::max);
+    mapper
+        .file_complexity
+        .iter()
+        .filter(|(_, file)| file.density() > 0.0)
+        .map(|(path, file)| (path.clone(), file.density() / densest))
+        .collect()
+}
+
+/// Files by how recently a commit changed them, 1 for the latest commit and 0 for files
+/// not changed in recent history. Sweeping commits, such as reformats, are skipped.
+fn recency_scores(mapper: &RepositoryMapper) -> HashMap<String, f64> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for root in &mapper.roots {
+        let commits = match git::recent_commits(root, HISTORY_DEPTH) {
+            Ok(commits) => commits,
+            Err(e) => {
+                warn!("Skipping git history of {}: {e}", root.display());
+                continue;
+            }
+        };
+        let commits: Vec<git::Commit> = commits
+            .into_iter()
+            .filter(|commit| commit.files.len() <= MAX_COMMIT_FILES)
+            .collect();
+        for (position, commit) in commits.iter().enumerate() {
+            for file in &commit.files {
+                if let Some(analyzed) = mapper.files.get(&normalize_path(&root.join(file))) {
+                    let score = position_score(position, commits.len());
+                    let best = scores.entry(analyzed.display().to_string()).or_default();
+                    *best = best.max(score);
+                }
+            }
+        }
+    }
+    scores
+}
@@ -0,0 +1,152 @@
+use crate::config::RateLimitConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Why a request was turned away, and how long the client should wait before retrying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    /// The API key has used up its request allowance.
+    RateLimited { retry_after: Duration },
+    /// Too many expensive operations are already running on the server.
+    TooManyConcurrent { retry_after: Duration },
+}
+
+impl LimitExceeded {
+    /// Number of whole seconds to report in the `Retry-After` header.
+    pub fn retry_after_secs(&self) -> u64 {
+        let retry_after = match self {
+            Self::RateLimited { retry_after } | Self::TooManyConcurrent { retry_after } => {
+                *retry_after
+            }
+        };
+        // Round up so clients never retry before a token is actually available
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        secs.max(1)
+    }
+
+    /// Human-readable message for the error response body.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::RateLimited { .. } => "Rate limit exceeded for this API key",
+            Self::TooManyConcurrent { .. } => "Too many concurrent operations, try again shortly",
+        }
+    }
+}
+
+/// A token bucket tracking the request allowance of a single API key.
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// The semaphore of a concurrency cap, and the cap it was sized for
+struct OperationSlots {
+    cap: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+/// Guards expensive endpoints with per-key rate limits and a global concurrency cap.
+pub struct RequestLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    operations: Mutex<Option<OperationSlots>>,
+}
+
+impl RequestLimiter {
+    /// Create a limiter using the concurrency cap from the given configuration.
+    pub fn new(limits: &RateLimitConfig) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            operations: Mutex::new(Self::slots(limits.max_concurrent_operations)),
+        }
+    }
+
+    fn slots(cap: usize) -> Option<OperationSlots> {
+        (cap > 0).then(|| OperationSlots {
+            cap,
+            semaphore: Arc::new(Semaphore::new(cap)),
+        })
+    }
+
+    /// Consume one request from the allowance of `key_name`.
+    ///
+    /// Rates are read from `limits` on every call so configuration changes take effect
+    /// without rebuilding the limiter.
+    pub fn check_rate(
+        &self,
+        key_name: &str,
+        limits: &RateLimitConfig,
+    ) -> Result<(), LimitExceeded> {
+        if limits.requests_per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = f64::from(limits.burst.max(1));
+        let per_second = f64::from(limits.requests_per_minute) / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("Rate limiter mutex should not be poisoned");
+        let bucket = buckets
+            .entry(key_name.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: capacity,
+                last_refill: now,
+            });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = elapsed.mul_add(per_second, bucket.tokens).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / per_second;
+            Err(LimitExceeded::RateLimited {
+                retry_after: Duration::from_secs_f64(wait),
+            })
+        }
+    }
+
+    /// Reserve a slot for an expensive operation, held until the returned permit is dropped.
+    ///
+    /// Returns `Ok(None)` when no concurrency cap is configured. When the cap in `limits`
+    /// has changed, the slots are replaced; operations already running finish on the old
+    /// ones, so the new cap may briefly be exceeded.
+    pub fn try_begin_operation(
+        &self,
+        limits: &RateLimitConfig,
+    ) -> Result<Option<OwnedSemaphorePermit>, LimitExceeded> {
+        let mut operations = self
+            .operations
+            .lock()
+            .expect("Rate limiter mutex should not be poisoned");
+        let cap = limits.max_concurrent_operations;
+        if operations.as_ref().map_or(0, |slots| slots.cap) != cap {
+            *operations = Self::slots(cap);
+        }
+        match operations.as_ref() {
+            Some(slots) => Arc::clone(&slots.semaphore)
+                .try_acquire_owned()
+                .map(Some)
+                .map_err(|_| LimitExceeded::TooManyConcurrent {
+                    retry_after: Duration::from_secs(1),
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Apply both the per-key rate limit and the global concurrency cap.
+    pub fn admit(
+        &self,
+        key_name: &str,
+        limits: &RateLimitConfig,
+    ) -> Result<Option<OwnedSemaphorePermit>, LimitExceeded> {
+        self.check_rate(key_name, limits)?;
+        self.try_begin_operation(limits)
+    }
+}
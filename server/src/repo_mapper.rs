@@ -1,8 +1,26 @@
+use crate::annotations::Annotation;
+use crate::architecture::{Hotspot, LayerViolation};
+use crate::complexity::{approximate_note, ComplexityMetrics, FileComplexity};
+use crate::dead_code::DeadCode;
+use crate::doc_coverage::{ComponentDocs, DocCoverage};
+use crate::entry_points::EntryPoint;
+use crate::file_graph::{strongly_connected_components, FileGraph};
+use crate::graph_export::{ExportError, ExportFormat, ExportGraph};
+use crate::graph_query::{Direction, GraphQuery, QueryError, Subgraph};
+use crate::impact::{ImpactError, ImpactReport};
+use crate::imports::normalize_path;
+use crate::language::{BuiltinAnalyzer, Container, ImportSource, LanguageAnalyzer, SymbolIndex};
+use crate::repo_settings::RepoSettings;
+use crate::signatures::Signatures;
+use crate::suggest::{FileSuggestion, SuggestError};
+use crate::syntax::{CallSite, ExtractedComponent, FileSyntax, ImportDecl, PackageDecl};
+use crate::test_correlation::TestCorrelation;
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Represents a code component (function, method, class) in the repository
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,19 +34,27 @@ pub struct CodeComponent {
     pub visibility: Visibility,
     pub dependencies: Vec<String>, // IDs of components this depends on
     pub dependents: Vec<String>,   // IDs of components that depend on this
+    /// Cyclomatic complexity; for types and modules, the sum over their members
     pub complexity_score: u32,
+    #[serde(default)]
+    pub metrics: ComplexityMetrics,
+    #[serde(default)]
+    pub parent: Option<String>, // ID of the enclosing class, trait, impl target or module
+    #[serde(default)]
+    pub children: Vec<String>, // IDs of components nested directly inside this one
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ComponentType {
     Function,
     Method,
     Class,
+    Enum,
     Module,
     Interface,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Visibility {
     Public,
     Private,
@@ -51,7 +77,7 @@ pub struct DependencyEdge {
     pub edge_type: EdgeType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EdgeType {
     FunctionCall,
     ClassInheritance,
@@ -60,6 +86,61 @@ pub enum EdgeType {
     MethodCall,
 }
 
+impl EdgeType {
+    /// Name of the edge type in exported graphs and on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FunctionCall => "function_call",
+            Self::ClassInheritance => "class_inheritance",
+            Self::ModuleImport => "module_import",
+            Self::FieldAccess => "field_access",
+            Self::MethodCall => "method_call",
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "function_call" => Ok(Self::FunctionCall),
+            "class_inheritance" => Ok(Self::ClassInheritance),
+            "module_import" => Ok(Self::ModuleImport),
+            "field_access" => Ok(Self::FieldAccess),
+            "method_call" => Ok(Self::MethodCall),
+            other => Err(format!(
+                "Unknown edge type '{other}', expected 'function_call', 'method_call', 'class_inheritance', 'module_import' or 'field_access'"
+            )),
+        }
+    }
+}
+
+/// Granularity of a repository map
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapLevel {
+    /// Functions, types and modules, linked by imports and calls
+    #[default]
+    Component,
+    /// Source files, linked by imports
+    File,
+}
+
+impl std::str::FromStr for MapLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "component" => Ok(Self::Component),
+            "file" => Ok(Self::File),
+            other => Err(format!(
+                "Unknown map level '{other}', expected 'component' or 'file'"
+            )),
+        }
+    }
+}
+
 /// Repository insights and statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryInsights {
@@ -67,8 +148,14 @@ pub struct RepositoryInsights {
     pub total_components: usize,
     pub most_complex_files: Vec<String>,
     pub most_connected_components: Vec<String>,
+    /// Layers from top to bottom, from `.contexter.toml` or recognized by name
     pub architectural_layers: Vec<ArchitecturalLayer>,
-    pub entry_points: Vec<String>,
+    /// Imports from a lower layer into a higher one
+    #[serde(default)]
+    pub layering_violations: Vec<LayerViolation>,
+    /// Binaries, `main` functions, scripts, package entry modules and HTTP routes
+    pub entry_points: Vec<EntryPoint>,
+    /// Files ranked by fan-in times recent churn, highest first
     pub dependency_hotspots: Vec<String>,
 }
 
@@ -84,11 +171,53 @@ pub struct RepositoryMapper {
     pub graph: DependencyGraph,
     pub insights: RepositoryInsights,
     pub topological_order: Vec<String>,
+    /// File-level import graph, built alongside the component graph
+    pub file_graph: FileGraph,
+    /// Complexity of each analyzed file, keyed like the file graph
+    pub file_complexity: BTreeMap<String, FileComplexity>,
+    /// Files imported by many others that also change often, highest score first
+    pub hotspots: Vec<Hotspot>,
+    /// Registered language analyzers, most recently registered first
+    analyzers: Vec<Arc<dyn LanguageAnalyzer>>,
+    /// Imports waiting to be resolved once every file has been parsed
+    pending_imports: Vec<PendingImport>,
+    /// Calls waiting to be resolved once every component is known
+    pending_calls: Vec<(PathBuf, Vec<CallSite>)>,
+    /// Package components keyed by `Container::Package` keys
+    packages: HashMap<String, String>,
+    /// Package-qualified type names such as `com.acme.Store`
+    types: HashMap<String, String>,
+    /// The module component standing for each `Container::File` file
+    file_modules: HashMap<PathBuf, String>,
+    /// Every analyzed file, keyed by its normalized path
+    pub(crate) files: HashMap<PathBuf, PathBuf>,
+    /// Roots passed to the last analysis
+    pub(crate) roots: Vec<PathBuf>,
+    /// The `.contexter.toml` settings of each root, in the order of `roots`
+    pub(crate) settings: Vec<RepoSettings>,
+}
+
+/// An import whose target is only known after all files are parsed
+struct PendingImport {
+    /// Components that the import makes dependent on its target
+    from: Vec<String>,
+    analyzer: Arc<dyn LanguageAnalyzer>,
+    file: PathBuf,
+    import: ImportDecl,
 }
 
 impl RepositoryMapper {
-    /// Create a new repository mapper
+    /// Create a new repository mapper with the built-in language analyzers
     pub fn new() -> Self {
+        let mut mapper = Self::without_analyzers();
+        for analyzer in BuiltinAnalyzer::all() {
+            mapper.register_analyzer(analyzer);
+        }
+        mapper
+    }
+
+    /// Create a repository mapper that understands no languages until analyzers are registered
+    pub fn without_analyzers() -> Self {
         Self {
             graph: DependencyGraph {
                 components: HashMap::new(),
@@ -101,22 +230,68 @@ impl RepositoryMapper {
                 most_complex_files: Vec::new(),
                 most_connected_components: Vec::new(),
                 architectural_layers: Vec::new(),
+                layering_violations: Vec::new(),
                 entry_points: Vec::new(),
                 dependency_hotspots: Vec::new(),
             },
             topological_order: Vec::new(),
+            file_graph: FileGraph::default(),
+            file_complexity: BTreeMap::new(),
+            hotspots: Vec::new(),
+            analyzers: Vec::new(),
+            pending_imports: Vec::new(),
+            pending_calls: Vec::new(),
+            packages: HashMap::new(),
+            types: HashMap::new(),
+            file_modules: HashMap::new(),
+            files: HashMap::new(),
+            roots: Vec::new(),
+            settings: Vec::new(),
         }
     }
 
+    /// Register a language analyzer, taking precedence over those registered before it
+    pub fn register_analyzer<A: LanguageAnalyzer + 'static>(&mut self, analyzer: A) {
+        debug!("Registering language analyzer: {}", analyzer.name());
+        self.analyzers.insert(0, Arc::new(analyzer));
+    }
+
+    /// Names of the registered analyzers, in order of precedence
+    pub fn analyzer_names(&self) -> Vec<&str> {
+        self.analyzers
+            .iter()
+            .map(|analyzer| analyzer.name())
+            .collect()
+    }
+
+    fn analyzer_for(&self, path: &Path) -> Option<Arc<dyn LanguageAnalyzer>> {
+        self.analyzers
+            .iter()
+            .find(|analyzer| analyzer.matches(path))
+            .cloned()
+    }
+
     /// Analyze a repository and build the dependency graph
     pub fn analyze_repository(
         &mut self,
         repo_path: &Path,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Starting repository analysis for: {}", repo_path.display());
+        self.analyze_roots(&[repo_path.to_path_buf()])
+    }
+
+    /// Analyze several repository roots as one logical project
+    pub fn analyze_roots(&mut self, roots: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+        self.roots = roots.to_vec();
+        self.settings = roots
+            .iter()
+            .map(|root| RepoSettings::load(root))
+            .collect::<Result<_, _>>()?;
 
         // Step 1: Discover and parse files
-        self.discover_files(repo_path)?;
+        for repo_path in roots {
+            info!("Starting repository analysis for: {}", repo_path.display());
+            self.discover_files(repo_path)?;
+        }
 
         // Step 2: Build dependency graph
         self.build_dependency_graph();
@@ -127,7 +302,13 @@ impl RepositoryMapper {
         // Step 4: Compute topological order
         self.compute_topological_order();
 
-        // Step 5: Generate insights
+        // Step 5: Sum complexity into types, modules and files
+        self.aggregate_complexity();
+
+        // Step 6: Group files into layers and find hotspots
+        self.analyze_architecture();
+
+        // Step 7: Generate insights
         self.generate_insights();
 
         info!(
@@ -141,17 +322,29 @@ impl RepositoryMapper {
     fn discover_files(&mut self, repo_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         debug!("Discovering files in: {}", repo_path.display());
 
+        let mut extensions: Vec<&str> = self
+            .analyzers
+            .iter()
+            .flat_map(|analyzer| analyzer.extensions().iter().copied())
+            .collect();
+        extensions.sort_unstable();
+        extensions.dedup();
+        if extensions.is_empty() {
+            warn!("No language analyzers registered; nothing to analyze");
+            return Ok(());
+        }
+
         // Use existing file gathering logic from contexter
         let files = crate::contexter::gather_relevant_files(
             repo_path
                 .to_str()
                 .expect("Repository path should be valid UTF-8"),
-            &["rs", "py", "js", "ts"], // Support common languages
+            &extensions,
             vec![],
         )?;
 
         info!("Found {} source files", files.len());
-        self.insights.total_files = files.len();
+        self.insights.total_files += files.len();
 
         // For each file, extract basic components
         for file_path in files {
@@ -161,25 +354,259 @@ impl RepositoryMapper {
         Ok(())
     }
 
-    /// Parse a single file to extract components
+    /// Parse a single file to extract components. Files that are not valid UTF-8, such as
+    /// Latin-1 C sources, are decoded lossily rather than failing the analysis.
     fn parse_file(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string(file_path)?;
+        let bytes = std::fs::read(file_path)?;
+        let content = String::from_utf8_lossy(&bytes);
+        self.files
+            .insert(normalize_path(file_path), file_path.clone());
         let extension = file_path
             .extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("");
 
+        if let Some(analyzer) = self.analyzer_for(file_path) {
+            if let Some(file_syntax) = analyzer.extract(file_path, &content) {
+                let calls = analyzer.extract_call_sites(file_path, &content);
+                self.record_file_size(file_path, &content, Some(file_syntax.sloc));
+                self.add_file_syntax(file_path, &analyzer, &file_syntax, &content);
+                if !calls.is_empty() {
+                    self.pending_calls.push((file_path.clone(), calls));
+                }
+                return Ok(());
+            }
+            debug!(
+                "{} analyzer could not parse {}; falling back to regex extraction",
+                analyzer.name(),
+                file_path.display()
+            );
+        }
+
+        self.record_file_size(file_path, &content, None);
         match extension {
             "rs" => self.parse_rust_file(file_path, &content)?,
             "py" => self.parse_python_file(file_path, &content)?,
-            "js" | "ts" => self.parse_javascript_file(file_path, &content)?,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => {
+                self.parse_javascript_file(file_path, &content)?;
+            }
             _ => {} // Skip unsupported file types for now
         }
 
         Ok(())
     }
 
-    /// Basic Rust file parsing (simplified)
+    /// Start a file's complexity totals with its size; its functions are added once every
+    /// file is parsed. Without a counted `sloc`, lines of code are estimated from the text.
+    fn record_file_size(&mut self, file_path: &Path, content: &str, sloc: Option<usize>) {
+        let lines: Vec<&str> = content.lines().collect();
+        let sloc = sloc
+            .filter(|&sloc| sloc > 0)
+            .unwrap_or_else(|| crate::complexity::estimate(&lines, false).sloc);
+        let path = file_path.display().to_string();
+        self.file_complexity.insert(
+            path.clone(),
+            FileComplexity {
+                path,
+                loc: lines.len(),
+                sloc,
+                ..FileComplexity::default()
+            },
+        );
+    }
+
+    /// Add a parsed file's components, its package or file module, and its imports
+    fn add_file_syntax(
+        &mut self,
+        file_path: &Path,
+        analyzer: &Arc<dyn LanguageAnalyzer>,
+        file_syntax: &FileSyntax,
+        content: &str,
+    ) {
+        let ids = self.add_extracted_components(file_path, &file_syntax.components);
+        let top_level: Vec<(&ExtractedComponent, &String)> = file_syntax
+            .components
+            .iter()
+            .zip(&ids)
+            .filter(|(component, _)| component.parent.is_none())
+            .collect();
+
+        let container = match analyzer.container(file_path, file_syntax) {
+            Some(Container::Package { key, name, line }) => {
+                Some(self.package_component(file_path, key, &PackageDecl { name, line }))
+            }
+            Some(Container::File) => Some(self.file_module_component(file_path, content)),
+            None => None,
+        };
+        if let Some(container_id) = &container {
+            for (_, id) in &top_level {
+                self.attach_child(container_id, id);
+            }
+        }
+
+        // Register types under their package-qualified names for import resolution
+        if let Some(package) = &file_syntax.package {
+            for (component, id) in file_syntax.components.iter().zip(&ids) {
+                if matches!(
+                    component.component_type,
+                    ComponentType::Class | ComponentType::Interface | ComponentType::Enum
+                ) {
+                    let type_name = component.qualified_name.replace("::", ".");
+                    self.types
+                        .insert(format!("{}.{type_name}", package.name), id.clone());
+                }
+            }
+        }
+
+        let top_level_types: Vec<String> = top_level
+            .iter()
+            .filter(|(component, _)| {
+                matches!(
+                    component.component_type,
+                    ComponentType::Class | ComponentType::Interface | ComponentType::Enum
+                )
+            })
+            .map(|(_, id)| (*id).clone())
+            .collect();
+        let from = match (analyzer.import_source(), container) {
+            (ImportSource::TopLevelTypes, container) if top_level_types.is_empty() => {
+                container.into_iter().collect()
+            }
+            (ImportSource::TopLevelTypes, _) => top_level_types,
+            (ImportSource::Container, Some(container)) => vec![container],
+            (ImportSource::Container, None) => {
+                top_level.iter().map(|(_, id)| (*id).clone()).collect()
+            }
+        };
+        // Imports are kept even without components to depend on, for the file graph
+        for import in &file_syntax.imports {
+            self.pending_imports.push(PendingImport {
+                from: from.clone(),
+                analyzer: Arc::clone(analyzer),
+                file: file_path.to_path_buf(),
+                import: import.clone(),
+            });
+        }
+    }
+
+    /// Find or create the module component for a package
+    fn package_component(
+        &mut self,
+        file_path: &Path,
+        key: String,
+        package: &PackageDecl,
+    ) -> String {
+        if let Some(id) = self.packages.get(&key) {
+            return id.clone();
+        }
+
+        let dir = file_path.parent().unwrap_or_else(|| Path::new(""));
+        let id = format!("{}::{}", dir.display(), package.name);
+        self.graph.components.insert(
+            id.clone(),
+            CodeComponent {
+                id: id.clone(),
+                name: package.name.clone(),
+                component_type: ComponentType::Module,
+                file_path: file_path.to_path_buf(),
+                start_line: package.line,
+                end_line: package.line,
+                visibility: Visibility::Public,
+                dependencies: Vec::new(),
+                dependents: Vec::new(),
+                complexity_score: 1,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
+            },
+        );
+        self.packages.insert(key, id.clone());
+        id
+    }
+
+    /// Create the module component standing for a file, e.g. a C/C++ source or header
+    fn file_module_component(&mut self, file_path: &Path, content: &str) -> String {
+        let name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let id = format!("{}::{name}", file_path.display());
+        self.graph.components.insert(
+            id.clone(),
+            CodeComponent {
+                id: id.clone(),
+                name,
+                component_type: ComponentType::Module,
+                file_path: file_path.to_path_buf(),
+                start_line: 1,
+                end_line: content.lines().count().max(1),
+                visibility: Visibility::Public,
+                dependencies: Vec::new(),
+                dependents: Vec::new(),
+                complexity_score: 1,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
+            },
+        );
+        self.file_modules
+            .insert(file_path.to_path_buf(), id.clone());
+        id
+    }
+
+    fn attach_child(&mut self, parent_id: &str, child_id: &str) {
+        if let Some(child) = self.graph.components.get_mut(child_id) {
+            child.parent = Some(parent_id.to_string());
+        }
+        if let Some(parent) = self.graph.components.get_mut(parent_id) {
+            parent.children.push(child_id.to_string());
+        }
+    }
+
+    /// Add components extracted from a syntax tree, linking parents and children
+    fn add_extracted_components(
+        &mut self,
+        file_path: &Path,
+        extracted: &[ExtractedComponent],
+    ) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::with_capacity(extracted.len());
+        for component in extracted {
+            let mut id = format!("{}::{}", file_path.display(), component.qualified_name);
+            // Overloads and repeated trait impls share a qualified name
+            if self.graph.components.contains_key(&id) {
+                id = format!("{id}@{}", component.start_line);
+            }
+            ids.push(id.clone());
+            // Reserve the ID so later duplicates in this file are disambiguated
+            self.graph.components.insert(
+                id.clone(),
+                CodeComponent {
+                    id,
+                    name: component.name.clone(),
+                    component_type: component.component_type.clone(),
+                    file_path: file_path.to_path_buf(),
+                    start_line: component.start_line,
+                    end_line: component.end_line,
+                    visibility: component.visibility.clone(),
+                    dependencies: Vec::new(),
+                    dependents: Vec::new(),
+                    complexity_score: component.metrics.cyclomatic.max(1),
+                    metrics: component.metrics,
+                    parent: None,
+                    children: Vec::new(),
+                },
+            );
+        }
+
+        for (idx, component) in extracted.iter().enumerate() {
+            if let Some(parent_idx) = component.parent {
+                self.attach_child(&ids[parent_idx], &ids[idx]);
+            }
+        }
+        ids
+    }
+
+    /// Regex-based Rust parsing, used when the syntax tree cannot be built
     fn parse_rust_file(
         &mut self,
         file_path: &Path,
@@ -221,6 +648,9 @@ impl RepositoryMapper {
                 dependencies: Vec::new(), // Will be populated in build_dependency_graph
                 dependents: Vec::new(),
                 complexity_score: 1,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
             };
 
             self.graph.components.insert(id, component);
@@ -248,6 +678,9 @@ impl RepositoryMapper {
                 dependencies: Vec::new(),
                 dependents: Vec::new(),
                 complexity_score: 2,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
             };
 
             self.graph.components.insert(id, component);
@@ -257,7 +690,7 @@ impl RepositoryMapper {
         Ok(())
     }
 
-    /// Basic Python file parsing (placeholder)
+    /// Regex-based Python parsing, used when the syntax tree cannot be built
     fn parse_python_file(
         &mut self,
         file_path: &Path,
@@ -291,6 +724,9 @@ impl RepositoryMapper {
                 dependencies: Vec::new(),
                 dependents: Vec::new(),
                 complexity_score: 1,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
             };
 
             self.graph.components.insert(id, component);
@@ -318,6 +754,9 @@ impl RepositoryMapper {
                 dependencies: Vec::new(),
                 dependents: Vec::new(),
                 complexity_score: 2,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
             };
 
             self.graph.components.insert(id, component);
@@ -327,7 +766,7 @@ impl RepositoryMapper {
         Ok(())
     }
 
-    /// Basic JavaScript/TypeScript file parsing (placeholder)
+    /// Regex-based JavaScript/TypeScript parsing, used when the syntax tree cannot be built
     fn parse_javascript_file(
         &mut self,
         file_path: &Path,
@@ -364,6 +803,9 @@ impl RepositoryMapper {
                     dependencies: Vec::new(),
                     dependents: Vec::new(),
                     complexity_score: 1,
+                    metrics: ComplexityMetrics::default(),
+                    parent: None,
+                    children: Vec::new(),
                 };
 
                 self.graph.components.insert(id, component);
@@ -387,6 +829,9 @@ impl RepositoryMapper {
                 dependencies: Vec::new(),
                 dependents: Vec::new(),
                 complexity_score: 2,
+                metrics: ComplexityMetrics::default(),
+                parent: None,
+                children: Vec::new(),
             };
 
             self.graph.components.insert(id, component);
@@ -396,44 +841,181 @@ impl RepositoryMapper {
         Ok(())
     }
 
-    /// Build the dependency graph by parsing ASTs
+    /// Build the dependency graph from the imports and calls found while parsing
     fn build_dependency_graph(&mut self) {
         debug!("Building dependency graph");
 
-        // For now, this is a simplified implementation
-        // In a full implementation, this would analyze function calls, imports, etc.
-        // to build the actual dependency edges
-
-        // Create some example dependencies based on file structure
-        let component_ids: Vec<String> = self.graph.components.keys().cloned().collect();
-
-        for (i, from_id) in component_ids.iter().enumerate() {
-            if let Some(to_id) = component_ids.get(i + 1) {
-                // Create a simple dependency chain for demonstration
-                self.graph.edges.push(DependencyEdge {
-                    from: from_id.clone(),
-                    to: to_id.clone(),
-                    edge_type: EdgeType::FunctionCall,
-                });
+        let mut seen: HashSet<(String, String)> = self
+            .graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.clone(), edge.to.clone()))
+            .collect();
 
-                // Update component dependencies
-                if let Some(from_component) = self.graph.components.get_mut(from_id) {
-                    from_component.dependencies.push(to_id.clone());
+        let pending_imports = std::mem::take(&mut self.pending_imports);
+        let mut import_edges = Vec::new();
+        let mut file_edges = Vec::new();
+        {
+            let index = SymbolIndex {
+                packages: &self.packages,
+                types: &self.types,
+                file_modules: &self.file_modules,
+                components: &self.graph.components,
+                files: &self.files,
+            };
+            for pending in &pending_imports {
+                let from_file = pending.file.display().to_string();
+                for to_file in
+                    pending
+                        .analyzer
+                        .resolve_import_files(&pending.import, &pending.file, &index)
+                {
+                    file_edges.push((from_file.clone(), to_file.display().to_string()));
                 }
-                if let Some(to_component) = self.graph.components.get_mut(to_id) {
-                    to_component.dependents.push(from_id.clone());
+
+                let Some(to_id) =
+                    pending
+                        .analyzer
+                        .resolve_import(&pending.import, &pending.file, &index)
+                else {
+                    continue;
+                };
+                for from_id in &pending.from {
+                    import_edges.push((from_id.clone(), to_id.clone()));
                 }
             }
         }
+        for (from_id, to_id) in import_edges {
+            self.add_edge(&mut seen, from_id, to_id, EdgeType::ModuleImport);
+        }
+
+        let files = self
+            .files
+            .values()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        self.file_graph = FileGraph::build(files, file_edges);
+
+        for (from_id, to_id, edge_type) in self.resolve_calls() {
+            self.add_edge(&mut seen, from_id, to_id, edge_type);
+        }
+    }
+
+    fn add_edge(
+        &mut self,
+        seen: &mut HashSet<(String, String)>,
+        from_id: String,
+        to_id: String,
+        edge_type: EdgeType,
+    ) {
+        if from_id == to_id
+            || !self.graph.components.contains_key(&to_id)
+            || !seen.insert((from_id.clone(), to_id.clone()))
+        {
+            return;
+        }
+        if let Some(from_component) = self.graph.components.get_mut(&from_id) {
+            from_component.dependencies.push(to_id.clone());
+        }
+        if let Some(to_component) = self.graph.components.get_mut(&to_id) {
+            to_component.dependents.push(from_id.clone());
+        }
+        self.graph.edges.push(DependencyEdge {
+            from: from_id,
+            to: to_id,
+            edge_type,
+        });
+    }
+
+    /// Match call sites to the functions they call.
+    ///
+    /// The caller is the innermost function or method around the call. The callee is
+    /// matched by name, preferring a unique match in the same file, then a unique match
+    /// anywhere in the repository; ambiguous names are skipped.
+    fn resolve_calls(&mut self) -> Vec<(String, String, EdgeType)> {
+        let mut callables_by_name: HashMap<&str, Vec<&CodeComponent>> = HashMap::new();
+        let mut callables_by_file: HashMap<&Path, Vec<&CodeComponent>> = HashMap::new();
+        for component in self.graph.components.values() {
+            if matches!(
+                component.component_type,
+                ComponentType::Function | ComponentType::Method
+            ) {
+                callables_by_name
+                    .entry(component.name.as_str())
+                    .or_default()
+                    .push(component);
+                callables_by_file
+                    .entry(component.file_path.as_path())
+                    .or_default()
+                    .push(component);
+            }
+        }
+
+        let mut edges = Vec::new();
+        for (file, calls) in &self.pending_calls {
+            let in_file = callables_by_file.get(file.as_path());
+            for call in calls {
+                let calling = in_file.and_then(|components| {
+                    components
+                        .iter()
+                        .filter(|c| c.start_line <= call.line && call.line <= c.end_line)
+                        .min_by_key(|c| c.end_line - c.start_line)
+                });
+                let Some(caller) = calling else {
+                    continue;
+                };
+                let Some(candidates) = callables_by_name.get(call.callee.as_str()) else {
+                    continue;
+                };
+                let same_file: Vec<&&CodeComponent> =
+                    candidates.iter().filter(|c| c.file_path == *file).collect();
+                let target = match (same_file.as_slice(), candidates.as_slice()) {
+                    ([only], _) => *only,
+                    ([], [only]) => only,
+                    _ => continue,
+                };
+                let edge_type = if call.method {
+                    EdgeType::MethodCall
+                } else {
+                    EdgeType::FunctionCall
+                };
+                edges.push((caller.id.clone(), target.id.clone(), edge_type));
+            }
+        }
+        self.pending_calls.clear();
+        edges
     }
 
     /// Detect cycles in the dependency graph using Tarjan's algorithm
     fn detect_cycles(&mut self) {
-        // Implementation of Tarjan's strongly connected components algorithm
         debug!("Detecting cycles in dependency graph");
 
-        // Placeholder - actual cycle detection will be implemented
-        self.graph.cycles = Vec::new();
+        let mut ids: Vec<&String> = self.graph.components.keys().collect();
+        ids.sort();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let mut adjacency = vec![Vec::new(); ids.len()];
+        for edge in &self.graph.edges {
+            if let (Some(&from), Some(&to)) =
+                (index.get(edge.from.as_str()), index.get(edge.to.as_str()))
+            {
+                adjacency[from].push(to);
+            }
+        }
+
+        self.graph.cycles = strongly_connected_components(&adjacency)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                let mut cycle: Vec<String> =
+                    component.into_iter().map(|idx| ids[idx].clone()).collect();
+                cycle.sort();
+                cycle
+            })
+            .collect();
     }
 
     /// Compute topological ordering of components (dependencies first)
@@ -495,6 +1077,84 @@ impl RepositoryMapper {
         self.topological_order = topo_order;
     }
 
+    /// Sum the complexity of functions and methods into their enclosing types and modules,
+    /// and into their files
+    fn aggregate_complexity(&mut self) {
+        let containers: Vec<(String, ComplexityMetrics)> = self
+            .graph
+            .components
+            .values()
+            .filter(|component| !is_function(component))
+            .map(|component| (component.id.clone(), self.member_complexity(component)))
+            .collect();
+        for (id, totals) in containers {
+            if let Some(component) = self.graph.components.get_mut(&id) {
+                component.metrics.cyclomatic = totals.cyclomatic;
+                component.metrics.cognitive = totals.cognitive;
+                component.metrics.max_nesting = totals.max_nesting;
+                component.metrics.approximate |= totals.approximate;
+                component.complexity_score = totals.cyclomatic.max(1);
+            }
+        }
+
+        for component in self.graph.components.values() {
+            if is_function(component) {
+                let path = component.file_path.display().to_string();
+                self.file_complexity
+                    .entry(path.clone())
+                    .or_insert_with(|| FileComplexity {
+                        path,
+                        ..FileComplexity::default()
+                    })
+                    .add(&component.metrics);
+            }
+        }
+    }
+
+    /// Complexity summed over the functions and methods nested in a component
+    fn member_complexity(&self, component: &CodeComponent) -> ComplexityMetrics {
+        if is_function(component) {
+            return component.metrics;
+        }
+        let mut totals = ComplexityMetrics::default();
+        for child in &component.children {
+            if let Some(child) = self.graph.components.get(child) {
+                let metrics = self.member_complexity(child);
+                totals.cyclomatic += metrics.cyclomatic;
+                totals.cognitive += metrics.cognitive;
+                totals.max_nesting = totals.max_nesting.max(metrics.max_nesting);
+                totals.approximate |= metrics.approximate;
+            }
+        }
+        totals
+    }
+
+    /// Analyzed files with functions, most complex first: by cognitive complexity, then
+    /// cyclomatic complexity
+    pub fn complexity_ranking(&self) -> Vec<&FileComplexity> {
+        let mut files: Vec<&FileComplexity> = self
+            .file_complexity
+            .values()
+            .filter(|file| file.functions > 0)
+            .collect();
+        files.sort_by(|a, b| {
+            b.cognitive
+                .cmp(&a.cognitive)
+                .then(b.cyclomatic.cmp(&a.cyclomatic))
+                .then(a.path.cmp(&b.path))
+        });
+        files
+    }
+
+    /// Complexity totals of an analyzed file
+    pub fn file_complexity_of(&self, path: &Path) -> Option<&FileComplexity> {
+        let analyzed = self
+            .files
+            .get(&normalize_path(path))
+            .map_or(path, PathBuf::as_path);
+        self.file_complexity.get(&analyzed.display().to_string())
+    }
+
     /// Generate repository insights and statistics
     pub fn generate_insights(&mut self) {
         debug!("Generating repository insights");
@@ -512,20 +1172,37 @@ impl RepositoryMapper {
             })
             .collect();
 
-        component_connections.sort_by(|a, b| b.1.cmp(&a.1));
+        component_connections.sort_by_key(|b| std::cmp::Reverse(b.1));
         self.insights.most_connected_components = component_connections
             .into_iter()
             .take(10)
             .map(|(id, _)| id)
             .collect();
 
-        // Find entry points (components with no dependencies)
-        self.insights.entry_points = self
-            .graph
-            .components
+        self.insights.most_complex_files = self
+            .complexity_ranking()
+            .into_iter()
+            .take(10)
+            .map(|file| file.path.clone())
+            .collect();
+
+        self.insights.entry_points = crate::entry_points::detect_entry_points(self);
+    }
+
+    /// Group files into architectural layers, find imports that cross layers upwards, and
+    /// rank files by fan-in times git churn
+    fn analyze_architecture(&mut self) {
+        let (layers, violations) = crate::architecture::detect_layers(self, &self.settings);
+        if !violations.is_empty() {
+            warn!("Found {} layering violations", violations.len());
+        }
+        self.insights.architectural_layers = layers;
+        self.insights.layering_violations = violations;
+        self.hotspots = crate::architecture::find_hotspots(self, 10);
+        self.insights.dependency_hotspots = self
+            .hotspots
             .iter()
-            .filter(|(_, component)| component.dependencies.is_empty())
-            .map(|(id, _)| id.clone())
+            .map(|hotspot| hotspot.path.clone())
             .collect();
     }
 
@@ -543,7 +1220,7 @@ impl RepositoryMapper {
             self.insights.entry_points.len(),
             self.graph.cycles.len()
         )
-        .unwrap();
+        .expect("Writing to a String should not fail");
 
         // Group components by file path for organized display
         let mut file_groups: HashMap<PathBuf, Vec<&CodeComponent>> = HashMap::new();
@@ -555,11 +1232,13 @@ impl RepositoryMapper {
         }
 
         for (file_path, components) in file_groups {
-            writeln!(&mut map, "{}", file_path.display()).unwrap();
+            writeln!(&mut map, "{}", file_path.display())
+                .expect("Writing to a String should not fail");
             for component in components {
                 let type_indicator = match component.component_type {
                     ComponentType::Function | ComponentType::Method => "fn",
                     ComponentType::Class => "struct",
+                    ComponentType::Enum => "enum",
                     ComponentType::Module => "mod",
                     ComponentType::Interface => "trait",
                 };
@@ -576,7 +1255,7 @@ impl RepositoryMapper {
                     component.dependencies.len(),
                     component.dependents.len()
                 )
-                .unwrap();
+                .expect("Writing to a String should not fail");
             }
             map.push('\n');
         }
@@ -587,16 +1266,13 @@ impl RepositoryMapper {
 
         if !self.insights.entry_points.is_empty() {
             map.push_str("Entry Points:\n");
-            for entry_id in &self.insights.entry_points {
-                if let Some(component) = self.graph.components.get(entry_id) {
-                    writeln!(
-                        &mut map,
-                        "  {} ({})",
-                        component.name,
-                        component.file_path.display()
-                    )
-                    .unwrap();
-                }
+            for entry in &self.insights.entry_points {
+                writeln!(
+                    &mut map,
+                    "  {} [{}] ({}:{})",
+                    entry.name, entry.kind, entry.file, entry.line
+                )
+                .expect("Writing to a String should not fail");
             }
             map.push('\n');
         }
@@ -618,14 +1294,215 @@ impl RepositoryMapper {
                         component.name,
                         component.file_path.display()
                     )
-                    .unwrap();
+                    .expect("Writing to a String should not fail");
                 }
             }
         }
 
+        self.write_most_complex(&mut map);
+        self.write_architecture(&mut map);
         map
     }
 
+    fn write_architecture(&self, map: &mut String) {
+        if !self.insights.architectural_layers.is_empty() {
+            map.push_str("\nLayers:\n");
+            for layer in &self.insights.architectural_layers {
+                writeln!(map, "  {} ({} files)", layer.name, layer.files.len())
+                    .expect("Writing to a String should not fail");
+            }
+        }
+        if !self.insights.layering_violations.is_empty() {
+            map.push_str("\nLayering Violations:\n");
+            for violation in &self.insights.layering_violations {
+                writeln!(
+                    map,
+                    "  {} ({}) -> {} ({})",
+                    violation.from, violation.from_layer, violation.to, violation.to_layer
+                )
+                .expect("Writing to a String should not fail");
+            }
+        }
+        if !self.hotspots.is_empty() {
+            map.push_str("\nHotspots:\n");
+            for (i, hotspot) in self.hotspots.iter().take(5).enumerate() {
+                writeln!(
+                    map,
+                    "  {}. {} (fan-in {}, {} recent commits)",
+                    i + 1,
+                    hotspot.path,
+                    hotspot.fan_in,
+                    hotspot.churn
+                )
+                .expect("Writing to a String should not fail");
+            }
+        }
+    }
+
+    fn write_most_complex(&self, map: &mut String) {
+        let most_complex = self.complexity_ranking();
+        if most_complex.is_empty() {
+            return;
+        }
+        map.push_str("\nMost Complex:\n");
+        for (i, file) in most_complex.iter().take(5).enumerate() {
+            writeln!(
+                map,
+                "  {}. {} (cognitive {}, cyclomatic {}, {} SLOC{})",
+                i + 1,
+                file.path,
+                file.cognitive,
+                file.cyclomatic,
+                file.sloc,
+                approximate_note(file.approximate)
+            )
+            .expect("Writing to a String should not fail");
+        }
+    }
+
+    /// Generate a text overview of the file-level import graph
+    pub fn generate_file_map(&self) -> String {
+        self.file_graph.render()
+    }
+
+    /// Export the component or file graph in a standard graph format. With a focus, only
+    /// the part around the focused component or file is exported.
+    pub fn export_graph(
+        &self,
+        level: MapLevel,
+        format: ExportFormat,
+        focus: Option<&GraphQuery>,
+    ) -> Result<String, ExportError> {
+        let graph = match level {
+            MapLevel::Component => ExportGraph::components(self, focus)?,
+            MapLevel::File => ExportGraph::files(self, focus)?,
+        };
+        Ok(graph.render(format))
+    }
+
+    /// The component best matching `query`, by ID, name, qualified name such as
+    /// `Config::merge`, path-qualified name such as `config.rs::Config::merge`, or fuzzily
+    /// by name. Fails when several components match equally well.
+    pub fn resolve_component(&self, query: &str) -> Result<&CodeComponent, QueryError> {
+        crate::graph_query::resolve_component(self, query)
+    }
+
+    /// The components within `depth` edges of the component matching `seed`, following only
+    /// `edge_types` (all when empty) in `direction`
+    pub fn subgraph(
+        &self,
+        seed: &str,
+        depth: usize,
+        direction: Direction,
+        edge_types: &[EdgeType],
+    ) -> Result<Subgraph, QueryError> {
+        crate::graph_query::subgraph(self, seed, depth, direction, edge_types)
+    }
+
+    /// Rank files related to `seeds` by import proximity, git co-change history, shared
+    /// identifiers and test/source pairing.
+    ///
+    /// Seeds are paths as given, relative to an analyzed root, or other files on disk.
+    pub fn suggest_related(
+        &self,
+        seeds: &[PathBuf],
+        limit: usize,
+    ) -> Result<Vec<FileSuggestion>, SuggestError> {
+        crate::suggest::suggest_related(self, seeds, limit)
+    }
+
+    /// The components, files and tests that depend on `changed`, directly or through up to
+    /// `depth` dependency edges (any number when `None`), closest first.
+    ///
+    /// Changed files are paths as given, relative to an analyzed root, or other files on disk.
+    pub fn impact(
+        &self,
+        changed: &[PathBuf],
+        depth: Option<usize>,
+    ) -> Result<ImpactReport, ImpactError> {
+        crate::impact::analyze_impact(self, changed, depth)
+    }
+
+    /// Files under the analyzed roots changed between the git revision `base` and the work
+    /// tree, staged or not; deleted files are left out
+    pub fn changed_files(&self, base: &str) -> Result<Vec<PathBuf>, ImpactError> {
+        crate::impact::changed_files(self, base)
+    }
+
+    /// Components nothing depends on or mentions that are not entry points, and imports
+    /// their file never mentions. Entry points include `main`, tests, trait implementations,
+    /// components registered through attributes or decorators, the API of Rust libraries
+    /// and the `[dead_code]` entry points of `.contexter.toml`.
+    pub fn find_dead_code(&self) -> DeadCode {
+        crate::dead_code::find_dead_code(self)
+    }
+
+    /// `TODO`, `FIXME`, `HACK`, `XXX` and `SAFETY` comments and Rust `#[allow]` attributes in
+    /// the analyzed files, each with the component it is on or in, and who last changed it
+    /// if `with_blame` is set
+    pub fn annotations(&self, with_blame: bool) -> Vec<Annotation> {
+        crate::annotations::find_annotations(self, with_blame)
+    }
+
+    /// Doc comments or docstrings of every public function, method and type outside tests,
+    /// with the parameters they leave unmentioned
+    pub fn component_docs(&self) -> Vec<ComponentDocs> {
+        crate::doc_coverage::component_docs(self)
+    }
+
+    /// Documentation coverage of the public API, overall, per file and per directory
+    pub fn doc_coverage(&self) -> DocCoverage {
+        DocCoverage::from_docs(&self.component_docs())
+    }
+
+    /// The public interface of the analyzed files, with bodies elided, for
+    /// `gather --signatures-only`
+    pub fn signatures(&self) -> Signatures<'_> {
+        Signatures::new(self)
+    }
+
+    /// Map test files and test functions to the source components they exercise, through
+    /// calls, naming conventions and imports
+    pub fn correlate_tests(&self) -> TestCorrelation {
+        crate::test_correlation::correlate_tests(self)
+    }
+
+    /// Test files exercising any of `files`, or named after them, that are not among them
+    pub fn tests_for_files(&self, files: &[PathBuf]) -> Vec<PathBuf> {
+        let correlation = self.correlate_tests();
+        let given: HashSet<PathBuf> = files.iter().map(|file| normalize_path(file)).collect();
+        let mut tests: Vec<PathBuf> = Vec::new();
+        for file in files {
+            let analyzed = self.files.get(&normalize_path(file)).unwrap_or(file);
+            for test in correlation.tests_for_file(&analyzed.display().to_string()) {
+                let test = PathBuf::from(test);
+                if !given.contains(&normalize_path(&test)) && !tests.contains(&test) {
+                    tests.push(test);
+                }
+            }
+        }
+        tests
+    }
+
+    /// The `.contexter.toml` settings of the analyzed root containing `path`
+    pub(crate) fn settings_for(&self, path: &Path) -> Option<&RepoSettings> {
+        self.roots
+            .iter()
+            .zip(&self.settings)
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, settings)| settings)
+    }
+
+    /// `path` relative to the analyzed root containing it
+    pub(crate) fn relative_to_root<'a>(&self, path: &'a Path) -> &'a Path {
+        self.roots
+            .iter()
+            .filter_map(|root| path.strip_prefix(root).ok())
+            .min_by_key(|relative| relative.components().count())
+            .unwrap_or(path)
+    }
+
     /// Get processing order for incremental context building (like `DocAgent`)
     pub fn get_processing_order(&self) -> &[String] {
         &self.topological_order
@@ -648,6 +1525,13 @@ impl RepositoryMapper {
     }
 }
 
+fn is_function(component: &CodeComponent) -> bool {
+    matches!(
+        component.component_type,
+        ComponentType::Function | ComponentType::Method
+    )
+}
+
 impl Default for RepositoryMapper {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,167 @@
+use crate::ranking::Ranking;
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Per-repository settings file, read from the root of each analyzed repository
+pub const SETTINGS_FILE: &str = ".contexter.toml";
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Invalid {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid pattern '{pattern}' for layer '{layer}' in {path}: {source}")]
+    Pattern {
+        path: PathBuf,
+        layer: String,
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[error("Invalid dead code pattern '{pattern}' in {path}: {source}")]
+    DeadCodePattern {
+        path: PathBuf,
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[error("Invalid priority pattern '{pattern}' in {path}: {source}")]
+    PriorityPattern {
+        path: PathBuf,
+        pattern: String,
+        source: glob::PatternError,
+    },
+}
+
+/// Settings a repository keeps in its `.contexter.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RepoSettings {
+    /// Architectural layers from top to bottom, replacing the built-in heuristics
+    #[serde(default)]
+    pub layers: Vec<LayerRule>,
+    #[serde(default)]
+    pub dead_code: DeadCodeSettings,
+    /// How gathered files are ordered
+    #[serde(default)]
+    pub ranking: Ranking,
+}
+
+/// Names dead code detection should treat as used
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeadCodeSettings {
+    /// Components called dynamically, e.g. by a plugin loader. Glob patterns matching a
+    /// component's name, its qualified name such as `Plugin::load`, or its path and
+    /// qualified name such as `src/plugins/*::load`.
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+    /// Imported names used without being mentioned, such as traits providing methods.
+    /// Glob patterns matching the imported name.
+    #[serde(default)]
+    pub used_imports: Vec<String>,
+}
+
+/// An architectural layer and the files it contains
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayerRule {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Glob patterns relative to the repository root. A pattern matching a directory
+    /// covers every file below it.
+    pub paths: Vec<String>,
+}
+
+impl RepoSettings {
+    /// Load the settings of the repository at `root`, or the defaults if it has none
+    pub fn load(root: &Path) -> Result<Self, SettingsError> {
+        let path = root.join(SETTINGS_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(SettingsError::Io { path, source }),
+        };
+        let settings: Self = match toml::from_str(&content) {
+            Ok(settings) => settings,
+            Err(source) => return Err(SettingsError::Parse { path, source }),
+        };
+
+        for layer in &settings.layers {
+            for pattern in &layer.paths {
+                if let Err(source) = Pattern::new(pattern) {
+                    return Err(SettingsError::Pattern {
+                        path,
+                        layer: layer.name.clone(),
+                        pattern: pattern.clone(),
+                        source,
+                    });
+                }
+            }
+        }
+        let dead_code = &settings.dead_code;
+        for pattern in dead_code.entry_points.iter().chain(&dead_code.used_imports) {
+            if let Err(source) = Pattern::new(pattern) {
+                return Err(SettingsError::DeadCodePattern {
+                    path,
+                    pattern: pattern.clone(),
+                    source,
+                });
+            }
+        }
+        for pattern in &settings.ranking.priority {
+            if let Err(source) = Pattern::new(pattern) {
+                return Err(SettingsError::PriorityPattern {
+                    path,
+                    pattern: pattern.clone(),
+                    source,
+                });
+            }
+        }
+        Ok(settings)
+    }
+}
+
+impl DeadCodeSettings {
+    /// Whether a component, known by each of `names`, is a configured entry point
+    pub fn is_entry_point(&self, names: &[&str]) -> bool {
+        matches_any(&self.entry_points, names)
+    }
+
+    /// Whether an import of `name` is configured as used
+    pub fn is_used_import(&self, name: &str) -> bool {
+        matches_any(&self.used_imports, &[name])
+    }
+}
+
+fn matches_any(patterns: &[String], names: &[&str]) -> bool {
+    patterns
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern).ok())
+        .any(|pattern| names.iter().any(|name| pattern.matches(name)))
+}
+
+impl LayerRule {
+    /// Whether the layer contains `path`, given relative to the repository root
+    pub fn matches(&self, path: &Path) -> bool {
+        self.paths.iter().any(|pattern| matches_path(pattern, path))
+    }
+}
+
+/// Whether a glob pattern matches `path` or a directory above it. Both are relative to the
+/// repository root.
+pub(crate) fn matches_path(pattern: &str, path: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::default()
+    };
+    let Ok(pattern) = Pattern::new(pattern.trim_end_matches('/')) else {
+        return false;
+    };
+    path.ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| pattern.matches_path_with(ancestor, options))
+}
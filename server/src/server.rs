@@ -1,12 +1,41 @@
-use crate::config::Config;
+use crate::config::{Config, ProjectConfig, ProjectRoot};
+use crate::metrics::Metrics;
+use crate::rate_limit::RequestLimiter;
+use crate::workspace::WorkspaceMember;
 use actix_cors::Cors;
+use actix_web::middleware::from_fn;
 use actix_web::{web, App, HttpServer};
+use log::warn;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 pub struct AppState {
     pub config: Arc<RwLock<Config>>,
+    pub limiter: RequestLimiter,
+    pub metrics: Arc<Metrics>,
+    pub config_path: Option<PathBuf>,
+}
+
+impl AppState {
+    pub fn new(config: Config) -> Self {
+        let limiter = RequestLimiter::new(&config.rate_limits);
+        AppState {
+            config: Arc::new(RwLock::new(config)),
+            limiter,
+            metrics: Arc::new(Metrics::new()),
+            config_path: None,
+        }
+    }
+
+    /// Associate the state with the config file it was loaded from, enabling reloads.
+    #[must_use]
+    pub fn with_config_path(mut self, path: PathBuf) -> Self {
+        self.config_path = Some(path);
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -18,6 +47,22 @@ pub struct ProjectListResponse {
 pub struct ProjectSummary {
     pub name: String,
     pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<ProjectRoot>,
+}
+
+impl ProjectSummary {
+    pub fn new(name: String, project: &ProjectConfig) -> Self {
+        let roots = match project {
+            ProjectConfig::Path(_) => Vec::new(),
+            ProjectConfig::MultiRoot { roots } => roots.clone(),
+        };
+        ProjectSummary {
+            name,
+            path: project.display_path(),
+            roots,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -25,11 +70,35 @@ pub struct ProjectMetadata {
     pub name: String,
     pub path: String,
     pub files: Vec<String>,
+    #[serde(default)]
+    pub members: Vec<WorkspaceMember>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct ProjectContentResponse {
     pub content: String,
+    /// Which part `content` is, counting from 1, when split with `chunk_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<usize>,
+    /// How many parts there are in all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parts: Option<usize>,
+    /// The files in this part
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HealthResponse {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReloadResponse {
+    pub projects: usize,
+    pub api_keys: usize,
 }
 
 #[derive(Serialize)]
@@ -41,26 +110,105 @@ pub struct ErrorResponse {
 pub struct RepositoryAnalysisResponse {
     pub project_name: String,
     pub total_components: usize,
-    pub entry_points: Vec<String>,
+    /// Binaries, `main` functions, scripts, package entry modules and HTTP routes
+    pub entry_points: Vec<crate::entry_points::EntryPoint>,
     pub dependency_cycles: usize,
     pub most_connected_components: Vec<String>,
     pub topological_order: Vec<String>,
+    /// Public components that no test exercises
+    #[serde(default)]
+    pub untested_public_components: Vec<String>,
+    /// Layers from top to bottom, from `.contexter.toml` or recognized by name
+    #[serde(default)]
+    pub architectural_layers: Vec<crate::repo_mapper::ArchitecturalLayer>,
+    /// Imports from a lower layer into a higher one
+    #[serde(default)]
+    pub layering_violations: Vec<crate::architecture::LayerViolation>,
+    /// Files imported by many others that also change often, highest score first
+    #[serde(default)]
+    pub dependency_hotspots: Vec<crate::architecture::Hotspot>,
+    /// Components nothing uses and imports their file never mentions
+    #[serde(default)]
+    pub dead_code: crate::dead_code::DeadCode,
+    /// How much of the public API has doc comments, overall, per file and per directory
+    #[serde(default)]
+    pub doc_coverage: crate::doc_coverage::DocCoverage,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct RepositoryMapResponse {
     pub project_name: String,
+    #[serde(default)]
+    pub level: crate::repo_mapper::MapLevel,
     pub map: String,
     pub insights: crate::repo_mapper::RepositoryInsights,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_graph: Option<crate::file_graph::FileGraph>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SuggestResponse {
+    pub project_name: String,
+    pub suggestions: Vec<crate::suggest::FileSuggestion>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GraphQueryResponse {
+    pub project_name: String,
+    pub subgraph: crate::graph_query::Subgraph,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnnotationsResponse {
+    pub project_name: String,
+    /// By file and line, with project-relative file paths
+    pub annotations: Vec<crate::annotations::Annotation>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImpactResponse {
+    pub project_name: String,
+    pub impact: crate::impact::ImpactReport,
+    /// The changed and affected files and their tests, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+/// Returned when a graph query's seed matches several components equally well
+#[derive(Serialize, Deserialize)]
+pub struct AmbiguousComponentResponse {
+    pub error: String,
+    /// IDs of the matching components, any of which can be queried instead
+    pub candidates: Vec<String>,
 }
 
 pub fn config_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(
+    cfg.route(
+        "/healthz",
+        web::get().to(crate::server_handlers::health_check),
+    )
+    .route(
+        "/readyz",
+        web::get().to(crate::server_handlers::readiness_check),
+    )
+    .route(
+        "/metrics",
+        web::get().to(crate::server_handlers::export_metrics),
+    )
+    .service(
         web::scope("/api/v1")
+            .route(
+                "/admin/reload",
+                web::post().to(crate::server_handlers::reload_config),
+            )
             .route(
                 "/projects",
                 web::get().to(crate::server_handlers::list_projects),
             )
+            .route(
+                "/projects",
+                web::post().to(crate::server_handlers::create_project),
+            )
             .route(
                 "/projects/{name}",
                 web::get().to(crate::server_handlers::get_project_metadata),
@@ -69,6 +217,14 @@ pub fn config_routes(cfg: &mut web::ServiceConfig) {
                 "/projects/{name}",
                 web::post().to(crate::server_handlers::run_contexter),
             )
+            .route(
+                "/projects/{name}",
+                web::patch().to(crate::server_handlers::update_project),
+            )
+            .route(
+                "/projects/{name}",
+                web::delete().to(crate::server_handlers::delete_project),
+            )
             .route(
                 "/projects/{name}/analyze",
                 web::post().to(crate::server_handlers::analyze_repository),
@@ -76,6 +232,22 @@ pub fn config_routes(cfg: &mut web::ServiceConfig) {
             .route(
                 "/projects/{name}/map",
                 web::get().to(crate::server_handlers::get_repository_map),
+            )
+            .route(
+                "/projects/{name}/suggest",
+                web::post().to(crate::server_handlers::suggest_related_files),
+            )
+            .route(
+                "/projects/{name}/graph/query",
+                web::post().to(crate::server_handlers::query_graph),
+            )
+            .route(
+                "/projects/{name}/impact",
+                web::post().to(crate::server_handlers::analyze_impact),
+            )
+            .route(
+                "/projects/{name}/annotations",
+                web::get().to(crate::server_handlers::list_annotations),
             ),
     );
 }
@@ -85,15 +257,26 @@ pub async fn run_server(config: Config) -> std::io::Result<()> {
     // Extract values before creating the app state to avoid Send issues
     let listen_address = config.listen_address.clone();
     let port = config.port;
+    if config.admin_keys.is_empty() {
+        warn!(
+            "No API key has admin scope, so projects cannot be managed over the API. \
+             Create one with `contexter config generate-key <name> --admin`"
+        );
+    }
 
-    let app_state = web::Data::new(AppState {
-        config: Arc::new(RwLock::new(config)),
-    });
+    let mut app_state = AppState::new(config);
+    match Config::get_config_path() {
+        Ok(path) => app_state = app_state.with_config_path(path),
+        Err(e) => warn!("Config hot reload disabled: {e}"),
+    }
+    let app_state = web::Data::new(app_state);
+    crate::config_watcher::watch_config(app_state.clone(), Duration::from_secs(2));
 
     HttpServer::new(move || {
         let cors = Cors::permissive();
 
         App::new()
+            .wrap(from_fn(crate::metrics::track_requests))
             .wrap(cors)
             .app_data(app_state.clone())
             .configure(config_routes)
@@ -1,17 +1,458 @@
-use crate::contexter::{concatenate_files, gather_relevant_files};
-use crate::repo_mapper::RepositoryMapper;
+use crate::annotations::AnnotationKind;
+use crate::chunking::chunk_project_files_with;
+use crate::config::{Config, ProjectConfig, RateLimitConfig};
+use crate::config_watcher::{self, ReloadError};
+use crate::contexter::{
+    concatenate_project_files, concatenate_project_files_with, ConcatOptions, GatherPreset,
+    ProjectFile,
+};
+use crate::dedup::DedupOptions;
+use crate::doc_coverage::render_undocumented;
+use crate::graph_export::ExportFormat;
+use crate::graph_query::{Direction, GraphQuery, QueryError};
+use crate::ranking::{RankSignal, Ranking};
+use crate::rate_limit::LimitExceeded;
+use crate::repo_mapper::{MapLevel, RepositoryMapper};
 use crate::server::{
-    AppState, ErrorResponse, ProjectContentResponse, ProjectListResponse, ProjectMetadata,
-    ProjectSummary, RepositoryAnalysisResponse, RepositoryMapResponse,
+    AmbiguousComponentResponse, AnnotationsResponse, AppState, ErrorResponse, GraphQueryResponse,
+    HealthResponse, ImpactResponse, ProjectContentResponse, ProjectListResponse, ProjectMetadata,
+    ProjectSummary, ReloadResponse, RepositoryAnalysisResponse, RepositoryMapResponse,
+    SuggestResponse,
+};
+use crate::truncate::FileLimits;
+use crate::utils::{authenticated_key_name, validate_api_key};
+use crate::workspace::{
+    gather_project_files, project_label, project_members, resolve_project_path, WorkspaceError,
 };
-use crate::utils::validate_api_key;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use log::{debug, error, info, warn};
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 pub struct ContexterRequest {
     pub paths: Option<Vec<String>>,
+    /// Workspace members (Cargo crates, npm packages, Go modules) to include by name
+    pub members: Option<Vec<String>>,
+    /// Also include the tests of the gathered files
+    pub with_tests: Option<bool>,
+    /// Gather a selection instead of whole files, e.g. `undocumented`
+    pub preset: Option<GatherPreset>,
+    /// Only public signatures, doc comments and type definitions, with bodies elided
+    pub signatures_only: Option<bool>,
+    /// Per-file caps, e.g. `max_file_lines`, that larger files are elided down to
+    #[serde(flatten)]
+    pub limits: FileLimits,
+    /// Near-duplicate collapsing and license header stripping, e.g. `near_duplicates`
+    #[serde(flatten)]
+    pub dedup: DedupOptions,
+    /// Split the context into parts of at most this many tokens and return one of them
+    pub chunk_tokens: Option<usize>,
+    /// The part to return with `chunk_tokens`, counting from 1; the first by default
+    pub part: Option<usize>,
+    /// Weights of the signals to put important files first by, e.g. `{"fan-in": 2}`
+    pub rank: Option<BTreeMap<RankSignal, f64>>,
+    /// Glob patterns of files to put before all others, in order
+    pub priority: Option<Vec<String>>,
+}
+
+impl ContexterRequest {
+    /// The ranking asked for, with the project's `.contexter.toml` filling in the rest
+    fn ranking(&self, project: &ProjectConfig) -> Ranking {
+        let requested = Ranking {
+            signals: self.rank.clone().unwrap_or_default(),
+            priority: self.priority.clone().unwrap_or_default(),
+        };
+        let configured = Ranking::configured(&project_root_paths(project)).unwrap_or_else(|e| {
+            warn!("Ignoring the configured ranking: {e}");
+            Ranking::default()
+        });
+        requested.or(configured)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SuggestRequest {
+    /// Files to find related files for, relative to the project
+    pub files: Vec<String>,
+    /// Maximum number of suggestions, 10 by default
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ImpactRequest {
+    /// Changed files, relative to the project
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Also take the files changed since this git revision, e.g. `HEAD` or `main`
+    pub diff: Option<String>,
+    /// Dependency steps to follow, all by default
+    pub depth: Option<usize>,
+    /// Also return the contents of the changed and affected files and their tests
+    pub with_context: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct MapQuery {
+    /// `component` (default) or `file`
+    pub level: Option<MapLevel>,
+    /// Export the graph as `dot`, `mermaid`, `graphml` or `jgf` instead of a map
+    pub format: Option<ExportFormat>,
+    /// Component ID or name, or file path with `level=file`, to export the graph around
+    pub focus: Option<String>,
+    /// Dependency steps around the focus to export, 1 by default
+    pub depth: Option<usize>,
+    /// `upstream`, `downstream` or `both` (default) from the focus
+    pub direction: Option<Direction>,
+}
+
+#[derive(Deserialize)]
+pub struct AnnotationsQuery {
+    /// Comma-separated kinds to list, e.g. `todo,fixme`; all by default
+    pub kind: Option<String>,
+    /// Only list annotations in files under this project-relative path
+    pub path: Option<String>,
+    /// Add who last changed each annotated line and when, from git blame
+    pub blame: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateProjectRequest {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProjectRequest {
+    pub name: Option<String>,
+    pub path: Option<PathBuf>,
+}
+
+/// Reasons a request is refused access to an admin endpoint.
+enum AdminAuthError {
+    InvalidKey,
+    NotAdmin,
+}
+
+impl AdminAuthError {
+    fn into_response(self) -> HttpResponse {
+        match self {
+            Self::InvalidKey => HttpResponse::Unauthorized().json(ErrorResponse {
+                error: "Invalid or missing API key".to_string(),
+            }),
+            Self::NotAdmin => HttpResponse::Forbidden().json(ErrorResponse {
+                error: "This API key is not allowed to perform admin operations".to_string(),
+            }),
+        }
+    }
+}
+
+/// Checks that the request carries a valid API key with admin scope.
+fn authorize_admin(req: &HttpRequest, config: &Config) -> Result<String, AdminAuthError> {
+    let key_name = authenticated_key_name(req, config).ok_or(AdminAuthError::InvalidKey)?;
+    if config.is_admin_key(&key_name) {
+        Ok(key_name)
+    } else {
+        warn!("API key '{key_name}' attempted an admin operation");
+        Err(AdminAuthError::NotAdmin)
+    }
+}
+
+/// Ensures a project name can be used as a single URL path segment.
+fn validate_project_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        Err("Project name must not be empty".to_string())
+    } else if name.contains('/') {
+        Err("Project name must not contain '/'".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Resolves a project path to an absolute directory that exists on disk.
+fn validate_project_path(path: &Path) -> Result<PathBuf, String> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Project path {} is not accessible: {e}", path.display()))?;
+    if canonical.is_dir() {
+        Ok(canonical)
+    } else {
+        Err(format!(
+            "Project path {} is not a directory",
+            canonical.display()
+        ))
+    }
+}
+
+fn project_root_paths(project: &ProjectConfig) -> Vec<PathBuf> {
+    project.roots().into_iter().map(|root| root.path).collect()
+}
+
+/// Writes the updated configuration to disk, if the server was started from a config file.
+fn persist_config(data: &AppState, config: &Config) -> Result<(), String> {
+    if let Some(path) = &data.config_path {
+        config.save_to(path).map_err(|e| e.to_string())
+    } else {
+        debug!("No config file path; keeping project changes in memory only");
+        Ok(())
+    }
+}
+
+/// What an expensive request needs from the configuration, copied out so that the lock is
+/// not held while the project is walked and analyzed
+struct ProjectAccess {
+    key_name: Option<String>,
+    project: Option<ProjectConfig>,
+    rate_limits: RateLimitConfig,
+}
+
+#[allow(clippy::future_not_send)]
+async fn project_access(req: &HttpRequest, data: &AppState, project_name: &str) -> ProjectAccess {
+    let config = data.config.read().await;
+    ProjectAccess {
+        key_name: authenticated_key_name(req, &config),
+        project: config.projects.get(project_name).cloned(),
+        rate_limits: config.rate_limits.clone(),
+    }
+}
+
+/// Builds the 429 response returned when a request is turned away by the limiter.
+fn limit_exceeded_response(key_name: &str, limit: &LimitExceeded) -> HttpResponse {
+    warn!(
+        "Rejecting request from API key '{key_name}': {}",
+        limit.message()
+    );
+    HttpResponse::TooManyRequests()
+        .insert_header(("Retry-After", limit.retry_after_secs().to_string()))
+        .json(ErrorResponse {
+            error: limit.message().to_string(),
+        })
+}
+
+// Operational endpoints (unauthenticated)
+
+pub async fn health_check() -> impl Responder {
+    HttpResponse::Ok().json(HealthResponse {
+        status: "ok".to_string(),
+        problems: Vec::new(),
+    })
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn readiness_check(data: web::Data<AppState>) -> impl Responder {
+    let config = data.config.read().await;
+
+    let mut problems = Vec::new();
+    if config.api_keys.is_empty() {
+        problems.push("No API keys configured".to_string());
+    }
+    for (name, project) in &config.projects {
+        for root in project.roots() {
+            if !root.path.is_dir() {
+                problems.push(format!(
+                    "Project '{name}' path {} is not a directory",
+                    root.path.display()
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        HttpResponse::Ok().json(HealthResponse {
+            status: "ready".to_string(),
+            problems,
+        })
+    } else {
+        warn!("Readiness check failed: {}", problems.join("; "));
+        HttpResponse::ServiceUnavailable().json(HealthResponse {
+            status: "not ready".to_string(),
+            problems,
+        })
+    }
+}
+
+pub async fn export_metrics(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(data.metrics.render())
+}
+
+// Administrative endpoints
+
+#[allow(clippy::future_not_send)]
+pub async fn reload_config(req: HttpRequest, data: web::Data<AppState>) -> impl Responder {
+    let config_guard = data.config.read().await;
+    let authorized = authorize_admin(&req, &config_guard);
+    drop(req); // Drop req to make future Send
+    drop(config_guard); // Release the read lock so the reload can take the write lock
+
+    if let Err(e) = authorized {
+        return e.into_response();
+    }
+
+    match config_watcher::reload_config(&data).await {
+        Ok(config) => HttpResponse::Ok().json(ReloadResponse {
+            projects: config.projects.len(),
+            api_keys: config.api_keys.len(),
+        }),
+        Err(ReloadError::Unavailable) => {
+            warn!("Config reload requested but no config file path is set");
+            HttpResponse::Conflict().json(ErrorResponse {
+                error: ReloadError::Unavailable.to_string(),
+            })
+        }
+        Err(e) => {
+            error!("Config reload failed: {e}");
+            HttpResponse::UnprocessableEntity().json(ErrorResponse {
+                error: e.to_string(),
+            })
+        }
+    }
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn create_project(
+    req: HttpRequest,
+    project: web::Json<CreateProjectRequest>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let mut config = data.config.write().await;
+    let authorized = authorize_admin(&req, &config);
+    drop(req); // Drop req to make future Send
+
+    if let Err(e) = authorized {
+        return e.into_response();
+    }
+
+    let CreateProjectRequest { name, path } = project.into_inner();
+    if let Err(e) = validate_project_name(&name) {
+        return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+    }
+    if config.projects.contains_key(&name) {
+        return HttpResponse::Conflict().json(ErrorResponse {
+            error: format!("Project '{name}' already exists"),
+        });
+    }
+    let path = match validate_project_path(&path) {
+        Ok(path) => path,
+        Err(e) => return HttpResponse::BadRequest().json(ErrorResponse { error: e }),
+    };
+
+    let mut updated = config.clone();
+    updated.add_project(name.clone(), path.clone());
+    if let Err(e) = persist_config(&data, &updated) {
+        error!("Failed to save config after adding project {name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to save configuration".to_string(),
+        });
+    }
+    *config = updated;
+
+    info!("Project '{name}' added with path {}", path.display());
+    HttpResponse::Created().json(ProjectSummary {
+        name,
+        path: path.to_string_lossy().into_owned(),
+        roots: Vec::new(),
+    })
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn update_project(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    changes: web::Json<UpdateProjectRequest>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let mut config = data.config.write().await;
+    let authorized = authorize_admin(&req, &config);
+    drop(req); // Drop req to make future Send
+
+    if let Err(e) = authorized {
+        return e.into_response();
+    }
+
+    let project_name = project_name.into_inner();
+    let Some(current_project) = config.projects.get(&project_name).cloned() else {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    };
+
+    let UpdateProjectRequest { name, path } = changes.into_inner();
+    let new_name = name.unwrap_or_else(|| project_name.clone());
+    if let Err(e) = validate_project_name(&new_name) {
+        return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+    }
+    if new_name != project_name && config.projects.contains_key(&new_name) {
+        return HttpResponse::Conflict().json(ErrorResponse {
+            error: format!("Project '{new_name}' already exists"),
+        });
+    }
+    if path.is_some() && matches!(current_project, ProjectConfig::MultiRoot { .. }) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: format!(
+                "Project '{project_name}' has several roots; change them with `contexter config add-project`"
+            ),
+        });
+    }
+    let new_project = match path.map(|p| validate_project_path(&p)) {
+        Some(Ok(path)) => ProjectConfig::Path(path),
+        Some(Err(e)) => return HttpResponse::BadRequest().json(ErrorResponse { error: e }),
+        None => current_project,
+    };
+
+    let mut updated = config.clone();
+    updated.remove_project(&project_name);
+    updated
+        .projects
+        .insert(new_name.clone(), new_project.clone());
+    if let Err(e) = persist_config(&data, &updated) {
+        error!("Failed to save config after updating project {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to save configuration".to_string(),
+        });
+    }
+    *config = updated;
+
+    info!("Project '{project_name}' updated");
+    HttpResponse::Ok().json(ProjectSummary::new(new_name, &new_project))
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn delete_project(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let mut config = data.config.write().await;
+    let authorized = authorize_admin(&req, &config);
+    drop(req); // Drop req to make future Send
+
+    if let Err(e) = authorized {
+        return e.into_response();
+    }
+
+    let project_name = project_name.into_inner();
+    let mut updated = config.clone();
+    if updated.remove_project(&project_name).is_none() {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    }
+    if let Err(e) = persist_config(&data, &updated) {
+        error!("Failed to save config after removing project {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to save configuration".to_string(),
+        });
+    }
+    *config = updated;
+
+    info!("Project '{project_name}' removed");
+    HttpResponse::NoContent().finish()
 }
 
 #[allow(clippy::future_not_send)]
@@ -32,10 +473,7 @@ pub async fn list_projects(req: HttpRequest, data: web::Data<AppState>) -> impl
     let projects: Vec<ProjectSummary> = config
         .projects
         .iter()
-        .map(|(name, path)| ProjectSummary {
-            name: name.clone(),
-            path: path.to_string_lossy().into_owned(),
-        })
+        .map(|(name, project)| ProjectSummary::new(name.clone(), project))
         .collect();
 
     info!("Listed {} projects", projects.len());
@@ -49,42 +487,25 @@ pub async fn get_project_metadata(
     project_name: web::Path<String>,
     data: web::Data<AppState>,
 ) -> impl Responder {
-    let config = data.config.read().await;
-    let is_valid = validate_api_key(&req, &config);
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
     drop(req); // Drop req to make future Send
 
-    if !is_valid {
+    if access.key_name.is_none() {
         return HttpResponse::Unauthorized().json(ErrorResponse {
             error: "Invalid or missing API key".to_string(),
         });
     }
 
-    let project_name = project_name.into_inner();
-
-    if let Some(project_path) = config.projects.get(&project_name) {
+    if let Some(project) = access.project.as_ref() {
         debug!("Gathering metadata for project: {project_name}");
-        match gather_relevant_files(
-            project_path
-                .to_str()
-                .expect("Project path should be valid UTF-8"),
-            &[],
-            vec![],
-        ) {
+        match gather_project_files(project, None, None) {
             Ok(files) => {
-                let file_paths: Vec<String> = files
-                    .iter()
-                    .map(|path| {
-                        path.strip_prefix(project_path)
-                            .expect("Path should be under project directory")
-                            .to_string_lossy()
-                            .into_owned()
-                    })
-                    .collect();
-
                 let metadata = ProjectMetadata {
                     name: project_name,
-                    path: project_path.to_string_lossy().into_owned(),
-                    files: file_paths,
+                    path: project.display_path(),
+                    files: files.into_iter().map(|f| f.display_path).collect(),
+                    members: project_members(project),
                 };
 
                 info!(
@@ -115,64 +536,96 @@ pub async fn run_contexter(
     contexter_req: web::Json<Option<ContexterRequest>>,
     data: web::Data<AppState>,
 ) -> impl Responder {
-    let config = data.config.read().await;
-    let is_valid = validate_api_key(&req, &config);
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
     drop(req); // Drop req to make future Send
 
-    if !is_valid {
+    let Some(key_name) = access.key_name else {
         return HttpResponse::Unauthorized().json(ErrorResponse {
             error: "Invalid or missing API key".to_string(),
         });
-    }
+    };
 
-    let project_name = project_name.into_inner();
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
 
-    if let Some(project_path) = config.projects.get(&project_name) {
-        let base_path = project_path.clone();
-        let files_to_process =
-            if let Some(ContexterRequest { paths: Some(paths) }) = contexter_req.into_inner() {
-                debug!("Running contexter on specific paths for project: {project_name}");
-                paths
-                    .iter()
-                    .flat_map(|p| {
-                        let full_path = base_path.join(p);
-                        gather_relevant_files(
-                            full_path.to_str().expect("Path should be valid UTF-8"),
-                            &[],
-                            vec![],
-                        )
-                        .unwrap_or_default()
-                    })
-                    .collect()
-            } else {
-                debug!("Running contexter on entire project: {project_name}");
-                match gather_relevant_files(
-                    project_path
-                        .to_str()
-                        .expect("Project path should be valid UTF-8"),
-                    &[],
-                    vec![],
-                ) {
-                    Ok(files) => files,
-                    Err(e) => {
-                        error!("Error gathering files for project {project_name}: {e}");
-                        return HttpResponse::InternalServerError().json(ErrorResponse {
-                            error: "Failed to gather files".to_string(),
-                        });
-                    }
-                }
-            };
+    if let Some(project) = access.project.as_ref() {
+        let request = contexter_req.into_inner().unwrap_or_default();
+        let paths = request.paths.as_deref();
+        let members = request.members.as_deref();
+        if paths.is_some() || members.is_some() {
+            debug!("Running contexter on specific paths for project: {project_name}");
+        } else {
+            debug!("Running contexter on entire project: {project_name}");
+        }
 
-        match concatenate_files(files_to_process) {
-            Ok((content, processed_files)) => {
+        let mut files_to_process = match gather_project_files(project, paths, members) {
+            Ok(files) => files,
+            Err(e @ (WorkspaceError::UnknownMember(_) | WorkspaceError::OutsideProject(_))) => {
+                warn!("Bad contexter request for project {project_name}: {e}");
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: e.to_string(),
+                });
+            }
+            Err(e) => {
+                error!("Error gathering files for project {project_name}: {e}");
+                return HttpResponse::InternalServerError().json(ErrorResponse {
+                    error: "Failed to gather files".to_string(),
+                });
+            }
+        };
+
+        let with_tests = request.with_tests.unwrap_or(false);
+        let signatures_only = request.signatures_only.unwrap_or(false);
+        let ranking = request.ranking(project);
+        let mut mapper = RepositoryMapper::new();
+        if request.preset.is_some() || with_tests || signatures_only || !ranking.is_empty() {
+            let started = Instant::now();
+            let analysis = mapper.analyze_roots(&project_root_paths(project));
+            data.metrics.record_analysis(started.elapsed());
+            if let Err(e) = analysis {
+                error!("Error analyzing repository {project_name}: {e}");
+                return HttpResponse::InternalServerError().json(ErrorResponse {
+                    error: "Failed to analyze repository".to_string(),
+                });
+            }
+        }
+
+        if let Some(GatherPreset::Undocumented) = request.preset {
+            let content = render_undocumented(&mapper.component_docs(), &files_to_process);
+            data.metrics
+                .record_context_served(content.len(), files_to_process.len());
+            return HttpResponse::Ok().json(ProjectContentResponse {
+                content,
+                ..ProjectContentResponse::default()
+            });
+        }
+
+        if with_tests {
+            let gathered: Vec<PathBuf> = files_to_process.iter().map(|f| f.path.clone()).collect();
+            for path in mapper.tests_for_files(&gathered) {
+                let display_path = project_label(project, &path);
+                files_to_process.push(ProjectFile { path, display_path });
+            }
+        }
+
+        match concatenate_requested(files_to_process, &request, &mapper, &ranking) {
+            Ok((response, processed_files)) => {
+                data.metrics
+                    .record_context_served(response.content.len(), processed_files);
                 info!(
-                    "Successfully ran contexter on {} files for project: {}",
-                    processed_files.len(),
-                    project_name
+                    "Successfully ran contexter on {processed_files} files for project: {project_name}"
                 );
-                let response = ProjectContentResponse { content };
                 HttpResponse::Ok().json(response)
             }
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+                warn!("Bad contexter request for project {project_name}: {e}");
+                HttpResponse::BadRequest().json(ErrorResponse {
+                    error: e.to_string(),
+                })
+            }
             Err(e) => {
                 error!("Error concatenating files for project {project_name}: {e}");
                 HttpResponse::InternalServerError().json(ErrorResponse {
@@ -188,6 +641,66 @@ pub async fn run_contexter(
     }
 }
 
+/// Concatenates gathered files as a contexter request asks, eliding them to its limits
+/// and keeping only their signatures if it says so, or with `chunk_tokens` only the part
+/// it asks for. Files are ordered by `ranking` if it is set. Also returns how many files the
+/// content holds.
+fn concatenate_requested(
+    mut files: Vec<ProjectFile>,
+    request: &ContexterRequest,
+    mapper: &RepositoryMapper,
+    ranking: &Ranking,
+) -> std::io::Result<(ProjectContentResponse, usize)> {
+    let ranked = !ranking.is_empty();
+    if ranked {
+        ranking.rank(mapper, &mut files);
+    }
+    let options = ConcatOptions {
+        limits: request.limits,
+        ranked,
+        dedup: request.dedup,
+    };
+    let signatures_only = request.signatures_only.unwrap_or(false);
+    let signatures = mapper.signatures();
+    let transform = |path: &Path, content| {
+        if signatures_only {
+            signatures.for_file(path)
+        } else {
+            Some(content)
+        }
+    };
+    let Some(max_tokens) = request.chunk_tokens else {
+        let (content, processed_files) =
+            concatenate_project_files_with(files, &options, transform)?;
+        let response = ProjectContentResponse {
+            content,
+            ..ProjectContentResponse::default()
+        };
+        return Ok((response, processed_files.len()));
+    };
+
+    let mut chunks = chunk_project_files_with(files, &options, max_tokens, transform)?;
+    let part = request.part.unwrap_or(1);
+    if part == 0 || part > chunks.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Part {part} is out of range, there are {} parts",
+                chunks.len()
+            ),
+        ));
+    }
+    let chunk = chunks.swap_remove(part - 1);
+    let processed_files = chunk.files.len();
+    let response = ProjectContentResponse {
+        content: chunk.content,
+        part: Some(chunk.part),
+        parts: Some(chunk.parts),
+        files: chunk.files,
+    };
+    Ok((response, processed_files))
+}
+
 // Repository mapping endpoints
 
 #[allow(clippy::future_not_send)]
@@ -196,23 +709,29 @@ pub async fn analyze_repository(
     project_name: web::Path<String>,
     data: web::Data<AppState>,
 ) -> impl Responder {
-    let config = data.config.read().await;
-    let is_valid = validate_api_key(&req, &config);
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
     drop(req); // Drop req to make future Send
 
-    if !is_valid {
+    let Some(key_name) = access.key_name else {
         return HttpResponse::Unauthorized().json(ErrorResponse {
             error: "Invalid or missing API key".to_string(),
         });
-    }
+    };
 
-    let project_name = project_name.into_inner();
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
 
-    if let Some(project_path) = config.projects.get(&project_name) {
+    if let Some(project) = access.project.as_ref() {
         debug!("Analyzing repository structure for project: {project_name}");
 
         let mut mapper = RepositoryMapper::new();
-        match mapper.analyze_repository(project_path) {
+        let started = Instant::now();
+        let analysis = mapper.analyze_roots(&project_root_paths(project));
+        data.metrics.record_analysis(started.elapsed());
+        match analysis {
             Ok(()) => {
                 info!("Successfully analyzed repository: {project_name}");
                 let response = RepositoryAnalysisResponse {
@@ -222,6 +741,12 @@ pub async fn analyze_repository(
                     dependency_cycles: mapper.graph.cycles.len(),
                     most_connected_components: mapper.insights.most_connected_components.clone(),
                     topological_order: mapper.topological_order.clone(),
+                    untested_public_components: mapper.correlate_tests().untested_public,
+                    architectural_layers: mapper.insights.architectural_layers.clone(),
+                    layering_violations: mapper.insights.layering_violations.clone(),
+                    dependency_hotspots: mapper.hotspots.clone(),
+                    dead_code: mapper.find_dead_code(),
+                    doc_coverage: mapper.doc_coverage(),
                 };
                 HttpResponse::Ok().json(response)
             }
@@ -244,32 +769,64 @@ pub async fn analyze_repository(
 pub async fn get_repository_map(
     req: HttpRequest,
     project_name: web::Path<String>,
+    query: web::Query<MapQuery>,
     data: web::Data<AppState>,
 ) -> impl Responder {
-    let config = data.config.read().await;
-    let is_valid = validate_api_key(&req, &config);
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
     drop(req); // Drop req to make future Send
 
-    if !is_valid {
+    let Some(key_name) = access.key_name else {
         return HttpResponse::Unauthorized().json(ErrorResponse {
             error: "Invalid or missing API key".to_string(),
         });
-    }
+    };
 
-    let project_name = project_name.into_inner();
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
 
-    if let Some(project_path) = config.projects.get(&project_name) {
+    if let Some(project) = access.project.as_ref() {
         debug!("Generating repository map for project: {project_name}");
 
         let mut mapper = RepositoryMapper::new();
-        match mapper.analyze_repository(project_path) {
+        let started = Instant::now();
+        let analysis = mapper.analyze_roots(&project_root_paths(project));
+        data.metrics.record_analysis(started.elapsed());
+        match analysis {
             Ok(()) => {
-                let map = mapper.generate_repository_map();
+                let level = query.level.unwrap_or_default();
+                if let Some(format) = query.format {
+                    let focus = query.focus.clone().map(|seed| GraphQuery {
+                        seed,
+                        depth: query.depth.unwrap_or(1),
+                        direction: query.direction.unwrap_or_default(),
+                        edge_types: Vec::new(),
+                    });
+                    return match mapper.export_graph(level, format, focus.as_ref()) {
+                        Ok(exported) => {
+                            info!("Exported repository graph for: {project_name}");
+                            HttpResponse::Ok()
+                                .content_type(format.content_type())
+                                .body(exported)
+                        }
+                        Err(e) => HttpResponse::BadRequest().json(ErrorResponse {
+                            error: e.to_string(),
+                        }),
+                    };
+                }
+                let (map, file_graph) = match level {
+                    MapLevel::Component => (mapper.generate_repository_map(), None),
+                    MapLevel::File => (mapper.generate_file_map(), Some(mapper.file_graph)),
+                };
                 info!("Successfully generated repository map for: {project_name}");
                 let response = RepositoryMapResponse {
                     project_name: project_name.clone(),
+                    level,
                     map,
                     insights: mapper.insights,
+                    file_graph,
                 };
                 HttpResponse::Ok().json(response)
             }
@@ -287,3 +844,336 @@ pub async fn get_repository_map(
         })
     }
 }
+
+#[allow(clippy::future_not_send)]
+pub async fn suggest_related_files(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    body: web::Json<SuggestRequest>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
+    drop(req); // Drop req to make future Send
+
+    let Some(key_name) = access.key_name else {
+        return HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Invalid or missing API key".to_string(),
+        });
+    };
+
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
+
+    let Some(project) = access.project.as_ref() else {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    };
+
+    let body = body.into_inner();
+    let mut seeds = Vec::with_capacity(body.files.len());
+    for file in &body.files {
+        match resolve_project_path(project, file) {
+            Some(path) if path.is_file() => seeds.push(path),
+            _ => {
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: format!("File '{file}' not found"),
+                })
+            }
+        }
+    }
+
+    debug!("Suggesting files related to {seeds:?} for project: {project_name}");
+    let mut mapper = RepositoryMapper::new();
+    let started = Instant::now();
+    let analysis = mapper.analyze_roots(&project_root_paths(project));
+    data.metrics.record_analysis(started.elapsed());
+    if let Err(e) = analysis {
+        error!("Error analyzing repository {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to analyze repository".to_string(),
+        });
+    }
+
+    match mapper.suggest_related(&seeds, body.limit.unwrap_or(10)) {
+        Ok(mut suggestions) => {
+            for suggestion in &mut suggestions {
+                suggestion.relabel(|path| project_label(project, Path::new(path)));
+            }
+            info!(
+                "Suggested {} related files for: {project_name}",
+                suggestions.len()
+            );
+            HttpResponse::Ok().json(SuggestResponse {
+                project_name,
+                suggestions,
+            })
+        }
+        Err(e) => HttpResponse::BadRequest().json(ErrorResponse {
+            error: e.to_string(),
+        }),
+    }
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn query_graph(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    body: web::Json<GraphQuery>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
+    drop(req); // Drop req to make future Send
+
+    let Some(key_name) = access.key_name else {
+        return HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Invalid or missing API key".to_string(),
+        });
+    };
+
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
+
+    let Some(project) = access.project.as_ref() else {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    };
+
+    let query = body.into_inner();
+    debug!(
+        "Querying the graph around '{}' for project: {project_name}",
+        query.seed
+    );
+    let mut mapper = RepositoryMapper::new();
+    let started = Instant::now();
+    let analysis = mapper.analyze_roots(&project_root_paths(project));
+    data.metrics.record_analysis(started.elapsed());
+    if let Err(e) = analysis {
+        error!("Error analyzing repository {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to analyze repository".to_string(),
+        });
+    }
+
+    match mapper.subgraph(&query.seed, query.depth, query.direction, &query.edge_types) {
+        Ok(subgraph) => {
+            info!(
+                "Found {} components around '{}' for: {project_name}",
+                subgraph.nodes.len(),
+                subgraph.seed
+            );
+            HttpResponse::Ok().json(GraphQueryResponse {
+                project_name,
+                subgraph,
+            })
+        }
+        Err(e @ QueryError::NotFound(_)) => HttpResponse::NotFound().json(ErrorResponse {
+            error: e.to_string(),
+        }),
+        Err(QueryError::Ambiguous { query, candidates }) => {
+            HttpResponse::Conflict().json(AmbiguousComponentResponse {
+                error: format!("'{query}' matches several components"),
+                candidates,
+            })
+        }
+    }
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn analyze_impact(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    body: web::Json<ImpactRequest>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
+    drop(req); // Drop req to make future Send
+
+    let Some(key_name) = access.key_name else {
+        return HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Invalid or missing API key".to_string(),
+        });
+    };
+
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
+
+    let Some(project) = access.project.as_ref() else {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    };
+
+    let body = body.into_inner();
+    let mut changed = Vec::with_capacity(body.files.len());
+    for file in &body.files {
+        match resolve_project_path(project, file) {
+            Some(path) if path.is_file() => changed.push(path),
+            _ => {
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: format!("File '{file}' not found"),
+                })
+            }
+        }
+    }
+
+    let mut mapper = RepositoryMapper::new();
+    let started = Instant::now();
+    let analysis = mapper.analyze_roots(&project_root_paths(project));
+    data.metrics.record_analysis(started.elapsed());
+    if let Err(e) = analysis {
+        error!("Error analyzing repository {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to analyze repository".to_string(),
+        });
+    }
+
+    if let Some(base) = &body.diff {
+        match mapper.changed_files(base) {
+            Ok(files) => changed.extend(files),
+            Err(e) => {
+                warn!("Bad impact request for project {project_name}: {e}");
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    debug!("Analyzing the impact of {changed:?} for project: {project_name}");
+    let mut impact = match mapper.impact(&changed, body.depth) {
+        Ok(impact) => impact,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: e.to_string(),
+            })
+        }
+    };
+
+    let mut gathered = None;
+    if body.with_context.unwrap_or(false) {
+        let files = impact
+            .context_files()
+            .into_iter()
+            .map(|path| ProjectFile {
+                display_path: project_label(project, &path),
+                path,
+            })
+            .collect();
+        match concatenate_project_files(files) {
+            Ok((content, processed_files)) => {
+                data.metrics
+                    .record_context_served(content.len(), processed_files.len());
+                gathered = Some(content);
+            }
+            Err(e) => {
+                error!("Error concatenating files for project {project_name}: {e}");
+                return HttpResponse::InternalServerError().json(ErrorResponse {
+                    error: "Failed to concatenate files".to_string(),
+                });
+            }
+        }
+    }
+
+    impact.relabel(|path| project_label(project, Path::new(path)));
+    info!(
+        "Found {} affected files and {} affected test files for: {project_name}",
+        impact.files.len(),
+        impact.tests.len()
+    );
+    HttpResponse::Ok().json(ImpactResponse {
+        project_name,
+        impact,
+        context: gathered,
+    })
+}
+
+#[allow(clippy::future_not_send)]
+pub async fn list_annotations(
+    req: HttpRequest,
+    project_name: web::Path<String>,
+    query: web::Query<AnnotationsQuery>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let project_name = project_name.into_inner();
+    let access = project_access(&req, &data, &project_name).await;
+    drop(req); // Drop req to make future Send
+
+    let Some(key_name) = access.key_name else {
+        return HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Invalid or missing API key".to_string(),
+        });
+    };
+
+    let _permit = match data.limiter.admit(&key_name, &access.rate_limits) {
+        Ok(permit) => permit,
+        Err(limit) => return limit_exceeded_response(&key_name, &limit),
+    };
+
+    let Some(project) = access.project.as_ref() else {
+        warn!("Project not found: {project_name}");
+        return HttpResponse::NotFound().json(ErrorResponse {
+            error: format!("Project '{project_name}' not found"),
+        });
+    };
+
+    let query = query.into_inner();
+    let kinds: Vec<AnnotationKind> = match query
+        .kind
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|kind| !kind.trim().is_empty())
+        .map(str::parse)
+        .collect()
+    {
+        Ok(kinds) => kinds,
+        Err(e) => return HttpResponse::BadRequest().json(ErrorResponse { error: e }),
+    };
+
+    debug!("Listing annotations for project: {project_name}");
+    let mut mapper = RepositoryMapper::new();
+    let started = Instant::now();
+    let analysis = mapper.analyze_roots(&project_root_paths(project));
+    data.metrics.record_analysis(started.elapsed());
+    if let Err(e) = analysis {
+        error!("Error analyzing repository {project_name}: {e}");
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to analyze repository".to_string(),
+        });
+    }
+
+    let prefix = query
+        .path
+        .as_deref()
+        .map(|path| path.trim_start_matches("./"));
+    let mut annotations = mapper.annotations(query.blame.unwrap_or(false));
+    for annotation in &mut annotations {
+        annotation.file = project_label(project, Path::new(&annotation.file));
+    }
+    annotations.retain(|annotation| {
+        (kinds.is_empty() || kinds.contains(&annotation.kind))
+            && prefix.is_none_or(|prefix| Path::new(&annotation.file).starts_with(prefix))
+    });
+    info!(
+        "Found {} annotations for: {project_name}",
+        annotations.len()
+    );
+    HttpResponse::Ok().json(AnnotationsResponse {
+        project_name,
+        annotations,
+    })
+}
@@ -0,0 +1,369 @@
+use crate::doc_coverage::{docstring_span, python_signature_end};
+use crate::imports::normalize_path;
+use crate::repo_mapper::{CodeComponent, ComponentType, RepositoryMapper, Visibility};
+use log::debug;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How bodies are delimited in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyStyle {
+    /// Braces, with methods in `impl` blocks apart from their type
+    Rust,
+    /// Indentation, with docstrings opening bodies
+    Python,
+    /// Braces, with methods inside their class
+    Braces,
+}
+
+impl BodyStyle {
+    fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Some(match extension {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "mts" | "cts" | "tsx" | "go" | "java" | "kt"
+            | "kts" | "c" | "h" | "hh" | "hpp" | "hxx" | "cc" | "cpp" | "cxx" => Self::Braces,
+            _ => return None,
+        })
+    }
+}
+
+/// Public type aliases, which are not components of their own
+fn type_alias_regex(path: &Path) -> Option<Regex> {
+    let pattern = match path.extension()?.to_str()? {
+        "rs" => r"^\s*pub\s+type\s+\w+",
+        "ts" | "mts" | "cts" | "tsx" => r"^\s*export\s+(?:declare\s+)?type\s+\w+",
+        "go" => r"^type\s+\p{Lu}\w*\s",
+        _ => return None,
+    };
+    Some(Regex::new(pattern).expect("Invalid type alias regex"))
+}
+
+/// The public interface of analyzed files, with bodies elided
+pub struct Signatures<'a> {
+    components: HashMap<PathBuf, Vec<&'a CodeComponent>>,
+}
+
+impl<'a> Signatures<'a> {
+    pub(crate) fn new(mapper: &'a RepositoryMapper) -> Self {
+        let mut components: HashMap<PathBuf, Vec<&CodeComponent>> = HashMap::new();
+        for component in mapper.graph.components.values() {
+            components
+                .entry(normalize_path(&component.file_path))
+                .or_default()
+                .push(component);
+        }
+        for in_file in components.values_mut() {
+            in_file.sort_by_key(|c| (c.start_line, std::cmp::Reverse(c.end_line)));
+        }
+        Self { components }
+    }
+
+    /// Public functions, types, traits and type aliases of a file, with their doc comments
+    /// and with function bodies elided as `{ ... }`, or `...` in Python. `None` for files
+    /// that are not analyzed source or declare nothing public.
+    pub fn for_file(&self, path: &Path) -> Option<String> {
+        let style = BodyStyle::for_path(path)?;
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                debug!("Skipping unreadable file {}: {e}", path.display());
+                return None;
+            }
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        let components = self
+            .components
+            .get(&normalize_path(path))
+            .map_or(&[][..], Vec::as_slice);
+        let file = FileSurface {
+            lines: &lines,
+            components,
+            style,
+        };
+
+        // Blocks by the line they start at, so that the surface keeps the file's order
+        let mut blocks: BTreeMap<usize, String> = BTreeMap::new();
+        let mut impls: BTreeMap<usize, Vec<&CodeComponent>> = BTreeMap::new();
+        for component in components.iter().filter(|c| file.is_top_level(c)) {
+            if style == BodyStyle::Rust && component.component_type == ComponentType::Method {
+                if let Some(header) = impl_header(&lines, component.start_line) {
+                    impls.entry(header).or_default().push(component);
+                }
+            } else if is_public(component) && component.component_type != ComponentType::Module {
+                blocks.insert(component.start_line - 1, file.render(component));
+            }
+        }
+        for (header, methods) in impls {
+            if let Some(block) = file.render_impl(header, &methods) {
+                blocks.insert(header, block);
+            }
+        }
+        if let Some(alias) = type_alias_regex(path) {
+            for (idx, line) in lines.iter().enumerate() {
+                let inside = components.iter().any(|c| {
+                    c.component_type != ComponentType::Module
+                        && c.start_line <= idx + 1
+                        && idx < c.end_line
+                });
+                if !inside && alias.is_match(line) {
+                    let mut block = file.leading(idx + 1);
+                    block.push_str(&statement(&lines, idx));
+                    blocks.insert(idx, block);
+                }
+            }
+        }
+
+        if blocks.is_empty() {
+            return None;
+        }
+        let surface: Vec<String> = blocks.into_values().collect();
+        Some(surface.join("\n\n"))
+    }
+}
+
+fn is_public(component: &CodeComponent) -> bool {
+    component.visibility == Visibility::Public
+}
+
+/// Index of the `impl` line opening the block a Rust method at `line` is in
+pub(crate) fn impl_header(lines: &[&str], line: usize) -> Option<usize> {
+    (0..line.saturating_sub(1)).rev().find(|&idx| {
+        let text = lines[idx].trim_start();
+        (text.starts_with("impl") && !text.starts_with("impl_")) || text.starts_with("unsafe impl")
+    })
+}
+
+/// A statement from line `idx` until it ends, for type aliases
+fn statement(lines: &[&str], idx: usize) -> String {
+    let mut depth = 0i32;
+    let mut end = idx;
+    for (offset, line) in lines[idx..].iter().enumerate() {
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        end = idx + offset;
+        let text = line.trim_end();
+        let continues = ['=', '|', '&', ',', '<', '(', '{', '[']
+            .iter()
+            .any(|c| text.ends_with(*c));
+        if depth <= 0 && (text.ends_with(';') || !continues) {
+            break;
+        }
+    }
+    lines[idx..=end].join("\n")
+}
+
+struct FileSurface<'a> {
+    lines: &'a [&'a str],
+    components: &'a [&'a CodeComponent],
+    style: BodyStyle,
+}
+
+impl FileSurface<'_> {
+    /// Whether `inner` is written inside `outer`, as methods are inside a Python or Java
+    /// class but not inside a Rust struct
+    fn encloses(outer: &CodeComponent, inner: &CodeComponent) -> bool {
+        outer.id != inner.id
+            && outer.component_type != ComponentType::Module
+            && outer.start_line <= inner.start_line
+            && inner.end_line <= outer.end_line
+    }
+
+    fn is_top_level(&self, component: &CodeComponent) -> bool {
+        !self
+            .components
+            .iter()
+            .any(|outer| Self::encloses(outer, component))
+    }
+
+    /// Components written directly inside `component`
+    fn members(&self, component: &CodeComponent) -> Vec<&CodeComponent> {
+        let nested: Vec<&CodeComponent> = self
+            .components
+            .iter()
+            .copied()
+            .filter(|inner| Self::encloses(component, inner))
+            .collect();
+        nested
+            .iter()
+            .copied()
+            .filter(|inner| !nested.iter().any(|outer| Self::encloses(outer, inner)))
+            .collect()
+    }
+
+    fn render(&self, component: &CodeComponent) -> String {
+        let mut block = self.leading(component.start_line);
+        let members = self.members(component);
+        let has_methods = members
+            .iter()
+            .any(|m| m.component_type == ComponentType::Method);
+        match component.component_type {
+            ComponentType::Function | ComponentType::Method => {
+                block.push_str(&self.signature(component));
+            }
+            ComponentType::Class | ComponentType::Interface if has_methods => {
+                block.push_str(&self.header(component));
+                let public: Vec<&&CodeComponent> =
+                    members.iter().filter(|m| is_public(m)).collect();
+                for member in &public {
+                    block.push('\n');
+                    block.push_str(&self.render(member));
+                }
+                if self.style == BodyStyle::Python && public.is_empty() {
+                    block.push('\n');
+                    block.push_str(&indentation(self.line(component.start_line)));
+                    block.push_str("    ...");
+                } else if self.style != BodyStyle::Python {
+                    block.push('\n');
+                    block.push_str(&indentation(self.line(component.start_line)));
+                    block.push('}');
+                }
+            }
+            _ => block.push_str(&self.span(component.start_line, component.end_line)),
+        }
+        block
+    }
+
+    /// A Rust `impl` block: trait implementations as one line, inherent ones with the
+    /// signatures of their public methods
+    fn render_impl(&self, header: usize, methods: &[&CodeComponent]) -> Option<String> {
+        let opening = self.until_body(header + 1, usize::MAX)?;
+        let is_trait_impl = opening.contains(" for ");
+        if is_trait_impl {
+            return Some(format!(
+                "{} {{ ... }}",
+                opening.trim_end_matches('{').trim_end()
+            ));
+        }
+        let public: Vec<&&CodeComponent> = methods.iter().filter(|m| is_public(m)).collect();
+        if public.is_empty() {
+            return None;
+        }
+        let mut block = opening;
+        for method in public {
+            block.push('\n');
+            block.push_str(&self.render(method));
+        }
+        block.push('\n');
+        block.push_str(&indentation(self.lines[header]));
+        block.push('}');
+        Some(block)
+    }
+
+    fn line(&self, number: usize) -> &str {
+        self.lines
+            .get(number.saturating_sub(1))
+            .copied()
+            .unwrap_or("")
+    }
+
+    /// Lines `start..=end`, 1-based
+    fn span(&self, start: usize, end: usize) -> String {
+        let end = end.min(self.lines.len());
+        self.lines
+            .get(start.saturating_sub(1)..end)
+            .unwrap_or(&[])
+            .join("\n")
+    }
+
+    /// Doc comments, attributes and decorators right above line `start`
+    fn leading(&self, start: usize) -> String {
+        let mut first = start.saturating_sub(1);
+        while first > 0 {
+            let text = self.lines[first - 1].trim_start();
+            let is_leading = ["///", "//", "/*", "*", "#[", "@"]
+                .iter()
+                .any(|prefix| text.starts_with(prefix))
+                || (self.style == BodyStyle::Python && text.starts_with('#'));
+            if !is_leading || text.starts_with("//!") {
+                break;
+            }
+            first -= 1;
+        }
+        if first + 1 == start {
+            return String::new();
+        }
+        let mut leading = self.span(first + 1, start - 1);
+        leading.push('\n');
+        leading
+    }
+
+    /// A declaration up to and including the `{` opening its body, or through its end
+    /// if it has none
+    fn until_body(&self, start: usize, end: usize) -> Option<String> {
+        let end = end.min(self.lines.len());
+        let mut depth = 0i32;
+        let mut text = String::new();
+        for line in self.lines.get(start.saturating_sub(1)..end)? {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            for (idx, c) in line.char_indices() {
+                match c {
+                    '(' | '[' => depth += 1,
+                    ')' | ']' => depth -= 1,
+                    '{' | ';' if depth == 0 => {
+                        text.push_str(&line[..=idx]);
+                        return Some(text);
+                    }
+                    _ => {}
+                }
+            }
+            text.push_str(line);
+        }
+        Some(text)
+    }
+
+    /// A function's signature with its body elided
+    fn signature(&self, component: &CodeComponent) -> String {
+        if self.style == BodyStyle::Python {
+            let mut header = self.header(component);
+            let has_docstring =
+                python_signature_end(self.lines, component.start_line, component.end_line)
+                    .and_then(|end| docstring_span(self.lines, end, component.end_line))
+                    .is_some();
+            if !has_docstring {
+                header.push('\n');
+                header.push_str(&indentation(self.line(component.start_line)));
+                header.push_str("    ...");
+            }
+            return header;
+        }
+        let declaration = self
+            .until_body(component.start_line, component.end_line)
+            .unwrap_or_default();
+        match declaration.strip_suffix('{') {
+            Some(opening) => format!("{} {{ ... }}", opening.trim_end()),
+            None => declaration,
+        }
+    }
+
+    /// A type's declaration up to its body, and in Python its docstring
+    fn header(&self, component: &CodeComponent) -> String {
+        if self.style != BodyStyle::Python {
+            return self
+                .until_body(component.start_line, component.end_line)
+                .unwrap_or_default();
+        }
+        let Some(signature_end) =
+            python_signature_end(self.lines, component.start_line, component.end_line)
+        else {
+            return self.line(component.start_line).to_string();
+        };
+        let end = docstring_span(self.lines, signature_end, component.end_line)
+            .map_or(signature_end, |(_, last)| last);
+        self.span(component.start_line, end + 1)
+    }
+}
+
+fn indentation(line: &str) -> String {
+    line.chars().take_while(|c| c.is_whitespace()).collect()
+}
@@ -0,0 +1,379 @@
+use crate::git;
+use crate::imports::normalize_path;
+use crate::repo_mapper::RepositoryMapper;
+use crate::test_correlation::is_test_pair;
+use log::{debug, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Score for a file importing or imported by a seed
+const IMPORT_WEIGHT: f64 = 1.0;
+/// Score for a file two imports away from a seed
+const NEARBY_IMPORT_WEIGHT: f64 = 0.4;
+/// Score for a file changed in every commit that changed a seed
+const CO_CHANGE_WEIGHT: f64 = 0.8;
+/// Score for a file sharing rare identifiers with a seed
+const IDENTIFIER_WEIGHT: f64 = 0.6;
+/// Score for the test of a seed, or the source file a seed tests
+const TEST_PAIR_WEIGHT: f64 = 1.0;
+/// Commits read from each root's history
+pub(crate) const HISTORY_DEPTH: usize = 500;
+/// Commits touching more files than this (mass renames, reformatting) say little about coupling
+pub(crate) const MAX_COMMIT_FILES: usize = 50;
+/// Shared identifiers listed in a reason
+const MAX_REPORTED_NAMES: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum SuggestError {
+    #[error("No files given to find related files for")]
+    NoSeeds,
+    #[error("File '{0}' not found")]
+    UnknownFile(String),
+}
+
+/// A file related to the seed files, with the evidence for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSuggestion {
+    pub path: String,
+    pub score: f64,
+    pub reasons: Vec<SuggestionReason>,
+}
+
+impl FileSuggestion {
+    /// Rewrite every path in the suggestion, e.g. into project-relative labels
+    pub fn relabel(&mut self, label: impl Fn(&str) -> String) {
+        self.path = label(&self.path);
+        for reason in &mut self.reasons {
+            match reason {
+                SuggestionReason::NearbyImport { seed, via } => {
+                    *seed = label(seed);
+                    *via = label(via);
+                }
+                SuggestionReason::ImportedBy { seed }
+                | SuggestionReason::Imports { seed }
+                | SuggestionReason::CoChanged { seed, .. }
+                | SuggestionReason::SharedIdentifiers { seed, .. }
+                | SuggestionReason::TestPair { seed } => *seed = label(seed),
+            }
+        }
+    }
+}
+
+/// Why a file was suggested
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuggestionReason {
+    /// The seed imports the file
+    ImportedBy { seed: String },
+    /// The file imports the seed
+    Imports { seed: String },
+    /// The file is two imports away from the seed
+    NearbyImport { seed: String, via: String },
+    /// The file and the seed were changed in the same commits
+    CoChanged { seed: String, commits: usize },
+    /// One file defines names the other uses
+    SharedIdentifiers { seed: String, names: Vec<String> },
+    /// The file tests the seed, or the seed tests the file
+    TestPair { seed: String },
+}
+
+impl fmt::Display for SuggestionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ImportedBy { seed } => write!(f, "imported by {seed}"),
+            Self::Imports { seed } => write!(f, "imports {seed}"),
+            Self::NearbyImport { seed, via } => {
+                write!(f, "near {seed} in the import graph via {via}")
+            }
+            Self::CoChanged { seed, commits } => {
+                write!(f, "changed with {seed} in {commits} commit(s)")
+            }
+            Self::SharedIdentifiers { seed, names } => {
+                write!(f, "shares {} with {seed}", names.join(", "))
+            }
+            Self::TestPair { seed } => write!(f, "test pair of {seed}"),
+        }
+    }
+}
+
+/// Accumulated scores and reasons per candidate file
+#[derive(Default)]
+struct Scores(BTreeMap<String, FileSuggestion>);
+
+impl Scores {
+    fn add(&mut self, path: &str, score: f64, reason: SuggestionReason) {
+        let suggestion = self
+            .0
+            .entry(path.to_string())
+            .or_insert_with(|| FileSuggestion {
+                path: path.to_string(),
+                score: 0.0,
+                reasons: Vec::new(),
+            });
+        suggestion.score += score;
+        suggestion.reasons.push(reason);
+    }
+}
+
+/// Rank files related to the seed files, best first
+pub(crate) fn suggest_related(
+    mapper: &RepositoryMapper,
+    seeds: &[PathBuf],
+    limit: usize,
+) -> Result<Vec<FileSuggestion>, SuggestError> {
+    if seeds.is_empty() {
+        return Err(SuggestError::NoSeeds);
+    }
+    let seeds = seeds
+        .iter()
+        .map(|seed| {
+            resolve_file(mapper, seed)
+                .ok_or_else(|| SuggestError::UnknownFile(seed.display().to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut scores = Scores::default();
+    add_import_proximity(mapper, &seeds, &mut scores);
+    add_test_pairs(mapper, &seeds, &mut scores);
+    add_shared_identifiers(mapper, &seeds, &mut scores);
+    add_co_changes(mapper, &seeds, &mut scores);
+
+    let mut suggestions: Vec<FileSuggestion> = scores
+        .0
+        .into_values()
+        .filter(|suggestion| !seeds.contains(&suggestion.path))
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/// The key used for a file: its path as discovered if it was analyzed, else its normalized path
+fn file_key(mapper: &RepositoryMapper, path: &Path) -> String {
+    let normalized = normalize_path(path);
+    mapper
+        .files
+        .get(&normalized)
+        .unwrap_or(&normalized)
+        .display()
+        .to_string()
+}
+
+/// Find a file among the analyzed files, as given or relative to a root, or on disk
+pub(crate) fn resolve_file(mapper: &RepositoryMapper, file: &Path) -> Option<String> {
+    let candidates: Vec<PathBuf> = std::iter::once(file.to_path_buf())
+        .chain(mapper.roots.iter().map(|root| root.join(file)))
+        .collect();
+    if let Some(found) = candidates
+        .iter()
+        .find(|path| mapper.files.contains_key(&normalize_path(path)))
+    {
+        return Some(file_key(mapper, found));
+    }
+    candidates
+        .iter()
+        .rev()
+        .find(|path| path.is_file())
+        .map(|path| file_key(mapper, path))
+}
+
+fn add_import_proximity(mapper: &RepositoryMapper, seeds: &[String], scores: &mut Scores) {
+    let graph = &mapper.file_graph;
+    let neighbors = |path: &str| -> BTreeSet<&String> {
+        graph.files.get(path).map_or_else(BTreeSet::new, |node| {
+            node.imports.iter().chain(&node.imported_by).collect()
+        })
+    };
+
+    for seed in seeds {
+        let Some(node) = graph.files.get(seed) else {
+            continue;
+        };
+        for imported in &node.imports {
+            scores.add(
+                imported,
+                IMPORT_WEIGHT,
+                SuggestionReason::ImportedBy { seed: seed.clone() },
+            );
+        }
+        for importer in &node.imported_by {
+            scores.add(
+                importer,
+                IMPORT_WEIGHT,
+                SuggestionReason::Imports { seed: seed.clone() },
+            );
+        }
+
+        let direct = neighbors(seed);
+        let mut nearby: BTreeMap<&String, &String> = BTreeMap::new();
+        for via in &direct {
+            for next in neighbors(via) {
+                if next != seed && !direct.contains(next) {
+                    nearby.entry(next).or_insert(via);
+                }
+            }
+        }
+        for (path, via) in nearby {
+            scores.add(
+                path,
+                NEARBY_IMPORT_WEIGHT,
+                SuggestionReason::NearbyImport {
+                    seed: seed.clone(),
+                    via: via.clone(),
+                },
+            );
+        }
+    }
+}
+
+fn add_test_pairs(mapper: &RepositoryMapper, seeds: &[String], scores: &mut Scores) {
+    for seed in seeds {
+        let seed_path = mapper.relative_to_root(Path::new(seed));
+        for file in mapper.files.values() {
+            let path = file.display().to_string();
+            if path != *seed && is_test_pair(seed_path, mapper.relative_to_root(file)) {
+                scores.add(
+                    &path,
+                    TEST_PAIR_WEIGHT,
+                    SuggestionReason::TestPair { seed: seed.clone() },
+                );
+            }
+        }
+    }
+}
+
+fn add_shared_identifiers(mapper: &RepositoryMapper, seeds: &[String], scores: &mut Scores) {
+    let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("Invalid identifier regex");
+
+    // Names each file defines, skipping ones too short to be distinctive
+    let mut definitions: HashMap<String, BTreeSet<&str>> = HashMap::new();
+    for component in mapper.graph.components.values() {
+        if component.name.len() >= 3 {
+            definitions
+                .entry(component.file_path.display().to_string())
+                .or_default()
+                .insert(component.name.as_str());
+        }
+    }
+
+    let mut tokens: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut document_frequency: HashMap<String, usize> = HashMap::new();
+    for file in mapper.files.values() {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            debug!("Skipping unreadable file {}", file.display());
+            continue;
+        };
+        let file_tokens: HashSet<String> = identifier
+            .find_iter(&content)
+            .map(|token| token.as_str().to_string())
+            .collect();
+        for token in &file_tokens {
+            *document_frequency.entry(token.clone()).or_default() += 1;
+        }
+        tokens.insert(file.display().to_string(), file_tokens);
+    }
+
+    let empty_definitions = BTreeSet::new();
+    for seed in seeds {
+        let Some(seed_tokens) = tokens.get(seed) else {
+            continue;
+        };
+        let seed_definitions = definitions.get(seed).unwrap_or(&empty_definitions);
+        for (path, file_tokens) in &tokens {
+            if path == seed {
+                continue;
+            }
+            let file_definitions = definitions.get(path).unwrap_or(&empty_definitions);
+            let mut shared: Vec<(&str, f64)> = seed_definitions
+                .iter()
+                .filter(|name| file_tokens.contains(**name))
+                .chain(
+                    file_definitions
+                        .iter()
+                        .filter(|name| seed_tokens.contains(**name)),
+                )
+                .map(|name| {
+                    let frequency = document_frequency.get(*name).copied().unwrap_or(1);
+                    (
+                        *name,
+                        1.0 / f64::from(u32::try_from(frequency).unwrap_or(u32::MAX)),
+                    )
+                })
+                .collect();
+            if shared.is_empty() {
+                continue;
+            }
+            shared.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            shared.dedup_by_key(|(name, _)| *name);
+
+            let rarity: f64 = shared.iter().map(|(_, weight)| weight).sum();
+            scores.add(
+                path,
+                IDENTIFIER_WEIGHT * rarity.min(1.0),
+                SuggestionReason::SharedIdentifiers {
+                    seed: seed.clone(),
+                    names: shared
+                        .iter()
+                        .take(MAX_REPORTED_NAMES)
+                        .map(|(name, _)| (*name).to_string())
+                        .collect(),
+                },
+            );
+        }
+    }
+}
+
+fn add_co_changes(mapper: &RepositoryMapper, seeds: &[String], scores: &mut Scores) {
+    let mut seed_commits: HashMap<&String, usize> = HashMap::new();
+    let mut co_changes: BTreeMap<(&String, String), usize> = BTreeMap::new();
+
+    for root in &mapper.roots {
+        let commits = match git::recent_commits(root, HISTORY_DEPTH) {
+            Ok(commits) => commits,
+            Err(e) => {
+                warn!("Skipping git history of {}: {e}", root.display());
+                continue;
+            }
+        };
+        for commit in commits {
+            if commit.files.len() > MAX_COMMIT_FILES {
+                continue;
+            }
+            let changed: Vec<String> = commit
+                .files
+                .iter()
+                .map(|file| root.join(file))
+                .filter(|path| mapper.files.contains_key(&normalize_path(path)) || path.is_file())
+                .map(|path| file_key(mapper, &path))
+                .collect();
+            for seed in seeds.iter().filter(|seed| changed.contains(seed)) {
+                *seed_commits.entry(seed).or_default() += 1;
+                for other in changed.iter().filter(|path| *path != seed) {
+                    *co_changes.entry((seed, other.clone())).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    for ((seed, path), commits) in co_changes {
+        let total = seed_commits.get(seed).copied().unwrap_or(commits).max(1);
+        let share = f64::from(u32::try_from(commits).unwrap_or(u32::MAX))
+            / f64::from(u32::try_from(total).unwrap_or(u32::MAX));
+        scores.add(
+            &path,
+            CO_CHANGE_WEIGHT * share,
+            SuggestionReason::CoChanged {
+                seed: seed.clone(),
+                commits,
+            },
+        );
+    }
+}
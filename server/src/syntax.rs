@@ -0,0 +1,1679 @@
+use crate::complexity::{self, ComplexityMetrics};
+use crate::repo_mapper::{ComponentType, Visibility};
+use log::debug;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+/// Languages the repository mapper can extract components from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+}
+
+impl SourceLanguage {
+    /// Every supported language
+    pub const ALL: [Self; 10] = [
+        Self::Rust,
+        Self::Python,
+        Self::JavaScript,
+        Self::TypeScript,
+        Self::Tsx,
+        Self::Go,
+        Self::Java,
+        Self::Kotlin,
+        Self::C,
+        Self::Cpp,
+    ];
+
+    /// File extensions of the language's sources
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &["rs"],
+            Self::Python => &["py", "pyi"],
+            Self::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            Self::TypeScript => &["ts", "mts", "cts"],
+            Self::Tsx => &["tsx"],
+            Self::Go => &["go"],
+            Self::Java => &["java"],
+            Self::Kotlin => &["kt", "kts"],
+            Self::C => &["c"],
+            // Headers are parsed as C++, which accepts nearly all C declarations
+            Self::Cpp => &["h", "hh", "hpp", "hxx", "cc", "cpp", "cxx"],
+        }
+    }
+
+    /// The language of a source file, by extension
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|language| language.extensions().contains(&extension))
+    }
+
+    /// Tree-sitter grammar for the language, if one is bundled
+    fn grammar(self) -> Option<Language> {
+        match self {
+            Self::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
+            Self::Python => Some(tree_sitter_python::LANGUAGE.into()),
+            Self::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+            Self::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+            Self::Tsx => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+            Self::Go => Some(tree_sitter_go::LANGUAGE.into()),
+            Self::Java => Some(tree_sitter_java::LANGUAGE.into()),
+            Self::C => Some(tree_sitter_c::LANGUAGE.into()),
+            Self::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
+            Self::Kotlin => None,
+        }
+    }
+}
+
+/// A component found in a parse tree, before it is placed in the dependency graph.
+///
+/// Analyzers outside this crate build one with [`ExtractedComponent::new`] and the `with_`
+/// methods, so that new fields do not break them.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ExtractedComponent {
+    pub name: String,
+    /// Name including enclosing components, e.g. `Parser::parse`
+    pub qualified_name: String,
+    pub component_type: ComponentType,
+    pub visibility: Visibility,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Index of the enclosing component in the extracted list
+    pub parent: Option<usize>,
+    pub metrics: ComplexityMetrics,
+}
+
+impl ExtractedComponent {
+    /// A public, top-level component qualified by its name alone, with no metrics
+    pub fn new(
+        name: impl Into<String>,
+        component_type: ComponentType,
+        start_line: usize,
+        end_line: usize,
+    ) -> Self {
+        let name = name.into();
+        Self {
+            qualified_name: name.clone(),
+            name,
+            component_type,
+            visibility: Visibility::Public,
+            start_line,
+            end_line,
+            parent: None,
+            metrics: ComplexityMetrics::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_qualified_name(mut self, qualified_name: impl Into<String>) -> Self {
+        self.qualified_name = qualified_name.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Nests the component in the one at `parent` in the extracted list
+    #[must_use]
+    pub fn with_parent(mut self, parent: usize) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: ComplexityMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+impl Default for ExtractedComponent {
+    /// An unnamed public function on line 1
+    fn default() -> Self {
+        Self::new(String::new(), ComponentType::Function, 1, 1)
+    }
+}
+
+/// The package a file declares, e.g. `package store` or `package com.acme.store;`
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PackageDecl {
+    pub name: String,
+    pub line: usize,
+}
+
+impl PackageDecl {
+    pub fn new(name: impl Into<String>, line: usize) -> Self {
+        Self {
+            name: name.into(),
+            line,
+        }
+    }
+}
+
+/// An import, `use` or `#include` as written in the source
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ImportDecl {
+    /// Import path with quotes, brackets and wildcards stripped
+    pub path: String,
+    pub line: usize,
+    /// `#include <...>` rather than `#include "..."`
+    pub system: bool,
+}
+
+impl ImportDecl {
+    pub fn new(path: impl Into<String>, line: usize) -> Self {
+        Self {
+            path: path.into(),
+            line,
+            system: false,
+        }
+    }
+
+    /// Marks the import as a system `#include <...>`
+    #[must_use]
+    pub fn as_system(mut self) -> Self {
+        self.system = true;
+        self
+    }
+}
+
+/// A call found in the source, identified by the name of the called function or method
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CallSite {
+    pub callee: String,
+    pub line: usize,
+    /// Called through a receiver or path, e.g. `store.get()`
+    pub method: bool,
+}
+
+impl CallSite {
+    /// A call of a plain function; see [`CallSite::as_method`]
+    pub fn new(callee: impl Into<String>, line: usize) -> Self {
+        Self {
+            callee: callee.into(),
+            line,
+            method: false,
+        }
+    }
+
+    /// Marks the call as made through a receiver or path
+    #[must_use]
+    pub fn as_method(mut self) -> Self {
+        self.method = true;
+        self
+    }
+}
+
+/// Everything extracted from a single source file. Analyzers outside this crate start from
+/// [`FileSyntax::default`] and fill in its fields.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct FileSyntax {
+    pub package: Option<PackageDecl>,
+    pub imports: Vec<ImportDecl>,
+    pub components: Vec<ExtractedComponent>,
+    /// Lines of the file containing code
+    pub sloc: usize,
+}
+
+/// Parses `source` and extracts its components.
+///
+/// Returns `None` when the source could not be parsed, so callers can fall back to a
+/// simpler extractor.
+pub fn extract_components(
+    language: SourceLanguage,
+    source: &str,
+) -> Option<Vec<ExtractedComponent>> {
+    parse_source(language, source).map(|syntax| syntax.components)
+}
+
+/// Parses `source` and extracts its package declaration, imports and components.
+///
+/// Returns `None` when the source could not be parsed.
+pub fn parse_source(language: SourceLanguage, source: &str) -> Option<FileSyntax> {
+    let Some(grammar) = language.grammar() else {
+        return Some(kotlin::parse(source));
+    };
+    let mut parser = Parser::new();
+    parser.set_language(&grammar).ok()?;
+    let tree = parser.parse(source, None)?;
+    let root = tree.root_node();
+
+    let mut extractor = Extractor {
+        source: source.as_bytes(),
+        syntax: FileSyntax::default(),
+        impl_parents: Vec::new(),
+    };
+    match language {
+        SourceLanguage::Rust => extractor.visit_rust(root, None, RustScope::Module),
+        SourceLanguage::Python => extractor.visit_python(root, None, false),
+        SourceLanguage::JavaScript | SourceLanguage::TypeScript | SourceLanguage::Tsx => {
+            extractor.visit_js(root, None, false);
+            extractor.apply_commonjs_exports(source);
+            extractor.collect_js_imports(root);
+        }
+        SourceLanguage::Go => extractor.visit_go(root),
+        SourceLanguage::Java => extractor.visit_java(root, None, false),
+        SourceLanguage::C | SourceLanguage::Cpp => extractor.visit_c(root, None),
+        // Without a grammar, Kotlin is scanned line by line above
+        SourceLanguage::Kotlin => {}
+    }
+    extractor.resolve_impl_parents();
+    extractor.syntax.sloc = complexity::source_lines(root);
+
+    if root.has_error() && extractor.syntax.components.is_empty() {
+        debug!("Parse tree has errors and no components; falling back");
+        return None;
+    }
+    Some(extractor.syntax)
+}
+
+/// Parses `source` and collects the function and method calls in it
+pub fn extract_call_sites(language: SourceLanguage, source: &str) -> Vec<CallSite> {
+    let Some(grammar) = language.grammar() else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+
+    let bytes = source.as_bytes();
+    let text = |node: Node| node.utf8_text(bytes).unwrap_or_default().to_string();
+    let mut calls = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+
+        let callee = match node.kind() {
+            "call_expression" | "call" => node.child_by_field_name("function"),
+            "method_invocation" => node.child_by_field_name("name"),
+            _ => continue,
+        };
+        let Some(callee) = callee.and_then(|callee| call_target(callee, &text)) else {
+            continue;
+        };
+        let method = node.kind() == "method_invocation" || callee.1;
+        calls.push(CallSite {
+            callee: callee.0,
+            line: node.start_position().row + 1,
+            method,
+        });
+    }
+    calls.sort_by_key(|call| call.line);
+    calls
+}
+
+/// Expands a Rust use tree such as `crate::{a::B, c::{self, D}}` into individual paths,
+/// dropping `as` renames
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    use_tree_bindings(tree)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Expands a Rust use tree into individual paths and the names they bind: `B` for
+/// `crate::a::B`, `c` for `crate::c::{self}`, `E` for `D as E` and `*` for globs
+pub(crate) fn use_tree_bindings(tree: &str) -> Vec<(String, String)> {
+    let tree: String = tree.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some(open) = tree.find('{') else {
+        let mut parts = tree.split(" as ");
+        let path = parts.next().unwrap_or(&tree).trim();
+        let mut segments = path.rsplit("::");
+        let last = segments.next().unwrap_or(path);
+        let name = match parts.next() {
+            Some(alias) => alias.trim(),
+            None if last == "self" => segments.next().unwrap_or(last),
+            None => last,
+        };
+        return vec![(path.to_string(), name.to_string())];
+    };
+    let Some(close) = tree.rfind('}') else {
+        return Vec::new();
+    };
+    let prefix = tree[..open].trim();
+
+    let mut paths = Vec::new();
+    let mut depth = 0usize;
+    let mut start = open + 1;
+    for (idx, c) in tree[open + 1..close].char_indices() {
+        let idx = idx + open + 1;
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                paths.push(&tree[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    paths.push(&tree[start..close]);
+
+    paths
+        .into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .flat_map(|item| use_tree_bindings(&format!("{prefix}{item}")))
+        .collect()
+}
+
+/// Name of the function a call expression invokes, and whether it goes through a receiver
+fn call_target(node: Node, text: &impl Fn(Node) -> String) -> Option<(String, bool)> {
+    match node.kind() {
+        "identifier" => Some((text(node), false)),
+        // `store.get`, `self.parse`, `ptr->next`
+        "field_expression" | "member_expression" | "attribute" | "selector_expression" => node
+            .child_by_field_name("field")
+            .or_else(|| node.child_by_field_name("property"))
+            .or_else(|| node.child_by_field_name("attribute"))
+            .map(|field| (text(field), true)),
+        // `Parser::new`, `std::move`
+        "scoped_identifier" | "qualified_identifier" => node
+            .child_by_field_name("name")
+            .and_then(|name| call_target(name, text))
+            .map(|(name, _)| (name, true)),
+        // `parse::<T>`, `make<T>`
+        "generic_function" | "template_function" => node
+            .child_by_field_name("function")
+            .or_else(|| node.child_by_field_name("name"))
+            .and_then(|function| call_target(function, text)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RustScope {
+    Module,
+    Trait,
+    Impl { trait_impl: bool },
+}
+
+struct Extractor<'a> {
+    source: &'a [u8],
+    syntax: FileSyntax,
+    /// Methods from `impl` blocks, with the name of the type they belong to
+    impl_parents: Vec<(usize, String)>,
+}
+
+impl Extractor<'_> {
+    fn text(&self, node: Node) -> String {
+        node.utf8_text(self.source).unwrap_or_default().to_string()
+    }
+
+    fn field_text(&self, node: Node, field: &str) -> Option<String> {
+        node.child_by_field_name(field).map(|n| self.text(n))
+    }
+
+    fn push(
+        &mut self,
+        node: Node,
+        name: String,
+        component_type: ComponentType,
+        visibility: Visibility,
+        parent: Option<usize>,
+    ) -> usize {
+        let qualified_name = match parent {
+            Some(idx) => format!("{}::{name}", self.syntax.components[idx].qualified_name),
+            None => name.clone(),
+        };
+        let function = matches!(
+            component_type,
+            ComponentType::Function | ComponentType::Method
+        );
+        self.syntax.components.push(ExtractedComponent {
+            name,
+            qualified_name,
+            component_type,
+            visibility,
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            parent,
+            metrics: complexity::measure(node, self.source, function),
+        });
+        self.syntax.components.len() - 1
+    }
+
+    fn visit_children<F>(&mut self, node: Node, mut visit: F)
+    where
+        F: FnMut(&mut Self, Node),
+    {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children {
+            visit(self, child);
+        }
+    }
+
+    /// Attaches `impl` methods to the struct, enum or trait they implement, when it is
+    /// declared in the same file.
+    fn resolve_impl_parents(&mut self) {
+        for (idx, type_name) in std::mem::take(&mut self.impl_parents) {
+            let parent = self.syntax.components.iter().position(|c| {
+                c.name == type_name
+                    && matches!(
+                        c.component_type,
+                        ComponentType::Class | ComponentType::Enum | ComponentType::Interface
+                    )
+            });
+            let component = &mut self.syntax.components[idx];
+            component.parent = parent;
+        }
+    }
+
+    // Rust
+
+    fn rust_visibility(&self, node: Node) -> Visibility {
+        let mut cursor = node.walk();
+        let modifier = node
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "visibility_modifier");
+        match modifier.map(|m| self.text(m)) {
+            Some(text) if text == "pub" => Visibility::Public,
+            Some(_) => Visibility::Internal,
+            None => Visibility::Private,
+        }
+    }
+
+    fn visit_rust(&mut self, node: Node, parent: Option<usize>, scope: RustScope) {
+        match node.kind() {
+            "function_item" | "function_signature_item" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let (component_type, visibility) = match scope {
+                    RustScope::Module => (ComponentType::Function, self.rust_visibility(node)),
+                    // Trait items and trait impl items are as visible as the trait itself
+                    RustScope::Trait | RustScope::Impl { trait_impl: true } => {
+                        (ComponentType::Method, Visibility::Public)
+                    }
+                    RustScope::Impl { trait_impl: false } => {
+                        (ComponentType::Method, self.rust_visibility(node))
+                    }
+                };
+                self.push(node, name, component_type, visibility, parent);
+            }
+            "struct_item" | "union_item" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = self.rust_visibility(node);
+                    self.push(node, name, ComponentType::Class, visibility, parent);
+                }
+            }
+            "enum_item" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = self.rust_visibility(node);
+                    self.push(node, name, ComponentType::Enum, visibility, parent);
+                }
+            }
+            "trait_item" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = self.rust_visibility(node);
+                let idx = self.push(node, name, ComponentType::Interface, visibility, parent);
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, |this, child| {
+                        this.visit_rust(child, Some(idx), RustScope::Trait);
+                    });
+                }
+            }
+            "impl_item" => {
+                let Some(type_node) = node.child_by_field_name("type") else {
+                    return;
+                };
+                let type_name = self.rust_type_name(type_node);
+                let trait_impl = node.child_by_field_name("trait").is_some();
+                let Some(body) = node.child_by_field_name("body") else {
+                    return;
+                };
+                let first_method = self.syntax.components.len();
+                self.visit_children(body, |this, child| {
+                    this.visit_rust(child, parent, RustScope::Impl { trait_impl });
+                });
+                for idx in first_method..self.syntax.components.len() {
+                    let component = &mut self.syntax.components[idx];
+                    component.qualified_name = format!("{type_name}::{}", component.name);
+                    self.impl_parents.push((idx, type_name.clone()));
+                }
+            }
+            "mod_item" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = self.rust_visibility(node);
+                let body = node.child_by_field_name("body");
+                if body.is_none() && parent.is_none() {
+                    // `mod foo;` pulls in `foo.rs` or `foo/mod.rs`
+                    self.syntax.imports.push(ImportDecl {
+                        path: format!("self::{name}"),
+                        line: Self::node_line(node),
+                        system: false,
+                    });
+                }
+                let idx = self.push(node, name, ComponentType::Module, visibility, parent);
+                if let Some(body) = body {
+                    self.visit_children(body, |this, child| {
+                        this.visit_rust(child, Some(idx), RustScope::Module);
+                    });
+                }
+            }
+            // Only file-level imports: `use super::*` in an inline test module refers to the file
+            "use_declaration" if parent.is_none() => {
+                if let Some(argument) = self.field_text(node, "argument") {
+                    let line = Self::node_line(node);
+                    for path in expand_use_tree(&argument) {
+                        self.syntax.imports.push(ImportDecl {
+                            path,
+                            line,
+                            system: false,
+                        });
+                    }
+                }
+            }
+            "source_file" | "declaration_list" => {
+                self.visit_children(node, |this, child| {
+                    this.visit_rust(child, parent, scope);
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Strips generics and paths from an `impl` target, e.g. `crate::Foo<T>` becomes `Foo`.
+    fn rust_type_name(&self, node: Node) -> String {
+        match node.kind() {
+            "generic_type" => node
+                .child_by_field_name("type")
+                .map_or_else(|| self.text(node), |t| self.rust_type_name(t)),
+            "scoped_type_identifier" => node
+                .child_by_field_name("name")
+                .map_or_else(|| self.text(node), |n| self.text(n)),
+            "reference_type" => node
+                .child_by_field_name("type")
+                .map_or_else(|| self.text(node), |t| self.rust_type_name(t)),
+            _ => self.text(node),
+        }
+    }
+
+    // Python
+
+    fn python_visibility(name: &str) -> Visibility {
+        if name.starts_with("__") && name.ends_with("__") {
+            Visibility::Public
+        } else if name.starts_with('_') {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }
+    }
+
+    fn visit_python(&mut self, node: Node, parent: Option<usize>, in_class: bool) {
+        match node.kind() {
+            "function_definition" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let component_type = if in_class {
+                        ComponentType::Method
+                    } else {
+                        ComponentType::Function
+                    };
+                    let visibility = Self::python_visibility(&name);
+                    self.push(node, name, component_type, visibility, parent);
+                }
+            }
+            "class_definition" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = Self::python_visibility(&name);
+                let idx = self.push(node, name, ComponentType::Class, visibility, parent);
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, |this, child| {
+                        this.visit_python(child, Some(idx), true);
+                    });
+                }
+            }
+            "decorated_definition" => {
+                if let Some(definition) = node.child_by_field_name("definition") {
+                    self.visit_python(definition, parent, in_class);
+                }
+            }
+            "import_statement" => {
+                self.visit_children(node, |this, child| {
+                    if let Some(module) = this.python_import_name(child) {
+                        this.syntax.imports.push(ImportDecl {
+                            path: module,
+                            line: Self::node_line(node),
+                            system: false,
+                        });
+                    }
+                });
+            }
+            "import_from_statement" => self.visit_python_from_import(node),
+            "module" | "block" | "if_statement" | "try_statement" => {
+                self.visit_children(node, |this, child| {
+                    this.visit_python(child, parent, in_class);
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Module named by `a.b` or `a.b as c`
+    fn python_import_name(&self, node: Node) -> Option<String> {
+        match node.kind() {
+            "dotted_name" => Some(self.text(node)),
+            "aliased_import" => self.field_text(node, "name"),
+            _ => None,
+        }
+    }
+
+    /// `from a.b import c, d` is recorded as `a.b.c` and `a.b.d`, since `c` may be a
+    /// submodule; resolution falls back to `a.b` when it is not
+    fn visit_python_from_import(&mut self, node: Node) {
+        let Some(module) = self.field_text(node, "module_name") else {
+            return;
+        };
+        let line = Self::node_line(node);
+        let join = |name: &str| {
+            if module.ends_with('.') {
+                format!("{module}{name}")
+            } else {
+                format!("{module}.{name}")
+            }
+        };
+
+        let mut cursor = node.walk();
+        let names: Vec<String> = node
+            .children_by_field_name("name", &mut cursor)
+            .filter_map(|name| self.python_import_name(name))
+            .map(|name| join(&name))
+            .collect();
+        let paths = if names.is_empty() {
+            vec![module.clone()]
+        } else {
+            names
+        };
+        for path in paths {
+            self.syntax.imports.push(ImportDecl {
+                path,
+                line,
+                system: false,
+            });
+        }
+    }
+
+    // JavaScript / TypeScript
+
+    /// Collects `import`/`export ... from`, `require()` and dynamic `import()` specifiers
+    fn collect_js_imports(&mut self, root: Node) {
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+
+            let specifier = match node.kind() {
+                // TypeScript `import fs = require("fs")` puts the source on the clause
+                "import_statement" | "export_statement" | "import_require_clause" => {
+                    node.child_by_field_name("source")
+                }
+                "call_expression" => {
+                    let is_import = node
+                        .child_by_field_name("function")
+                        .is_some_and(|function| {
+                            function.kind() == "import" || self.text(function) == "require"
+                        });
+                    let first_argument = node.child_by_field_name("arguments").and_then(|args| {
+                        let mut cursor = args.walk();
+                        let first = args.named_children(&mut cursor).next();
+                        first
+                    });
+                    first_argument.filter(|arg| is_import && arg.kind() == "string")
+                }
+                _ => None,
+            };
+            if let Some(specifier) = specifier {
+                self.syntax.imports.push(ImportDecl {
+                    path: self
+                        .text(specifier)
+                        .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+                        .to_string(),
+                    line: Self::node_line(node),
+                    system: false,
+                });
+            }
+        }
+        self.syntax.imports.sort_by_key(|import| import.line);
+    }
+
+    fn js_member_visibility(&self, node: Node) -> Visibility {
+        let mut cursor = node.walk();
+        let modifier = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "accessibility_modifier");
+        if let Some(modifier) = modifier {
+            return match self.text(modifier).as_str() {
+                "private" => Visibility::Private,
+                "protected" => Visibility::Protected,
+                _ => Visibility::Public,
+            };
+        }
+        let name_is_private = node
+            .child_by_field_name("name")
+            .or_else(|| node.child_by_field_name("property"))
+            .is_some_and(|n| n.kind() == "private_property_identifier");
+        if name_is_private {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }
+    }
+
+    fn js_top_level_visibility(exported: bool) -> Visibility {
+        if exported {
+            Visibility::Public
+        } else {
+            Visibility::Private
+        }
+    }
+
+    fn is_function_value(node: Node) -> bool {
+        matches!(
+            node.kind(),
+            "arrow_function" | "function_expression" | "function" | "generator_function"
+        )
+    }
+
+    fn visit_js(&mut self, node: Node, parent: Option<usize>, exported: bool) {
+        match node.kind() {
+            "program" | "statement_block" => {
+                self.visit_children(node, |this, child| this.visit_js(child, parent, false));
+            }
+            "export_statement" => {
+                self.visit_children(node, |this, child| this.visit_js(child, parent, true));
+            }
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = Self::js_top_level_visibility(exported);
+                    self.push(node, name, ComponentType::Function, visibility, parent);
+                }
+            }
+            "lexical_declaration" | "variable_declaration" => {
+                self.visit_children(node, |this, declarator| {
+                    if declarator.kind() != "variable_declarator" {
+                        return;
+                    }
+                    let is_function = declarator
+                        .child_by_field_name("value")
+                        .is_some_and(Self::is_function_value);
+                    let name = declarator
+                        .child_by_field_name("name")
+                        .filter(|n| n.kind() == "identifier")
+                        .map(|n| this.text(n));
+                    if let (true, Some(name)) = (is_function, name) {
+                        let visibility = Self::js_top_level_visibility(exported);
+                        this.push(
+                            declarator,
+                            name,
+                            ComponentType::Function,
+                            visibility,
+                            parent,
+                        );
+                    }
+                });
+            }
+            "class_declaration" | "abstract_class_declaration" | "class" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = Self::js_top_level_visibility(exported);
+                let idx = self.push(node, name, ComponentType::Class, visibility, parent);
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, |this, member| this.visit_js_member(member, idx));
+                }
+            }
+            "interface_declaration" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = Self::js_top_level_visibility(exported);
+                    self.push(node, name, ComponentType::Interface, visibility, parent);
+                }
+            }
+            "enum_declaration" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = Self::js_top_level_visibility(exported);
+                    self.push(node, name, ComponentType::Enum, visibility, parent);
+                }
+            }
+            "internal_module" | "module" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = Self::js_top_level_visibility(exported);
+                let idx = self.push(node, name, ComponentType::Module, visibility, parent);
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, |this, child| this.visit_js(child, Some(idx), false));
+                }
+            }
+            "expression_statement" => {
+                // `namespace Foo {}` parses as an expression statement in some positions
+                self.visit_children(node, |this, child| {
+                    if child.kind() == "internal_module" {
+                        this.visit_js(child, parent, exported);
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_js_member(&mut self, member: Node, class_idx: usize) {
+        match member.kind() {
+            "method_definition" | "method_signature" | "abstract_method_signature" => {
+                if let Some(name) = self.field_text(member, "name") {
+                    let visibility = self.js_member_visibility(member);
+                    self.push(
+                        member,
+                        name,
+                        ComponentType::Method,
+                        visibility,
+                        Some(class_idx),
+                    );
+                }
+            }
+            "field_definition" | "public_field_definition" => {
+                let is_function = member
+                    .child_by_field_name("value")
+                    .is_some_and(Self::is_function_value);
+                let name = self
+                    .field_text(member, "name")
+                    .or_else(|| self.field_text(member, "property"));
+                if let (true, Some(name)) = (is_function, name) {
+                    let visibility = self.js_member_visibility(member);
+                    self.push(
+                        member,
+                        name,
+                        ComponentType::Method,
+                        visibility,
+                        Some(class_idx),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks top-level components exported through `module.exports` or `exports.x` as public
+    fn apply_commonjs_exports(&mut self, source: &str) {
+        let mut exported: HashSet<String> = HashSet::new();
+
+        let member_export = Regex::new(r"(?:module\.)?exports\.(\w+)\s*=\s*(\w+)?")
+            .expect("CommonJS member export regex should be valid");
+        for cap in member_export.captures_iter(source) {
+            exported.insert(cap[1].to_string());
+            if let Some(value) = cap.get(2) {
+                exported.insert(value.as_str().to_string());
+            }
+        }
+
+        let object_export = Regex::new(r"module\.exports\s*=\s*\{([^}]*)\}")
+            .expect("CommonJS object export regex should be valid");
+        for cap in object_export.captures_iter(source) {
+            for entry in cap[1].split(',') {
+                let value = entry.rsplit(':').next().unwrap_or(entry).trim();
+                if !value.is_empty() {
+                    exported.insert(value.to_string());
+                }
+            }
+        }
+
+        let single_export = Regex::new(r"module\.exports\s*=\s*(\w+)\s*;?\s*$")
+            .expect("CommonJS single export regex should be valid");
+        for line in source.lines() {
+            if let Some(cap) = single_export.captures(line) {
+                exported.insert(cap[1].to_string());
+            }
+        }
+
+        for component in &mut self.syntax.components {
+            if component.parent.is_none() && exported.contains(&component.name) {
+                component.visibility = Visibility::Public;
+            }
+        }
+    }
+
+    fn node_line(node: Node) -> usize {
+        node.start_position().row + 1
+    }
+
+    // Go
+
+    fn go_visibility(name: &str) -> Visibility {
+        if name.starts_with(|c: char| c.is_uppercase()) {
+            Visibility::Public
+        } else {
+            Visibility::Private
+        }
+    }
+
+    fn visit_go(&mut self, root: Node) {
+        self.visit_children(root, |this, node| match node.kind() {
+            "package_clause" => {
+                let mut cursor = node.walk();
+                let name = node
+                    .named_children(&mut cursor)
+                    .find(|c| c.kind() == "package_identifier");
+                if let Some(name) = name {
+                    this.syntax.package = Some(PackageDecl {
+                        name: this.text(name),
+                        line: Self::node_line(node),
+                    });
+                }
+            }
+            "import_declaration" => this.visit_go_imports(node),
+            "function_declaration" => {
+                if let Some(name) = this.field_text(node, "name") {
+                    let visibility = Self::go_visibility(&name);
+                    this.push(node, name, ComponentType::Function, visibility, None);
+                }
+            }
+            "method_declaration" => {
+                let Some(name) = this.field_text(node, "name") else {
+                    return;
+                };
+                let visibility = Self::go_visibility(&name);
+                let idx = this.push(node, name, ComponentType::Method, visibility, None);
+                if let Some(receiver) = this.field_text(node, "receiver") {
+                    let type_name = Self::go_receiver_type(&receiver);
+                    let component = &mut this.syntax.components[idx];
+                    component.qualified_name = format!("{type_name}::{}", component.name);
+                    this.impl_parents.push((idx, type_name));
+                }
+            }
+            "type_declaration" => {
+                this.visit_children(node, |this, spec| {
+                    if spec.kind() == "type_spec" {
+                        this.visit_go_type_spec(spec);
+                    }
+                });
+            }
+            _ => {}
+        });
+    }
+
+    fn visit_go_imports(&mut self, node: Node) {
+        self.visit_children(node, |this, child| match child.kind() {
+            "import_spec" => {
+                if let Some(path) = this.field_text(child, "path") {
+                    this.syntax.imports.push(ImportDecl {
+                        path: path.trim_matches(|c| c == '"' || c == '`').to_string(),
+                        line: Self::node_line(child),
+                        system: false,
+                    });
+                }
+            }
+            "import_spec_list" => this.visit_go_imports(child),
+            _ => {}
+        });
+    }
+
+    /// Extracts the type from a receiver such as `(s *Server[T])`
+    fn go_receiver_type(receiver: &str) -> String {
+        let inner = receiver.trim_matches(|c| c == '(' || c == ')').trim();
+        let type_part = inner.split_whitespace().last().unwrap_or(inner);
+        let type_part = type_part.trim_start_matches('*');
+        type_part.split('[').next().unwrap_or(type_part).to_string()
+    }
+
+    fn visit_go_type_spec(&mut self, spec: Node) {
+        let (Some(name), Some(type_node)) = (
+            self.field_text(spec, "name"),
+            spec.child_by_field_name("type"),
+        ) else {
+            return;
+        };
+        let visibility = Self::go_visibility(&name);
+        match type_node.kind() {
+            "struct_type" => {
+                self.push(spec, name, ComponentType::Class, visibility, None);
+            }
+            "interface_type" => {
+                let idx = self.push(spec, name, ComponentType::Interface, visibility, None);
+                self.visit_children(type_node, |this, elem| {
+                    if elem.kind() == "method_elem" {
+                        if let Some(method) = this.field_text(elem, "name") {
+                            let visibility = Self::go_visibility(&method);
+                            this.push(elem, method, ComponentType::Method, visibility, Some(idx));
+                        }
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Java
+
+    /// Visibility from a declaration's modifiers; members without one are package-private
+    fn java_visibility(&self, node: Node, in_interface: bool) -> Visibility {
+        let mut cursor = node.walk();
+        let modifiers = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "modifiers")
+            .map(|m| self.text(m))
+            .unwrap_or_default();
+        let words: Vec<&str> = modifiers.split_whitespace().collect();
+        if words.contains(&"public") {
+            Visibility::Public
+        } else if words.contains(&"private") {
+            Visibility::Private
+        } else if words.contains(&"protected") {
+            Visibility::Protected
+        } else if in_interface {
+            Visibility::Public
+        } else {
+            Visibility::Internal
+        }
+    }
+
+    fn visit_java(&mut self, node: Node, parent: Option<usize>, in_interface: bool) {
+        match node.kind() {
+            "program"
+            | "class_body"
+            | "interface_body"
+            | "enum_body"
+            | "enum_body_declarations"
+            | "annotation_type_body" => {
+                self.visit_children(node, |this, child| {
+                    this.visit_java(child, parent, in_interface);
+                });
+            }
+            "package_declaration" => {
+                let mut cursor = node.walk();
+                let name = node
+                    .named_children(&mut cursor)
+                    .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"));
+                if let Some(name) = name {
+                    self.syntax.package = Some(PackageDecl {
+                        name: self.text(name),
+                        line: Self::node_line(node),
+                    });
+                }
+            }
+            "import_declaration" => {
+                let mut cursor = node.walk();
+                let path = node
+                    .named_children(&mut cursor)
+                    .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"));
+                if let Some(path) = path {
+                    self.syntax.imports.push(ImportDecl {
+                        path: self.text(path),
+                        line: Self::node_line(node),
+                        system: false,
+                    });
+                }
+            }
+            "class_declaration"
+            | "record_declaration"
+            | "interface_declaration"
+            | "annotation_type_declaration"
+            | "enum_declaration" => {
+                let Some(name) = self.field_text(node, "name") else {
+                    return;
+                };
+                let component_type = match node.kind() {
+                    "interface_declaration" | "annotation_type_declaration" => {
+                        ComponentType::Interface
+                    }
+                    "enum_declaration" => ComponentType::Enum,
+                    _ => ComponentType::Class,
+                };
+                let body_in_interface = component_type == ComponentType::Interface;
+                let visibility = self.java_visibility(node, in_interface);
+                let idx = self.push(node, name, component_type, visibility, parent);
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_java(body, Some(idx), body_in_interface);
+                }
+            }
+            "method_declaration"
+            | "constructor_declaration"
+            | "compact_constructor_declaration" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    let visibility = self.java_visibility(node, in_interface);
+                    self.push(node, name, ComponentType::Method, visibility, parent);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // C and C++
+
+    /// Finds the declared name in a (possibly nested) declarator, with its `Scope::` if any
+    fn c_declarator_name(&self, node: Node) -> Option<(Option<String>, String)> {
+        match node.kind() {
+            "identifier" | "field_identifier" | "destructor_name" | "operator_name"
+            | "type_identifier" => Some((None, self.text(node))),
+            "qualified_identifier" => {
+                let scope = self.field_text(node, "scope");
+                let (_, name) = self.c_declarator_name(node.child_by_field_name("name")?)?;
+                let scope = scope.map(|s| {
+                    // Keep only the innermost scope, without template arguments
+                    let last = s.rsplit("::").next().unwrap_or(&s).to_string();
+                    last.split('<').next().unwrap_or(&last).to_string()
+                });
+                Some((scope, name))
+            }
+            "function_declarator"
+            | "pointer_declarator"
+            | "reference_declarator"
+            | "parenthesized_declarator"
+            | "attributed_declarator" => {
+                let inner = node.child_by_field_name("declarator").or_else(|| {
+                    let mut cursor = node.walk();
+                    let found = node.named_children(&mut cursor).find(|c| {
+                        c.kind().ends_with("declarator") || c.kind().ends_with("identifier")
+                    });
+                    found
+                })?;
+                self.c_declarator_name(inner)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a declarator declares a function rather than a variable or function pointer
+    fn c_declares_function(node: Node) -> bool {
+        match node.kind() {
+            "function_declarator" => node
+                .child_by_field_name("declarator")
+                .is_some_and(|d| d.kind() != "parenthesized_declarator"),
+            "pointer_declarator" | "reference_declarator" => {
+                let mut cursor = node.walk();
+                let found = node
+                    .named_children(&mut cursor)
+                    .any(Self::c_declares_function);
+                found
+            }
+            _ => false,
+        }
+    }
+
+    fn c_is_static(&self, node: Node) -> bool {
+        let mut cursor = node.walk();
+        let found = node
+            .children(&mut cursor)
+            .any(|c| c.kind() == "storage_class_specifier" && self.text(c) == "static");
+        found
+    }
+
+    fn visit_c(&mut self, node: Node, parent: Option<usize>) {
+        match node.kind() {
+            "translation_unit"
+            | "declaration_list"
+            | "preproc_ifdef"
+            | "preproc_if"
+            | "preproc_else"
+            | "preproc_elif"
+            | "linkage_specification"
+            | "template_declaration" => {
+                self.visit_children(node, |this, child| this.visit_c(child, parent));
+            }
+            "preproc_include" => {
+                if let Some(path) = node.child_by_field_name("path") {
+                    let system = path.kind() == "system_lib_string";
+                    self.syntax.imports.push(ImportDecl {
+                        path: self
+                            .text(path)
+                            .trim_matches(|c| matches!(c, '"' | '<' | '>'))
+                            .to_string(),
+                        line: Self::node_line(node),
+                        system,
+                    });
+                }
+            }
+            "function_definition" => {
+                let Some((scope, name)) = node
+                    .child_by_field_name("declarator")
+                    .and_then(|d| self.c_declarator_name(d))
+                else {
+                    return;
+                };
+                let visibility = if self.c_is_static(node) {
+                    Visibility::Private
+                } else {
+                    Visibility::Public
+                };
+                if let Some(scope) = scope {
+                    // Out-of-line member definition such as `Parser::parse`
+                    let idx = self.push(node, name, ComponentType::Method, visibility, parent);
+                    let component = &mut self.syntax.components[idx];
+                    component.qualified_name = format!("{scope}::{}", component.name);
+                    self.impl_parents.push((idx, scope));
+                } else {
+                    self.push(node, name, ComponentType::Function, visibility, parent);
+                }
+            }
+            "struct_specifier" | "class_specifier" | "union_specifier" | "enum_specifier" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    self.visit_c_type(node, name, parent);
+                }
+            }
+            "type_definition" => {
+                let Some(type_node) = node.child_by_field_name("type") else {
+                    return;
+                };
+                if type_node.child_by_field_name("body").is_none() {
+                    return;
+                }
+                // `typedef struct { ... } Name;` takes its name from the typedef
+                let name = self.field_text(type_node, "name").or_else(|| {
+                    node.child_by_field_name("declarator")
+                        .and_then(|d| self.c_declarator_name(d))
+                        .map(|(_, name)| name)
+                });
+                if let Some(name) = name {
+                    self.visit_c_type(type_node, name, parent);
+                }
+            }
+            "declaration" => {
+                if let Some(type_node) = node.child_by_field_name("type") {
+                    self.visit_c(type_node, parent);
+                }
+            }
+            "namespace_definition" => {
+                let idx = self.field_text(node, "name").map(|name| {
+                    self.push(
+                        node,
+                        name,
+                        ComponentType::Module,
+                        Visibility::Public,
+                        parent,
+                    )
+                });
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_c(body, idx.or(parent));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_c_type(&mut self, node: Node, name: String, parent: Option<usize>) {
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        if node.kind() == "enum_specifier" {
+            self.push(node, name, ComponentType::Enum, Visibility::Public, parent);
+            return;
+        }
+        let idx = self.push(node, name, ComponentType::Class, Visibility::Public, parent);
+
+        // Members before any access specifier are private in a class, public in a struct
+        let mut access = if node.kind() == "class_specifier" {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        };
+        let mut cursor = body.walk();
+        let members: Vec<Node> = body.named_children(&mut cursor).collect();
+        for member in members {
+            let member = if member.kind() == "template_declaration" {
+                let mut cursor = member.walk();
+                let inner = member.named_children(&mut cursor).find(|c| {
+                    matches!(
+                        c.kind(),
+                        "function_definition" | "field_declaration" | "declaration"
+                    )
+                });
+                match inner {
+                    Some(inner) => inner,
+                    None => continue,
+                }
+            } else {
+                member
+            };
+            match member.kind() {
+                "access_specifier" => {
+                    let text = self.text(member);
+                    access = if text.contains("private") {
+                        Visibility::Private
+                    } else if text.contains("protected") {
+                        Visibility::Protected
+                    } else {
+                        Visibility::Public
+                    };
+                }
+                "function_definition" | "field_declaration" | "declaration" => {
+                    let Some(declarator) = member.child_by_field_name("declarator") else {
+                        continue;
+                    };
+                    let is_method = member.kind() == "function_definition"
+                        || Self::c_declares_function(declarator);
+                    if !is_method {
+                        continue;
+                    }
+                    if let Some((_, method)) = self.c_declarator_name(declarator) {
+                        self.push(
+                            member,
+                            method,
+                            ComponentType::Method,
+                            access.clone(),
+                            Some(idx),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Line-based Kotlin scanner, used because no Kotlin grammar is bundled
+mod kotlin {
+    use super::{ExtractedComponent, FileSyntax, ImportDecl, PackageDecl};
+    use crate::complexity::{self, ComplexityMetrics};
+    use crate::repo_mapper::{ComponentType, Visibility};
+    use regex::Regex;
+
+    /// A declaration whose body is still open
+    struct OpenBody {
+        component: usize,
+        depth: usize,
+    }
+
+    pub fn parse(source: &str) -> FileSyntax {
+        let package_regex =
+            Regex::new(r"^\s*package\s+([\w.]+)").expect("Kotlin package regex should be valid");
+        let import_regex =
+            Regex::new(r"^\s*import\s+([\w.]*\w)").expect("Kotlin import regex should be valid");
+        let declaration_regex = Regex::new(
+            r"^\s*((?:(?:public|private|protected|internal|open|abstract|sealed|data|enum|annotation|inner|override|suspend|inline|operator|infix|final|const|tailrec|external|value)\s+)*)(class|interface|object|fun)\s+(?:<[^>]*>\s*)?(?:([\w.]+(?:<[^>]*>)?\??)\.)?(\w+)",
+        )
+        .expect("Kotlin declaration regex should be valid");
+
+        // Keywords and braces in strings and comments are not code
+        let masked = mask(source);
+        let mut syntax = FileSyntax::default();
+        let mut open: Vec<OpenBody> = Vec::new();
+        // A declaration whose signature spans lines, waiting for its `{` or its end
+        let mut pending: Option<(usize, usize)> = None;
+        let mut depth = 0usize;
+        let mut paren_depth = 0usize;
+        // Extension functions, with the type they extend
+        let mut receivers: Vec<(usize, String)> = Vec::new();
+
+        for (idx, code) in masked.lines().enumerate() {
+            let line_number = idx + 1;
+
+            if let Some(cap) = package_regex.captures(code) {
+                syntax.package = Some(PackageDecl {
+                    name: cap[1].to_string(),
+                    line: line_number,
+                });
+            } else if let Some(cap) = import_regex.captures(code) {
+                syntax.imports.push(ImportDecl {
+                    path: cap[1].to_string(),
+                    line: line_number,
+                    system: false,
+                });
+            }
+
+            let depth_before = depth;
+            if pending.is_none() {
+                if let Some(cap) = declaration_regex.captures(code) {
+                    let enclosing = open.last().map(|body| body.component);
+                    let in_function = enclosing.is_some_and(|idx| {
+                        matches!(
+                            syntax.components[idx].component_type,
+                            ComponentType::Function | ComponentType::Method
+                        )
+                    });
+                    // Local functions and classes are not part of the file's structure
+                    if !in_function {
+                        let (component, receiver) =
+                            declaration(&cap, enclosing, line_number, &syntax);
+                        if let Some(receiver) = receiver {
+                            receivers.push((syntax.components.len(), receiver));
+                        }
+                        syntax.components.push(component);
+                        pending = Some((syntax.components.len() - 1, depth_before));
+                    }
+                }
+            }
+
+            for c in code.chars() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    '(' => paren_depth += 1,
+                    ')' => paren_depth = paren_depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+
+            if let Some((component, opened_at)) = pending {
+                if depth > opened_at {
+                    open.push(OpenBody {
+                        component,
+                        depth: opened_at,
+                    });
+                    pending = None;
+                } else if paren_depth == 0 {
+                    syntax.components[component].end_line = line_number;
+                    pending = None;
+                }
+            }
+
+            while open.last().is_some_and(|body| depth <= body.depth) {
+                if let Some(body) = open.pop() {
+                    syntax.components[body.component].end_line = line_number;
+                }
+            }
+        }
+
+        let last_line = masked.lines().count();
+        for body in open {
+            syntax.components[body.component].end_line = last_line;
+        }
+        for (idx, receiver) in receivers {
+            let parent = syntax.components.iter().position(|c| {
+                c.name == receiver
+                    && matches!(
+                        c.component_type,
+                        ComponentType::Class | ComponentType::Enum | ComponentType::Interface
+                    )
+            });
+            syntax.components[idx].parent = parent;
+        }
+        measure(&masked, &mut syntax);
+        syntax
+    }
+
+    /// `source` with comments blanked out and the contents of string and character literals
+    /// replaced by `_`, so that lines inside strings remain code. Every line is kept in place.
+    /// Kotlin block comments nest, and string templates may hold braces.
+    fn mask(source: &str) -> String {
+        let chars: Vec<char> = source.chars().collect();
+        let mut masked = String::with_capacity(source.len());
+        let blank = |c: char| if c == '\n' { '\n' } else { ' ' };
+        let fill = |c: char| if c == '\n' { '\n' } else { '_' };
+        let mut i = 0;
+        while i < chars.len() {
+            let rest = &chars[i..];
+            let quote = if rest.starts_with(&['"', '"', '"']) {
+                3
+            } else {
+                usize::from(matches!(rest[0], '"' | '\''))
+            };
+            if quote > 0 {
+                let (length, closing) = string(rest, quote);
+                masked.extend(&rest[..quote]);
+                masked.extend(rest[quote..length - closing].iter().map(|&c| fill(c)));
+                masked.extend(&rest[length - closing..length]);
+                i += length;
+                continue;
+            }
+            let comment = if rest.starts_with(&['/', '/']) {
+                rest.iter().take_while(|&&c| c != '\n').count()
+            } else if rest.starts_with(&['/', '*']) {
+                block_comment(rest)
+            } else {
+                masked.push(rest[0]);
+                i += 1;
+                continue;
+            };
+            masked.extend(rest[..comment].iter().map(|&c| blank(c)));
+            i += comment;
+        }
+        masked
+    }
+
+    /// Length of the block comment opening `chars`, including the comments nested in it
+    fn block_comment(chars: &[char]) -> usize {
+        let mut depth = 0usize;
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i..].starts_with(&['/', '*']) {
+                depth += 1;
+                i += 2;
+            } else if chars[i..].starts_with(&['*', '/']) {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        i.min(chars.len())
+    }
+
+    /// Length of the literal opened by the first `quote` characters of `chars`, and of the
+    /// quote closing it, if any. Only raw `"""` strings span lines, and `${...}` templates
+    /// are skipped as a whole.
+    fn string(chars: &[char], quote: usize) -> (usize, usize) {
+        let raw = quote == 3;
+        let mut i = quote;
+        while i < chars.len() {
+            if chars[i..].starts_with(&chars[..quote]) {
+                return (i + quote, quote);
+            }
+            match chars[i] {
+                '\n' if !raw => return (i, 0),
+                '\\' if !raw => i += 2,
+                '$' if chars.get(i + 1) == Some(&'{') => {
+                    let mut depth = 0usize;
+                    while i < chars.len() {
+                        match chars[i] {
+                            '{' => depth += 1,
+                            '}' => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        (chars.len(), 0)
+    }
+
+    /// Estimate complexity from the lines each component spans
+    fn measure(source: &str, syntax: &mut FileSyntax) {
+        let lines: Vec<&str> = source.lines().collect();
+        for component in &mut syntax.components {
+            let function = matches!(
+                component.component_type,
+                ComponentType::Function | ComponentType::Method
+            );
+            let span = &lines[component.start_line - 1..component.end_line.min(lines.len())];
+            component.metrics = complexity::estimate(span, function);
+        }
+        syntax.sloc = complexity::estimate(&lines, false).sloc;
+    }
+
+    fn declaration(
+        cap: &regex::Captures,
+        enclosing: Option<usize>,
+        line_number: usize,
+        syntax: &FileSyntax,
+    ) -> (ExtractedComponent, Option<String>) {
+        let modifiers: Vec<&str> = cap[1].split_whitespace().collect();
+        let keyword = &cap[2];
+        let receiver = cap.get(3).map(|m| {
+            let receiver = m.as_str();
+            receiver
+                .split('<')
+                .next()
+                .unwrap_or(receiver)
+                .trim_end_matches('?')
+                .to_string()
+        });
+        let name = cap[4].to_string();
+
+        let visibility = if modifiers.contains(&"private") {
+            Visibility::Private
+        } else if modifiers.contains(&"protected") {
+            Visibility::Protected
+        } else if modifiers.contains(&"internal") {
+            Visibility::Internal
+        } else {
+            Visibility::Public
+        };
+        let component_type = match keyword {
+            "interface" => ComponentType::Interface,
+            "class" if modifiers.contains(&"enum") => ComponentType::Enum,
+            "class" | "object" => ComponentType::Class,
+            _ if enclosing.is_some() || receiver.is_some() => ComponentType::Method,
+            _ => ComponentType::Function,
+        };
+        let qualified_name = match (enclosing, &receiver) {
+            (Some(parent), _) => format!("{}::{name}", syntax.components[parent].qualified_name),
+            (None, Some(receiver)) => format!("{receiver}::{name}"),
+            (None, None) => name.clone(),
+        };
+
+        let component = ExtractedComponent {
+            name,
+            qualified_name,
+            component_type,
+            visibility,
+            start_line: line_number,
+            end_line: line_number,
+            parent: enclosing,
+            metrics: ComplexityMetrics::default(),
+        };
+        (component, receiver.filter(|_| enclosing.is_none()))
+    }
+}
@@ -0,0 +1,429 @@
+use crate::repo_mapper::{CodeComponent, ComponentType, RepositoryMapper, Visibility};
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// How a test was linked to a source component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Correlation {
+    /// The test calls the component
+    Call,
+    /// The test is named after the component, e.g. `test_parse_header` for `parse_header`
+    Naming,
+    /// The test file imports the component's file and refers to the component by name
+    Import,
+}
+
+/// A source component exercised by a test
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exercised {
+    pub component: String,
+    pub file: String,
+    pub via: Correlation,
+}
+
+/// A test function and the source components it exercises
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub id: String,
+    pub name: String,
+    pub exercises: Vec<Exercised>,
+}
+
+/// A file containing tests: a dedicated test file or a source file with an inline test module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestFile {
+    pub path: String,
+    /// Source files the tests are named after, e.g. `src/parser.rs` for `tests/parser_tests.rs`
+    pub sources: Vec<String>,
+    pub tests: Vec<TestCase>,
+    /// Components the file refers to through its imports, outside any single test
+    pub exercises: Vec<Exercised>,
+}
+
+/// Mapping of tests to the source components they exercise
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestCorrelation {
+    pub test_files: Vec<TestFile>,
+    /// Public source components no test exercises, directly or through their members
+    pub untested_public: Vec<String>,
+}
+
+impl TestCorrelation {
+    /// Test files that exercise components of `source`, or are named after it
+    pub fn tests_for_file(&self, source: &str) -> Vec<&str> {
+        self.test_files
+            .iter()
+            .filter(|test_file| test_file.path != source)
+            .filter(|test_file| {
+                test_file.sources.iter().any(|path| path == source)
+                    || test_file
+                        .exercises
+                        .iter()
+                        .chain(test_file.tests.iter().flat_map(|test| &test.exercises))
+                        .any(|exercised| exercised.file == source)
+            })
+            .map(|test_file| test_file.path.as_str())
+            .collect()
+    }
+
+    /// IDs of every component exercised by a test
+    pub fn tested_components(&self) -> BTreeSet<&str> {
+        self.test_files
+            .iter()
+            .flat_map(|test_file| {
+                test_file
+                    .exercises
+                    .iter()
+                    .chain(test_file.tests.iter().flat_map(|test| &test.exercises))
+            })
+            .map(|exercised| exercised.component.as_str())
+            .collect()
+    }
+}
+
+/// Whether one of the files is a test named after the other.
+///
+/// Paths should be relative to the project root, so that a root inside a directory named
+/// `test` does not make every file a test.
+pub(crate) fn is_test_pair(a: &Path, b: &Path) -> bool {
+    match (test_subject(a), test_subject(b)) {
+        (Some(subject), None) => source_name(b).is_some_and(|name| name == subject),
+        (None, Some(subject)) => source_name(a).is_some_and(|name| name == subject),
+        _ => false,
+    }
+}
+
+/// The name of the source a test file covers, e.g. `parser` for `parser_test.go`,
+/// `test_parser.py`, `parser.spec.ts`, `ParserTest.java` or `tests/parser.rs`
+pub(crate) fn test_subject(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let parts: Vec<&str> = file_name.split('.').collect();
+    if parts.len() >= 3 && matches!(parts[parts.len() - 2], "test" | "spec") {
+        return Some(parts[..parts.len() - 2].join(".").to_lowercase());
+    }
+
+    let stem = path.file_stem()?.to_str()?;
+    let stripped = ["_test", "_tests", "_spec", "Test", "Tests", "Spec"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .or_else(|| stem.strip_prefix("test_"))
+        .filter(|name| !name.is_empty());
+    if let Some(name) = stripped {
+        return Some(name.to_lowercase());
+    }
+
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|component| {
+            matches!(
+                component.as_os_str().to_str(),
+                Some("tests" | "test" | "__tests__" | "spec")
+            )
+        })
+    });
+    in_test_dir.then(|| stem.to_lowercase())
+}
+
+/// The name a source file is tested under; module files such as `mod.rs` use their directory
+fn source_name(path: &Path) -> Option<String> {
+    let stem = path.file_name()?.to_str()?.split('.').next()?;
+    if matches!(stem, "mod" | "__init__" | "index" | "lib") {
+        let dir = path.parent()?.file_name()?.to_str()?;
+        return Some(dir.to_lowercase());
+    }
+    Some(stem.to_lowercase())
+}
+
+/// Split an identifier into lowercase words at underscores and case changes
+pub(crate) fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Names a test may be named after, longest first: `test_parse_header_rejects_empty`
+/// gives `parseheaderrejectsempty`, `parseheaderrejects`, `parseheader` and `parse`
+fn tested_names(test_name: &str) -> Vec<String> {
+    let mut words = words(test_name);
+    if words.first().is_some_and(|word| word == "test") {
+        words.remove(0);
+    } else if words.last().is_some_and(|word| word == "test") {
+        words.pop();
+    } else {
+        return Vec::new();
+    }
+    (1..=words.len())
+        .rev()
+        .map(|len| words[..len].concat())
+        .filter(|name| name.len() >= 3)
+        .collect()
+}
+
+fn is_testable(component: &CodeComponent) -> bool {
+    !matches!(component.component_type, ComponentType::Module)
+}
+
+/// Whether a component sits inside an inline test module such as Rust's `mod tests`
+pub(crate) fn in_test_module(mapper: &RepositoryMapper, component: &CodeComponent) -> bool {
+    let mut parent = component.parent.as_ref();
+    while let Some(id) = parent {
+        let Some(ancestor) = mapper.graph.components.get(id) else {
+            return false;
+        };
+        if ancestor.component_type == ComponentType::Module
+            && matches!(ancestor.name.as_str(), "tests" | "test")
+        {
+            return true;
+        }
+        parent = ancestor.parent.as_ref();
+    }
+    false
+}
+
+/// Map tests to the source components they exercise
+pub(crate) fn correlate_tests(mapper: &RepositoryMapper) -> TestCorrelation {
+    let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("Invalid identifier regex");
+
+    let mut files: Vec<&PathBuf> = mapper.files.values().collect();
+    files.sort();
+    let is_test_file = |path: &Path| test_subject(mapper.relative_to_root(path)).is_some();
+
+    let mut components_by_file: BTreeMap<String, Vec<&CodeComponent>> = BTreeMap::new();
+    for component in mapper.graph.components.values() {
+        components_by_file
+            .entry(component.file_path.display().to_string())
+            .or_default()
+            .push(component);
+    }
+    let is_source = |component: &CodeComponent| {
+        is_testable(component)
+            && !is_test_file(&component.file_path)
+            && !in_test_module(mapper, component)
+    };
+
+    let mut test_files = Vec::new();
+    for file in files {
+        let path = file.display().to_string();
+        let dedicated = is_test_file(file);
+        let file_components = components_by_file
+            .get(&path)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let test_functions: Vec<&CodeComponent> = file_components
+            .iter()
+            .copied()
+            .filter(|component| {
+                matches!(
+                    component.component_type,
+                    ComponentType::Function | ComponentType::Method
+                ) && (dedicated || in_test_module(mapper, component))
+            })
+            .collect();
+        if !dedicated && test_functions.is_empty() {
+            continue;
+        }
+
+        let sources = if dedicated {
+            paired_sources(mapper, file)
+        } else {
+            vec![path.clone()]
+        };
+        let imported: Vec<String> = mapper
+            .file_graph
+            .files
+            .get(&path)
+            .map(|node| {
+                node.imports
+                    .iter()
+                    .filter(|import| !is_test_file(Path::new(import)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Components a test may be named after: in the paired sources, imports or this file
+        let named_candidates: Vec<&CodeComponent> = sources
+            .iter()
+            .chain(&imported)
+            .chain(std::iter::once(&path))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|file| components_by_file.get(file))
+            .flatten()
+            .copied()
+            .filter(|component| is_source(component))
+            .collect();
+
+        let tests = test_functions
+            .iter()
+            .map(|test| TestCase {
+                id: test.id.clone(),
+                name: test.name.clone(),
+                exercises: test_exercises(mapper, test, &named_candidates, is_source),
+            })
+            .collect();
+
+        let exercises = if dedicated {
+            import_exercises(&identifier, file, &imported, &components_by_file, is_source)
+        } else {
+            Vec::new()
+        };
+
+        test_files.push(TestFile {
+            path,
+            sources,
+            tests,
+            exercises,
+        });
+    }
+
+    let mut correlation = TestCorrelation {
+        test_files,
+        untested_public: Vec::new(),
+    };
+    correlation.untested_public = untested_public(mapper, &correlation, is_source);
+    correlation
+}
+
+/// Public source components not exercised by any test, directly or through their members
+fn untested_public(
+    mapper: &RepositoryMapper,
+    correlation: &TestCorrelation,
+    is_source: impl Fn(&CodeComponent) -> bool,
+) -> Vec<String> {
+    let tested = correlation.tested_components();
+    let mut untested: Vec<String> = mapper
+        .graph
+        .components
+        .values()
+        .filter(|component| component.visibility == Visibility::Public && is_source(component))
+        .filter(|component| !is_exercised(mapper, component, &tested))
+        .map(|component| component.id.clone())
+        .collect();
+    untested.sort();
+    untested
+}
+
+/// Source files a test file is named after
+fn paired_sources(mapper: &RepositoryMapper, test_file: &Path) -> Vec<String> {
+    let relative = mapper.relative_to_root(test_file);
+    let sources: BTreeSet<String> = mapper
+        .files
+        .values()
+        .filter(|other| is_test_pair(relative, mapper.relative_to_root(other)))
+        .map(|other| other.display().to_string())
+        .collect();
+    sources.into_iter().collect()
+}
+
+/// Components a test function calls or is named after
+fn test_exercises(
+    mapper: &RepositoryMapper,
+    test: &CodeComponent,
+    named_candidates: &[&CodeComponent],
+    is_source: impl Fn(&CodeComponent) -> bool,
+) -> Vec<Exercised> {
+    let mut exercises = Vec::new();
+    let mut seen = HashSet::new();
+    for dependency in &test.dependencies {
+        if let Some(component) = mapper.graph.components.get(dependency) {
+            if is_source(component) && seen.insert(component.id.clone()) {
+                exercises.push(Exercised {
+                    component: component.id.clone(),
+                    file: component.file_path.display().to_string(),
+                    via: Correlation::Call,
+                });
+            }
+        }
+    }
+
+    for name in tested_names(&test.name) {
+        let matches: Vec<&&CodeComponent> = named_candidates
+            .iter()
+            .filter(|component| words(&component.name).concat() == name)
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        for component in matches {
+            if seen.insert(component.id.clone()) {
+                exercises.push(Exercised {
+                    component: component.id.clone(),
+                    file: component.file_path.display().to_string(),
+                    via: Correlation::Naming,
+                });
+            }
+        }
+        break;
+    }
+    exercises
+}
+
+/// Components of imported files that a test file refers to by name
+fn import_exercises(
+    identifier: &Regex,
+    file: &Path,
+    imported: &[String],
+    components_by_file: &BTreeMap<String, Vec<&CodeComponent>>,
+    is_source: impl Fn(&CodeComponent) -> bool,
+) -> Vec<Exercised> {
+    let Ok(content) = std::fs::read_to_string(file) else {
+        debug!("Skipping unreadable test file {}", file.display());
+        return Vec::new();
+    };
+    let tokens: HashSet<&str> = identifier
+        .find_iter(&content)
+        .map(|token| token.as_str())
+        .collect();
+
+    let mut exercises: Vec<Exercised> = imported
+        .iter()
+        .filter_map(|path| components_by_file.get(path))
+        .flatten()
+        .filter(|component| is_source(component) && tokens.contains(component.name.as_str()))
+        .map(|component| Exercised {
+            component: component.id.clone(),
+            file: component.file_path.display().to_string(),
+            via: Correlation::Import,
+        })
+        .collect();
+    exercises.sort_by(|a, b| a.component.cmp(&b.component));
+    exercises
+}
+
+/// Whether a component or anything nested inside it is exercised
+fn is_exercised(
+    mapper: &RepositoryMapper,
+    component: &CodeComponent,
+    tested: &BTreeSet<&str>,
+) -> bool {
+    tested.contains(component.id.as_str())
+        || component.children.iter().any(|child| {
+            mapper
+                .graph
+                .components
+                .get(child)
+                .is_some_and(|child| is_exercised(mapper, child, tested))
+        })
+}
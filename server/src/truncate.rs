@@ -0,0 +1,363 @@
+use crate::doc_coverage::python_signature_end;
+use crate::repo_mapper::ComponentType;
+use crate::signatures::impl_header;
+use crate::syntax::{self, SourceLanguage};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+use std::path::Path;
+use structopt::StructOpt;
+
+/// Lines kept at the start and end of a truncated file unless set otherwise
+pub const DEFAULT_KEEP_LINES: usize = 20;
+
+/// Per-file size caps. A file over any of them is elided until it fits: first function
+/// bodies, then everything but imports, declarations and its first and last lines, then
+/// the end of what is left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, StructOpt)]
+pub struct FileLimits {
+    #[structopt(long, help = "Elide files longer than this many lines")]
+    pub max_file_lines: Option<usize>,
+
+    #[structopt(long, help = "Elide files larger than this many bytes")]
+    pub max_file_bytes: Option<usize>,
+
+    /// Tokens are estimated as one per four characters
+    #[structopt(
+        long,
+        help = "Elide files over this many tokens, estimated as one per four characters"
+    )]
+    pub max_file_tokens: Option<usize>,
+
+    /// Lines always kept at the start and end of an elided file
+    #[structopt(
+        long,
+        help = "Lines kept at the start and end of elided files [default: 20]"
+    )]
+    pub keep_lines: Option<usize>,
+}
+
+impl FileLimits {
+    /// The caps `text` exceeds, e.g. `500 lines`
+    fn exceeded(&self, text: &str) -> Vec<String> {
+        let measures = [
+            (self.max_file_lines, text.lines().count(), "lines"),
+            (self.max_file_bytes, text.len(), "bytes"),
+            (self.max_file_tokens, estimate_tokens(text), "tokens"),
+        ];
+        measures
+            .into_iter()
+            .filter_map(|(cap, size, unit)| cap.filter(|cap| size > *cap).map(|_| (cap, unit)))
+            .map(|(cap, unit)| format!("{} {unit}", cap.unwrap_or_default()))
+            .collect()
+    }
+
+    fn fits(&self, text: &str) -> bool {
+        self.max_file_lines
+            .is_none_or(|cap| text.lines().count() <= cap)
+            && self.max_file_bytes.is_none_or(|cap| text.len() <= cap)
+            && self
+                .max_file_tokens
+                .is_none_or(|cap| estimate_tokens(text) <= cap)
+    }
+}
+
+/// Rough token count of `text`, at one token per four characters
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// What was cut from a file to fit its limits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+    pub original_lines: usize,
+    pub elided_lines: usize,
+    /// Runs of elided lines, each replaced by one marker
+    pub elided_sections: usize,
+    /// Function bodies elided in whole or in part
+    pub elided_bodies: usize,
+    /// The caps the file was over, e.g. `500 lines`
+    pub exceeded: Vec<String>,
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} lines elided in {} section(s), {} of them function bodies, to fit {}",
+            self.elided_lines,
+            self.original_lines,
+            self.elided_sections,
+            self.elided_bodies,
+            self.exceeded.join(" and ")
+        )
+    }
+}
+
+/// Line ranges of a file that matter for elision, 0-based and inclusive
+#[derive(Debug, Default)]
+struct Structure {
+    /// Function and method bodies, without their signature and closing line
+    bodies: Vec<(usize, usize)>,
+    /// Imports, package declarations, and the opening and closing lines of declarations
+    outline: Vec<bool>,
+}
+
+impl Structure {
+    fn of(path: &Path, content: &str, lines: &[&str]) -> Self {
+        let mut structure = Self {
+            bodies: Vec::new(),
+            outline: vec![false; lines.len()],
+        };
+        let Some(language) = SourceLanguage::for_path(path) else {
+            return structure;
+        };
+        let Some(parsed) = syntax::parse_source(language, content) else {
+            return structure;
+        };
+
+        let mut mark = |from: usize, to: usize| {
+            for idx in from..=to.min(lines.len().saturating_sub(1)) {
+                structure.outline[idx] = true;
+            }
+        };
+        let declarations = parsed.package.iter().map(|package| package.line);
+        for line in declarations.chain(parsed.imports.iter().map(|import| import.line)) {
+            let start = line.saturating_sub(1);
+            mark(start, statement_end(lines, start));
+        }
+        for component in &parsed.components {
+            let start = component.start_line.saturating_sub(1);
+            let end = component.end_line.saturating_sub(1).min(lines.len() - 1);
+            let opening = if language == SourceLanguage::Python {
+                python_signature_end(lines, component.start_line, component.end_line)
+            } else {
+                (start..=end).find(|&idx| lines[idx].contains('{'))
+            };
+            let Some(opening) = opening else {
+                mark(start, start);
+                continue;
+            };
+            mark(start, opening);
+            // Python bodies run to the last line; others stop before the closing brace
+            let body_end = if language == SourceLanguage::Python {
+                end
+            } else {
+                mark(end, end);
+                end.saturating_sub(1)
+            };
+            if language == SourceLanguage::Rust && component.component_type == ComponentType::Method
+            {
+                // Keep the `impl` block a method is in, which is not a component itself
+                if let Some(header) = impl_header(lines, component.start_line) {
+                    if let Some(opening) = (header..start).find(|&idx| lines[idx].contains('{')) {
+                        mark(header, opening);
+                    }
+                    let closing = statement_end(lines, header);
+                    mark(closing, closing);
+                }
+            }
+            let is_function = matches!(
+                component.component_type,
+                ComponentType::Function | ComponentType::Method
+            );
+            if is_function && opening < body_end {
+                structure.bodies.push((opening + 1, body_end));
+            }
+        }
+        structure
+    }
+}
+
+/// Index of the line a statement starting at `start` ends on, past any open brackets
+fn statement_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0i32;
+    for (idx, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 {
+            return idx;
+        }
+    }
+    start
+}
+
+/// How an elision marker is commented out in a file
+fn comment_style(path: &Path) -> (&'static str, &'static str) {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    match extension {
+        "py" | "pyi" | "rb" | "sh" | "bash" | "zsh" | "yaml" | "yml" | "toml" | "pl" | "r"
+        | "ini" | "cfg" | "conf" | "mk" | "cmake" => ("# ", ""),
+        "html" | "htm" | "xml" | "svg" | "md" | "vue" => ("<!-- ", " -->"),
+        "sql" | "lua" | "hs" => ("-- ", ""),
+        _ => ("// ", ""),
+    }
+}
+
+/// Elides `content` down to `limits`, keeping its structure where possible. Returns the
+/// content as is, with no truncation, when it already fits.
+pub fn truncate(path: &Path, content: &str, limits: &FileLimits) -> (String, Option<Truncation>) {
+    let exceeded = limits.exceeded(content);
+    if exceeded.is_empty() {
+        return (content.to_string(), None);
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let total = lines.len();
+    let keep_lines = limits.keep_lines.unwrap_or(DEFAULT_KEEP_LINES);
+    let structure = Structure::of(path, content, &lines);
+    let renderer = Renderer {
+        lines: &lines,
+        comment: comment_style(path),
+        bodies: &structure.bodies,
+    };
+    let protected = |idx: usize| idx < keep_lines || idx + keep_lines >= total;
+
+    // Function bodies first
+    let mut bodies_elided = vec![true; total];
+    for &(start, end) in &structure.bodies {
+        for (idx, kept) in bodies_elided
+            .iter_mut()
+            .enumerate()
+            .take(end + 1)
+            .skip(start)
+        {
+            *kept = protected(idx);
+        }
+    }
+    // Then everything but the outline, from the end of the file back
+    let outline: Vec<bool> = (0..total)
+        .map(|idx| protected(idx) || structure.outline[idx])
+        .collect();
+    let mut attempt = renderer.render(&bodies_elided);
+    if !limits.fits(&attempt.0) {
+        let cutoff = last_fitting(limits, &renderer, total, |cutoff, idx| {
+            if idx < cutoff {
+                bodies_elided[idx]
+            } else {
+                outline[idx]
+            }
+        });
+        attempt = cutoff.unwrap_or_else(|| renderer.render(&outline));
+    }
+    // Then the outline too, keeping as much of its start as fits with the last lines, or
+    // without them
+    for tail in [keep_lines.min(total), 0] {
+        if limits.fits(&attempt.0) {
+            break;
+        }
+        let cutoff = last_fitting(limits, &renderer, total - tail, |cutoff, idx| {
+            outline[idx] && (idx < cutoff || idx >= total - tail)
+        });
+        if let Some(cutoff) = cutoff {
+            attempt = cutoff;
+        }
+    }
+    if !limits.fits(&attempt.0) {
+        attempt = renderer.render(&vec![false; total]);
+    }
+    let (text, kept, sections, bodies) = attempt;
+    let truncation = Truncation {
+        original_lines: total,
+        elided_lines: total - kept,
+        elided_sections: sections,
+        elided_bodies: bodies,
+        exceeded,
+    };
+    (text, Some(truncation))
+}
+
+/// Renders the largest cutoff in `0..=max` whose lines, kept where `keep(cutoff, idx)`,
+/// fit `limits`, or `None` if not even a cutoff of 0 does
+fn last_fitting<F>(
+    limits: &FileLimits,
+    renderer: &Renderer,
+    max: usize,
+    keep: F,
+) -> Option<(String, usize, usize, usize)>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let render = |cutoff: usize| {
+        let mask: Vec<bool> = (0..renderer.lines.len())
+            .map(|idx| keep(cutoff, idx))
+            .collect();
+        renderer.render(&mask)
+    };
+    let (mut low, mut high) = (0, max);
+    if !limits.fits(&render(low).0) {
+        return None;
+    }
+    while low < high {
+        let middle = (low + high).div_ceil(2);
+        if limits.fits(&render(middle).0) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Some(render(low))
+}
+
+struct Renderer<'a> {
+    lines: &'a [&'a str],
+    comment: (&'static str, &'static str),
+    bodies: &'a [(usize, usize)],
+}
+
+impl Renderer<'_> {
+    /// The kept lines, with each run of elided lines replaced by a marker. A single line
+    /// is kept rather than replaced, unless it is the whole file. Also returns how many
+    /// lines were kept, how many runs were elided and how many of them were in function
+    /// bodies.
+    fn render(&self, keep: &[bool]) -> (String, usize, usize, usize) {
+        let kept_or_edge = |idx: Option<usize>| idx.and_then(|idx| keep.get(idx)) != Some(&false);
+        let keep: Vec<bool> = (0..keep.len())
+            .map(|idx| {
+                keep[idx]
+                    || (keep.len() > 1
+                        && kept_or_edge(idx.checked_sub(1))
+                        && kept_or_edge(Some(idx + 1)))
+            })
+            .collect();
+        let mut text = String::new();
+        let (mut kept, mut sections) = (0, 0);
+        let mut idx = 0;
+        while idx < self.lines.len() {
+            if keep[idx] {
+                text.push_str(self.lines[idx]);
+                text.push('\n');
+                kept += 1;
+                idx += 1;
+                continue;
+            }
+            let start = idx;
+            while idx < self.lines.len() && !keep[idx] {
+                idx += 1;
+            }
+            let indentation: String = self.lines[start..idx]
+                .iter()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+                .unwrap_or_default();
+            let (open, close) = self.comment;
+            let count = idx - start;
+            let noun = if count == 1 { "line" } else { "lines" };
+            writeln!(
+                text,
+                "{indentation}{open}... {count} {noun} elided ...{close}"
+            )
+            .expect("Writing to a String should not fail");
+            sections += 1;
+        }
+        let bodies = self
+            .bodies
+            .iter()
+            .filter(|&&(start, end)| keep[start..=end].iter().any(|kept| !kept))
+            .count();
+        (text, kept, sections, bodies)
+    }
+}
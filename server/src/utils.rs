@@ -22,20 +22,16 @@ pub fn hash_api_key(key: &str) -> String {
 
 /// Validates the API key provided in the request against the stored API keys in the configuration.
 pub fn validate_api_key(req: &HttpRequest, config: &Config) -> bool {
-    if config.api_keys.is_empty() {
-        return false;
-    }
+    authenticated_key_name(req, config).is_some()
+}
 
-    if let Some(api_key) = req.headers().get("X-API-Key") {
-        let api_key = api_key
-            .to_str()
-            .expect("API key header should be valid UTF-8");
-        let hashed_key = hash_api_key(api_key);
-        config
-            .api_keys
-            .values()
-            .any(|stored_key| constant_time_eq(stored_key.as_bytes(), hashed_key.as_bytes()))
-    } else {
-        false
-    }
+/// Returns the configured name of the API key presented in the request, if it is valid.
+pub fn authenticated_key_name(req: &HttpRequest, config: &Config) -> Option<String> {
+    let api_key = req.headers().get("X-API-Key")?.to_str().ok()?;
+    let hashed_key = hash_api_key(api_key);
+    config
+        .api_keys
+        .iter()
+        .find(|(_, stored_key)| constant_time_eq(stored_key.as_bytes(), hashed_key.as_bytes()))
+        .map(|(name, _)| name.clone())
 }
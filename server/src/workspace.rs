@@ -0,0 +1,441 @@
+use crate::config::{ProjectConfig, ProjectRoot};
+use crate::contexter::{gather_relevant_files, ProjectFile};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkspaceKind {
+    Cargo,
+    Npm,
+    Pnpm,
+    Go,
+}
+
+/// A package or module inside a project root that requests can target by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub kind: WorkspaceKind,
+    /// Location of the member, labelled like output paths (root prefix + relative path)
+    pub display_path: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error("Unknown workspace member '{0}'")]
+    UnknownMember(String),
+    #[error("Path '{0}' is outside the project")]
+    OutsideProject(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Builds the output label for a file under a project root.
+fn label_for(root: &ProjectRoot, path: &Path) -> String {
+    let relative = path.strip_prefix(&root.path).unwrap_or(path);
+    let relative = relative.to_string_lossy();
+    if root.prefix.is_empty() {
+        relative.into_owned()
+    } else if relative.is_empty() {
+        root.prefix.clone()
+    } else {
+        format!("{}/{relative}", root.prefix)
+    }
+}
+
+/// Gathers the files of a project, labelled with per-root prefixes.
+///
+/// When `paths` or `members` are given, only files under those request paths (relative to
+/// the project, including any root prefix) or workspace members are returned. Request paths
+/// that do not exist are skipped.
+pub fn gather_project_files(
+    project: &ProjectConfig,
+    paths: Option<&[String]>,
+    members: Option<&[String]>,
+) -> Result<Vec<ProjectFile>, WorkspaceError> {
+    gather_project_files_with(project, paths, members, &[], &[])
+}
+
+/// Gathers project files like [`gather_project_files`], keeping only files with one of
+/// `extensions`, if any, that match none of the `ignore` patterns.
+pub fn gather_project_files_with(
+    project: &ProjectConfig,
+    paths: Option<&[String]>,
+    members: Option<&[String]>,
+    extensions: &[&str],
+    ignore: &[String],
+) -> Result<Vec<ProjectFile>, WorkspaceError> {
+    let roots = project.roots();
+    let mut targets: Vec<(ProjectRoot, PathBuf)> = Vec::new();
+
+    if let Some(paths) = paths {
+        for requested in paths {
+            targets.extend(resolve_request_path(&roots, requested)?);
+        }
+    }
+
+    if let Some(members) = members {
+        let available = detect_project_members(&roots);
+        for name in members {
+            let member = available
+                .iter()
+                .find(|(_, m)| m.name == *name)
+                .ok_or_else(|| WorkspaceError::UnknownMember(name.clone()))?;
+            targets.push((member.0.clone(), member.1.path.clone()));
+        }
+    }
+
+    if paths.is_none() && members.is_none() {
+        targets = roots.iter().map(|r| (r.clone(), r.path.clone())).collect();
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut files = Vec::new();
+    for (root, target) in targets {
+        if !target.exists() {
+            debug!("Skipping missing path {}", target.display());
+            continue;
+        }
+        for path in gather_relevant_files(&target.to_string_lossy(), extensions, ignore.to_vec())? {
+            if seen.insert(path.clone()) {
+                let display_path = label_for(&root, &path);
+                files.push(ProjectFile { path, display_path });
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Finds the file a request path (relative to the project, including any root prefix)
+/// refers to, preferring a root where it exists. `None` for paths leading out of the project.
+pub fn resolve_project_path(project: &ProjectConfig, requested: &str) -> Option<PathBuf> {
+    resolve_request_path(&project.roots(), requested)
+        .ok()?
+        .into_iter()
+        .next()
+        .map(|(_, path)| path)
+}
+
+/// Labels a file under one of the project's roots the way gathered output paths are labelled
+pub fn project_label(project: &ProjectConfig, path: &Path) -> String {
+    project
+        .roots()
+        .iter()
+        .filter(|root| path.starts_with(&root.path))
+        .max_by_key(|root| root.path.components().count())
+        .map_or_else(|| path.display().to_string(), |root| label_for(root, path))
+}
+
+/// Maps a request path to the root it belongs to and its location on disk.
+fn resolve_request_path(
+    roots: &[ProjectRoot],
+    requested: &str,
+) -> Result<Vec<(ProjectRoot, PathBuf)>, WorkspaceError> {
+    // Absolute paths and `..` could reach files outside every root
+    let escapes = Path::new(requested)
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err(WorkspaceError::OutsideProject(requested.to_string()));
+    }
+    let requested = requested.trim_start_matches("./");
+
+    // An explicit root prefix selects that root
+    for root in roots.iter().filter(|r| !r.prefix.is_empty()) {
+        if requested == root.prefix {
+            return Ok(vec![(root.clone(), root.path.clone())]);
+        }
+        if let Some(rest) = requested.strip_prefix(&format!("{}/", root.prefix)) {
+            return Ok(vec![(root.clone(), root.path.join(rest))]);
+        }
+    }
+
+    // Otherwise look the path up in every root
+    let candidates: Vec<_> = roots
+        .iter()
+        .map(|root| (root.clone(), root.path.join(requested)))
+        .collect();
+    let existing: Vec<_> = candidates
+        .iter()
+        .filter(|(_, path)| path.exists())
+        .cloned()
+        .collect();
+    if existing.is_empty() {
+        Ok(candidates.into_iter().take(1).collect())
+    } else {
+        Ok(existing)
+    }
+}
+
+/// Detects workspace members across all roots of a project.
+fn detect_project_members(roots: &[ProjectRoot]) -> Vec<(ProjectRoot, WorkspaceMember)> {
+    roots
+        .iter()
+        .flat_map(|root| {
+            detect_workspace_members(&root.path)
+                .into_iter()
+                .map(|mut member| {
+                    member.display_path = label_for(root, &member.path);
+                    (root.clone(), member)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Lists the workspace members of every root of a project.
+pub fn project_members(project: &ProjectConfig) -> Vec<WorkspaceMember> {
+    detect_project_members(&project.roots())
+        .into_iter()
+        .map(|(_, member)| member)
+        .collect()
+}
+
+/// Detects Cargo, npm/pnpm and Go workspace members inside a directory.
+pub fn detect_workspace_members(root: &Path) -> Vec<WorkspaceMember> {
+    let mut members = Vec::new();
+    members.extend(detect_cargo_members(root));
+    members.extend(detect_npm_members(root));
+    members.extend(detect_pnpm_members(root));
+    members.extend(detect_go_members(root));
+    members.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.name.cmp(&b.name)));
+    members
+}
+
+fn member(name: String, kind: WorkspaceKind, path: PathBuf, root: &Path) -> WorkspaceMember {
+    let display_path = path
+        .strip_prefix(root)
+        .unwrap_or(&path)
+        .to_string_lossy()
+        .into_owned();
+    WorkspaceMember {
+        name,
+        kind,
+        display_path,
+        path,
+    }
+}
+
+/// Expands workspace glob patterns into directories containing `manifest`.
+fn expand_member_globs(root: &Path, patterns: &[String], manifest: &str) -> Vec<PathBuf> {
+    let mut dirs = BTreeSet::new();
+    for pattern in patterns {
+        if pattern.starts_with('!') {
+            continue;
+        }
+        let full_pattern = root.join(pattern.trim_start_matches("./"));
+        match glob::glob(&full_pattern.to_string_lossy()) {
+            Ok(entries) => {
+                for dir in entries.flatten() {
+                    if dir.join(manifest).is_file() {
+                        dirs.insert(dir);
+                    }
+                }
+            }
+            Err(e) => warn!("Invalid workspace pattern '{pattern}': {e}"),
+        }
+    }
+    let excluded: Vec<PathBuf> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .map(|p| root.join(p.trim_start_matches("./")))
+        .collect();
+    dirs.into_iter()
+        .filter(|dir| !excluded.contains(dir))
+        .collect()
+}
+
+fn string_array(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(toml::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    match content.parse::<toml::Value>() {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Failed to parse {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Failed to parse {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn detect_cargo_members(root: &Path) -> Vec<WorkspaceMember> {
+    let Some(manifest) = read_toml(&root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let Some(workspace) = manifest.get("workspace") else {
+        return Vec::new();
+    };
+
+    let mut patterns = string_array(workspace.get("members"));
+    patterns.extend(
+        string_array(workspace.get("exclude"))
+            .into_iter()
+            .map(|p| format!("!{p}")),
+    );
+
+    let mut dirs = expand_member_globs(root, &patterns, "Cargo.toml");
+    if manifest.get("package").is_some() {
+        dirs.insert(0, root.to_path_buf());
+    }
+
+    dirs.into_iter()
+        .map(|dir| {
+            let name = read_toml(&dir.join("Cargo.toml"))
+                .and_then(|m| {
+                    m.get("package")
+                        .and_then(|p| p.get("name"))
+                        .and_then(toml::Value::as_str)
+                        .map(ToString::to_string)
+                })
+                .unwrap_or_else(|| dir_name(&dir));
+            member(name, WorkspaceKind::Cargo, dir, root)
+        })
+        .collect()
+}
+
+fn package_json_name(dir: &Path) -> String {
+    read_json(&dir.join("package.json"))
+        .and_then(|p| {
+            p.get("name")
+                .and_then(|n| n.as_str())
+                .map(ToString::to_string)
+        })
+        .unwrap_or_else(|| dir_name(dir))
+}
+
+fn detect_npm_members(root: &Path) -> Vec<WorkspaceMember> {
+    let Some(package) = read_json(&root.join("package.json")) else {
+        return Vec::new();
+    };
+    let workspaces = match package.get("workspaces") {
+        Some(serde_json::Value::Array(items)) => items.clone(),
+        Some(serde_json::Value::Object(obj)) => obj
+            .get("packages")
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default(),
+        _ => return Vec::new(),
+    };
+    let patterns: Vec<String> = workspaces
+        .iter()
+        .filter_map(|v| v.as_str().map(ToString::to_string))
+        .collect();
+
+    expand_member_globs(root, &patterns, "package.json")
+        .into_iter()
+        .map(|dir| member(package_json_name(&dir), WorkspaceKind::Npm, dir, root))
+        .collect()
+}
+
+fn detect_pnpm_members(root: &Path) -> Vec<WorkspaceMember> {
+    let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) else {
+        return Vec::new();
+    };
+
+    // Only the `packages:` list is needed, so a line-oriented read is sufficient
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) && !trimmed.starts_with('-') {
+            in_packages = trimmed.starts_with("packages:");
+            continue;
+        }
+        if in_packages {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                patterns.push(item.trim().trim_matches(['\'', '"']).to_string());
+            }
+        }
+    }
+
+    expand_member_globs(root, &patterns, "package.json")
+        .into_iter()
+        .map(|dir| member(package_json_name(&dir), WorkspaceKind::Pnpm, dir, root))
+        .collect()
+}
+
+fn go_module_name(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("go.mod")).ok()?;
+    content.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("module ")
+            .map(|m| m.trim().trim_matches('"').to_string())
+    })
+}
+
+fn detect_go_members(root: &Path) -> Vec<WorkspaceMember> {
+    let dirs: Vec<PathBuf> = if let Ok(content) = fs::read_to_string(root.join("go.work")) {
+        // `use ./dir` or a `use ( ... )` block
+        let mut dirs = Vec::new();
+        let mut in_block = false;
+        for line in content.lines() {
+            let trimmed = line.split("//").next().unwrap_or("").trim();
+            if in_block {
+                if trimmed == ")" {
+                    in_block = false;
+                } else if !trimmed.is_empty() {
+                    dirs.push(root.join(trimmed.trim_start_matches("./")));
+                }
+            } else if let Some(rest) = trimmed.strip_prefix("use") {
+                let rest = rest.trim();
+                if rest == "(" {
+                    in_block = true;
+                } else if !rest.is_empty() {
+                    dirs.push(root.join(rest.trim_start_matches("./")));
+                }
+            }
+        }
+        dirs
+    } else {
+        gather_relevant_files(&root.to_string_lossy(), &["mod"], vec![])
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.file_name().is_some_and(|n| n == "go.mod"))
+            .filter_map(|p| p.parent().map(Path::to_path_buf))
+            .collect()
+    };
+
+    dirs.into_iter()
+        .filter_map(|dir| {
+            let name = go_module_name(&dir)?;
+            Some(member(name, WorkspaceKind::Go, dir, root))
+        })
+        .collect()
+}
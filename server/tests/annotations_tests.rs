@@ -0,0 +1,141 @@
+use contexter::annotations::{Annotation, AnnotationKind};
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Ada", "-c", "user.email=ada@example.com"])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(status.status.success(), "git {args:?} failed: {status:?}");
+}
+
+fn annotations(dir: &TempDir, with_blame: bool) -> Vec<Annotation> {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    mapper.annotations(with_blame)
+}
+
+/// Kind, line, text and the name of the owning component of each annotation
+fn summary(annotations: &[Annotation]) -> Vec<(AnnotationKind, usize, &str, Option<&str>)> {
+    annotations
+        .iter()
+        .map(|a| {
+            let owner = a.component.as_deref().and_then(|id| id.rsplit("::").next());
+            (a.kind, a.line, a.text.as_str(), owner)
+        })
+        .collect()
+}
+
+const PARSER: &str = r#"/// FIXME: escapes are not handled
+#[allow(dead_code, clippy::too_many_lines)]
+fn parse(text: &str) -> &str {
+    let marker = "TODO: not a comment";
+    // SAFETY: the caller checked the length
+    unsafe { text.get_unchecked(0..marker.len()) }
+}
+
+fn render() {} // TODO(ada): use a template
+
+/* XXX this is slow */
+fn slow() {}
+"#;
+
+#[test]
+fn test_markers_and_their_components() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    fs::write(dir.path().join("parser.rs"), PARSER).expect("Failed to write parser.rs");
+    fs::write(
+        dir.path().join("jobs.py"),
+        "class Job:\n    def run(self):\n        # HACK: retry twice\n        return 1\n",
+    )
+    .expect("Failed to write jobs.py");
+
+    let found = annotations(&dir, false);
+    assert_eq!(
+        summary(&found),
+        vec![
+            (AnnotationKind::Hack, 3, "retry twice", Some("run")),
+            (
+                AnnotationKind::Fixme,
+                1,
+                "escapes are not handled",
+                Some("parse")
+            ),
+            (
+                AnnotationKind::Allow,
+                2,
+                "dead_code, clippy::too_many_lines",
+                Some("parse")
+            ),
+            (
+                AnnotationKind::Safety,
+                5,
+                "the caller checked the length",
+                Some("parse")
+            ),
+            (
+                AnnotationKind::Todo,
+                9,
+                "(ada): use a template",
+                Some("render")
+            ),
+            (AnnotationKind::Xxx, 11, "this is slow", Some("slow")),
+        ]
+    );
+    assert!(found.iter().all(|a| a.blame.is_none()));
+}
+
+#[test]
+fn test_blame_of_committed_lines() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let file = dir.path().join("app.py");
+    fs::write(
+        &file,
+        "def main():\n    # TODO: parse arguments\n    pass\n",
+    )
+    .expect("Failed to write app.py");
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Add main"]);
+    fs::write(
+        &file,
+        "def main():\n    # TODO: parse arguments\n    # FIXME: exit code\n    pass\n",
+    )
+    .expect("Failed to write app.py");
+
+    let found = annotations(&dir, true);
+    assert_eq!(found.len(), 2);
+    let blame = found[0]
+        .blame
+        .as_ref()
+        .expect("Committed line should be blamed");
+    assert_eq!(blame.author, "Ada");
+    assert_eq!(blame.commit.len(), 40);
+    let authored = Command::new("git")
+        .arg("-C")
+        .arg(dir.path())
+        .args(["log", "-1", "--format=%ad", "--date=format-local:%Y-%m-%d"])
+        .env("TZ", "UTC")
+        .output()
+        .expect("Failed to run git");
+    assert_eq!(blame.date, String::from_utf8_lossy(&authored.stdout).trim());
+    assert_eq!(found[1].kind, AnnotationKind::Fixme);
+    assert!(found[1].blame.is_none());
+}
+
+#[test]
+fn test_kind_parsing() {
+    assert_eq!("todo".parse(), Ok(AnnotationKind::Todo));
+    assert_eq!("FIXME".parse(), Ok(AnnotationKind::Fixme));
+    assert_eq!(" allow ".parse(), Ok(AnnotationKind::Allow));
+    assert!("note".parse::<AnnotationKind>().is_err());
+}
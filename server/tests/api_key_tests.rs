@@ -73,3 +73,23 @@ async fn test_remove_api_key() {
     config.remove_api_key(name);
     assert_eq!(config.api_keys.len(), 0);
 }
+
+#[tokio::test]
+async fn test_configs_without_admin_keys_have_no_admin_keys() {
+    let legacy = r#"{
+        "projects": {},
+        "port": 3030,
+        "listen_address": "127.0.0.1",
+        "api_keys": { "extension": "abc", "laptop": "def" }
+    }"#;
+    let mut config: Config = serde_json::from_str(legacy).expect("Legacy config should parse");
+    assert!(config.admin_keys.is_empty());
+    assert!(!config.is_admin_key("extension"));
+
+    // Admin scope is only ever granted explicitly, and survives a save
+    config.grant_admin("laptop");
+    let saved = serde_json::to_string(&config).expect("Config should serialize");
+    let reloaded: Config = serde_json::from_str(&saved).expect("Saved config should parse");
+    assert!(reloaded.is_admin_key("laptop"));
+    assert!(!reloaded.is_admin_key("extension"));
+}
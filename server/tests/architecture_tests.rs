@@ -0,0 +1,281 @@
+use contexter::architecture::LayerViolation;
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(status.status.success(), "git {args:?} failed: {status:?}");
+}
+
+fn commit(dir: &Path, files: &[(&str, &str)], message: &str) {
+    for (path, content) in files {
+        write(&dir.join(path), content);
+    }
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+fn analyze(root: &Path) -> RepositoryMapper {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    mapper
+}
+
+/// Layer names with the files in each, relative to `root`
+fn layers(mapper: &RepositoryMapper, root: &Path) -> Vec<(String, Vec<String>)> {
+    mapper
+        .insights
+        .architectural_layers
+        .iter()
+        .map(|layer| {
+            let files = layer
+                .files
+                .iter()
+                .map(|file| relative(root, file))
+                .collect();
+            (layer.name.clone(), files)
+        })
+        .collect()
+}
+
+fn relative(root: &Path, file: &str) -> String {
+    Path::new(file)
+        .strip_prefix(root)
+        .expect("File should be under the root")
+        .display()
+        .to_string()
+}
+
+#[test]
+fn test_layers_are_recognized_by_name() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("app/__init__.py"), "");
+    write(
+        &root.join("app/handlers/orders.py"),
+        "from ..services.ordering import place\n\ndef post(request):\n    return place(request)\n",
+    );
+    write(
+        &root.join("app/services/ordering.py"),
+        "from ..db.store import save\n\ndef place(order):\n    return save(order)\n",
+    );
+    write(
+        &root.join("app/db/store.py"),
+        "from ..handlers.orders import post\n\ndef save(order):\n    return order\n",
+    );
+    write(&root.join("app/util.py"), "def noop():\n    pass\n");
+    write(
+        &root.join("tests/test_ordering.py"),
+        "def test_place():\n    assert True\n",
+    );
+
+    let mapper = analyze(root);
+    assert_eq!(
+        layers(&mapper, root),
+        vec![
+            (
+                "tests".to_string(),
+                vec!["tests/test_ordering.py".to_string()]
+            ),
+            (
+                "handlers".to_string(),
+                vec!["app/handlers/orders.py".to_string()]
+            ),
+            (
+                "domain".to_string(),
+                vec!["app/services/ordering.py".to_string()]
+            ),
+            (
+                "persistence".to_string(),
+                vec!["app/db/store.py".to_string()]
+            ),
+            ("shared".to_string(), vec!["app/util.py".to_string()]),
+        ]
+    );
+
+    // Only the import from storage back up into the handlers crosses layers upwards
+    let violations: Vec<(String, String, String, String)> = mapper
+        .insights
+        .layering_violations
+        .iter()
+        .map(|v| {
+            (
+                relative(root, &v.from),
+                relative(root, &v.to),
+                v.from_layer.clone(),
+                v.to_layer.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        violations,
+        vec![(
+            "app/db/store.py".to_string(),
+            "app/handlers/orders.py".to_string(),
+            "persistence".to_string(),
+            "handlers".to_string(),
+        )]
+    );
+
+    let map = mapper.generate_repository_map();
+    assert!(map.contains("Layers:"));
+    assert!(map.contains("Layering Violations:"));
+}
+
+#[test]
+fn test_layers_from_settings_file() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join(".contexter.toml"),
+        r#"
+[[layers]]
+name = "web"
+description = "Request handling"
+paths = ["src/web/"]
+
+[[layers]]
+name = "core"
+paths = ["src/core", "src/shared/*.py"]
+"#,
+    );
+    write(&root.join("src/__init__.py"), "");
+    write(
+        &root.join("src/web/routes.py"),
+        "from ..core.rules import check\n\ndef index():\n    return check()\n",
+    );
+    write(
+        &root.join("src/core/rules.py"),
+        "from ..web.routes import index\n\ndef check():\n    return index\n",
+    );
+    write(
+        &root.join("src/shared/text.py"),
+        "def strip(s):\n    return s\n",
+    );
+    write(&root.join("scripts/build.py"), "print('build')\n");
+
+    let mapper = analyze(root);
+    // Rules replace the heuristics, and files no rule matches stay out of every layer
+    assert_eq!(
+        layers(&mapper, root),
+        vec![
+            ("web".to_string(), vec!["src/web/routes.py".to_string()]),
+            (
+                "core".to_string(),
+                vec![
+                    "src/core/rules.py".to_string(),
+                    "src/shared/text.py".to_string()
+                ]
+            ),
+        ]
+    );
+    assert_eq!(
+        mapper.insights.architectural_layers[0].description,
+        "Request handling"
+    );
+    assert_eq!(
+        mapper.insights.layering_violations,
+        vec![LayerViolation {
+            from: root.join("src/core/rules.py").display().to_string(),
+            to: root.join("src/web/routes.py").display().to_string(),
+            from_layer: "core".to_string(),
+            to_layer: "web".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_invalid_settings_fail_analysis() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join(".contexter.toml"),
+        "[[layers]]\nname = \"web\"\npaths = [\"src/[\"]\n",
+    );
+    write(&root.join("main.py"), "print('hi')\n");
+
+    let error = RepositoryMapper::new()
+        .analyze_repository(root)
+        .expect_err("An invalid pattern should fail analysis");
+    assert!(error.to_string().contains("src/["), "{error}");
+
+    write(&root.join(".contexter.toml"), "[[layers]\n");
+    assert!(RepositoryMapper::new().analyze_repository(root).is_err());
+}
+
+#[test]
+fn test_hotspots_weigh_fan_in_by_churn() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    commit(
+        root,
+        &[
+            ("pkg/__init__.py", ""),
+            ("pkg/config.py", "DEBUG = False\n"),
+            ("pkg/models.py", "class Order:\n    pass\n"),
+            (
+                "pkg/api.py",
+                "from .config import DEBUG\nfrom .models import Order\n",
+            ),
+            (
+                "pkg/jobs.py",
+                "from .config import DEBUG\nfrom .models import Order\n",
+            ),
+            ("pkg/cli.py", "from .config import DEBUG\n"),
+        ],
+        "Initial commit",
+    );
+    for i in 0..3 {
+        commit(
+            root,
+            &[("pkg/config.py", &format!("DEBUG = False\nLEVEL = {i}\n"))],
+            "Tune config",
+        );
+    }
+    commit(
+        root,
+        &[("pkg/models.py", "class Order:\n    id = 0\n")],
+        "Add order id",
+    );
+
+    let mapper = analyze(root);
+    let hotspots: Vec<(String, usize, usize, usize)> = mapper
+        .hotspots
+        .iter()
+        .map(|h| (relative(root, &h.path), h.fan_in, h.churn, h.score))
+        .collect();
+    assert_eq!(
+        hotspots,
+        vec![
+            ("pkg/config.py".to_string(), 3, 4, 12),
+            ("pkg/models.py".to_string(), 2, 2, 4),
+        ]
+    );
+    assert_eq!(
+        mapper.insights.dependency_hotspots,
+        vec![
+            root.join("pkg/config.py").display().to_string(),
+            root.join("pkg/models.py").display().to_string(),
+        ]
+    );
+    assert!(mapper.generate_repository_map().contains("Hotspots:"));
+}
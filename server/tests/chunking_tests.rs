@@ -0,0 +1,171 @@
+use contexter::chunking::{chunk_project_files_with, part_path, ContextChunk};
+use contexter::contexter::{ConcatOptions, ProjectFile};
+use contexter::truncate::estimate_tokens;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+fn project(files: &[(&str, String)]) -> (TempDir, Vec<ProjectFile>) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let mut project_files = Vec::new();
+    for (name, content) in files {
+        let path = dir.path().join(name);
+        fs::write(&path, content).expect("Failed to write file");
+        project_files.push(ProjectFile {
+            path,
+            display_path: (*name).to_string(),
+        });
+    }
+    (dir, project_files)
+}
+
+fn chunk(files: Vec<ProjectFile>, max_tokens: usize) -> io::Result<Vec<ContextChunk>> {
+    chunk_project_files_with(
+        files,
+        &ConcatOptions::default(),
+        max_tokens,
+        |_, content| Some(content),
+    )
+}
+
+/// `count` functions of ten lines each
+fn functions(count: usize) -> String {
+    let functions: Vec<String> = (1..=count)
+        .map(|n| {
+            let body: Vec<String> = (1..=8)
+                .map(|line| format!("    let value_{line} = {n} * {line};"))
+                .collect();
+            format!("fn step_{n}() {{\n{}\n}}\n", body.join("\n"))
+        })
+        .collect();
+    functions.join("\n")
+}
+
+#[test]
+fn test_parts_hold_whole_files_under_the_limit() {
+    let (_dir, files) = project(&[
+        ("a.rs", format!("// a\n{}", functions(3))),
+        ("b.rs", format!("// b\n{}", functions(3))),
+        ("c.rs", format!("// c\n{}", functions(3))),
+        ("notes.md", "# Notes\n".to_string()),
+    ]);
+    let chunks = chunk(files, 450).expect("Chunking should succeed");
+
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert!(chunk.tokens <= 450, "Part {} is too large", chunk.part);
+        assert_eq!(chunk.tokens, estimate_tokens(&chunk.content));
+        assert_eq!(chunk.parts, chunks.len());
+        assert!(!chunk.content.contains("Lines:"));
+    }
+    let first = &chunks[0];
+    assert_eq!(first.files, vec!["notes.md", "a.rs"]);
+    assert!(first.content.starts_with(&format!(
+        "========================================\nPart 1/{}, files notes.md – a.rs\nTable of Contents:\n- notes.md (part 1)\n- a.rs (part 1)\n",
+        chunks.len()
+    )));
+    assert!(first.content.contains("Section: Documentation"));
+    assert!(first.content.contains("Section: Source Files"));
+
+    // Only the first part has the table of contents, and every file is in one part
+    for chunk in &chunks[1..] {
+        assert!(!chunk.content.contains("Table of Contents:"));
+        assert!(chunk.content.starts_with(&format!(
+            "========================================\nPart {}/{}, file",
+            chunk.part,
+            chunks.len()
+        )));
+    }
+    let all: Vec<String> = chunks.iter().flat_map(|c| c.files.clone()).collect();
+    assert_eq!(all, vec!["notes.md", "a.rs", "b.rs", "c.rs"]);
+}
+
+#[test]
+fn test_large_files_split_between_functions() {
+    let (_dir, files) = project(&[("steps.rs", functions(12))]);
+    let chunks = chunk(files, 300).expect("Chunking should succeed");
+
+    assert!(chunks.len() > 2);
+    assert!(chunks[0]
+        .content
+        .contains(&format!("- steps.rs (parts 1-{})\n", chunks.len())));
+    let mut next = 1;
+    for chunk in &chunks {
+        assert!(chunk.tokens <= 300, "Part {} is too large", chunk.part);
+        assert_eq!(chunk.files, vec!["steps.rs"]);
+        let header = format!("Lines: {next}-");
+        let start = chunk
+            .content
+            .find(&header)
+            .expect("Each part continues where the last one stopped");
+        let range = &chunk.content[start + header.len()..];
+        let end: usize = range[..range.find(' ').expect("Range ends")]
+            .parse()
+            .expect("Line number");
+        // Each part ends with a function's closing brace
+        let body = chunk.content.trim_end_matches('\n');
+        assert!(body.ends_with('}'), "Part {} ends mid-function", chunk.part);
+        next = end + 1;
+    }
+    assert_eq!(next, 12 * 11);
+}
+
+#[test]
+fn test_large_tables_of_contents_fill_parts_of_their_own() {
+    let files: Vec<(String, String)> = (1..=200)
+        .map(|n| {
+            (
+                format!("module_{n:03}_with_a_fairly_long_name.py"),
+                format!("VALUE = {n}\n"),
+            )
+        })
+        .collect();
+    let files: Vec<(&str, String)> = files
+        .iter()
+        .map(|(name, content)| (name.as_str(), content.clone()))
+        .collect();
+    let (_dir, files) = project(&files);
+    let chunks = chunk(files, 200).expect("Chunking should succeed");
+
+    let table_parts = chunks.iter().take_while(|c| c.files.is_empty()).count();
+    assert!(table_parts > 1, "The table alone is over 200 tokens");
+    assert!(chunks[0].content.contains(&format!(
+        "Part 1/{}, table of contents\nTable of Contents:\n",
+        chunks.len()
+    )));
+    let mut listed = 0;
+    for chunk in &chunks {
+        assert!(chunk.tokens <= 200, "Part {} is too large", chunk.part);
+        for line in chunk.content.lines().filter(|line| line.starts_with("- ")) {
+            listed += 1;
+            // Files start after the table, in the part it says
+            let (name, part) = line[2..].split_once(" (part ").expect("One part per file");
+            let part: usize = part.trim_end_matches(')').parse().expect("Part number");
+            assert!(part > table_parts);
+            assert!(chunks[part - 1].files.iter().any(|file| file == name));
+        }
+    }
+    assert_eq!(listed, 200);
+    let all: Vec<String> = chunks.iter().flat_map(|c| c.files.clone()).collect();
+    assert_eq!(all.len(), 200);
+}
+
+#[test]
+fn test_too_small_parts_are_rejected() {
+    let (_dir, files) = project(&[("a.rs", functions(1))]);
+    let error = chunk(files, 10).expect_err("Ten tokens hold no files");
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_part_paths() {
+    assert_eq!(
+        part_path(Path::new("out/context.md"), 2),
+        Path::new("out/context.part2.md")
+    );
+    assert_eq!(
+        part_path(Path::new("context"), 1),
+        Path::new("context.part1")
+    );
+}
@@ -0,0 +1,29 @@
+use contexter::cli::Cli;
+use structopt::clap::ErrorKind;
+use structopt::StructOpt;
+
+/// The help `args` print
+fn help(args: &[&str]) -> String {
+    let error = Cli::clap()
+        .get_matches_from_safe(args)
+        .expect_err("Asking for help exits early");
+    assert_eq!(error.kind, ErrorKind::HelpDisplayed);
+    error.message
+}
+
+#[test]
+fn test_gather_help_keeps_its_description() {
+    let gather = help(&["contexter", "gather", "--help"]);
+    assert_eq!(gather.lines().nth(1), Some("Gather context from files"));
+    // Options of the structs flattened into the command are still listed
+    for option in ["--max-file-tokens", "--near-duplicates", "--chunk-tokens"] {
+        assert!(gather.contains(option), "{option} is missing");
+    }
+
+    let top = help(&["contexter", "--help"]);
+    let line = top
+        .lines()
+        .find(|line| line.trim_start().starts_with("gather "))
+        .expect("gather is listed");
+    assert!(line.ends_with("Gather context from files"), "{line}");
+}
@@ -0,0 +1,353 @@
+use contexter::complexity::ComplexityMetrics;
+use contexter::repo_mapper::{ComponentType, RepositoryMapper};
+use contexter::syntax::{parse_source, SourceLanguage};
+use std::fs;
+use tempfile::tempdir;
+
+fn metrics(language: SourceLanguage, source: &str, name: &str) -> ComplexityMetrics {
+    let syntax = parse_source(language, source).expect("Source should parse");
+    syntax
+        .components
+        .iter()
+        .find(|component| component.name == name)
+        .unwrap_or_else(|| panic!("{name} should be extracted"))
+        .metrics
+}
+
+/// Cyclomatic and cognitive complexity, maximum nesting and parameter count
+fn summary(metrics: ComplexityMetrics) -> (u32, u32, u32, usize) {
+    (
+        metrics.cyclomatic,
+        metrics.cognitive,
+        metrics.max_nesting,
+        metrics.parameters,
+    )
+}
+
+#[test]
+fn test_rust_branches_loops_and_matches() {
+    let source = r#"
+fn classify(n: i32, flags: &[bool]) -> &'static str {
+    if n < 0 && flags.is_empty() {
+        return "negative";
+    } else if n == 0 {
+        return "zero";
+    } else {
+        for flag in flags {
+            if *flag {
+                return "flagged";
+            }
+        }
+    }
+    match n {
+        1 => "one",
+        2 => "two",
+        _ => "many",
+    }
+}
+
+impl Counter {
+    pub fn bump(&mut self, by: u32) {
+        self.items.retain(|item| item.live);
+    }
+}
+"#;
+    assert_eq!(
+        summary(metrics(SourceLanguage::Rust, source, "classify")),
+        (8, 10, 3, 2)
+    );
+    // `self` is not a parameter; the closure nests but makes no decision
+    assert_eq!(
+        summary(metrics(SourceLanguage::Rust, source, "bump")),
+        (1, 0, 1, 1)
+    );
+}
+
+#[test]
+fn test_lines_of_code_skip_blanks_and_comments() {
+    let source = r"
+/// Doc comments belong to no function body
+fn answer() -> u32 {
+    // Explain
+
+    let x = 42; // trailing
+    x
+}
+";
+    let answer = metrics(SourceLanguage::Rust, source, "answer");
+    assert_eq!((answer.loc, answer.sloc), (6, 4));
+
+    let syntax = parse_source(SourceLanguage::Rust, source).expect("Source should parse");
+    assert_eq!(syntax.sloc, 4);
+}
+
+#[test]
+fn test_python_metrics() {
+    let source = r"
+class Cart:
+    def total(self, items, discount=0):
+        result = 0
+        for item in items:
+            if item.price > 0 and not item.free:
+                result += item.price
+            elif item.refund:
+                result -= item.price
+            else:
+                pass
+        try:
+            return result - discount
+        except ValueError:
+            return 0
+";
+    assert_eq!(
+        summary(metrics(SourceLanguage::Python, source, "total")),
+        (6, 7, 2, 2)
+    );
+}
+
+#[test]
+fn test_javascript_arrow_functions_and_switches() {
+    let source = r#"
+export const pick = (items, key) => items.filter((item) => item[key] ? true : false);
+
+function grade(score) {
+  switch (true) {
+    case score > 90: return "A";
+    case score > 80: return "B";
+    default: return "C";
+  }
+}
+"#;
+    // The ternary sits inside a callback
+    assert_eq!(
+        summary(metrics(SourceLanguage::JavaScript, source, "pick")),
+        (2, 2, 2, 2)
+    );
+    assert_eq!(
+        summary(metrics(SourceLanguage::JavaScript, source, "grade")),
+        (3, 1, 1, 1)
+    );
+}
+
+#[test]
+fn test_go_java_and_c_metrics() {
+    let go = r"
+package calc
+
+func Sum(a, b int, rest ...int) int {
+	total := a + b
+	for _, r := range rest {
+		if r > 0 || r < -10 {
+			total += r
+		} else {
+			total -= r
+		}
+	}
+	return total
+}
+";
+    assert_eq!(
+        summary(metrics(SourceLanguage::Go, go, "Sum")),
+        (4, 5, 2, 3)
+    );
+
+    let java = r"
+class Shop {
+    int price(int qty, boolean member) {
+        int p = qty > 10 ? 9 : 10;
+        try {
+            p = adjust(p);
+        } catch (Exception e) {
+            p = 0;
+        }
+        return member ? p - 1 : p;
+    }
+}
+";
+    assert_eq!(
+        summary(metrics(SourceLanguage::Java, java, "price")),
+        (4, 3, 1, 2)
+    );
+
+    let c = r"
+int run(void) {
+    int i = 0;
+    while (i < 10) {
+        i++;
+    }
+    return i;
+}
+";
+    assert_eq!(summary(metrics(SourceLanguage::C, c, "run")), (2, 1, 1, 0));
+}
+
+#[test]
+fn test_kotlin_metrics_are_estimated_from_lines() {
+    let source = r"
+fun check(a: Int, b: Int): Boolean {
+    if (a > 0 && b > 0) {
+        return true
+    }
+    return false
+}
+";
+    let check = metrics(SourceLanguage::Kotlin, source, "check");
+    assert_eq!(summary(check), (3, 2, 1, 2));
+    assert_eq!((check.loc, check.sloc), (6, 6));
+    assert!(check.approximate);
+    assert!(!metrics(SourceLanguage::Rust, "fn f() {}\n", "f").approximate);
+}
+
+#[test]
+fn test_kotlin_strings_and_comments_are_not_code() {
+    let source = r#"
+fun describe(items: List<String>): String {
+    // if the list is empty, for now, while nothing { happens
+    /* when { /* nested */ if } */
+    val open = "{ if (x) for"
+    val brace = '{'
+    val template = "${items.size} items { while"
+    val raw = """
+        if (a && b) {
+    """
+    if (items.isEmpty()) {
+        return "none"
+    }
+    return open + brace + template + raw
+}
+
+fun after() {
+    if (ready) {
+        run()
+    }
+}
+"#;
+    let describe = metrics(SourceLanguage::Kotlin, source, "describe");
+    assert_eq!(summary(describe), (2, 1, 1, 1));
+    assert_eq!((describe.loc, describe.sloc), (14, 12));
+    // The braces in strings and comments leave the next function's extent intact
+    let syntax = parse_source(SourceLanguage::Kotlin, source).expect("Source should parse");
+    let after = syntax
+        .components
+        .iter()
+        .find(|component| component.name == "after")
+        .expect("after should be extracted");
+    assert_eq!((after.start_line, after.end_line), (17, 21));
+    assert_eq!(summary(after.metrics), (2, 1, 1, 0));
+}
+
+#[test]
+fn test_kotlin_else_if_is_not_nested() {
+    let source = r"
+fun sign(n: Int): Int {
+    if (n > 0) {
+        return 1
+    } else if (n < 0) {
+        for (i in 0..n) {
+            if (i == n) {
+                return -1
+            }
+        }
+    }
+    return 0
+}
+";
+    // The `else if` is at the top level; the loop and the `if` inside it are nested
+    assert_eq!(
+        summary(metrics(SourceLanguage::Kotlin, source, "sign")),
+        (5, 1 + 1 + 2 + 3, 3, 1)
+    );
+}
+
+#[test]
+fn test_complexity_is_aggregated_into_types_and_files() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        dir.path().join("orders.py"),
+        r"
+class Orders:
+    def add(self, order):
+        if order.valid:
+            self.items.append(order)
+
+    def total(self):
+        return sum(o.price for o in self.items if o.paid)
+",
+    )
+    .expect("Failed to write orders.py");
+    fs::write(
+        dir.path().join("util.py"),
+        "def identity(x):\n    return x\n",
+    )
+    .expect("Failed to write util.py");
+    fs::write(dir.path().join("empty.py"), "# Nothing here\n").expect("Failed to write empty.py");
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+
+    let orders = mapper
+        .graph
+        .components
+        .values()
+        .find(|c| c.name == "Orders")
+        .expect("Orders should be extracted");
+    assert_eq!(orders.component_type, ComponentType::Class);
+    // `add` has one branch; `total` a loop and a filter in its generator
+    assert_eq!(orders.complexity_score, 2 + 3);
+    assert_eq!(orders.metrics.cognitive, 1);
+
+    let file = mapper
+        .file_complexity_of(&dir.path().join("orders.py"))
+        .expect("orders.py should be measured");
+    assert_eq!(file.functions, 2);
+    assert_eq!((file.cyclomatic, file.max_cyclomatic), (5, 3));
+    assert_eq!((file.loc, file.sloc), (8, 6));
+
+    let ranking: Vec<&str> = mapper
+        .insights
+        .most_complex_files
+        .iter()
+        .map(|path| path.rsplit('/').next().unwrap_or(path))
+        .collect();
+    assert_eq!(ranking, vec!["orders.py", "util.py"]);
+    assert!(mapper.generate_repository_map().contains("Most Complex:"));
+}
+
+#[test]
+fn test_kotlin_complexity_is_listed_as_approximate() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        dir.path().join("Orders.kt"),
+        "class Orders {\n    fun add(valid: Boolean) {\n        if (valid) {\n            save()\n        }\n    }\n}\n",
+    )
+    .expect("Failed to write Orders.kt");
+    fs::write(
+        dir.path().join("util.py"),
+        "def check(x):\n    if x:\n        return x\n",
+    )
+    .expect("Failed to write util.py");
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    let orders = mapper
+        .graph
+        .components
+        .values()
+        .find(|c| c.name == "Orders")
+        .expect("Orders should be extracted");
+    assert!(orders.metrics.approximate);
+
+    let map = mapper.generate_repository_map();
+    let listed = |name: &str| {
+        map.lines()
+            .find(|line| line.contains(name) && line.contains("cognitive"))
+            .unwrap_or_else(|| panic!("{name} should be listed"))
+            .to_string()
+    };
+    assert!(listed("Orders.kt").ends_with("SLOC, approximate)"));
+    assert!(listed("util.py").ends_with(" SLOC)"));
+}
@@ -0,0 +1,90 @@
+use actix_web::web;
+use contexter::config::Config;
+use contexter::config_watcher::watch_config;
+use contexter::server::AppState;
+use contexter::utils::hash_api_key;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::tempdir;
+
+const INTERVAL: Duration = Duration::from_millis(20);
+
+fn config(projects: &[&str]) -> Config {
+    let mut config = Config::default();
+    config
+        .api_keys
+        .insert("test_key_name".to_string(), hash_api_key("test_key"));
+    for name in projects {
+        config.add_project((*name).to_string(), format!("/srv/{name}").into());
+    }
+    config
+}
+
+/// Write `content`, leaving the watcher a few polls to see the previous file first
+async fn write(path: &Path, content: &str) {
+    actix_rt::time::sleep(INTERVAL * 3).await;
+    fs::write(path, content).expect("Failed to write config file");
+}
+
+/// Wait until the running configuration has `projects` projects, for at most five seconds
+async fn wait_for_projects(state: &AppState, projects: usize) -> bool {
+    for _ in 0..250 {
+        if state.config.read().await.projects.len() == projects {
+            return true;
+        }
+        actix_rt::time::sleep(INTERVAL).await;
+    }
+    false
+}
+
+#[actix_rt::test]
+async fn test_watched_config_reloads_and_keeps_the_last_valid_one() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("config.json");
+    let initial = config(&["first"]);
+    fs::write(
+        &path,
+        serde_json::to_string(&initial).expect("Config should serialize"),
+    )
+    .expect("Failed to write config file");
+    let state = web::Data::new(AppState::new(initial).with_config_path(path.clone()));
+    watch_config(state.clone(), INTERVAL);
+
+    let changed = config(&["first", "second"]);
+    write(
+        &path,
+        &serde_json::to_string(&changed).expect("Config should serialize"),
+    )
+    .await;
+    assert!(
+        wait_for_projects(&state, 2).await,
+        "The change is picked up"
+    );
+
+    // Neither a broken file nor one failing validation replaces the running configuration
+    write(&path, "{ not json").await;
+    let mut keyless = config(&["first", "second", "third"]);
+    keyless.api_keys.clear();
+    write(
+        &path,
+        &serde_json::to_string(&keyless).expect("Config should serialize"),
+    )
+    .await;
+    actix_rt::time::sleep(INTERVAL * 10).await;
+    let running = state.config.read().await.clone();
+    assert_eq!(running.projects.len(), 2);
+    assert!(running.api_keys.contains_key("test_key_name"));
+
+    // The watcher keeps going after a rejected file
+    let fixed = config(&["first", "second", "third"]);
+    write(
+        &path,
+        &serde_json::to_string(&fixed).expect("Config should serialize"),
+    )
+    .await;
+    assert!(
+        wait_for_projects(&state, 3).await,
+        "The fixed file is picked up"
+    );
+}
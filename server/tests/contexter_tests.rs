@@ -1,3 +1,5 @@
+#![allow(clippy::unwrap_used, clippy::similar_names)] // Test assertions unwrap freely
+
 use contexter::contexter::{concatenate_files, gather_relevant_files};
 use std::fs::File;
 use std::io::Write;
@@ -200,3 +202,29 @@ fn test_built_in_exclusions() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_exclusions_match_paths_relative_to_the_directory() -> std::io::Result<()> {
+    let dir = tempdir()?;
+    // A project that itself lives under directories the built-in exclusions name
+    let project = dir.path().join("build").join("target").join("app");
+    std::fs::create_dir_all(project.join("src/generated"))?;
+    std::fs::create_dir_all(project.join("target"))?;
+    File::create(project.join("src/main.rs"))?;
+    File::create(project.join("src/generated/schema.rs"))?;
+    File::create(project.join("target/output.rs"))?;
+
+    let files = gather_relevant_files(
+        project.to_str().unwrap(),
+        &[],
+        vec![String::from("^src/generated/")],
+    )?;
+
+    let relative: Vec<_> = files
+        .iter()
+        .map(|f| f.strip_prefix(&project).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(relative, vec![std::path::PathBuf::from("src/main.rs")]);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use contexter::dead_code::DeadCode;
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn dead_code(files: &[(&str, &str)]) -> (TempDir, DeadCode) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    for (name, content) in files {
+        write(&dir.path().join(name), content);
+    }
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    let dead = mapper.find_dead_code();
+    (dir, dead)
+}
+
+fn unused_names(dead: &DeadCode) -> Vec<&str> {
+    dead.components.iter().map(|c| c.name.as_str()).collect()
+}
+
+fn unused_imports(dead: &DeadCode) -> Vec<(&str, usize)> {
+    dead.imports
+        .iter()
+        .map(|import| (import.name.as_str(), import.line))
+        .collect()
+}
+
+#[test]
+fn test_unused_functions_are_reported() {
+    let (dir, dead) = dead_code(&[
+        (
+            "app.py",
+            "from store import save\n\n\ndef main():\n    return save()\n",
+        ),
+        (
+            "store.py",
+            "def save():\n    return 1\n\n\ndef forgotten():\n    return 2\n",
+        ),
+    ]);
+
+    assert_eq!(unused_names(&dead), vec!["forgotten"]);
+    let forgotten = &dead.components[0];
+    assert_eq!(
+        forgotten.file,
+        dir.path().join("store.py").display().to_string()
+    );
+    assert_eq!(forgotten.line, 5);
+}
+
+#[test]
+fn test_entry_points_are_not_dead() {
+    let (_dir, dead) = dead_code(&[
+        (
+            "web.py",
+            "@app.route('/')\ndef index():\n    return 'hi'\n\n\ndef __repr__():\n    return ''\n",
+        ),
+        ("tests/test_web.py", "def test_index():\n    assert True\n"),
+        (
+            "src/main.rs",
+            r#"struct Job;
+
+impl std::fmt::Display for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "job")
+    }
+}
+
+#[tokio::main]
+async fn main() {}
+
+fn orphan() {}
+"#,
+        ),
+    ]);
+
+    assert_eq!(unused_names(&dead), vec!["orphan"]);
+}
+
+#[test]
+fn test_configured_entry_points_and_used_imports() {
+    let files = [
+        (
+            "plugins/loader.py",
+            "import registry\n\n\ndef load_plugin():\n    return 1\n\n\ndef unload_plugin():\n    return 2\n",
+        ),
+        ("registry.py", "PLUGINS = []\n"),
+    ];
+    let (_dir, dead) = dead_code(&files);
+    assert_eq!(unused_names(&dead), vec!["load_plugin", "unload_plugin"]);
+    assert_eq!(unused_imports(&dead), vec![("registry", 1)]);
+
+    let mut configured = files.to_vec();
+    configured.push((
+        ".contexter.toml",
+        "[dead_code]\nentry_points = [\"plugins/*::load_*\"]\nused_imports = [\"regist*\"]\n",
+    ));
+    let (_dir, dead) = dead_code(&configured);
+    assert_eq!(unused_names(&dead), vec!["unload_plugin"]);
+    assert!(dead.imports.is_empty());
+}
+
+#[test]
+fn test_unused_imports() {
+    let (_dir, dead) = dead_code(&[
+        (
+            "app.py",
+            "import os\nimport sys as system\nfrom json import dumps, loads\n\n\ndef main():\n    return loads(system.argv[1])\n",
+        ),
+        (
+            "src/main.rs",
+            "use std::collections::{HashMap, HashSet};\nuse std::io::Write;\npub use std::fmt::Debug;\n\nfn main() {\n    let _map: HashMap<u8, u8> = HashMap::new();\n}\n",
+        ),
+    ]);
+
+    let imports = unused_imports(&dead);
+    assert_eq!(imports, vec![("os", 1), ("dumps", 3), ("HashSet", 1)]);
+    assert_eq!(dead.imports[2].path, "std::collections::HashSet");
+}
@@ -0,0 +1,153 @@
+use contexter::contexter::{concatenate_project_files_with, ConcatOptions, ProjectFile};
+use contexter::dedup::{content_hash, strip_license_header, DedupOptions};
+use std::fs;
+use std::io;
+use tempfile::{tempdir, TempDir};
+
+fn project(files: &[(&str, String)]) -> (TempDir, Vec<ProjectFile>) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let mut project_files = Vec::new();
+    for (name, content) in files {
+        let path = dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create parent directory");
+        }
+        fs::write(&path, content).expect("Failed to write file");
+        project_files.push(ProjectFile {
+            path,
+            display_path: (*name).to_string(),
+        });
+    }
+    (dir, project_files)
+}
+
+fn concatenate(files: Vec<ProjectFile>, dedup: DedupOptions) -> io::Result<(String, Vec<String>)> {
+    let options = ConcatOptions {
+        dedup,
+        ..ConcatOptions::default()
+    };
+    concatenate_project_files_with(files, &options, |_, content| Some(content))
+}
+
+/// A parser of `count` rules named after `name`
+fn parser(name: &str, count: usize) -> String {
+    let rules: Vec<String> = (1..=count)
+        .map(|n| {
+            format!(
+                "def parse_{name}_{n}(tokens):\n    value = tokens.pop({n})\n    return value * {n}\n"
+            )
+        })
+        .collect();
+    rules.join("\n")
+}
+
+const MIT_HEADER: &str = "// Copyright (c) 2024 Example Authors\n//\n// Permission is hereby granted, free of charge, to any person obtaining a copy\n\n";
+
+#[test]
+fn test_content_hash_is_sha256() {
+    assert_eq!(
+        content_hash("hello"),
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+}
+
+#[test]
+fn test_license_headers_are_stripped() {
+    let source = format!("{MIT_HEADER}use std::io;\n\nfn main() {{}}\n");
+    assert_eq!(
+        strip_license_header(&source),
+        Some(("use std::io;\n\nfn main() {}\n".to_string(), 4))
+    );
+
+    let block = "/*\n * Licensed under the Apache License, Version 2.0\n */\npackage main\n";
+    assert_eq!(
+        strip_license_header(block),
+        Some(("package main\n".to_string(), 3))
+    );
+
+    // The shebang stays, and an SPDX line is stripped with the block after it
+    let script = "#!/usr/bin/env python3\n# SPDX-License-Identifier: MIT\n\n# Copyright 2024 Example\n\nimport os\n";
+    assert_eq!(
+        strip_license_header(script),
+        Some(("#!/usr/bin/env python3\nimport os\n".to_string(), 4))
+    );
+
+    // Comments without a license, doc comments and attributes are left alone
+    for content in [
+        "// Parses the configuration\nfn parse() {}\n",
+        "//! Copyright notices are parsed here\nfn parse() {}\n",
+        "#[derive(Debug)]\n// Copyright 2024\nstruct Notice;\n",
+        "#include <stdio.h>\n",
+    ] {
+        assert_eq!(strip_license_header(content), None, "{content}");
+    }
+}
+
+#[test]
+fn test_near_duplicates_collapse_into_the_first() -> io::Result<()> {
+    let original = parser("expr", 12);
+    // The same rules reindented and with one rule changed
+    let reformatted = original
+        .replace("    ", "  ")
+        .replace("tokens.pop(7)", "tokens.pop(70)");
+    let (_dir, files) = project(&[
+        ("a/grammar.py", original.clone()),
+        ("b/grammar.py", reformatted),
+        ("c/grammar.py", original.clone()),
+        ("lexer.py", parser("token", 12)),
+    ]);
+    let near = DedupOptions {
+        near_duplicates: Some(0.8),
+        ..DedupOptions::default()
+    };
+    let (content, filenames) = concatenate(files.clone(), near)?;
+    assert_eq!(filenames, vec!["a/grammar.py", "lexer.py"]);
+    let note = content
+        .lines()
+        .find(|line| line.starts_with("Duplicates: "))
+        .expect("The kept file lists its duplicates");
+    assert!(note.starts_with("Duplicates: b/grammar.py ("));
+    assert!(note.ends_with("% similar), c/grammar.py (identical)"));
+    assert_eq!(content.matches("Duplicates: ").count(), 1);
+
+    // Without near-duplicate detection only exact copies are collapsed
+    let (content, filenames) = concatenate(files, DedupOptions::default())?;
+    assert_eq!(filenames, vec!["a/grammar.py", "b/grammar.py", "lexer.py"]);
+    assert!(content.contains("Duplicates: c/grammar.py (identical)\n"));
+    Ok(())
+}
+
+#[test]
+fn test_files_differing_in_license_header_are_identical_once_stripped() -> io::Result<()> {
+    let body = "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+    let (_dir, files) = project(&[
+        ("one.rs", format!("{MIT_HEADER}{body}")),
+        (
+            "two.rs",
+            format!("// Copyright (c) 2019 Someone Else\n\n{body}"),
+        ),
+    ]);
+    let strip = DedupOptions {
+        strip_license_headers: true,
+        ..DedupOptions::default()
+    };
+    let (content, filenames) = concatenate(files, strip)?;
+    assert_eq!(filenames, vec!["one.rs"]);
+    assert!(content.contains("License Header: 4 line(s) stripped\n"));
+    assert!(content.contains("Duplicates: two.rs (identical)\n"));
+    assert!(!content.contains("Copyright"));
+    Ok(())
+}
+
+#[test]
+fn test_similarity_must_be_a_share() {
+    let (_dir, files) = project(&[("a.py", parser("a", 1))]);
+    for threshold in [0.0, 1.5, f64::NAN] {
+        let options = DedupOptions {
+            near_duplicates: Some(threshold),
+            ..DedupOptions::default()
+        };
+        let error = concatenate(files.clone(), options).expect_err("Not a share");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,173 @@
+use contexter::contexter::ProjectFile;
+use contexter::doc_coverage::{render_undocumented, ComponentDocs};
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn analyze(files: &[(&str, &str)]) -> (TempDir, RepositoryMapper) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    for (name, content) in files {
+        write(&dir.path().join(name), content);
+    }
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    (dir, mapper)
+}
+
+fn find<'a>(docs: &'a [ComponentDocs], name: &str) -> &'a ComponentDocs {
+    docs.iter()
+        .find(|component| component.name == name)
+        .unwrap_or_else(|| panic!("No docs entry for {name}"))
+}
+
+#[test]
+fn test_rust_doc_comments_and_parameters() {
+    let (_dir, mapper) = analyze(&[(
+        "src/lib.rs",
+        r#"use std::fmt;
+
+/// Opens a connection to `host`
+///
+/// Retries up to three times.
+#[must_use]
+pub fn connect(host: &str, port: u16) -> bool {
+    !host.is_empty() && port > 0
+}
+
+pub fn disconnect(force: bool) {}
+
+/** A pool of connections */
+pub struct Pool;
+
+impl Pool {
+    /// Takes a connection, waiting at most `timeout` milliseconds
+    pub fn take(&mut self, timeout: u64, mut retries: u8) {}
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pool")
+    }
+}
+
+fn internal() {}
+"#,
+    )]);
+    let docs = mapper.component_docs();
+
+    let connect = find(&docs, "connect");
+    assert!(connect.documented);
+    assert_eq!(connect.doc_lines, 2);
+    assert_eq!(connect.parameters, vec!["host", "port"]);
+    assert_eq!(connect.undocumented_parameters, vec!["port"]);
+
+    let disconnect = find(&docs, "disconnect");
+    assert!(!disconnect.documented);
+    assert_eq!(disconnect.undocumented_parameters, vec!["force"]);
+
+    assert_eq!(find(&docs, "Pool").doc_lines, 1);
+    let take = find(&docs, "take");
+    assert_eq!(take.parameters, vec!["timeout", "retries"]);
+    assert_eq!(take.undocumented_parameters, vec!["retries"]);
+
+    // Trait implementations and private items are not part of the documented API
+    assert!(docs.iter().all(|c| c.name != "fmt" && c.name != "internal"));
+}
+
+#[test]
+fn test_docstrings_jsdoc_and_go_comments() {
+    let (_dir, mapper) = analyze(&[
+        (
+            "shapes.py",
+            "class Shape:\n    \"\"\"\n    A drawable shape.\n\n    Shapes know their area.\n    \"\"\"\n\n    def scale(self, factor, *, origin=None):\n        '''Scales by factor'''\n        return self\n\n\ndef _helper():\n    pass\n",
+        ),
+        (
+            "web/api.ts",
+            "/**\n * Fetches a user.\n * @param id - the user ID\n */\nexport function fetchUser(id: string, options?: object): void {}\n\nexport const noop = () => {};\n",
+        ),
+        (
+            "store/store.go",
+            "package store\n\n// Save writes value under key\nfunc Save(key, value string, ttl int) error {\n\treturn nil\n}\n\nfunc Load(key string) string {\n\treturn key\n}\n",
+        ),
+    ]);
+    let docs = mapper.component_docs();
+
+    let shape = find(&docs, "Shape");
+    assert!(shape.documented);
+    assert_eq!(shape.doc_lines, 2);
+    let scale = find(&docs, "scale");
+    assert_eq!(scale.parameters, vec!["factor", "origin"]);
+    assert_eq!(scale.undocumented_parameters, vec!["origin"]);
+    assert!(docs.iter().all(|c| c.name != "_helper"));
+
+    let fetch_user = find(&docs, "fetchUser");
+    assert_eq!(fetch_user.doc_lines, 2);
+    assert_eq!(fetch_user.parameters, vec!["id", "options"]);
+    assert_eq!(fetch_user.undocumented_parameters, vec!["options"]);
+
+    let save = find(&docs, "Save");
+    assert!(save.documented);
+    assert_eq!(save.parameters, vec!["key", "value", "ttl"]);
+    assert_eq!(save.undocumented_parameters, vec!["ttl"]);
+    assert!(!find(&docs, "Load").documented);
+}
+
+#[test]
+fn test_coverage_per_file_and_module() {
+    let (dir, mapper) = analyze(&[
+        (
+            "pkg/a.py",
+            "def one():\n    \"\"\"One\"\"\"\n\n\ndef two():\n    pass\n",
+        ),
+        ("pkg/b.py", "def three():\n    \"\"\"Three\"\"\"\n"),
+        ("main.py", "def main(argv):\n    pass\n"),
+    ]);
+    let coverage = mapper.doc_coverage();
+
+    assert_eq!(coverage.total.public_components, 4);
+    assert_eq!(coverage.total.documented, 2);
+    assert!((coverage.total.coverage - 0.5).abs() < f64::EPSILON);
+    assert_eq!(coverage.total.parameters, 1);
+    assert_eq!(coverage.total.documented_parameters, 0);
+
+    let a = &coverage.files[&dir.path().join("pkg/a.py").display().to_string()];
+    assert_eq!((a.public_components, a.documented), (2, 1));
+    let pkg = &coverage.modules[&dir.path().join("pkg").display().to_string()];
+    assert_eq!((pkg.public_components, pkg.documented), (3, 2));
+    let root = &coverage.modules[&dir.path().display().to_string()];
+    assert_eq!((root.public_components, root.documented), (1, 0));
+}
+
+#[test]
+fn test_render_undocumented_selects_gathered_files() {
+    let (dir, mapper) = analyze(&[
+        (
+            "tools.py",
+            "def documented(path):\n    \"\"\"Reads path\"\"\"\n\n\ndef partial(path, mode):\n    \"\"\"Opens path\"\"\"\n\n\ndef bare():\n    return 1\n",
+        ),
+        ("other.py", "def elsewhere():\n    return 2\n"),
+    ]);
+    let files = vec![ProjectFile {
+        path: dir.path().join("tools.py"),
+        display_path: "tools.py".to_string(),
+    }];
+    let content = render_undocumented(&mapper.component_docs(), &files);
+
+    assert!(content.contains(
+        "File: tools.py:5-6\nComponent: partial (Function)\nUndocumented parameters: mode\n"
+    ));
+    assert!(content.contains("File: tools.py:9-10\nComponent: bare (Function)\nUndocumented\n"));
+    assert!(content.contains("def bare():\n    return 1\n"));
+    assert!(!content.contains("def documented"));
+    assert!(!content.contains("elsewhere"));
+}
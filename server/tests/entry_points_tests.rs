@@ -0,0 +1,273 @@
+use contexter::entry_points::{EntryPoint, EntryPointKind};
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn entry_points(files: &[(&str, &str)]) -> (TempDir, Vec<EntryPoint>) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    for (name, content) in files {
+        write(&dir.path().join(name), content);
+    }
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    (dir, mapper.insights.entry_points)
+}
+
+/// Kind, name and file relative to `dir` of each entry point
+fn summary(dir: &TempDir, entry_points: &[EntryPoint]) -> Vec<(EntryPointKind, String, String)> {
+    entry_points
+        .iter()
+        .map(|entry| {
+            let file = Path::new(&entry.file)
+                .strip_prefix(dir.path())
+                .expect("Entry point should be in the project")
+                .display()
+                .to_string();
+            (entry.kind, entry.name.clone(), file)
+        })
+        .collect()
+}
+
+fn component_name(entry: &EntryPoint) -> Option<&str> {
+    entry
+        .component
+        .as_deref()
+        .and_then(|id| id.rsplit("::").next())
+}
+
+#[test]
+fn test_rust_binaries_and_main_functions() {
+    let (dir, found) = entry_points(&[
+        (
+            "Cargo.toml",
+            "[package]\nname = \"app\"\n\n[[bin]]\nname = \"tool\"\npath = \"src/tool.rs\"\n",
+        ),
+        ("src/main.rs", "fn main() {\n    app::run();\n}\n"),
+        ("src/tool.rs", "fn main() {}\n"),
+        ("src/bin/extra.rs", "fn main() {}\n"),
+        ("src/lib.rs", "pub fn run() {}\n\nfn leaf() {}\n"),
+        (
+            "examples/demo.rs",
+            "#[tokio::main]\nasync fn main() {\n    app::run();\n}\n",
+        ),
+    ]);
+
+    assert_eq!(
+        summary(&dir, &found),
+        vec![
+            (EntryPointKind::Binary, "app".into(), "src/main.rs".into()),
+            (
+                EntryPointKind::Binary,
+                "extra".into(),
+                "src/bin/extra.rs".into()
+            ),
+            (EntryPointKind::Binary, "tool".into(), "src/tool.rs".into()),
+            (
+                EntryPointKind::AsyncMain,
+                "main".into(),
+                "examples/demo.rs".into()
+            ),
+        ]
+    );
+    assert!(found
+        .iter()
+        .all(|entry| component_name(entry) == Some("main")));
+    assert_eq!(found[0].line, 1);
+}
+
+#[test]
+fn test_python_scripts_and_routes() {
+    let (dir, found) = entry_points(&[
+        (
+            "cli.py",
+            "import sys\n\n\ndef run(args):\n    return args\n\n\nif __name__ == \"__main__\":\n    run(sys.argv)\n",
+        ),
+        ("tool/__main__.py", "print('hi')\n"),
+        (
+            "web.py",
+            "from flask import Flask\n\napp = Flask(__name__)\n\n\n@app.route(\"/users\", methods=[\"POST\"])\ndef create_user():\n    return ''\n\n\n# @app.get(\"/old\")\n@app.get(\"/users/<id>\")\ndef get_user(id):\n    return id\n",
+        ),
+    ]);
+
+    assert_eq!(
+        summary(&dir, &found),
+        vec![
+            (EntryPointKind::Script, "cli".into(), "cli.py".into()),
+            (
+                EntryPointKind::Script,
+                "tool".into(),
+                "tool/__main__.py".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "GET /users/<id>".into(),
+                "web.py".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "POST /users".into(),
+                "web.py".into()
+            ),
+        ]
+    );
+    assert_eq!(found[0].line, 8);
+    assert_eq!(component_name(&found[0]), Some("run"));
+    assert_eq!(component_name(&found[2]), Some("get_user"));
+    assert_eq!(component_name(&found[3]), Some("create_user"));
+}
+
+#[test]
+fn test_package_json_and_express_routes() {
+    let (dir, found) = entry_points(&[
+        (
+            "package.json",
+            r#"{"name": "server", "main": "./index.js", "bin": {"serve": "bin/serve.js"}}"#,
+        ),
+        (
+            "index.js",
+            "const app = require('express')();\n\nfunction listUsers(req, res) {\n  res.send([]);\n}\n\napp.get('/users', auth, listUsers);\napp.post('/users', (req, res) => res.send());\n",
+        ),
+        ("bin/serve.js", "require('../index');\n"),
+    ]);
+
+    assert_eq!(
+        summary(&dir, &found),
+        vec![
+            (
+                EntryPointKind::PackageMain,
+                "server".into(),
+                "index.js".into()
+            ),
+            (
+                EntryPointKind::PackageBin,
+                "serve".into(),
+                "bin/serve.js".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "GET /users".into(),
+                "index.js".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "POST /users".into(),
+                "index.js".into()
+            ),
+        ]
+    );
+    assert_eq!(component_name(&found[2]), Some("listUsers"));
+    assert_eq!(found[3].component, None);
+}
+
+#[test]
+fn test_actix_and_go_routes() {
+    let (dir, found) = entry_points(&[
+        (
+            "server.rs",
+            r#"use actix_web::{get, web, App};
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/health", web::get().to(handlers::health))
+        .service(web::resource("/items").route(web::post().to(create_item)));
+}
+
+async fn create_item() {}
+
+#[get("/about")]
+async fn about() {}
+"#,
+        ),
+        ("handlers.rs", "pub async fn health() {}\n"),
+        (
+            "main.go",
+            "package main\n\nfunc status(w http.ResponseWriter, r *http.Request) {}\n\nfunc main() {\n\thttp.HandleFunc(\"GET /status\", status)\n}\n",
+        ),
+    ]);
+
+    assert_eq!(
+        summary(&dir, &found),
+        vec![
+            (EntryPointKind::Main, "main".into(), "main.go".into()),
+            (
+                EntryPointKind::HttpRoute,
+                "GET /about".into(),
+                "server.rs".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "GET /health".into(),
+                "server.rs".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "GET /status".into(),
+                "main.go".into()
+            ),
+            (
+                EntryPointKind::HttpRoute,
+                "POST /items".into(),
+                "server.rs".into()
+            ),
+        ]
+    );
+    let handlers: Vec<Option<&str>> = found[1..].iter().map(component_name).collect();
+    assert_eq!(
+        handlers,
+        vec![
+            Some("about"),
+            Some("health"),
+            Some("status"),
+            Some("create_item")
+        ]
+    );
+}
+
+#[test]
+fn test_routes_in_test_fixtures_are_not_entry_points() {
+    let (dir, found) = entry_points(&[
+        (
+            "src/server.rs",
+            r#"use actix_web::web;
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/items", web::get().to(list_items));
+}
+
+async fn list_items() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_routes() {
+        let fixture = r"cfg.route(\"/about\", web::get().to(about))";
+        assert!(fixture.contains("/about"));
+    }
+}
+"#,
+        ),
+        (
+            "tests/routes_test.rs",
+            r#"const FIXTURE: &str = "cfg.route(\"/health\", web::get().to(health))";
+"#,
+        ),
+    ]);
+
+    assert_eq!(
+        summary(&dir, &found),
+        vec![(
+            EntryPointKind::HttpRoute,
+            "GET /items".into(),
+            "src/server.rs".into()
+        )]
+    );
+}
@@ -0,0 +1,188 @@
+use contexter::graph_export::{ExportError, ExportFormat, ExportGraph};
+use contexter::graph_query::{Direction, GraphQuery, QueryError};
+use contexter::repo_mapper::{MapLevel, RepositoryMapper};
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+/// `main` calls `load`, which calls `parse`; `report` stands alone
+fn analyzed_project() -> (TempDir, RepositoryMapper) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    fs::write(
+        dir.path().join("app.py"),
+        "from loader import load\n\ndef main():\n    return load('x')\n",
+    )
+    .expect("Failed to write app.py");
+    fs::write(
+        dir.path().join("loader.py"),
+        "from parser import parse\n\ndef load(path):\n    return parse(path)\n",
+    )
+    .expect("Failed to write loader.py");
+    fs::write(
+        dir.path().join("parser.py"),
+        "def parse(text):\n    return text\n\ndef report(a, b):\n    return a < b\n",
+    )
+    .expect("Failed to write parser.py");
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    (dir, mapper)
+}
+
+fn focus(seed: &str, depth: usize) -> GraphQuery {
+    GraphQuery {
+        seed: seed.to_string(),
+        depth,
+        direction: Direction::Both,
+        edge_types: Vec::new(),
+    }
+}
+
+fn labels(graph: &ExportGraph) -> Vec<&str> {
+    let mut labels: Vec<&str> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+    labels.sort_unstable();
+    labels
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Path should have a file name")
+}
+
+#[test]
+fn test_focus_and_depth_limit_the_exported_components() {
+    let (_dir, mapper) = analyzed_project();
+
+    let whole = ExportGraph::components(&mapper, None).expect("Export should succeed");
+    assert!(labels(&whole).contains(&"report"));
+
+    let near =
+        ExportGraph::components(&mapper, Some(&focus("load", 1))).expect("Export should succeed");
+    assert_eq!(labels(&near), vec!["load", "main", "parse"]);
+    assert!(near
+        .edges
+        .iter()
+        .all(|edge| edge.relation == "function_call"));
+
+    let parse =
+        ExportGraph::components(&mapper, Some(&focus("parse", 1))).expect("Export should succeed");
+    assert_eq!(labels(&parse), vec!["load", "parse"]);
+    let parse =
+        ExportGraph::components(&mapper, Some(&focus("parse", 2))).expect("Export should succeed");
+    assert_eq!(labels(&parse), vec!["load", "main", "parse"]);
+
+    assert!(matches!(
+        ExportGraph::components(&mapper, Some(&focus("missing", 1))),
+        Err(ExportError::Component(QueryError::NotFound(name))) if name == "missing"
+    ));
+}
+
+#[test]
+fn test_file_graph_export() {
+    let (_dir, mapper) = analyzed_project();
+
+    let files =
+        ExportGraph::files(&mapper, Some(&focus("parser.py", 1))).expect("Export should succeed");
+    let names: Vec<&str> = files.nodes.iter().map(|n| file_name(&n.id)).collect();
+    assert_eq!(names, vec!["loader.py", "parser.py"]);
+    assert_eq!(files.edges.len(), 1);
+    assert_eq!(files.edges[0].relation, "import");
+
+    assert!(matches!(
+        ExportGraph::files(&mapper, Some(&focus("missing.py", 1))),
+        Err(ExportError::UnknownFile(_))
+    ));
+}
+
+#[test]
+fn test_dot_and_mermaid_output() {
+    let (_dir, mapper) = analyzed_project();
+
+    let dot = mapper
+        .export_graph(
+            MapLevel::Component,
+            ExportFormat::Dot,
+            Some(&focus("load", 1)),
+        )
+        .expect("Export should succeed");
+    assert!(dot.starts_with("digraph contexter {\n"));
+    assert!(dot.contains("[label=\"load\", shape=ellipse"));
+    assert!(dot.contains("::main\" -> \""));
+    assert!(dot.contains("[label=\"function_call\"];"));
+    assert!(dot.trim_end().ends_with('}'));
+
+    let mermaid = mapper
+        .export_graph(MapLevel::File, ExportFormat::Mermaid, None)
+        .expect("Export should succeed");
+    let lines: Vec<&str> = mermaid.lines().collect();
+    assert_eq!(lines[0], "graph LR");
+    assert_eq!(lines.iter().filter(|line| line.contains("-->")).count(), 2);
+    assert!(lines.iter().any(|line| line.ends_with("app.py\"]")));
+}
+
+#[test]
+fn test_graphml_and_jgf_output() {
+    let (_dir, mapper) = analyzed_project();
+
+    let graphml = mapper
+        .export_graph(
+            MapLevel::Component,
+            ExportFormat::GraphMl,
+            Some(&focus("parse", 1)),
+        )
+        .expect("Export should succeed");
+    assert!(graphml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert_eq!(graphml.matches("<node id=").count(), 2);
+    assert_eq!(graphml.matches("<edge id=").count(), 1);
+    assert!(graphml.contains("<data key=\"kind\">function</data>"));
+    assert!(graphml.trim_end().ends_with("</graphml>"));
+
+    let jgf = mapper
+        .export_graph(MapLevel::Component, ExportFormat::Jgf, None)
+        .expect("Export should succeed");
+    let value: serde_json::Value = serde_json::from_str(&jgf).expect("JGF should be JSON");
+    let graph = &value["graph"];
+    assert_eq!(graph["directed"], true);
+    assert_eq!(graph["type"], "component");
+    let nodes = graph["nodes"]
+        .as_object()
+        .expect("Nodes should be an object");
+    let (_, report) = nodes
+        .iter()
+        .find(|(_, node)| node["label"] == "report")
+        .expect("report should be exported");
+    assert_eq!(report["metadata"]["kind"], "function");
+    let edges = graph["edges"].as_array().expect("Edges should be an array");
+    assert!(edges
+        .iter()
+        .all(|edge| nodes.contains_key(edge["source"].as_str().unwrap_or_default())));
+}
+
+#[test]
+fn test_labels_are_escaped() {
+    let (_dir, mut mapper) = analyzed_project();
+    let component = mapper
+        .graph
+        .components
+        .values_mut()
+        .find(|c| c.name == "report")
+        .expect("report should be extracted");
+    component.name = "a<\"b\">".to_string();
+
+    let dot = mapper
+        .export_graph(MapLevel::Component, ExportFormat::Dot, None)
+        .expect("Export should succeed");
+    assert!(dot.contains(r#"label="a<\"b\">""#));
+    let graphml = mapper
+        .export_graph(MapLevel::Component, ExportFormat::GraphMl, None)
+        .expect("Export should succeed");
+    assert!(graphml.contains("a&lt;&quot;b&quot;&gt;"));
+    let mermaid = mapper
+        .export_graph(MapLevel::Component, ExportFormat::Mermaid, None)
+        .expect("Export should succeed");
+    assert!(mermaid.contains("a<#quot;b#quot;>"));
+}
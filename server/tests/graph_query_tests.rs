@@ -0,0 +1,162 @@
+use contexter::graph_query::{Direction, QueryError, Subgraph};
+use contexter::repo_mapper::{EdgeType, RepositoryMapper};
+use std::fs;
+use tempfile::{tempdir, TempDir};
+
+/// `main` calls `Job::run`, which calls `load` and `Job::save`; `load` calls `parse_config`.
+/// Two unrelated `helper` functions share a name.
+fn analyzed_project() -> (TempDir, RepositoryMapper) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let files = [
+        (
+            "jobs.py",
+            r#"from loader import load
+
+
+class Job:
+    def run(self):
+        data = load("x")
+        return self.save(data)
+
+    def save(self, data):
+        return data
+
+
+def main():
+    return Job().run()
+"#,
+        ),
+        (
+            "loader.py",
+            "from parser import parse_config\n\n\ndef load(path):\n    return parse_config(path)\n",
+        ),
+        (
+            "parser.py",
+            "def parse_config(text):\n    return text\n\n\ndef helper():\n    return None\n",
+        ),
+        ("cache.py", "def helper(key):\n    return key\n"),
+    ];
+    for (name, content) in files {
+        fs::write(dir.path().join(name), content).expect("Failed to write source file");
+    }
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    (dir, mapper)
+}
+
+/// Names of the subgraph's nodes with their distances, in order
+fn reached(subgraph: &Subgraph) -> Vec<(&str, usize)> {
+    subgraph
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_str(), node.distance))
+        .collect()
+}
+
+#[test]
+fn test_depth_and_direction() {
+    let (_dir, mapper) = analyzed_project();
+
+    let downstream = mapper
+        .subgraph("parse_config", 3, Direction::Downstream, &[])
+        .expect("Query should succeed");
+    assert_eq!(
+        reached(&downstream),
+        vec![("parse_config", 0), ("load", 1), ("run", 2), ("main", 3)]
+    );
+    let shallow = mapper
+        .subgraph("parse_config", 2, Direction::Downstream, &[])
+        .expect("Query should succeed");
+    assert_eq!(
+        reached(&shallow),
+        vec![("parse_config", 0), ("load", 1), ("run", 2)]
+    );
+    assert!(mapper
+        .subgraph("parse_config", 3, Direction::Upstream, &[])
+        .expect("Query should succeed")
+        .upstream()
+        .is_empty());
+
+    // Both directions are followed separately: `save` is not reached through `run`
+    let both = mapper
+        .subgraph("load", 2, Direction::Both, &[])
+        .expect("Query should succeed");
+    let upstream: Vec<&str> = both.upstream().iter().map(|n| n.name.as_str()).collect();
+    let downstream: Vec<&str> = both.downstream().iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(upstream, vec!["parse_config"]);
+    assert_eq!(downstream, vec!["run", "main"]);
+    assert!(both.nodes.iter().all(|node| node.name != "save"));
+    assert_eq!(both.edges.len(), 3);
+}
+
+#[test]
+fn test_edge_type_filter() {
+    let (_dir, mapper) = analyzed_project();
+
+    let methods = mapper
+        .subgraph("run", 1, Direction::Upstream, &[EdgeType::MethodCall])
+        .expect("Query should succeed");
+    assert_eq!(reached(&methods), vec![("run", 0), ("save", 1)]);
+    assert!(methods
+        .edges
+        .iter()
+        .all(|edge| edge.edge_type == EdgeType::MethodCall));
+
+    let functions = mapper
+        .subgraph("run", 1, Direction::Upstream, &[EdgeType::FunctionCall])
+        .expect("Query should succeed");
+    assert_eq!(reached(&functions), vec![("run", 0), ("load", 1)]);
+}
+
+#[test]
+fn test_fuzzy_matching() {
+    let (dir, mapper) = analyzed_project();
+    let id = |query: &str| {
+        mapper
+            .resolve_component(query)
+            .unwrap_or_else(|e| panic!("'{query}' should resolve: {e}"))
+            .id
+            .clone()
+    };
+    let run = dir.path().join("jobs.py").display().to_string() + "::Job::run";
+
+    assert_eq!(id(&run), run);
+    assert_eq!(id("Job::run"), run);
+    assert_eq!(id("Job.run"), run);
+    assert_eq!(id("jobs.py::Job::run"), run);
+    assert_eq!(id("JOB"), id("Job"));
+    assert_eq!(id("parse_conf"), id("parse_config"));
+    assert_eq!(id("prscfg"), id("parse_config"));
+    assert!(id("parser.py::helper").ends_with("parser.py::helper"));
+
+    assert!(matches!(
+        mapper.resolve_component("nothing_like_it"),
+        Err(QueryError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_ambiguous_names_list_candidates() {
+    let (dir, mapper) = analyzed_project();
+
+    let Err(QueryError::Ambiguous { query, candidates }) = mapper.resolve_component("helper")
+    else {
+        panic!("'helper' should be ambiguous");
+    };
+    assert_eq!(query, "helper");
+    assert_eq!(
+        candidates,
+        vec![
+            dir.path().join("cache.py").display().to_string() + "::helper",
+            dir.path().join("parser.py").display().to_string() + "::helper",
+        ]
+    );
+
+    let error = mapper
+        .subgraph("helper", 1, Direction::Both, &[])
+        .expect_err("An ambiguous seed should fail");
+    assert!(error.to_string().contains("cache.py::helper"));
+}
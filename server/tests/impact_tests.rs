@@ -0,0 +1,193 @@
+use contexter::git::GitError;
+use contexter::impact::{ImpactError, ImpactReport};
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(status.status.success(), "git {args:?} failed: {status:?}");
+}
+
+/// `main` calls `load`, which calls `parse_config`; `test_load` calls `load`, and
+/// `report` is unrelated
+fn project() -> TempDir {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("parser.py"),
+        "def parse_config(text):\n    return text\n",
+    );
+    write(
+        &root.join("loader.py"),
+        "from parser import parse_config\n\n\ndef load(path):\n    return parse_config(path)\n",
+    );
+    write(
+        &root.join("app.py"),
+        "from loader import load\n\n\ndef main():\n    return load(\"x\")\n",
+    );
+    write(&root.join("report.py"), "def report():\n    return 1\n");
+    write(
+        &root.join("tests/test_loader.py"),
+        "from loader import load\n\n\ndef test_load():\n    assert load(\"x\")\n",
+    );
+    dir
+}
+
+fn analyze(root: &Path) -> RepositoryMapper {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    mapper
+}
+
+fn relative(root: &Path, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .expect("Path should be under the root")
+        .display()
+        .to_string()
+}
+
+/// Paths relative to a root, with their distances
+type Distances = Vec<(String, usize)>;
+
+/// Affected files and test files
+fn affected(root: &Path, report: &ImpactReport) -> (Distances, Distances) {
+    let files = report
+        .files
+        .iter()
+        .map(|file| (relative(root, &file.path), file.distance))
+        .collect();
+    let tests = report
+        .tests
+        .iter()
+        .map(|test| (relative(root, &test.path), test.distance))
+        .collect();
+    (files, tests)
+}
+
+#[test]
+fn test_dependents_are_affected_transitively() {
+    let dir = project();
+    let root = dir.path();
+    let mapper = analyze(root);
+
+    let report = mapper
+        .impact(&[PathBuf::from("parser.py")], None)
+        .expect("Impact analysis should succeed");
+    assert_eq!(
+        report.changed_files,
+        vec![root.join("parser.py").display().to_string()]
+    );
+    assert!(report.changed_components[0].ends_with("parser.py::parse_config"));
+
+    let components: Vec<(&str, usize, f64)> = report
+        .components
+        .iter()
+        .map(|c| (c.name.as_str(), c.distance, c.score))
+        .collect();
+    assert_eq!(components, vec![("load", 1, 0.5), ("main", 2, 1.0 / 3.0)]);
+    let (files, tests) = affected(root, &report);
+    assert_eq!(
+        files,
+        vec![("loader.py".to_string(), 1), ("app.py".to_string(), 2)]
+    );
+    assert_eq!(tests, vec![("tests/test_loader.py".to_string(), 2)]);
+    assert_eq!(report.tests[0].tests, vec!["test_load"]);
+
+    let context: Vec<String> = report
+        .context_files()
+        .iter()
+        .map(|path| relative(root, &path.display().to_string()))
+        .collect();
+    assert_eq!(
+        context,
+        vec!["parser.py", "loader.py", "app.py", "tests/test_loader.py"]
+    );
+}
+
+#[test]
+fn test_depth_limits_the_impact() {
+    let dir = project();
+    let root = dir.path();
+    let mapper = analyze(root);
+
+    let report = mapper
+        .impact(&[root.join("parser.py")], Some(1))
+        .expect("Impact analysis should succeed");
+    let (files, tests) = affected(root, &report);
+    assert_eq!(files, vec![("loader.py".to_string(), 1)]);
+    assert!(tests.is_empty());
+
+    // A changed test file is its own affected test, and nothing depends on it
+    let report = mapper
+        .impact(&[PathBuf::from("tests/test_loader.py")], Some(1))
+        .expect("Impact analysis should succeed");
+    let (files, tests) = affected(root, &report);
+    assert!(files.is_empty());
+    assert_eq!(tests, vec![("tests/test_loader.py".to_string(), 0)]);
+}
+
+#[test]
+fn test_changed_files_from_git_diff() {
+    let dir = project();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "Initial commit"]);
+    write(
+        &root.join("loader.py"),
+        "from parser import parse_config\n\n\ndef load(path):\n    return parse_config(path.strip())\n",
+    );
+    fs::remove_file(root.join("report.py")).expect("Failed to remove report.py");
+
+    let mapper = analyze(root);
+    let changed = mapper
+        .changed_files("HEAD")
+        .expect("Diffing against HEAD should succeed");
+    assert_eq!(changed, vec![root.join("loader.py")]);
+    let report = mapper
+        .impact(&changed, None)
+        .expect("Impact analysis should succeed");
+    let (files, tests) = affected(root, &report);
+    assert_eq!(files, vec![("app.py".to_string(), 1)]);
+    assert_eq!(tests, vec![("tests/test_loader.py".to_string(), 1)]);
+
+    assert!(matches!(
+        mapper.changed_files("--output=/tmp/x"),
+        Err(ImpactError::Git(GitError::InvalidRevision(_)))
+    ));
+    assert!(mapper.changed_files("no-such-revision").is_err());
+}
+
+#[test]
+fn test_missing_changes_are_errors() {
+    let dir = project();
+    let mapper = analyze(dir.path());
+
+    assert!(matches!(
+        mapper.impact(&[], None),
+        Err(ImpactError::NoChanges)
+    ));
+    assert!(matches!(
+        mapper.impact(&[PathBuf::from("missing.py")], None),
+        Err(ImpactError::UnknownFile(file)) if file == "missing.py"
+    ));
+}
@@ -1,19 +1,25 @@
 use actix_cors::Cors;
+use actix_web::middleware::from_fn;
 use actix_web::{test, web, App};
-use contexter::config::Config;
-use contexter::server::{AppState, ProjectContentResponse, ProjectListResponse, ProjectMetadata};
+use contexter::config::{Config, ProjectConfig, ProjectRoot};
+use contexter::repo_mapper::MapLevel;
+use contexter::server::{
+    AmbiguousComponentResponse, AnnotationsResponse, AppState, GraphQueryResponse, HealthResponse,
+    ImpactResponse, ProjectContentResponse, ProjectListResponse, ProjectMetadata, ProjectSummary,
+    ReloadResponse, RepositoryAnalysisResponse, RepositoryMapResponse, SuggestResponse,
+};
+use contexter::suggest::SuggestionReason;
 
 use env_logger::Env;
 use log::{debug, info};
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Write;
-use std::sync::Arc;
 use std::sync::Once;
 use tempfile::TempDir;
-use tokio::sync::RwLock;
 
 const TEST_API_KEY: &str = "test_api_key";
+const TEST_READONLY_API_KEY: &str = "test_readonly_api_key";
 
 // Ensure logger is initialized only once
 static INIT: Once = Once::new();
@@ -54,10 +60,13 @@ fn setup_test_app() -> (Config, web::Data<AppState>, TempDir) {
     config
         .api_keys
         .insert("test_key_name".to_string(), hash_api_key(TEST_API_KEY));
+    config.grant_admin("test_key_name");
+    config.api_keys.insert(
+        "readonly_key_name".to_string(),
+        hash_api_key(TEST_READONLY_API_KEY),
+    );
 
-    let app_state = web::Data::new(AppState {
-        config: Arc::new(RwLock::new(config.clone())),
-    });
+    let app_state = web::Data::new(AppState::new(config.clone()));
 
     (config, app_state, temp_dir)
 }
@@ -235,3 +244,1212 @@ async fn test_project_not_found() {
     assert_eq!(resp.status(), 404);
     assert_cors_headers(resp.headers());
 }
+
+#[actix_rt::test]
+async fn test_rate_limited_requests_return_429() {
+    initialize_logger();
+    info!("Running test_rate_limited_requests_return_429");
+
+    let (mut config, _, _temp_dir) = setup_test_app();
+    config.rate_limits.requests_per_minute = 1;
+    config.rate_limits.burst = 1;
+    let app_state = web::Data::new(AppState::new(config));
+
+    let app = test::init_service(
+        App::new()
+            .wrap(Cors::permissive())
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let make_request = || {
+        test::TestRequest::post()
+            .uri("/api/v1/projects/test_project")
+            .insert_header(("X-API-Key", TEST_API_KEY))
+            .set_json(serde_json::json!({ "paths": ["file1.rs"] }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, make_request()).await;
+    assert_eq!(resp.status(), 200);
+
+    let resp = test::call_service(&app, make_request()).await;
+    info!("Response status: {:?}", resp.status());
+    assert_eq!(resp.status(), 429);
+    let retry_after: u64 = resp
+        .headers()
+        .get("Retry-After")
+        .expect("Retry-After header should be set")
+        .to_str()
+        .expect("Retry-After should be ASCII")
+        .parse()
+        .expect("Retry-After should be a number of seconds");
+    assert!(retry_after >= 1);
+}
+
+#[actix_rt::test]
+async fn test_health_and_readiness_are_unauthenticated() {
+    initialize_logger();
+    info!("Running test_health_and_readiness_are_unauthenticated");
+
+    let (_, app_state, _temp_dir) = setup_test_app();
+
+    let app = test::init_service(
+        App::new()
+            .wrap(Cors::permissive())
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/healthz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let resp: HealthResponse =
+        serde_json::from_slice(&body).expect("Failed to parse HealthResponse");
+    assert_eq!(resp.status, "ready");
+}
+
+#[actix_rt::test]
+async fn test_readiness_reports_missing_project_directory() {
+    initialize_logger();
+    info!("Running test_readiness_reports_missing_project_directory");
+
+    let (mut config, _, temp_dir) = setup_test_app();
+    config.add_project(
+        "missing".to_string(),
+        temp_dir.path().join("does_not_exist"),
+    );
+    let app_state = web::Data::new(AppState::new(config));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+
+    let body = test::read_body(resp).await;
+    let resp: HealthResponse =
+        serde_json::from_slice(&body).expect("Failed to parse HealthResponse");
+    assert_eq!(resp.problems.len(), 1);
+    assert!(resp.problems[0].contains("missing"));
+}
+
+#[actix_rt::test]
+async fn test_metrics_report_requests_and_context() {
+    initialize_logger();
+    info!("Running test_metrics_report_requests_and_context");
+
+    let (_, app_state, _temp_dir) = setup_test_app();
+
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(contexter::metrics::track_requests))
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "paths": ["file1.rs"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let text = String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8");
+    debug!("Metrics output:\n{text}");
+
+    assert!(text.contains(
+        "contexter_http_requests_total{route=\"/api/v1/projects/{name}\",method=\"POST\",status=\"200\"} 1"
+    ));
+    assert!(text.contains("contexter_files_processed_total 1"));
+    assert!(!text.contains("contexter_context_bytes_total 0"));
+    assert!(text.contains("contexter_http_request_duration_seconds_bucket"));
+}
+
+#[actix_rt::test]
+async fn test_admin_reload_swaps_config_and_rolls_back_on_error() {
+    initialize_logger();
+    info!("Running test_admin_reload_swaps_config_and_rolls_back_on_error");
+
+    let (mut config, _, temp_dir) = setup_test_app();
+    let config_path = temp_dir.path().join("config.json");
+    let app_state =
+        web::Data::new(AppState::new(config.clone()).with_config_path(config_path.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state.clone())
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    // A valid file with an additional project is swapped in
+    config.add_project("second".to_string(), temp_dir.path().to_path_buf());
+    std::fs::write(
+        &config_path,
+        serde_json::to_string(&config).expect("Config should serialize"),
+    )
+    .expect("Failed to write config file");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/reload")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ReloadResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ReloadResponse");
+    assert_eq!(resp.projects, 2);
+    assert_eq!(app_state.config.read().await.projects.len(), 2);
+
+    // A broken file is rejected and the previous configuration kept
+    std::fs::write(&config_path, "{ not json").expect("Failed to write config file");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/reload")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    assert_eq!(app_state.config.read().await.projects.len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_admin_reload_rejects_config_without_api_keys() {
+    initialize_logger();
+    info!("Running test_admin_reload_rejects_config_without_api_keys");
+
+    let (config, _, temp_dir) = setup_test_app();
+    let config_path = temp_dir.path().join("config.json");
+    let app_state =
+        web::Data::new(AppState::new(config.clone()).with_config_path(config_path.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state.clone())
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let mut locked_out = config;
+    locked_out.api_keys.clear();
+    std::fs::write(
+        &config_path,
+        serde_json::to_string(&locked_out).expect("Config should serialize"),
+    )
+    .expect("Failed to write config file");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/reload")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    assert_eq!(app_state.config.read().await.api_keys.len(), 2);
+}
+
+#[actix_rt::test]
+async fn test_project_management_endpoints() {
+    initialize_logger();
+    info!("Running test_project_management_endpoints");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let new_project_path = temp_dir.path().join("new_project");
+    std::fs::create_dir_all(&new_project_path).expect("Failed to create new project directory");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state.clone())
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    // Create
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "name": "new_project", "path": new_project_path }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    assert!(app_state
+        .config
+        .read()
+        .await
+        .projects
+        .contains_key("new_project"));
+
+    // Creating the same name again conflicts
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "name": "new_project", "path": new_project_path }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+
+    // Rename
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/projects/new_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "name": "renamed_project" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let summary: ProjectSummary =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectSummary");
+    assert_eq!(summary.name, "renamed_project");
+
+    // Delete
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/projects/renamed_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+    assert_eq!(app_state.config.read().await.projects.len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_project_management_validation_and_scope() {
+    initialize_logger();
+    info!("Running test_project_management_validation_and_scope");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state.clone())
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    // Non-admin keys are forbidden
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_READONLY_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    // Paths must exist
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({
+            "name": "ghost",
+            "path": temp_dir.path().join("does_not_exist")
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // Unknown projects cannot be updated
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/projects/ghost")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "name": "still_ghost" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    assert_eq!(app_state.config.read().await.projects.len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_multi_root_projects_keep_their_roots() {
+    initialize_logger();
+    info!("Running test_multi_root_projects_keep_their_roots");
+
+    let (mut config, _, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    let roots = vec![
+        ProjectRoot {
+            path: project_path.clone(),
+            prefix: "backend".to_string(),
+        },
+        ProjectRoot {
+            path: project_path.join("subfolder"),
+            prefix: "frontend".to_string(),
+        },
+    ];
+    config.add_multi_root_project("product".to_string(), roots.clone());
+    let app_state = web::Data::new(AppState::new(config));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state.clone())
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    // A single path would replace every root
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/projects/product")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "path": project_path }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // Renaming keeps the roots
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/projects/product")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "name": "platform" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        app_state.config.read().await.projects.get("platform"),
+        Some(&ProjectConfig::MultiRoot { roots })
+    );
+}
+
+#[actix_rt::test]
+async fn test_project_changes_are_persisted() {
+    initialize_logger();
+    info!("Running test_project_changes_are_persisted");
+
+    let (config, _, temp_dir) = setup_test_app();
+    let config_path = temp_dir.path().join("config.json");
+    let app_state = web::Data::new(AppState::new(config).with_config_path(config_path.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+
+    let saved: Config = serde_json::from_str(
+        &std::fs::read_to_string(&config_path).expect("Config file should be written"),
+    )
+    .expect("Saved config should parse");
+    assert!(saved.projects.is_empty());
+    assert!(saved.is_admin_key("test_key_name"));
+}
+
+#[actix_rt::test]
+async fn test_repository_map_file_level() {
+    initialize_logger();
+    info!("Running test_repository_map_file_level");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(project_path.join("main.rs"), "mod file1;\n").expect("Failed to write main.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?level=file")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let resp: RepositoryMapResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryMapResponse");
+    assert_eq!(resp.level, MapLevel::File);
+    assert!(resp.map.starts_with("File Structure"));
+    let graph = resp
+        .file_graph
+        .expect("File level maps include the file graph");
+    assert_eq!(graph.files.len(), 3);
+    assert_eq!(graph.edges.len(), 1);
+    assert!(graph.edges[0].from.ends_with("main.rs"));
+    assert!(graph.edges[0].to.ends_with("file1.rs"));
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body = test::read_body(resp).await;
+    let resp: RepositoryMapResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryMapResponse");
+    assert_eq!(resp.level, MapLevel::Component);
+    assert!(resp.file_graph.is_none());
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?level=package")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_repository_map_export_formats() {
+    initialize_logger();
+    info!("Running test_repository_map_export_formats");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(project_path.join("main.rs"), "mod file1;\n").expect("Failed to write main.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?level=file&format=dot&focus=main.rs")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok()),
+        Some("text/vnd.graphviz; charset=utf-8")
+    );
+    let body = test::read_body(resp).await;
+    let dot = String::from_utf8(body.to_vec()).expect("DOT should be UTF-8");
+    assert!(dot.starts_with("digraph contexter {"));
+    assert_eq!(dot.matches(" -> ").count(), 1);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?format=jgf")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let jgf: serde_json::Value = serde_json::from_slice(&body).expect("JGF should be JSON");
+    assert_eq!(jgf["graph"]["type"], "component");
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?level=file&format=mermaid&focus=missing.rs")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/map?format=svg")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_suggest_related_files() {
+    initialize_logger();
+    info!("Running test_suggest_related_files");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(project_path.join("main.rs"), "mod file1;\n").expect("Failed to write main.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/suggest")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "files": ["file1.rs"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let resp: SuggestResponse =
+        serde_json::from_slice(&body).expect("Failed to parse SuggestResponse");
+    assert_eq!(resp.project_name, "test_project");
+    assert_eq!(resp.suggestions[0].path, "main.rs");
+    assert!(resp.suggestions[0]
+        .reasons
+        .contains(&SuggestionReason::Imports {
+            seed: "file1.rs".to_string()
+        }));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/suggest")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "files": ["missing.rs"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_context_with_tests_and_untested_components() {
+    initialize_logger();
+    info!("Running test_context_with_tests_and_untested_components");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("math.py"),
+        "def add(a, b):\n    return a + b\n\ndef sub(a, b):\n    return a - b\n",
+    )
+    .expect("Failed to write math.py");
+    std::fs::write(
+        project_path.join("test_math.py"),
+        "from math import add\n\ndef test_add():\n    assert add(1, 2) == 3\n",
+    )
+    .expect("Failed to write test_math.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "paths": ["math.py"], "with_tests": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp.content.contains("def sub(a, b):"));
+    assert!(resp.content.contains("def test_add():"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/analyze")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: RepositoryAnalysisResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryAnalysisResponse");
+    assert_eq!(resp.untested_public_components.len(), 1);
+    assert!(resp.untested_public_components[0].ends_with("sub"));
+}
+
+#[actix_rt::test]
+async fn test_analyze_reports_layers_and_violations() {
+    initialize_logger();
+    info!("Running test_analyze_reports_layers_and_violations");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join(".contexter.toml"),
+        "[[layers]]\nname = \"api\"\npaths = [\"api.py\"]\n\n[[layers]]\nname = \"store\"\npaths = [\"store.py\"]\n",
+    )
+    .expect("Failed to write .contexter.toml");
+    std::fs::write(project_path.join("api.py"), "from store import load\n")
+        .expect("Failed to write api.py");
+    std::fs::write(project_path.join("store.py"), "from api import app\n")
+        .expect("Failed to write store.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/analyze")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: RepositoryAnalysisResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryAnalysisResponse");
+    let layers: Vec<&str> = resp
+        .architectural_layers
+        .iter()
+        .map(|layer| layer.name.as_str())
+        .collect();
+    assert_eq!(layers, vec!["api", "store"]);
+    assert_eq!(resp.layering_violations.len(), 1);
+    assert!(resp.layering_violations[0].from.ends_with("store.py"));
+    assert_eq!(resp.layering_violations[0].to_layer, "api");
+}
+
+#[actix_rt::test]
+async fn test_graph_query() {
+    initialize_logger();
+    info!("Running test_graph_query");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("app.py"),
+        "from store import save\n\ndef handle():\n    return save()\n\ndef helper():\n    pass\n",
+    )
+    .expect("Failed to write app.py");
+    std::fs::write(
+        project_path.join("store.py"),
+        "def save():\n    return 1\n\ndef helper():\n    pass\n",
+    )
+    .expect("Failed to write store.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/graph/query")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "seed": "save", "direction": "downstream" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: GraphQueryResponse =
+        serde_json::from_slice(&body).expect("Failed to parse GraphQueryResponse");
+    assert!(resp.subgraph.seed.ends_with("store.py::save"));
+    let names: Vec<&str> = resp
+        .subgraph
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["save", "handle"]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/graph/query")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "seed": "helper" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let body = test::read_body(resp).await;
+    let resp: AmbiguousComponentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse AmbiguousComponentResponse");
+    assert_eq!(resp.candidates.len(), 2);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/graph/query")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "seed": "zzz_missing", "edge_types": ["FunctionCall"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_impact_analysis() {
+    initialize_logger();
+    info!("Running test_impact_analysis");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(project_path.join("store.py"), "def save():\n    return 1\n")
+        .expect("Failed to write store.py");
+    std::fs::write(
+        project_path.join("app.py"),
+        "from store import save\n\ndef handle():\n    return save()\n",
+    )
+    .expect("Failed to write app.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/impact")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "files": ["store.py"], "with_context": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ImpactResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ImpactResponse");
+    assert_eq!(resp.impact.changed_files, vec!["store.py"]);
+    let files: Vec<(&str, usize)> = resp
+        .impact
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file.distance))
+        .collect();
+    assert_eq!(files, vec![("app.py", 1)]);
+    let context = resp.context.expect("Context should be gathered");
+    assert!(context.contains("File: store.py"));
+    assert!(context.contains("File: app.py"));
+
+    for body in [
+        serde_json::json!({ "files": ["missing.py"] }),
+        serde_json::json!({ "files": [] }),
+        serde_json::json!({ "diff": "--output=x" }),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/projects/test_project/impact")
+            .insert_header(("X-API-Key", TEST_API_KEY))
+            .set_json(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_analyze_reports_dead_code() {
+    initialize_logger();
+    info!("Running test_analyze_reports_dead_code");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("app.py"),
+        "import os\n\n\ndef main():\n    return 1\n\n\ndef forgotten():\n    return 2\n",
+    )
+    .expect("Failed to write app.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/analyze")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: RepositoryAnalysisResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryAnalysisResponse");
+    let unused: Vec<&str> = resp
+        .dead_code
+        .components
+        .iter()
+        .map(|component| component.name.as_str())
+        .collect();
+    assert_eq!(unused, vec!["forgotten"]);
+    assert_eq!(resp.dead_code.imports.len(), 1);
+    assert_eq!(resp.dead_code.imports[0].name, "os");
+}
+
+#[actix_web::test]
+async fn test_doc_coverage_and_undocumented_preset() {
+    initialize_logger();
+    info!("Running test_doc_coverage_and_undocumented_preset");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("shapes.py"),
+        "def area(width, height):\n    \"\"\"Area of a width by height rectangle\"\"\"\n    return width * height\n\n\ndef perimeter(width, height):\n    return 2 * (width + height)\n",
+    )
+    .expect("Failed to write shapes.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project/analyze")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: RepositoryAnalysisResponse =
+        serde_json::from_slice(&body).expect("Failed to parse RepositoryAnalysisResponse");
+    let total = &resp.doc_coverage.total;
+    assert_eq!((total.public_components, total.documented), (2, 1));
+    assert_eq!((total.parameters, total.documented_parameters), (4, 2));
+    assert_eq!(resp.doc_coverage.files.len(), 1);
+    assert_eq!(resp.doc_coverage.modules.len(), 1);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "paths": ["shapes.py"], "preset": "undocumented" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp.content.contains("File: shapes.py:6-7"));
+    assert!(resp.content.contains("def perimeter(width, height):"));
+    assert!(!resp.content.contains("def area"));
+}
+
+#[actix_web::test]
+async fn test_context_signatures_only() {
+    initialize_logger();
+    info!("Running test_context_signatures_only");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("lib.rs"),
+        "/// Adds two numbers\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nfn secret() {}\n",
+    )
+    .expect("Failed to write lib.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "signatures_only": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp.content.contains("File: lib.rs"));
+    assert!(resp
+        .content
+        .contains("/// Adds two numbers\npub fn add(a: i32, b: i32) -> i32 { ... }\n"));
+    assert!(!resp.content.contains("a + b"));
+    assert!(!resp.content.contains("secret"));
+    // The placeholder files declare nothing public
+    assert!(!resp.content.contains("file1.rs"));
+}
+
+#[actix_web::test]
+async fn test_context_file_limits() {
+    initialize_logger();
+    info!("Running test_context_file_limits");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    let body: Vec<String> = (1..=30).map(|n| format!("    let x{n} = {n};")).collect();
+    std::fs::write(
+        project_path.join("long.rs"),
+        format!("pub fn long() {{\n{}\n}}\n", body.join("\n")),
+    )
+    .expect("Failed to write long.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({ "max_file_lines": 10, "keep_lines": 2 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp.content.contains(
+        "Truncated: 28 of 32 lines elided in 1 section(s), 1 of them function bodies, to fit 10 lines\n"
+    ));
+    assert!(resp.content.contains(
+        "pub fn long() {\n    let x1 = 1;\n    // ... 28 lines elided ...\n    let x30 = 30;\n}\n"
+    ));
+    // Files within the limit are left alone
+    assert!(resp.content.contains("File: file1.rs"));
+    assert_eq!(resp.content.matches("Truncated:").count(), 1);
+}
+
+#[actix_web::test]
+async fn test_context_parts() {
+    initialize_logger();
+    info!("Running test_context_parts");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    let functions: Vec<String> = (1..=40)
+        .map(|n| {
+            format!(
+                "fn step_{n}() {{\n    let value = {n} * 2;\n    println!(\"{{value}}\");\n}}\n"
+            )
+        })
+        .collect();
+    std::fs::write(project_path.join("steps.rs"), functions.join("\n"))
+        .expect("Failed to write steps.rs");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let request = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/v1/projects/test_project")
+            .insert_header(("X-API-Key", TEST_API_KEY))
+            .set_json(body)
+            .to_request()
+    };
+    let resp = test::call_service(
+        &app,
+        request(serde_json::json!({ "chunk_tokens": 400, "part": 2 })),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    let parts = resp
+        .parts
+        .expect("Chunked responses say how many parts there are");
+    assert_eq!(resp.part, Some(2));
+    assert!(parts > 2);
+    assert!(resp.content.starts_with(&format!(
+        "========================================\nPart 2/{parts}, "
+    )));
+    assert!(!resp.content.contains("Table of Contents:\n"));
+    assert!(!resp.files.is_empty());
+
+    // The table of contents is in the first part
+    let resp = test::call_service(
+        &app,
+        request(serde_json::json!({ "chunk_tokens": 400, "part": 1 })),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp.content.contains("Table of Contents:\n"));
+    assert!(resp.content.contains("- steps.rs (parts "));
+
+    let resp = test::call_service(
+        &app,
+        request(serde_json::json!({ "chunk_tokens": 400, "part": parts + 1 })),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_context_rejects_paths_outside_the_project() {
+    initialize_logger();
+    info!("Running test_context_rejects_paths_outside_the_project");
+
+    let (_, app_state, _temp_dir) = setup_test_app();
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    for path in ["..", "subfolder/../../test_project", "/etc"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/projects/test_project")
+            .insert_header(("X-API-Key", TEST_API_KEY))
+            .set_json(serde_json::json!({ "paths": [path] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400, "{path} should be rejected");
+    }
+}
+
+#[actix_web::test]
+async fn test_context_ranking() {
+    initialize_logger();
+    info!("Running test_context_ranking");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::write(
+        project_path.join("util.py"),
+        "def helper():\n    return 1\n",
+    )
+    .expect("Failed to write util.py");
+    std::fs::write(
+        project_path.join("app.py"),
+        "from util import helper\n\n\ndef main():\n    return helper()\n",
+    )
+    .expect("Failed to write app.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/projects/test_project")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .set_json(serde_json::json!({
+            "rank": { "fan-in": 1 },
+            "priority": ["subfolder/**"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    let position = |name: &str| {
+        resp.content
+            .find(&format!("File: {name}\n"))
+            .unwrap_or_else(|| panic!("{name} should be in the content"))
+    };
+    assert!(position("subfolder/file2.rs") < position("util.py"));
+    assert!(position("util.py") < position("app.py"));
+    assert!(position("util.py") < position("file1.rs"));
+}
+
+#[actix_web::test]
+async fn test_context_deduplication() {
+    initialize_logger();
+    info!("Running test_context_deduplication");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    let body = "def area(width, height):\n    return width * height\n";
+    std::fs::write(
+        project_path.join("shapes.py"),
+        format!("# Copyright 2024 Example Authors\n\n{body}"),
+    )
+    .expect("Failed to write shapes.py");
+    std::fs::write(
+        project_path.join("vendored.py"),
+        format!("# SPDX-License-Identifier: MIT\n{body}"),
+    )
+    .expect("Failed to write vendored.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let request = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/v1/projects/test_project")
+            .insert_header(("X-API-Key", TEST_API_KEY))
+            .set_json(body)
+            .to_request()
+    };
+    let resp = test::call_service(
+        &app,
+        request(serde_json::json!({
+            "strip_license_headers": true,
+            "near_duplicates": 0.9
+        })),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: ProjectContentResponse =
+        serde_json::from_slice(&body).expect("Failed to parse ProjectContentResponse");
+    assert!(resp
+        .content
+        .contains("License Header: 2 line(s) stripped\nDuplicates: vendored.py (identical)\n"));
+    assert!(!resp.content.contains("Copyright"));
+    assert!(!resp.content.contains("File: vendored.py"));
+
+    let resp = test::call_service(&app, request(serde_json::json!({ "near_duplicates": 2 }))).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_list_annotations() {
+    initialize_logger();
+    info!("Running test_list_annotations");
+
+    let (_, app_state, temp_dir) = setup_test_app();
+    let project_path = temp_dir.path().join("test_project");
+    std::fs::create_dir_all(project_path.join("jobs")).expect("Failed to create jobs");
+    std::fs::write(
+        project_path.join("jobs/run.py"),
+        "def run():\n    # TODO: retry\n    # FIXME: timeout\n    return 1\n",
+    )
+    .expect("Failed to write jobs/run.py");
+    std::fs::write(project_path.join("app.py"), "# TODO: logging\n")
+        .expect("Failed to write app.py");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(app_state)
+            .configure(contexter::server::config_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/annotations?kind=todo&path=jobs")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let resp: AnnotationsResponse =
+        serde_json::from_slice(&body).expect("Failed to parse AnnotationsResponse");
+    assert_eq!(resp.annotations.len(), 1);
+    assert_eq!(resp.annotations[0].file, "jobs/run.py");
+    assert_eq!(resp.annotations[0].text, "retry");
+    assert!(resp.annotations[0]
+        .component
+        .as_deref()
+        .is_some_and(|id| id.ends_with("::run")));
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/projects/test_project/annotations?kind=todo,note")
+        .insert_header(("X-API-Key", TEST_API_KEY))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
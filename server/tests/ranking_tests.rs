@@ -0,0 +1,286 @@
+use contexter::contexter::{concatenate_project_files_with, ConcatOptions, ProjectFile};
+use contexter::ranking::{parse_weighted_signal, RankSignal, Ranking};
+use contexter::repo_mapper::RepositoryMapper;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(status.status.success(), "git {args:?} failed: {status:?}");
+}
+
+const FILES: [&str; 5] = [
+    "app.py",
+    "core/settings.py",
+    "loader.py",
+    "parser.py",
+    "report.py",
+];
+
+/// `app` imports `loader` and `parser`, `loader` imports `parser`, `report` imports
+/// nothing and `core/settings` is imported by nothing
+fn project() -> TempDir {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("parser.py"),
+        "def parse_config(text):\n    return text\n",
+    );
+    write(
+        &root.join("loader.py"),
+        "from parser import parse_config\n\n\ndef load(path):\n    return parse_config(path)\n",
+    );
+    write(
+        &root.join("app.py"),
+        "from loader import load\nfrom parser import parse_config\n\n\ndef main():\n    return load(\"x\")\n",
+    );
+    write(&root.join("report.py"), "def report():\n    return 1\n");
+    write(&root.join("core/settings.py"), "DEBUG = False\n");
+    dir
+}
+
+fn analyze(root: &Path) -> RepositoryMapper {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    mapper
+}
+
+/// Display paths of the project's files in the order `ranking` puts them
+fn ranked(root: &Path, ranking: &Ranking) -> Vec<String> {
+    let mapper = analyze(root);
+    let mut files: Vec<ProjectFile> = FILES
+        .iter()
+        .map(|name| ProjectFile {
+            path: root.join(name),
+            display_path: (*name).to_string(),
+        })
+        .collect();
+    ranking.rank(&mapper, &mut files);
+    files.into_iter().map(|file| file.display_path).collect()
+}
+
+fn signals(signals: &[(RankSignal, f64)]) -> BTreeMap<RankSignal, f64> {
+    signals.iter().copied().collect()
+}
+
+#[test]
+fn test_parse_weighted_signal() {
+    assert_eq!(
+        parse_weighted_signal("fan-in"),
+        Ok((RankSignal::FanIn, 1.0))
+    );
+    assert_eq!(
+        parse_weighted_signal("recency=2.5"),
+        Ok((RankSignal::Recency, 2.5))
+    );
+    assert_eq!(
+        parse_weighted_signal("dependencies=0"),
+        Ok((RankSignal::Dependencies, 0.0))
+    );
+    assert!(parse_weighted_signal("popularity").is_err());
+    assert!(parse_weighted_signal("fan-in=-1").is_err());
+    assert!(parse_weighted_signal("fan-in=lots").is_err());
+}
+
+#[test]
+fn test_priority_patterns_come_first_in_order() {
+    let dir = project();
+    let ranking = Ranking {
+        priority: vec!["report.py".to_string(), "core/**".to_string()],
+        ..Ranking::default()
+    };
+    assert_eq!(
+        ranked(dir.path(), &ranking),
+        vec![
+            "report.py",
+            "core/settings.py",
+            "app.py",
+            "loader.py",
+            "parser.py"
+        ]
+    );
+}
+
+#[test]
+fn test_fan_in_and_dependencies() {
+    let dir = project();
+    let fan_in = Ranking {
+        signals: signals(&[(RankSignal::FanIn, 1.0)]),
+        ..Ranking::default()
+    };
+    let order = ranked(dir.path(), &fan_in);
+    assert_eq!(order[..2], ["parser.py", "loader.py"]);
+
+    let dependencies = Ranking {
+        signals: signals(&[(RankSignal::Dependencies, 1.0)]),
+        ..Ranking::default()
+    };
+    let order = ranked(dir.path(), &dependencies);
+    let position = |name: &str| order.iter().position(|file| file == name);
+    assert!(position("parser.py") < position("loader.py"));
+    assert!(position("loader.py") < position("app.py"));
+
+    // Priority still wins over any score
+    let both = Ranking {
+        priority: vec!["app.py".to_string()],
+        ..fan_in
+    };
+    assert_eq!(
+        ranked(dir.path(), &both)[..3],
+        ["app.py", "parser.py", "loader.py"]
+    );
+}
+
+#[test]
+fn test_complexity_puts_dense_logic_first() {
+    let dir = project();
+    let root = dir.path();
+    write(
+        &root.join("report.py"),
+        "def report(rows):\n    for row in rows:\n        if row:\n            if row.ok:\n                return 1\n    return 0\n",
+    );
+    write(
+        &root.join("loader.py"),
+        "from parser import parse_config\n\n\ndef load(path):\n    if path:\n        return parse_config(path)\n    return None\n",
+    );
+
+    let ranking = Ranking {
+        signals: signals(&[(RankSignal::Complexity, 1.0)]),
+        ..Ranking::default()
+    };
+    // Files without branches score nothing and follow by path
+    assert_eq!(
+        ranked(root, &ranking),
+        vec![
+            "report.py",
+            "loader.py",
+            "app.py",
+            "core/settings.py",
+            "parser.py"
+        ]
+    );
+    assert_eq!(
+        parse_weighted_signal("complexity=3"),
+        Ok((RankSignal::Complexity, 3.0))
+    );
+}
+
+#[test]
+fn test_recency_follows_git_history() {
+    let dir = project();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "Initial"]);
+    write(&root.join("report.py"), "def report():\n    return 2\n");
+    git(root, &["commit", "-q", "-am", "Report two"]);
+    write(
+        &root.join("loader.py"),
+        "def load(path):\n    return path\n",
+    );
+    git(root, &["commit", "-q", "-am", "Simplify loader"]);
+
+    let ranking = Ranking {
+        signals: signals(&[(RankSignal::Recency, 1.0)]),
+        ..Ranking::default()
+    };
+    assert_eq!(ranked(root, &ranking)[..2], ["loader.py", "report.py"]);
+}
+
+#[test]
+fn test_configured_ranking_and_overrides() {
+    let dir = project();
+    let root = dir.path();
+    write(
+        &root.join(".contexter.toml"),
+        "[ranking]\nsignals = { fan-in = 2, recency = 1 }\npriority = [\"core/**\"]\n",
+    );
+    let configured = Ranking::configured(&[root.to_path_buf()]).expect("Settings should load");
+    assert_eq!(
+        configured.signals,
+        signals(&[(RankSignal::FanIn, 2.0), (RankSignal::Recency, 1.0)])
+    );
+    assert_eq!(configured.priority, vec!["core/**"]);
+
+    // Requested signals replace the configured ones, but configured priorities remain
+    let requested = Ranking {
+        signals: signals(&[(RankSignal::Dependencies, 1.0)]),
+        ..Ranking::default()
+    }
+    .or(configured);
+    assert_eq!(
+        requested.signals,
+        signals(&[(RankSignal::Dependencies, 1.0)])
+    );
+    assert_eq!(requested.priority, vec!["core/**"]);
+
+    write(
+        &root.join(".contexter.toml"),
+        "[ranking]\npriority = [\"src/[\"]\n",
+    );
+    assert!(Ranking::configured(&[root.to_path_buf()]).is_err());
+}
+
+#[test]
+fn test_ranked_files_come_first_across_sections() {
+    let dir = project();
+    let root = dir.path();
+    write(&root.join("README.md"), "# Loader\n");
+    write(&root.join("settings.toml"), "debug = false\n");
+    let mapper = analyze(root);
+    let mut files: Vec<ProjectFile> = ["README.md", "app.py", "parser.py", "settings.toml"]
+        .iter()
+        .map(|name| ProjectFile {
+            path: root.join(name),
+            display_path: (*name).to_string(),
+        })
+        .collect();
+    let ranking = Ranking {
+        signals: signals(&[(RankSignal::FanIn, 1.0)]),
+        priority: vec!["app.py".to_string()],
+    };
+    ranking.rank(&mapper, &mut files);
+    let options = ConcatOptions {
+        ranked: true,
+        ..ConcatOptions::default()
+    };
+    let (content, filenames) =
+        concatenate_project_files_with(files, &options, |_, content| Some(content))
+            .expect("Concatenation should succeed");
+
+    // Source files outrank the configuration and documentation, which otherwise lead
+    assert_eq!(
+        filenames,
+        vec!["app.py", "parser.py", "README.md", "settings.toml"]
+    );
+    let banners: Vec<&str> = content
+        .lines()
+        .filter(|line| line.starts_with("Section: "))
+        .collect();
+    assert_eq!(
+        banners,
+        vec![
+            "Section: Source Files",
+            "Section: Documentation",
+            "Section: Configuration Files"
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+use contexter::config::RateLimitConfig;
+use contexter::rate_limit::{LimitExceeded, RequestLimiter};
+
+#[test]
+fn test_rate_limit_is_per_key() {
+    let limits = RateLimitConfig {
+        requests_per_minute: 60,
+        burst: 2,
+        max_concurrent_operations: 0,
+    };
+    let limiter = RequestLimiter::new(&limits);
+
+    assert!(limiter.check_rate("alice", &limits).is_ok());
+    assert!(limiter.check_rate("alice", &limits).is_ok());
+    assert!(matches!(
+        limiter.check_rate("alice", &limits),
+        Err(LimitExceeded::RateLimited { .. })
+    ));
+
+    // Another key has its own allowance
+    assert!(limiter.check_rate("bob", &limits).is_ok());
+}
+
+#[test]
+fn test_zero_rate_disables_limiting() {
+    let limits = RateLimitConfig {
+        requests_per_minute: 0,
+        burst: 1,
+        max_concurrent_operations: 0,
+    };
+    let limiter = RequestLimiter::new(&limits);
+
+    for _ in 0..100 {
+        assert!(limiter.check_rate("alice", &limits).is_ok());
+    }
+}
+
+#[test]
+fn test_concurrent_operation_cap() {
+    let limits = RateLimitConfig {
+        requests_per_minute: 0,
+        burst: 1,
+        max_concurrent_operations: 1,
+    };
+    let limiter = RequestLimiter::new(&limits);
+
+    let permit = limiter
+        .try_begin_operation(&limits)
+        .expect("First operation should be admitted");
+    assert!(permit.is_some());
+
+    let rejected = limiter
+        .try_begin_operation(&limits)
+        .expect_err("Second operation should be rejected");
+    assert_eq!(rejected.retry_after_secs(), 1);
+
+    drop(permit);
+    assert!(limiter.try_begin_operation(&limits).is_ok());
+}
+
+#[test]
+fn test_concurrency_cap_follows_reloaded_limits() {
+    let one = RateLimitConfig {
+        requests_per_minute: 0,
+        burst: 1,
+        max_concurrent_operations: 1,
+    };
+    let limiter = RequestLimiter::new(&one);
+    let _running = limiter
+        .try_begin_operation(&one)
+        .expect("First operation should be admitted");
+    assert!(limiter.try_begin_operation(&one).is_err());
+
+    // A raised cap admits more operations at once
+    let two = RateLimitConfig {
+        max_concurrent_operations: 2,
+        ..one.clone()
+    };
+    let first = limiter
+        .try_begin_operation(&two)
+        .expect("First operation under the new cap should be admitted");
+    let _second = limiter
+        .try_begin_operation(&two)
+        .expect("Second operation under the new cap should be admitted");
+    assert!(limiter.try_begin_operation(&two).is_err());
+    drop(first);
+    assert!(limiter.try_begin_operation(&two).is_ok());
+
+    // Without a cap every operation is admitted
+    let uncapped = RateLimitConfig {
+        max_concurrent_operations: 0,
+        ..one
+    };
+    assert!(matches!(limiter.try_begin_operation(&uncapped), Ok(None)));
+}
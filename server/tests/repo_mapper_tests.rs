@@ -0,0 +1,950 @@
+use contexter::language::{Container, LanguageAnalyzer, SymbolIndex};
+use contexter::repo_mapper::{
+    CodeComponent, ComponentType, EdgeType, RepositoryMapper, Visibility,
+};
+use contexter::syntax::{
+    extract_components, ExtractedComponent, FileSyntax, ImportDecl, SourceLanguage,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn find<'a>(mapper: &'a RepositoryMapper, name: &str) -> &'a CodeComponent {
+    mapper
+        .graph
+        .components
+        .values()
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("Component {name} should have been extracted"))
+}
+
+fn find_typed<'a>(
+    mapper: &'a RepositoryMapper,
+    name: &str,
+    component_type: &ComponentType,
+) -> &'a CodeComponent {
+    mapper
+        .graph
+        .components
+        .values()
+        .find(|c| c.name == name && c.component_type == *component_type)
+        .unwrap_or_else(|| panic!("{component_type:?} {name} should have been extracted"))
+}
+
+#[test]
+fn test_rust_components_have_types_visibility_and_nesting() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(
+        &dir.path().join("lib.rs"),
+        r"
+pub struct Parser {
+    input: String,
+}
+
+pub(crate) enum Token {
+    Word,
+}
+
+pub trait Parse {
+    fn parse(&self) -> Token;
+}
+
+impl Parser {
+    pub fn new(input: String) -> Self {
+        Self { input }
+    }
+
+    fn advance(&mut self) {}
+}
+
+impl Parse for Parser {
+    fn parse(&self) -> Token {
+        Token::Word
+    }
+}
+
+mod helpers {
+    pub fn trim(s: &str) -> &str {
+        s.trim()
+    }
+}
+",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+
+    let parser = find_typed(&mapper, "Parser", &ComponentType::Class);
+    assert_eq!(parser.component_type, ComponentType::Class);
+    assert_eq!(parser.visibility, Visibility::Public);
+    assert_eq!(parser.start_line, 2);
+    assert_eq!(parser.end_line, 4);
+
+    let token = find(&mapper, "Token");
+    assert_eq!(token.component_type, ComponentType::Enum);
+    assert_eq!(token.visibility, Visibility::Internal);
+
+    let parse_trait = find_typed(&mapper, "Parse", &ComponentType::Interface);
+    assert_eq!(parse_trait.component_type, ComponentType::Interface);
+    assert_eq!(parse_trait.children.len(), 1);
+
+    let new = find(&mapper, "new");
+    assert_eq!(new.component_type, ComponentType::Method);
+    assert_eq!(new.visibility, Visibility::Public);
+    assert_eq!(new.parent.as_deref(), Some(parser.id.as_str()));
+    assert!(new.id.ends_with("lib.rs::Parser::new"));
+
+    let advance = find(&mapper, "advance");
+    assert_eq!(advance.visibility, Visibility::Private);
+    assert!(parser.children.contains(&advance.id));
+
+    let helpers = find(&mapper, "helpers");
+    assert_eq!(helpers.component_type, ComponentType::Module);
+    let trim = find(&mapper, "trim");
+    assert_eq!(trim.component_type, ComponentType::Function);
+    assert_eq!(trim.parent.as_deref(), Some(helpers.id.as_str()));
+}
+
+#[test]
+fn test_python_methods_and_private_names() {
+    let source = r"
+class Repository:
+    def __init__(self, path):
+        self.path = path
+
+    @property
+    def name(self):
+        return self.path
+
+    def _scan(self):
+        pass
+
+def _helper():
+    pass
+
+def main():
+    pass
+";
+    let components =
+        extract_components(SourceLanguage::Python, source).expect("Source should parse");
+    let by_name = |name: &str| {
+        components
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("Component {name} should have been extracted"))
+    };
+
+    assert_eq!(by_name("Repository").component_type, ComponentType::Class);
+    assert_eq!(by_name("__init__").component_type, ComponentType::Method);
+    assert_eq!(by_name("__init__").visibility, Visibility::Public);
+    assert_eq!(by_name("name").component_type, ComponentType::Method);
+    assert_eq!(by_name("name").qualified_name, "Repository::name");
+    assert_eq!(by_name("_scan").visibility, Visibility::Private);
+    assert_eq!(by_name("_helper").component_type, ComponentType::Function);
+    assert_eq!(by_name("_helper").visibility, Visibility::Private);
+    assert_eq!(by_name("main").visibility, Visibility::Public);
+}
+
+#[test]
+fn test_typescript_exports_interfaces_and_member_visibility() {
+    let source = r"
+export interface Store {
+  get(key: string): string;
+}
+
+export enum Mode { Read, Write }
+
+export class MemoryStore implements Store {
+  private cache = new Map();
+
+  get(key: string): string {
+    return this.cache.get(key);
+  }
+
+  protected evict(): void {}
+
+  private reset = () => {
+    this.cache.clear();
+  };
+}
+
+const internal = (x: number) => x * 2;
+
+export function createStore(): Store {
+  return new MemoryStore();
+}
+";
+    let components =
+        extract_components(SourceLanguage::TypeScript, source).expect("Source should parse");
+    let by_name = |name: &str| {
+        components
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("Component {name} should have been extracted"))
+    };
+
+    assert_eq!(by_name("Store").component_type, ComponentType::Interface);
+    assert_eq!(by_name("Mode").component_type, ComponentType::Enum);
+    assert_eq!(by_name("MemoryStore").visibility, Visibility::Public);
+    assert_eq!(by_name("get").component_type, ComponentType::Method);
+    assert_eq!(by_name("get").qualified_name, "MemoryStore::get");
+    assert_eq!(by_name("evict").visibility, Visibility::Protected);
+    assert_eq!(by_name("reset").component_type, ComponentType::Method);
+    assert_eq!(by_name("reset").visibility, Visibility::Private);
+    assert_eq!(by_name("internal").component_type, ComponentType::Function);
+    assert_eq!(by_name("internal").visibility, Visibility::Private);
+    assert_eq!(by_name("createStore").visibility, Visibility::Public);
+    // The cache field is data, not a component
+    assert!(components.iter().all(|c| c.name != "cache"));
+}
+
+#[test]
+fn test_commonjs_exports_are_public() {
+    let source = r"
+function load() {}
+function unused() {}
+class Loader {
+  #secret() {}
+}
+module.exports = { load, Loader };
+";
+    let components =
+        extract_components(SourceLanguage::JavaScript, source).expect("Source should parse");
+    let by_name = |name: &str| {
+        components
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("Component {name} should have been extracted"))
+    };
+
+    assert_eq!(by_name("load").visibility, Visibility::Public);
+    assert_eq!(by_name("Loader").visibility, Visibility::Public);
+    assert_eq!(by_name("unused").visibility, Visibility::Private);
+    assert_eq!(by_name("#secret").visibility, Visibility::Private);
+}
+
+fn has_import_edge(mapper: &RepositoryMapper, from: &CodeComponent, to: &CodeComponent) -> bool {
+    mapper
+        .graph
+        .edges
+        .iter()
+        .any(|edge| edge.from == from.id && edge.to == to.id)
+        && from.dependencies.contains(&to.id)
+        && to.dependents.contains(&from.id)
+}
+
+#[test]
+fn test_go_packages_receivers_and_imports() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("go.mod"), "module example.com/shop\n\ngo 1.22\n");
+    write(
+        &root.join("store/store.go"),
+        r"package store
+
+type Store interface {
+	Get(key string) string
+}
+
+type memoryStore struct {
+	items map[string]string
+}
+
+func (m *memoryStore) Get(key string) string {
+	return m.items[key]
+}
+
+func New() Store {
+	return &memoryStore{}
+}
+",
+    );
+    write(
+        &root.join("main.go"),
+        r#"package main
+
+import (
+	"fmt"
+
+	"example.com/shop/store"
+)
+
+func main() {
+	fmt.Println(store.New())
+}
+"#,
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    let store_pkg = find(&mapper, "store");
+    assert_eq!(store_pkg.component_type, ComponentType::Module);
+    let main_pkg = find_typed(&mapper, "main", &ComponentType::Module);
+    assert_eq!(main_pkg.component_type, ComponentType::Module);
+    assert!(has_import_edge(&mapper, main_pkg, store_pkg));
+
+    let store_iface = find(&mapper, "Store");
+    assert_eq!(store_iface.component_type, ComponentType::Interface);
+    assert_eq!(store_iface.parent.as_deref(), Some(store_pkg.id.as_str()));
+
+    let memory = find(&mapper, "memoryStore");
+    assert_eq!(memory.component_type, ComponentType::Class);
+    assert_eq!(memory.visibility, Visibility::Private);
+
+    let method = mapper
+        .graph
+        .components
+        .values()
+        .find(|c| {
+            c.name == "Get"
+                && c.component_type == ComponentType::Method
+                && c.parent.as_deref() == Some(memory.id.as_str())
+        })
+        .expect("Receiver method should be attached to its struct");
+    assert!(method.id.ends_with("memoryStore::Get"));
+    assert_eq!(find(&mapper, "New").visibility, Visibility::Public);
+    // Standard library imports do not resolve to project components
+    let import_edges = mapper
+        .graph
+        .edges
+        .iter()
+        .filter(|edge| matches!(edge.edge_type, EdgeType::ModuleImport))
+        .count();
+    assert_eq!(import_edges, 1);
+}
+
+#[test]
+fn test_java_and_kotlin_packages_modifiers_and_imports() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("src/com/acme/store/Store.java"),
+        r"package com.acme.store;
+
+public interface Store {
+    String get(String key);
+}
+",
+    );
+    write(
+        &root.join("src/com/acme/store/MemoryStore.java"),
+        r"package com.acme.store;
+
+import java.util.HashMap;
+
+class MemoryStore implements Store {
+    private final HashMap<String, String> items = new HashMap<>();
+
+    public MemoryStore() {}
+
+    @Override
+    public String get(String key) {
+        return items.get(key);
+    }
+
+    protected void evict() {}
+
+    void reset() {}
+}
+",
+    );
+    write(
+        &root.join("src/com/acme/app/App.kt"),
+        r"package com.acme.app
+
+import com.acme.store.Store
+import com.acme.store.*
+
+data class Config(
+    val path: String,
+) {
+    fun describe(): String = path
+}
+
+internal class App(private val store: Store) {
+    private fun load(key: String): String {
+        fun normalize(k: String) = k.trim()
+        return store.get(normalize(key))
+    }
+
+    companion object {
+        fun create(): App = TODO()
+    }
+}
+
+fun Config.isEmpty(): Boolean = path.isEmpty()
+",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    let store_pkg = find(&mapper, "com.acme.store");
+    assert_eq!(store_pkg.component_type, ComponentType::Module);
+    assert_eq!(store_pkg.children.len(), 2);
+
+    let store = find(&mapper, "Store");
+    assert_eq!(store.component_type, ComponentType::Interface);
+    assert_eq!(store.visibility, Visibility::Public);
+    let memory = find_typed(&mapper, "MemoryStore", &ComponentType::Class);
+    assert_eq!(memory.component_type, ComponentType::Class);
+    assert_eq!(memory.visibility, Visibility::Internal);
+    assert_eq!(find(&mapper, "evict").visibility, Visibility::Protected);
+    assert_eq!(find(&mapper, "reset").visibility, Visibility::Internal);
+
+    let app = find(&mapper, "App");
+    assert_eq!(app.visibility, Visibility::Internal);
+    let load = find(&mapper, "load");
+    assert_eq!(load.component_type, ComponentType::Method);
+    assert_eq!(load.visibility, Visibility::Private);
+    assert_eq!(load.parent.as_deref(), Some(app.id.as_str()));
+    assert!(mapper
+        .graph
+        .components
+        .values()
+        .all(|c| c.name != "normalize"));
+
+    let config = find(&mapper, "Config");
+    assert_eq!(config.end_line, 10);
+    assert_eq!(
+        find(&mapper, "describe").parent.as_deref(),
+        Some(config.id.as_str())
+    );
+    let extension = find(&mapper, "isEmpty");
+    assert_eq!(extension.component_type, ComponentType::Method);
+    assert_eq!(extension.parent.as_deref(), Some(config.id.as_str()));
+
+    assert!(has_import_edge(&mapper, app, store));
+    assert!(has_import_edge(&mapper, app, store_pkg));
+}
+
+#[test]
+fn test_c_and_cpp_headers_includes_and_members() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("include/buffer.h"),
+        r"#ifndef BUFFER_H
+#define BUFFER_H
+
+typedef struct {
+    char *data;
+    int (*grow)(int);
+} Buffer;
+
+enum Mode { READ, WRITE };
+
+#endif
+",
+    );
+    write(
+        &root.join("src/buffer.c"),
+        r#"#include <stdlib.h>
+#include "../include/buffer.h"
+
+static int grow(int size) {
+    return size * 2;
+}
+
+Buffer *buffer_new(void) {
+    return malloc(sizeof(Buffer));
+}
+"#,
+    );
+    write(
+        &root.join("src/parser.hpp"),
+        r"#include <buffer.h>
+
+namespace text {
+class Parser {
+  public:
+    Parser();
+    int parse(const char *input);
+
+  private:
+    void advance() {}
+    int position;
+};
+}
+",
+    );
+    write(
+        &root.join("src/parser.cpp"),
+        r#"#include "parser.hpp"
+
+namespace text {
+int Parser::parse(const char *input) {
+    return 0;
+}
+}
+"#,
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    let header = find(&mapper, "buffer.h");
+    let source = find(&mapper, "buffer.c");
+    let parser_header = find(&mapper, "parser.hpp");
+    let parser_source = find(&mapper, "parser.cpp");
+    assert_eq!(header.component_type, ComponentType::Module);
+    assert!(has_import_edge(&mapper, source, header));
+    assert!(has_import_edge(&mapper, parser_header, header));
+    assert!(has_import_edge(&mapper, parser_source, parser_header));
+
+    let buffer = find(&mapper, "Buffer");
+    assert_eq!(buffer.component_type, ComponentType::Class);
+    assert_eq!(buffer.parent.as_deref(), Some(header.id.as_str()));
+    assert_eq!(find(&mapper, "Mode").component_type, ComponentType::Enum);
+    assert_eq!(find(&mapper, "grow").visibility, Visibility::Private);
+    assert_eq!(find(&mapper, "buffer_new").visibility, Visibility::Public);
+
+    let text = mapper
+        .graph
+        .components
+        .values()
+        .find(|c| c.name == "text" && c.file_path.ends_with("parser.hpp"))
+        .expect("Namespace should be extracted");
+    assert_eq!(text.component_type, ComponentType::Module);
+    let parser = find_typed(&mapper, "Parser", &ComponentType::Class);
+    assert_eq!(parser.parent.as_deref(), Some(text.id.as_str()));
+    let advance = find(&mapper, "advance");
+    assert_eq!(advance.visibility, Visibility::Private);
+    assert_eq!(advance.parent.as_deref(), Some(parser.id.as_str()));
+    assert!(mapper
+        .graph
+        .components
+        .values()
+        .all(|c| c.name != "position"));
+    assert!(mapper.graph.components.values().any(|c| c.name == "parse"
+        && c.component_type == ComponentType::Method
+        && c.file_path.ends_with("parser.cpp")));
+}
+
+/// A toy workflow DSL: `flow <name>` declares a component, `use <file>` imports another
+struct FlowAnalyzer;
+
+impl LanguageAnalyzer for FlowAnalyzer {
+    fn name(&self) -> &'static str {
+        "flow"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["flow"]
+    }
+
+    fn extract(&self, _path: &Path, source: &str) -> Option<FileSyntax> {
+        let mut syntax = FileSyntax::default();
+        for (idx, line) in source.lines().enumerate() {
+            if let Some(name) = line.strip_prefix("flow ") {
+                syntax.components.push(ExtractedComponent::new(
+                    name,
+                    ComponentType::Function,
+                    idx + 1,
+                    idx + 1,
+                ));
+            } else if let Some(path) = line.strip_prefix("use ") {
+                syntax.imports.push(ImportDecl::new(path, idx + 1));
+            }
+        }
+        Some(syntax)
+    }
+
+    fn container(&self, _path: &Path, _syntax: &FileSyntax) -> Option<Container> {
+        Some(Container::File)
+    }
+
+    fn resolve_import(
+        &self,
+        import: &ImportDecl,
+        file: &Path,
+        index: &SymbolIndex,
+    ) -> Option<String> {
+        index
+            .file_module(&file.parent()?.join(&import.path))
+            .map(str::to_string)
+    }
+}
+
+#[test]
+fn test_registered_analyzer_handles_custom_language() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("deploy.flow"), "use steps.flow\nflow deploy\n");
+    write(&root.join("steps.flow"), "flow compile\nflow package\n");
+
+    let mut mapper = RepositoryMapper::new();
+    mapper.register_analyzer(FlowAnalyzer);
+    assert_eq!(mapper.analyzer_names().first(), Some(&"flow"));
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    let deploy = find(&mapper, "deploy.flow");
+    let steps = find(&mapper, "steps.flow");
+    assert!(has_import_edge(&mapper, deploy, steps));
+    assert_eq!(steps.children.len(), 2);
+    assert_eq!(find(&mapper, "package").start_line, 2);
+
+    // Without the analyzer, .flow files are not discovered at all
+    let mut plain = RepositoryMapper::without_analyzers();
+    plain
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    assert_eq!(plain.insights.total_files, 0);
+}
+
+#[test]
+fn test_extracted_components_default_to_public_and_top_level() {
+    let component = ExtractedComponent::new("deploy", ComponentType::Function, 3, 7);
+    assert_eq!(component.qualified_name, "deploy");
+    assert_eq!(component.visibility, Visibility::Public);
+    assert_eq!(component.parent, None);
+    assert_eq!((component.start_line, component.end_line), (3, 7));
+
+    let method = component
+        .with_qualified_name("Pipeline.deploy")
+        .with_visibility(Visibility::Private)
+        .with_parent(0);
+    assert_eq!(method.name, "deploy");
+    assert_eq!(method.qualified_name, "Pipeline.deploy");
+    assert_eq!(method.visibility, Visibility::Private);
+    assert_eq!(method.parent, Some(0));
+
+    let include = ImportDecl::new("stdio.h", 1).as_system();
+    assert!(include.system);
+    assert!(!ImportDecl::new("steps.flow", 1).system);
+}
+
+#[test]
+fn test_call_sites_become_call_edges() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(
+        &dir.path().join("lib.rs"),
+        r"
+pub struct Store;
+
+impl Store {
+    pub fn open() -> Self {
+        Store
+    }
+
+    pub fn load(&self) -> String {
+        read_file()
+    }
+}
+
+fn read_file() -> String {
+    String::new()
+}
+
+pub fn run() {
+    let store = Store::open();
+    store.load();
+}
+",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+
+    let run = find(&mapper, "run");
+    let open = find(&mapper, "open");
+    let load = find(&mapper, "load");
+    let read_file = find(&mapper, "read_file");
+    let edge_type = |from: &CodeComponent, to: &CodeComponent| {
+        mapper
+            .graph
+            .edges
+            .iter()
+            .find(|edge| edge.from == from.id && edge.to == to.id)
+            .map(|edge| edge.edge_type.clone())
+    };
+
+    assert!(matches!(edge_type(run, open), Some(EdgeType::MethodCall)));
+    assert!(matches!(edge_type(run, load), Some(EdgeType::MethodCall)));
+    assert!(matches!(
+        edge_type(load, read_file),
+        Some(EdgeType::FunctionCall)
+    ));
+    assert!(run.dependencies.contains(&open.id));
+    assert!(read_file.dependents.contains(&load.id));
+    // `String::new` is not a project component
+    assert_eq!(mapper.graph.edges.len(), 3);
+}
+
+#[test]
+fn test_mutual_calls_are_reported_as_cycles() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(
+        &dir.path().join("parity.py"),
+        r"
+def is_even(n):
+    return n == 0 or is_odd(n - 1)
+
+def is_odd(n):
+    return n != 0 and is_even(n - 1)
+
+def main():
+    print(is_even(4))
+",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+
+    let is_even = find(&mapper, "is_even");
+    let is_odd = find(&mapper, "is_odd");
+    let mut expected = vec![is_even.id.clone(), is_odd.id.clone()];
+    expected.sort();
+    assert_eq!(mapper.graph.cycles, vec![expected]);
+}
+
+fn file_key(root: &Path, relative: &str) -> String {
+    root.join(relative).display().to_string()
+}
+
+fn imports(mapper: &RepositoryMapper, root: &Path, relative: &str) -> Vec<String> {
+    let root_prefix = format!("{}/", root.display());
+    mapper
+        .file_graph
+        .files
+        .get(&file_key(root, relative))
+        .unwrap_or_else(|| panic!("{relative} should be in the file graph"))
+        .imports
+        .iter()
+        .map(|path| path.trim_start_matches(&root_prefix).to_string())
+        .collect()
+}
+
+#[test]
+fn test_unknown_jvm_imports_do_not_resolve_to_an_enclosing_package() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("Main.java"),
+        "package com.acme;\n\nimport com.acme.Store;\n\npublic class Main {}\n",
+    );
+    write(
+        &root.join("com/acme/Store.kt"),
+        "package com.acme\n\nimport com.acme.util.Helper\nimport com.acme.text.format\n\nclass Store\n",
+    );
+    write(
+        &root.join("com/acme/text/Text.kt"),
+        "package com.acme.text\n\nfun format(text: String) = text\n",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    // `com.acme.util` does not exist, and `com.acme` merely encloses it
+    assert_eq!(
+        imports(&mapper, root, "Main.java"),
+        vec!["com/acme/Store.kt"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "com/acme/Store.kt"),
+        vec!["com/acme/text/Text.kt"]
+    );
+    assert!(mapper.file_graph.cycles.is_empty());
+}
+
+#[test]
+fn test_file_graph_resolves_rust_mod_and_use() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("Cargo.toml"), "[package]\nname = \"demo\"\n");
+    write(
+        &root.join("src/lib.rs"),
+        "mod parser;\nmod util;\nuse std::fmt;\npub use crate::parser::Parser;\n",
+    );
+    write(
+        &root.join("src/parser.rs"),
+        "use super::util;\nuse crate::util::helpers::{trim, split as split_words};\npub struct Parser;\n",
+    );
+    write(&root.join("src/util/mod.rs"), "pub mod helpers;\n");
+    write(
+        &root.join("src/util/helpers.rs"),
+        "use crate::parser::Parser;\npub fn trim() {}\npub fn split() {}\n",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    assert_eq!(
+        imports(&mapper, root, "src/lib.rs"),
+        vec!["src/parser.rs", "src/util/mod.rs"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "src/parser.rs"),
+        vec!["src/util/helpers.rs", "src/util/mod.rs"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "src/util/mod.rs"),
+        vec!["src/util/helpers.rs"]
+    );
+
+    let graph = &mapper.file_graph;
+    assert_eq!(
+        graph.cycles,
+        vec![vec![
+            file_key(root, "src/parser.rs"),
+            file_key(root, "src/util/helpers.rs"),
+            file_key(root, "src/util/mod.rs"),
+        ]]
+    );
+    let helpers = &graph.files[&file_key(root, "src/util/helpers.rs")];
+    assert_eq!((helpers.fan_in, helpers.fan_out), (2, 1));
+    assert_eq!(
+        graph.topological_order.last(),
+        Some(&file_key(root, "src/lib.rs"))
+    );
+}
+
+#[test]
+fn test_file_graph_resolves_python_packages_and_relative_imports() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("shop/__init__.py"), "");
+    write(&root.join("shop/base.py"), "class Base:\n    pass\n");
+    write(
+        &root.join("shop/models.py"),
+        "from .base import Base\n\nclass User(Base):\n    pass\n",
+    );
+    write(
+        &root.join("shop/api/views.py"),
+        "from .. import models\nfrom ..base import *\n",
+    );
+    write(
+        &root.join("app.py"),
+        "import os\nimport shop.base as base\nfrom shop.models import User\n",
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    assert_eq!(
+        imports(&mapper, root, "app.py"),
+        vec!["shop/base.py", "shop/models.py"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "shop/models.py"),
+        vec!["shop/base.py"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "shop/api/views.py"),
+        vec!["shop/base.py", "shop/models.py"]
+    );
+
+    let graph = &mapper.file_graph;
+    assert!(graph.cycles.is_empty());
+    let position = |relative: &str| {
+        graph
+            .topological_order
+            .iter()
+            .position(|path| *path == file_key(root, relative))
+            .expect("Every file should be ordered")
+    };
+    assert!(position("shop/base.py") < position("shop/models.py"));
+    assert!(position("shop/models.py") < position("app.py"));
+    assert_eq!(
+        graph.most_imported(1)[0].path,
+        file_key(root, "shop/base.py")
+    );
+}
+
+#[test]
+fn test_file_graph_resolves_tsconfig_aliases_and_index_files() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("tsconfig.json"),
+        r#"{
+  // Editor settings
+  "compilerOptions": {
+    "baseUrl": ".",
+    "paths": {
+      "@lib/*": ["src/lib/*"], /* shared code */
+    },
+  },
+}"#,
+    );
+    write(&root.join("src/lib/index.ts"), "export * from './math';\n");
+    write(
+        &root.join("src/lib/math.ts"),
+        "export function add(a: number, b: number) { return a + b; }\n",
+    );
+    write(&root.join("src/config.ts"), "export const port = 8080;\n");
+    write(
+        &root.join("src/app.tsx"),
+        r#"import React from "react";
+import { add } from "@lib/math";
+import * as lib from "./lib";
+const config = require("./config.js");
+export const load = () => import("src/lib/math");
+"#,
+    );
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+
+    assert_eq!(
+        imports(&mapper, root, "src/app.tsx"),
+        vec!["src/config.ts", "src/lib/index.ts", "src/lib/math.ts"]
+    );
+    assert_eq!(
+        imports(&mapper, root, "src/lib/index.ts"),
+        vec!["src/lib/math.ts"]
+    );
+    let math = &mapper.file_graph.files[&file_key(root, "src/lib/math.ts")];
+    assert_eq!(math.fan_in, 2);
+    assert_eq!(math.fan_out, 0);
+}
+
+#[test]
+fn test_files_that_are_not_utf8_are_still_analyzed() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    // "Café" in Latin-1, as legacy C sources are often encoded
+    fs::write(
+        root.join("b.c"),
+        b"/* Caf\xe9 */\nint brew(void) {\n    return 1;\n}\n",
+    )
+    .expect("Failed to write b.c");
+    write(&root.join("a.c"), "int grind(void) {\n    return 2;\n}\n");
+
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    assert_eq!(find(&mapper, "brew").start_line, 2);
+    assert_eq!(find(&mapper, "grind").start_line, 1);
+}
@@ -0,0 +1,199 @@
+use contexter::repo_mapper::RepositoryMapper;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+/// The signatures of `name` after analyzing `files`
+fn signatures(files: &[(&str, &str)], name: &str) -> Option<String> {
+    let dir = tempdir().expect("Failed to create temp dir");
+    for (file, content) in files {
+        write(&dir.path().join(file), content);
+    }
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(dir.path())
+        .expect("Analysis should succeed");
+    mapper.signatures().for_file(&dir.path().join(name))
+}
+
+#[test]
+fn test_rust_signatures() {
+    let source = r#"use std::fmt;
+
+/// Connection settings
+#[derive(Debug)]
+pub struct Settings {
+    pub host: String,
+    port: u16,
+}
+
+/// Shorthand for results in this crate
+pub type Result<T> = std::result::Result<T, String>;
+
+impl Settings {
+    /// Settings for `host`
+    pub fn new(host: &str) -> Self {
+        Self { host: host.to_string(), port: 80 }
+    }
+
+    fn validate(&self) -> bool {
+        self.port > 0
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.host)
+    }
+}
+
+pub trait Connect {
+    fn connect(&self) -> Result<()>;
+
+    fn retries(&self) -> u8 {
+        3
+    }
+}
+
+fn helper() {}
+"#;
+    let surface = signatures(&[("src/lib.rs", source)], "src/lib.rs").expect("Public items");
+    let expected = r"/// Connection settings
+#[derive(Debug)]
+pub struct Settings {
+    pub host: String,
+    port: u16,
+}
+
+/// Shorthand for results in this crate
+pub type Result<T> = std::result::Result<T, String>;
+
+impl Settings {
+    /// Settings for `host`
+    pub fn new(host: &str) -> Self { ... }
+}
+
+impl fmt::Display for Settings { ... }
+
+pub trait Connect {
+    fn connect(&self) -> Result<()>;
+    fn retries(&self) -> u8 { ... }
+}";
+    assert_eq!(surface, expected);
+}
+
+#[test]
+fn test_python_signatures() {
+    let source = r#"import os
+
+
+class Store:
+    """Keeps records on disk."""
+
+    def __init__(self, root):
+        self.root = root
+
+    @property
+    def size(self):
+        return len(os.listdir(self.root))
+
+    def _scan(self):
+        pass
+
+
+def open_store(root: str) -> Store:
+    """
+    Opens the store at root.
+    """
+    return Store(root)
+
+
+def connect(url):
+    return url
+
+
+def _private():
+    pass
+"#;
+    let surface = signatures(&[("store.py", source)], "store.py").expect("Public items");
+    let expected = r#"class Store:
+    """Keeps records on disk."""
+    def __init__(self, root):
+        ...
+    @property
+    def size(self):
+        ...
+
+def open_store(root: str) -> Store:
+    """
+    Opens the store at root.
+    """
+
+def connect(url):
+    ..."#;
+    assert_eq!(surface, expected);
+}
+
+#[test]
+fn test_typescript_exported_declarations() {
+    let source = r"import { readFile } from 'fs';
+
+/** A user record */
+export interface User {
+  id: string;
+  name?: string;
+}
+
+export type UserId = User['id'];
+
+export class Repository {
+  private cache = new Map<string, User>();
+
+  /** Finds a user by ID */
+  async find(id: UserId): Promise<User | undefined> {
+    return this.cache.get(id);
+  }
+
+  private load(): void {
+    readFile('users.json', () => {});
+  }
+}
+
+export const double = (value: number): number => value * 2;
+
+function internal(): void {}
+";
+    let surface = signatures(&[("src/repo.ts", source)], "src/repo.ts").expect("Public items");
+    let expected = r"/** A user record */
+export interface User {
+  id: string;
+  name?: string;
+}
+
+export type UserId = User['id'];
+
+export class Repository {
+  /** Finds a user by ID */
+  async find(id: UserId): Promise<User | undefined> { ... }
+}
+
+export const double = (value: number): number => value * 2;";
+    assert_eq!(surface, expected);
+}
+
+#[test]
+fn test_files_without_public_items() {
+    let files = [
+        ("src/main.rs", "fn main() {}\n"),
+        ("README.md", "# Readme\n"),
+    ];
+    assert_eq!(signatures(&files, "src/main.rs"), None);
+    assert_eq!(signatures(&files, "README.md"), None);
+}
@@ -0,0 +1,178 @@
+use contexter::repo_mapper::RepositoryMapper;
+use contexter::suggest::{FileSuggestion, SuggestError, SuggestionReason};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(status.status.success(), "git {args:?} failed: {status:?}");
+}
+
+fn commit(dir: &Path, files: &[(&str, &str)], message: &str) {
+    for (path, content) in files {
+        write(&dir.join(path), content);
+    }
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+fn analyze(root: &Path) -> RepositoryMapper {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    mapper
+}
+
+fn suggestion<'a>(
+    suggestions: &'a [FileSuggestion],
+    root: &Path,
+    relative: &str,
+) -> &'a FileSuggestion {
+    let path = root.join(relative).display().to_string();
+    suggestions
+        .iter()
+        .find(|s| s.path == path)
+        .unwrap_or_else(|| panic!("{relative} should be suggested, got {suggestions:#?}"))
+}
+
+#[test]
+fn test_imports_and_test_pairs_rank_first() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("shop/__init__.py"), "");
+    write(
+        &root.join("shop/cart.py"),
+        "from .pricing import total_price\n\nclass Cart:\n    def total(self):\n        return total_price(self)\n",
+    );
+    write(
+        &root.join("shop/pricing.py"),
+        "from .tax import VAT_RATE\n\ndef total_price(cart):\n    return 0\n",
+    );
+    write(&root.join("shop/tax.py"), "VAT_RATE = 0.2\n");
+    write(
+        &root.join("tests/test_cart.py"),
+        "def test_empty():\n    assert True\n",
+    );
+    write(&root.join("shop/unrelated.py"), "def noop():\n    pass\n");
+
+    let mapper = analyze(root);
+    let suggestions = mapper
+        .suggest_related(&[root.join("shop/cart.py")], 10)
+        .expect("Suggestions should succeed");
+
+    let pricing = suggestion(&suggestions, root, "shop/pricing.py");
+    assert!(pricing.reasons.contains(&SuggestionReason::ImportedBy {
+        seed: root.join("shop/cart.py").display().to_string()
+    }));
+    assert!(pricing
+        .reasons
+        .iter()
+        .any(|reason| matches!(reason, SuggestionReason::SharedIdentifiers { names, .. } if names.contains(&"total_price".to_string()))));
+
+    let test = suggestion(&suggestions, root, "tests/test_cart.py");
+    assert!(matches!(
+        test.reasons[..],
+        [SuggestionReason::TestPair { .. }]
+    ));
+
+    let tax = suggestion(&suggestions, root, "shop/tax.py");
+    assert!(matches!(
+        &tax.reasons[..],
+        [SuggestionReason::NearbyImport { via, .. }] if via.ends_with("pricing.py")
+    ));
+
+    assert_eq!(suggestions[0].path, pricing.path);
+    assert!(test.score > tax.score);
+    let seed = root.join("shop/cart.py").display().to_string();
+    assert!(suggestions
+        .iter()
+        .all(|s| !s.path.ends_with("unrelated.py") && s.path != seed));
+}
+
+#[test]
+fn test_co_changed_files_are_suggested() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    commit(
+        root,
+        &[
+            ("src/server.js", "module.exports = { start() {} };\n"),
+            ("src/routes.js", "module.exports = { list() {} };\n"),
+            ("docs/api.md", "# API\n"),
+        ],
+        "Initial commit",
+    );
+    commit(
+        root,
+        &[
+            (
+                "src/server.js",
+                "module.exports = { start() { return 1; } };\n",
+            ),
+            ("docs/api.md", "# API\n\nStart the server.\n"),
+        ],
+        "Document server start",
+    );
+    commit(
+        root,
+        &[(
+            "src/routes.js",
+            "module.exports = { list() { return []; } };\n",
+        )],
+        "Return empty routes",
+    );
+
+    let mapper = analyze(root);
+    let suggestions = mapper
+        .suggest_related(&[PathBuf::from("src/server.js")], 10)
+        .expect("Suggestions should succeed");
+
+    // Changed with every change of the seed
+    let docs = suggestion(&suggestions, root, "docs/api.md");
+    assert_eq!(
+        docs.reasons,
+        vec![SuggestionReason::CoChanged {
+            seed: root.join("src/server.js").display().to_string(),
+            commits: 2,
+        }]
+    );
+    let routes = suggestion(&suggestions, root, "src/routes.js");
+    assert!(matches!(
+        routes.reasons[..],
+        [SuggestionReason::CoChanged { commits: 1, .. }]
+    ));
+    assert!(docs.score > routes.score);
+}
+
+#[test]
+fn test_unknown_seeds_are_rejected() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(&dir.path().join("lib.rs"), "pub fn run() {}\n");
+    let mapper = analyze(dir.path());
+
+    assert!(matches!(
+        mapper.suggest_related(&[], 10),
+        Err(SuggestError::NoSeeds)
+    ));
+    assert!(matches!(
+        mapper.suggest_related(&[PathBuf::from("missing.rs")], 10),
+        Err(SuggestError::UnknownFile(file)) if file == "missing.rs"
+    ));
+}
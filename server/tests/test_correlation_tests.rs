@@ -0,0 +1,205 @@
+use contexter::repo_mapper::RepositoryMapper;
+use contexter::test_correlation::{Correlation, TestCorrelation, TestFile};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+fn analyze(root: &Path) -> RepositoryMapper {
+    let mut mapper = RepositoryMapper::new();
+    mapper
+        .analyze_repository(root)
+        .expect("Analysis should succeed");
+    mapper
+}
+
+fn test_file<'a>(correlation: &'a TestCorrelation, root: &Path, relative: &str) -> &'a TestFile {
+    let path = root.join(relative).display().to_string();
+    correlation
+        .test_files
+        .iter()
+        .find(|test_file| test_file.path == path)
+        .unwrap_or_else(|| panic!("{relative} should be a test file"))
+}
+
+/// The exercised component names of a test, with how they were found
+fn exercised(
+    mapper: &RepositoryMapper,
+    test_file: &TestFile,
+    test: &str,
+) -> Vec<(String, Correlation)> {
+    let test = test_file
+        .tests
+        .iter()
+        .find(|t| t.name == test)
+        .unwrap_or_else(|| panic!("{test} should be a test"));
+    test.exercises
+        .iter()
+        .map(|e| (mapper.graph.components[&e.component].name.clone(), e.via))
+        .collect()
+}
+
+#[test]
+fn test_python_tests_map_through_calls_naming_and_imports() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(&root.join("shop/__init__.py"), "");
+    write(
+        &root.join("shop/pricing.py"),
+        r"
+class Cart:
+    def total(self):
+        return total_price(self)
+
+def total_price(cart):
+    return 0
+
+def apply_discount(price, rate):
+    return price * (1 - rate)
+
+def unused_helper():
+    pass
+
+def _internal():
+    pass
+",
+    );
+    write(
+        &root.join("tests/test_pricing.py"),
+        r"
+from shop.pricing import Cart, apply_discount, total_price
+
+def test_total_price_of_empty_cart():
+    assert total_price(None) == 0
+
+def test_cart():
+    assert Cart is not None
+
+def test_discounts():
+    assert apply_discount(10, 0.5) == 5
+",
+    );
+
+    let mapper = analyze(root);
+    let correlation = mapper.correlate_tests();
+    assert_eq!(correlation.test_files.len(), 1);
+
+    let tests = test_file(&correlation, root, "tests/test_pricing.py");
+    assert_eq!(
+        tests.sources,
+        vec![root.join("shop/pricing.py").display().to_string()]
+    );
+    assert_eq!(
+        exercised(&mapper, tests, "test_total_price_of_empty_cart"),
+        vec![("total_price".to_string(), Correlation::Call)]
+    );
+    assert_eq!(
+        exercised(&mapper, tests, "test_cart"),
+        vec![("Cart".to_string(), Correlation::Naming)]
+    );
+    let mut imported: Vec<&str> = tests
+        .exercises
+        .iter()
+        .filter(|e| e.via == Correlation::Import)
+        .map(|e| mapper.graph.components[&e.component].name.as_str())
+        .collect();
+    imported.sort_unstable();
+    assert_eq!(imported, vec!["Cart", "apply_discount", "total_price"]);
+
+    // Private names are not reported; `Cart` is tested but its `total` method is not
+    let untested: Vec<&str> = correlation
+        .untested_public
+        .iter()
+        .map(|id| mapper.graph.components[id].name.as_str())
+        .collect();
+    assert_eq!(untested, vec!["total", "unused_helper"]);
+}
+
+#[test]
+fn test_rust_inline_and_integration_tests() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("Cargo.toml"),
+        "[package]\nname = \"calc-core\"\n",
+    );
+    write(
+        &root.join("src/lib.rs"),
+        r"
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+pub fn sub(a: i32, b: i32) -> i32 {
+    a - b
+}
+
+pub fn mul(a: i32, b: i32) -> i32 {
+    a * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let sum = add(1, 2);
+        assert_eq!(sum, 3);
+    }
+}
+",
+    );
+    write(
+        &root.join("tests/ops.rs"),
+        r"
+use calc_core::sub;
+
+#[test]
+fn subtracts() {
+    let difference = sub(3, 2);
+    assert_eq!(difference, 1);
+}
+",
+    );
+
+    let mapper = analyze(root);
+    let correlation = mapper.correlate_tests();
+
+    let inline = test_file(&correlation, root, "src/lib.rs");
+    assert_eq!(
+        exercised(&mapper, inline, "test_add"),
+        vec![("add".to_string(), Correlation::Call)]
+    );
+
+    let integration = test_file(&correlation, root, "tests/ops.rs");
+    assert!(integration.sources.is_empty());
+    assert_eq!(
+        exercised(&mapper, integration, "subtracts"),
+        vec![("sub".to_string(), Correlation::Call)]
+    );
+    assert_eq!(integration.exercises.len(), 1);
+    assert_eq!(integration.exercises[0].via, Correlation::Import);
+
+    let untested: Vec<&str> = correlation
+        .untested_public
+        .iter()
+        .map(|id| mapper.graph.components[id].name.as_str())
+        .collect();
+    assert_eq!(untested, vec!["mul"]);
+
+    let lib = root.join("src/lib.rs");
+    assert_eq!(
+        mapper.tests_for_files(std::slice::from_ref(&lib)),
+        vec![root.join("tests/ops.rs")]
+    );
+    assert!(mapper
+        .tests_for_files(&[root.join("tests/ops.rs"), lib])
+        .is_empty());
+}
@@ -0,0 +1,173 @@
+use contexter::truncate::{estimate_tokens, truncate, FileLimits};
+use std::path::Path;
+
+/// Lines `{prefix}1` to `{prefix}{count}`, each followed by a newline
+fn numbered(prefix: &str, count: usize) -> String {
+    let lines: Vec<String> = (1..=count).map(|n| format!("{prefix}{n}")).collect();
+    lines.join("\n") + "\n"
+}
+
+fn lines(limit: usize, keep: usize) -> FileLimits {
+    FileLimits {
+        max_file_lines: Some(limit),
+        keep_lines: Some(keep),
+        ..FileLimits::default()
+    }
+}
+
+#[test]
+fn test_files_within_limits_are_unchanged() {
+    let content = "fn main() {\n    println!(\"hi\");\n}\n";
+    let (text, truncation) = truncate(Path::new("main.rs"), content, &lines(3, 1));
+    assert_eq!(text, content);
+    assert_eq!(truncation, None);
+
+    let (text, truncation) = truncate(Path::new("main.rs"), content, &FileLimits::default());
+    assert_eq!(text, content);
+    assert_eq!(truncation, None);
+}
+
+#[test]
+fn test_rust_function_bodies_are_elided_first() {
+    let source = r"use std::collections::HashMap;
+
+/// Counts words
+pub fn count(text: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+pub struct Counter {
+    total: usize,
+}
+
+impl Counter {
+    pub fn add(&mut self, n: usize) {
+        self.total += n;
+        self.total = self.total.min(100);
+    }
+}
+";
+    let (text, truncation) = truncate(Path::new("src/count.rs"), source, &lines(18, 1));
+    let expected = r"use std::collections::HashMap;
+
+/// Counts words
+pub fn count(text: &str) -> HashMap<&str, usize> {
+    // ... 5 lines elided ...
+}
+
+pub struct Counter {
+    total: usize,
+}
+
+impl Counter {
+    pub fn add(&mut self, n: usize) {
+        // ... 2 lines elided ...
+    }
+}
+";
+    assert_eq!(text, expected);
+    let truncation = truncation.expect("File is over the limit");
+    assert_eq!(truncation.original_lines, 21);
+    assert_eq!(truncation.elided_lines, 7);
+    assert_eq!(truncation.elided_sections, 2);
+    assert_eq!(truncation.elided_bodies, 2);
+    assert_eq!(
+        truncation.to_string(),
+        "7 of 21 lines elided in 2 section(s), 2 of them function bodies, to fit 18 lines"
+    );
+}
+
+#[test]
+fn test_python_outline_keeps_imports_and_declarations() {
+    let source = r#"import os
+from typing import (
+    List,
+    Optional,
+)
+
+CACHE = {}
+LIMIT = 10
+
+
+class Store:
+    """Keeps records."""
+
+    def __init__(self, root):
+        self.root = root
+        self.items = []
+
+    def load(self):
+        return os.listdir(self.root)
+
+
+def main():
+    store = Store(".")
+    print(store.load())
+"#;
+    let (text, truncation) = truncate(Path::new("store.py"), source, &lines(16, 1));
+    let expected = r#"import os
+from typing import (
+    List,
+    Optional,
+)
+
+# ... 4 lines elided ...
+class Store:
+    # ... 2 lines elided ...
+    def __init__(self, root):
+        # ... 3 lines elided ...
+    def load(self):
+        # ... 3 lines elided ...
+def main():
+    store = Store(".")
+    print(store.load())
+"#;
+    // A marker for a single line would save nothing, so the line is kept
+    assert_eq!(text, expected);
+    let truncation = truncation.expect("File is over the limit");
+    assert_eq!(truncation.elided_sections, 4);
+    assert_eq!(truncation.elided_bodies, 2);
+}
+
+#[test]
+fn test_other_files_keep_their_first_and_last_lines() {
+    let content = numbered("line ", 100);
+    let (text, truncation) = truncate(Path::new("notes.md"), &content, &lines(10, 3));
+    let expected = "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\n\
+        <!-- ... 91 lines elided ... -->\nline 98\nline 99\nline 100\n";
+    assert_eq!(text, expected);
+    assert_eq!(truncation.expect("File is over the limit").elided_bodies, 0);
+
+    // Too tight for the last lines too, so only the start is kept
+    let (text, _) = truncate(Path::new("notes.txt"), &content, &lines(3, 3));
+    assert_eq!(text, "line 1\nline 2\n// ... 98 lines elided ...\n");
+}
+
+#[test]
+fn test_byte_and_token_limits() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("abcde"), 2);
+
+    let content = numbered("value = ", 50);
+    let limits = FileLimits {
+        max_file_bytes: Some(200),
+        max_file_tokens: Some(40),
+        keep_lines: Some(2),
+        ..FileLimits::default()
+    };
+    let (text, truncation) = truncate(Path::new("values.toml"), &content, &limits);
+    assert!(text.len() <= 200);
+    assert!(estimate_tokens(&text) <= 40);
+    assert!(text.starts_with("value = 1\nvalue = 2\n"));
+    assert!(text.ends_with("value = 49\nvalue = 50\n"));
+    let truncation = truncation.expect("File is over the limits");
+    assert_eq!(truncation.exceeded, vec!["200 bytes", "40 tokens"]);
+
+    // A file that is one long line, such as minified code, is still elided
+    let (text, _) = truncate(Path::new("app.min.js"), &"x".repeat(500), &limits);
+    assert_eq!(text, "// ... 1 line elided ...\n");
+}
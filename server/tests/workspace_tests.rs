@@ -0,0 +1,205 @@
+use contexter::config::{Config, ProjectConfig, ProjectRoot};
+use contexter::workspace::{
+    detect_workspace_members, gather_project_files, resolve_project_path, WorkspaceError,
+    WorkspaceKind,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Failed to create parent directory");
+    }
+    fs::write(path, content).expect("Failed to write file");
+}
+
+#[test]
+fn test_detect_cargo_workspace_members() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/skipped\"]\n",
+    );
+    write(
+        &root.join("crates/core/Cargo.toml"),
+        "[package]\nname = \"acme-core\"\n",
+    );
+    write(
+        &root.join("crates/cli/Cargo.toml"),
+        "[package]\nname = \"acme-cli\"\n",
+    );
+    write(
+        &root.join("crates/skipped/Cargo.toml"),
+        "[package]\nname = \"skipped\"\n",
+    );
+
+    let members = detect_workspace_members(root);
+    let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+
+    assert_eq!(names, vec!["acme-cli", "acme-core"]);
+    assert!(members.iter().all(|m| m.kind == WorkspaceKind::Cargo));
+    assert_eq!(members[0].display_path, "crates/cli");
+}
+
+#[test]
+fn test_detect_npm_pnpm_and_go_members() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("package.json"),
+        r#"{"name": "root", "workspaces": ["packages/*"]}"#,
+    );
+    write(
+        &root.join("packages/ui/package.json"),
+        r#"{"name": "@acme/ui"}"#,
+    );
+    write(
+        &root.join("pnpm-workspace.yaml"),
+        "packages:\n  - 'apps/*'\n  - '!apps/legacy'\n",
+    );
+    write(&root.join("apps/web/package.json"), r#"{"name": "web"}"#);
+    write(
+        &root.join("apps/legacy/package.json"),
+        r#"{"name": "legacy"}"#,
+    );
+    write(&root.join("go.work"), "go 1.22\n\nuse (\n\t./svc/api\n)\n");
+    write(
+        &root.join("svc/api/go.mod"),
+        "module github.com/acme/api\n\ngo 1.22\n",
+    );
+
+    let members = detect_workspace_members(root);
+    let find = |name: &str| members.iter().find(|m| m.name == name);
+
+    assert_eq!(find("@acme/ui").map(|m| m.kind), Some(WorkspaceKind::Npm));
+    assert_eq!(find("web").map(|m| m.kind), Some(WorkspaceKind::Pnpm));
+    assert!(find("legacy").is_none());
+    assert_eq!(
+        find("github.com/acme/api").map(|m| m.kind),
+        Some(WorkspaceKind::Go)
+    );
+}
+
+#[test]
+fn test_multi_root_files_are_prefixed() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(&dir.path().join("backend/src/main.rs"), "fn main() {}");
+    write(
+        &dir.path().join("frontend/src/app.ts"),
+        "export const app = 1;",
+    );
+
+    let project = ProjectConfig::MultiRoot {
+        roots: vec![
+            ProjectRoot {
+                path: dir.path().join("backend"),
+                prefix: "api".to_string(),
+            },
+            ProjectRoot {
+                path: dir.path().join("frontend"),
+                prefix: "web".to_string(),
+            },
+        ],
+    };
+
+    let files = gather_project_files(&project, None, None).expect("Failed to gather files");
+    let labels: Vec<&str> = files.iter().map(|f| f.display_path.as_str()).collect();
+    assert_eq!(labels, vec!["api/src/main.rs", "web/src/app.ts"]);
+
+    let paths = vec!["web/src".to_string()];
+    let files = gather_project_files(&project, Some(&paths), None).expect("Failed to gather files");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].display_path, "web/src/app.ts");
+}
+
+#[test]
+fn test_members_can_be_targeted_by_name() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let root = dir.path();
+    write(
+        &root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"core\", \"cli\"]\n",
+    );
+    write(
+        &root.join("core/Cargo.toml"),
+        "[package]\nname = \"core\"\n",
+    );
+    write(&root.join("core/src/lib.rs"), "pub fn core() {}");
+    write(&root.join("cli/Cargo.toml"), "[package]\nname = \"cli\"\n");
+    write(&root.join("cli/src/main.rs"), "fn main() {}");
+
+    let project = ProjectConfig::Path(root.to_path_buf());
+    let members = vec!["core".to_string()];
+    let files =
+        gather_project_files(&project, None, Some(&members)).expect("Failed to gather files");
+    let labels: Vec<&str> = files.iter().map(|f| f.display_path.as_str()).collect();
+    assert_eq!(labels, vec!["core/Cargo.toml", "core/src/lib.rs"]);
+
+    let unknown = vec!["nope".to_string()];
+    assert!(matches!(
+        gather_project_files(&project, None, Some(&unknown)),
+        Err(WorkspaceError::UnknownMember(name)) if name == "nope"
+    ));
+}
+
+#[test]
+fn test_config_accepts_plain_and_multi_root_projects() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "projects": {
+                "simple": "/srv/simple",
+                "product": {"roots": [
+                    {"path": "/srv/backend", "prefix": "backend"},
+                    {"path": "/srv/proto"}
+                ]}
+            },
+            "port": 3030,
+            "listen_address": "127.0.0.1"
+        }"#,
+    )
+    .expect("Config should parse");
+
+    assert_eq!(config.projects["simple"].roots().len(), 1);
+    let roots = config.projects["product"].roots();
+    assert_eq!(roots.len(), 2);
+    assert_eq!(roots[0].prefix, "backend");
+    assert_eq!(roots[1].prefix, "");
+}
+
+#[test]
+fn test_request_paths_cannot_leave_the_project() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write(&dir.path().join("secret.txt"), "password");
+    write(&dir.path().join("app/src/main.rs"), "fn main() {}");
+    let project = ProjectConfig::MultiRoot {
+        roots: vec![ProjectRoot {
+            path: dir.path().join("app"),
+            prefix: "app".to_string(),
+        }],
+    };
+
+    let outside = dir.path().join("secret.txt").display().to_string();
+    for requested in [
+        "../secret.txt",
+        "app/../../secret.txt",
+        "src/../..",
+        &outside,
+    ] {
+        let paths = vec![requested.to_string()];
+        assert!(
+            matches!(
+                gather_project_files(&project, Some(&paths), None),
+                Err(WorkspaceError::OutsideProject(path)) if path == requested
+            ),
+            "{requested} should be rejected"
+        );
+        assert_eq!(resolve_project_path(&project, requested), None);
+    }
+
+    let paths = vec!["./app/src".to_string()];
+    let files = gather_project_files(&project, Some(&paths), None).expect("Failed to gather files");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].display_path, "app/src/main.rs");
+}